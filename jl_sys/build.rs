@@ -398,11 +398,13 @@ fn generate_bindings(julia_dir: &str) {
         .allowlist_function("jl_gc_collect")
         .allowlist_function("jl_gc_enable")
         .allowlist_function("jl_gc_is_enabled")
+        .allowlist_function("jl_gc_live_bytes")
         .allowlist_function("jl_gc_mark_queue_obj")
         .allowlist_function("jl_gc_mark_queue_objarray")
         .allowlist_function("jl_gc_queue_root")
         .allowlist_function("jl_gc_safepoint")
         .allowlist_function("jl_gc_schedule_foreign_sweepfunc")
+        .allowlist_function("jl_gc_set_cb_post_gc")
         .allowlist_function("jl_gc_set_max_memory")
         .allowlist_function("jl_gensym")
         .allowlist_function("jl_get_binding_type")
@@ -479,6 +481,7 @@ fn generate_bindings(julia_dir: &str) {
         .allowlist_function("jl_ver_string")
         .allowlist_function("jl_yield")
         .allowlist_function("jlrs_catch_wrapper")
+        .allowlist_function("jlrs_catch_wrapper_with_backtrace")
         .allowlist_function("jlrs_lock")
         .allowlist_function("jlrs_unlock")
         .allowlist_function("jlrs_array_data_owner_offset")
@@ -1144,6 +1144,14 @@ pub type jl_gc_collection_t = ::std::os::raw::c_uint;
 extern "C" {
     pub fn jl_gc_collect(arg1: jl_gc_collection_t);
 }
+pub type jl_gc_cb_post_gc_t =
+    ::std::option::Option<unsafe extern "C" fn(full: ::std::os::raw::c_int)>;
+extern "C" {
+    pub fn jl_gc_set_cb_post_gc(cb: jl_gc_cb_post_gc_t, enable: ::std::os::raw::c_int);
+}
+extern "C" {
+    pub fn jl_gc_live_bytes() -> i64;
+}
 extern "C" {
     pub fn jl_gc_add_finalizer(v: *mut jl_value_t, f: *mut jl_function_t);
 }
@@ -1743,6 +1751,15 @@ extern "C" {
         frame_slice: *mut ::std::os::raw::c_void,
     ) -> jlrs_catch_t;
 }
+extern "C" {
+    pub fn jlrs_catch_wrapper_with_backtrace(
+        callback: *mut ::std::os::raw::c_void,
+        caller: jlrs_callback_caller_t,
+        result: *mut ::std::os::raw::c_void,
+        frame_slice: *mut ::std::os::raw::c_void,
+        backtrace: *mut *mut jl_value_t,
+    ) -> jlrs_catch_t;
+}
 extern "C" {
     pub fn jlrs_array_data_owner_offset(n_dims: u16) -> uint_t;
 }
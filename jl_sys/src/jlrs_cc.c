@@ -24,6 +24,32 @@ extern "C"
         return res;
     }
 
+    jlrs_catch_t jlrs_catch_wrapper_with_backtrace(void *callback, jlrs_callback_caller_t caller, void *result, void *frame_slice, jl_value_t **backtrace)
+    {
+        jlrs_catch_t res = {.tag = JLRS_CATCH_OK, .error = NULL};
+        *backtrace = NULL;
+
+#if !defined(JLRS_WINDOWS_LTS)
+        JL_TRY
+        {
+#endif
+            res = caller(callback, frame_slice, result);
+#if !defined(JLRS_WINDOWS_LTS)
+        }
+        JL_CATCH
+        {
+            res.tag = JLRS_CATCH_EXCEPTION;
+            res.error = jl_current_exception();
+            // Calling Base.catch_backtrace allocates, the exception is rooted by the exception
+            // stack until the catch block ends. Nothing is allocated after the backtrace has been
+            // captured so the caller can root both. The backtrace is NULL if catch_backtrace
+            // throws.
+            *backtrace = jl_call0(jl_get_function(jl_base_module, "catch_backtrace"));
+        }
+#endif
+        return res;
+    }
+
     uint_t jlrs_array_data_owner_offset(uint16_t n_dims)
     {
         return jl_array_data_owner_offset(n_dims);
@@ -238,6 +238,7 @@ struct jlrs_task_t {
 
     typedef jlrs_catch_t (*jlrs_callback_caller_t)(void *, void *, void *);
     jlrs_catch_t jlrs_catch_wrapper(void *callback, jlrs_callback_caller_t caller, void *result, void *frame_slice);
+    jlrs_catch_t jlrs_catch_wrapper_with_backtrace(void *callback, jlrs_callback_caller_t caller, void *result, void *frame_slice, jl_value_t **backtrace);
 
     uint_t jlrs_array_data_owner_offset(uint16_t n_dims);
     void jlrs_gc_queue_multiroot(jl_value_t *parent, jl_datatype_t *dt, const void *ptr) JL_NOTSAFEPOINT;
@@ -17,7 +17,11 @@ use crate::{
         stack_frame::StackFrame,
         target::frame::{AsyncGcFrame, GcFrame},
     },
-    runtime::async_rt::{PersistentHandle, PersistentMessage},
+    runtime::async_rt::{
+        cancel::{CancelState, Cancellable},
+        stats::{TaskKind, TaskOutcome, TaskTimer},
+        PersistentHandle, PersistentMessage,
+    },
 };
 
 pub(crate) type InnerPersistentMessage<P> = Box<
@@ -246,7 +250,16 @@ where
 
 #[async_trait(?Send)]
 pub(crate) trait PendingTaskEnvelope: Send {
-    async fn call(mut self: Box<Self>, stack: &'static Stack);
+    async fn call(
+        mut self: Box<Self>,
+        stack: &'static Stack,
+        cancel: Arc<CancelState>,
+        timer: TaskTimer,
+    );
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>);
+
+    fn kind(&self) -> TaskKind;
 }
 
 #[async_trait(?Send)]
@@ -255,7 +268,12 @@ where
     O: OneshotSender<JlrsResult<A::Output>>,
     A: AsyncTask,
 {
-    async fn call(mut self: Box<Self>, stack: &'static Stack) {
+    async fn call(
+        mut self: Box<Self>,
+        stack: &'static Stack,
+        cancel: Arc<CancelState>,
+        timer: TaskTimer,
+    ) {
         let (mut task, result_sender) = self.split();
 
         // Safety: the stack slots can be reallocated because it doesn't contain any frames
@@ -264,13 +282,33 @@ where
         let res = unsafe {
             let (owner, frame) = AsyncGcFrame::base(&stack);
 
-            let res = task.call_run(frame).await;
+            let res = Cancellable::new(task.call_run(frame), cancel.clone()).await;
             std::mem::drop(owner);
             res
         };
 
+        // If the task has completed its result is sent, even if it has been cancelled.
+        let res = match (res, cancel.cancelled()) {
+            (Some(res), _) => res,
+            (None, Some(reason)) => Err(Box::new(JlrsError::Cancelled(reason))),
+            (None, None) => unreachable!(),
+        };
+
+        let outcome = TaskOutcome::of(&res);
+        #[cfg(feature = "jlrs-tracing")]
+        tracing::debug!(?outcome, "async task finished");
+
+        timer.finish(outcome);
         result_sender.send(res);
     }
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>) {
+        self.sender.send(Err(error))
+    }
+
+    fn kind(&self) -> TaskKind {
+        TaskKind::Async
+    }
 }
 
 #[async_trait(?Send)]
@@ -279,7 +317,12 @@ where
     O: OneshotSender<JlrsResult<()>>,
     A: AsyncTask,
 {
-    async fn call(mut self: Box<Self>, stack: &'static Stack) {
+    async fn call(
+        mut self: Box<Self>,
+        stack: &'static Stack,
+        _cancel: Arc<CancelState>,
+        timer: TaskTimer,
+    ) {
         let sender = self.sender();
 
         // Safety: the stack slots can be reallocated because it doesn't contain any frames
@@ -292,8 +335,21 @@ where
             res
         };
 
+        let outcome = TaskOutcome::of(&res);
+        #[cfg(feature = "jlrs-tracing")]
+        tracing::debug!(?outcome, "task registered");
+
+        timer.finish(outcome);
         sender.send(res);
     }
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>) {
+        self.sender().send(Err(error))
+    }
+
+    fn kind(&self) -> TaskKind {
+        TaskKind::RegisterAsync
+    }
 }
 
 #[async_trait(?Send)]
@@ -302,7 +358,12 @@ where
     O: OneshotSender<JlrsResult<()>>,
     P: PersistentTask,
 {
-    async fn call(mut self: Box<Self>, stack: &'static Stack) {
+    async fn call(
+        mut self: Box<Self>,
+        stack: &'static Stack,
+        _cancel: Arc<CancelState>,
+        timer: TaskTimer,
+    ) {
         let sender = self.sender();
 
         // Safety: the stack slots can be reallocated because it doesn't contain any frames
@@ -315,8 +376,21 @@ where
             res
         };
 
+        let outcome = TaskOutcome::of(&res);
+        #[cfg(feature = "jlrs-tracing")]
+        tracing::debug!(?outcome, "task registered");
+
+        timer.finish(outcome);
         sender.send(res);
     }
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>) {
+        self.sender().send(Err(error))
+    }
+
+    fn kind(&self) -> TaskKind {
+        TaskKind::RegisterPersistent
+    }
 }
 
 #[async_trait(?Send)]
@@ -326,7 +400,12 @@ where
     O: OneshotSender<JlrsResult<PersistentHandle<P>>>,
     P: PersistentTask,
 {
-    async fn call(mut self: Box<Self>, stack: &'static Stack) {
+    async fn call(
+        mut self: Box<Self>,
+        stack: &'static Stack,
+        cancel: Arc<CancelState>,
+        timer: TaskTimer,
+    ) {
         let (mut persistent, handle_sender) = self.split();
         let handle_sender = handle_sender.sender;
         let (sender, mut receiver) = C::channel(NonZeroUsize::new(P::CHANNEL_CAPACITY));
@@ -338,31 +417,66 @@ where
 
             match persistent.call_init(frame).await {
                 Ok(mut state) => {
+                    #[cfg(feature = "jlrs-tracing")]
+                    tracing::debug!("persistent task initialized");
                     handle_sender.send(Ok(PersistentHandle::new(Arc::new(sender))));
 
                     let offset = stack.size();
 
+                    // The task stops receiving calls when it's cancelled.
                     loop {
-                        let mut msg = match receiver.recv().await {
-                            Ok(msg) => msg.msg,
-                            Err(_) => break,
+                        if cancel.check().is_some() {
+                            break;
+                        }
+
+                        let mut msg = match Cancellable::new(receiver.recv(), cancel.clone()).await
+                        {
+                            Some(Ok(msg)) => msg.msg,
+                            _ => break,
                         };
 
                         let frame = owner.reconstruct(offset);
                         let res = persistent.call_run(frame, &mut state, msg.input()).await;
 
+                        #[cfg(feature = "jlrs-tracing")]
+                        tracing::trace!(outcome = ?TaskOutcome::of(&res), "persistent task called");
                         msg.respond(res);
                     }
 
                     let frame = owner.reconstruct(offset);
                     persistent.exit(frame, &mut state).await;
+
+                    let outcome = match cancel.cancelled() {
+                        Some(_) => TaskOutcome::Cancelled,
+                        None => TaskOutcome::Completed,
+                    };
+
+                    #[cfg(feature = "jlrs-tracing")]
+                    tracing::debug!(?outcome, "persistent task finished");
+                    timer.finish(outcome);
+                }
+                Err(e) => {
+                    let res = Err(e);
+                    let outcome = TaskOutcome::of(&res);
+
+                    #[cfg(feature = "jlrs-tracing")]
+                    tracing::debug!(?outcome, "persistent task finished");
+                    timer.finish(outcome);
+                    handle_sender.send(res);
                 }
-                Err(e) => handle_sender.send(Err(e)),
             }
 
             std::mem::drop(owner);
         }
     }
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>) {
+        self.sender.sender.send(Err(error))
+    }
+
+    fn kind(&self) -> TaskKind {
+        TaskKind::Persistent
+    }
 }
 
 pub(crate) struct BlockingTask<F, O, T> {
@@ -396,9 +510,11 @@ where
 
 #[async_trait(?Send)]
 pub(crate) trait BlockingTaskEnvelope: Send {
-    fn call<'scope>(self: Box<Self>, stack: &'scope Stack);
+    fn call<'scope>(self: Box<Self>, stack: &'scope Stack, timer: TaskTimer);
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>);
 
-    async fn post<'scope>(self: Box<Self>, stack: &'scope Stack);
+    async fn post<'scope>(self: Box<Self>, stack: &'scope Stack, timer: TaskTimer);
 }
 
 #[async_trait(?Send)]
@@ -408,7 +524,7 @@ where
     O: OneshotSender<JlrsResult<T>>,
     T: Send + 'static,
 {
-    fn call<'scope>(self: Box<Self>, stack: &'scope Stack) {
+    fn call<'scope>(self: Box<Self>, stack: &'scope Stack, timer: TaskTimer) {
         // Safety: the stack slots can be reallocated because it doesn't contain any frames
         // yet. The frame is dropped at the end of the scope, the nested hierarchy of scopes is
         // maintained.
@@ -419,22 +535,32 @@ where
             res
         };
 
+        let outcome = TaskOutcome::of(&res);
+        #[cfg(feature = "jlrs-tracing")]
+        tracing::debug!(?outcome, "blocking task finished");
+
+        timer.finish(outcome);
         OneshotSender::send(ch, res);
     }
 
-    async fn post<'scope>(self: Box<Self>, stack: &'scope Stack) {
-        let ptr = Box::leak(self) as *mut _ as *mut c_void;
+    fn reject(self: Box<Self>, error: Box<JlrsError>) {
+        OneshotSender::send(self.sender, Err(error))
+    }
+
+    async fn post<'scope>(self: Box<Self>, stack: &'scope Stack, timer: TaskTimer) {
+        let posted = Box::new((self, timer));
+        let ptr = Box::leak(posted) as *mut _ as *mut c_void;
         unsafe {
             let (owner, mut frame) = AsyncGcFrame::base(&stack);
 
             unsafe extern "C" fn invoke<T: BlockingTaskEnvelope>(task: *mut c_void) {
-                let task = Box::from_raw(task.cast::<T>());
+                let (task, timer) = *Box::from_raw(task.cast::<(Box<T>, TaskTimer)>());
                 let mut frame = StackFrame::new();
                 let mut frame = frame.pin();
                 let frame = frame.stack_frame();
 
                 let stack = frame.sync_stack();
-                task.call(stack)
+                task.call(stack, timer);
             }
 
             let invoke_j = Value::new(&mut frame, invoke::<Self> as *mut c_void);
@@ -490,14 +616,16 @@ where
 }
 
 pub(crate) trait IncludeTaskEnvelope: Send {
-    fn call(self: Box<Self>, stack: &'static Stack);
+    fn call(self: Box<Self>, stack: &'static Stack, timer: TaskTimer);
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>);
 }
 
 impl<O> IncludeTaskEnvelope for IncludeTask<O>
 where
     O: OneshotSender<JlrsResult<()>>,
 {
-    fn call(self: Box<Self>, stack: &'static Stack) {
+    fn call(self: Box<Self>, stack: &'static Stack, timer: TaskTimer) {
         // Safety: the stack slots can be reallocated because it doesn't contain any frames
         // yet. The frame is dropped at the end of the scope, the nested hierarchy of scopes is
         // maintained.
@@ -508,8 +636,13 @@ where
             res
         };
 
+        timer.finish(TaskOutcome::of(&res));
         OneshotSender::send(ch, res);
     }
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>) {
+        OneshotSender::send(self.sender, Err(error))
+    }
 }
 
 pub(crate) struct SetErrorColorTask<O> {
@@ -554,14 +687,16 @@ where
 }
 
 pub(crate) trait SetErrorColorTaskEnvelope: Send {
-    fn call(self: Box<Self>, stack: &'static Stack);
+    fn call(self: Box<Self>, stack: &'static Stack, timer: TaskTimer);
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>);
 }
 
 impl<O> SetErrorColorTaskEnvelope for SetErrorColorTask<O>
 where
     O: OneshotSender<JlrsResult<()>>,
 {
-    fn call(self: Box<Self>, stack: &'static Stack) {
+    fn call(self: Box<Self>, stack: &'static Stack, timer: TaskTimer) {
         // Safety: the stack slots can be reallocated because it doesn't contain any frames
         // yet. The frame is dropped at the end of the scope, the nested hierarchy of scopes is
         // maintained.
@@ -572,6 +707,11 @@ where
             res
         };
 
+        timer.finish(TaskOutcome::of(&res));
         OneshotSender::send(ch, res);
     }
+
+    fn reject(self: Box<Self>, error: Box<JlrsError>) {
+        OneshotSender::send(self.sender, Err(error))
+    }
 }
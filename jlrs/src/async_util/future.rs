@@ -26,6 +26,7 @@ use crate::{
     error::{JuliaResult, CANNOT_DISPLAY_VALUE},
     memory::target::{frame::AsyncGcFrame, unrooted::Unrooted},
     private::Private,
+    runtime::async_rt::cancel::{self, CancelState},
 };
 
 pub(crate) struct TaskState<'frame, 'data> {
@@ -37,6 +38,9 @@ pub(crate) struct TaskState<'frame, 'data> {
 
 pub(crate) struct JuliaFuture<'frame, 'data> {
     shared_state: Arc<Mutex<TaskState<'frame, 'data>>>,
+    // The cancellation state of the async task that created this future, the Julia task is
+    // interrupted if the async task is cancelled.
+    cancel: Option<Arc<CancelState>>,
 }
 
 impl<'frame, 'data> JuliaFuture<'frame, 'data> {
@@ -122,7 +126,10 @@ impl<'frame, 'data> JuliaFuture<'frame, 'data> {
             }
         }
 
-        JuliaFuture { shared_state }
+        JuliaFuture {
+            shared_state,
+            cancel: None,
+        }
     }
 
     pub(crate) fn new_with_keywords<'value, V>(
@@ -222,7 +229,7 @@ impl<'frame, 'data> JuliaFuture<'frame, 'data> {
             }
         }
 
-        JuliaFuture { shared_state }
+        Self::register(shared_state, task)
     }
 
     fn new_future_with_keywords<'value, V>(
@@ -278,7 +285,33 @@ impl<'frame, 'data> JuliaFuture<'frame, 'data> {
             }
         }
 
-        JuliaFuture { shared_state }
+        Self::register(shared_state, task)
+    }
+}
+
+impl<'frame, 'data> JuliaFuture<'frame, 'data> {
+    fn register(shared_state: Arc<Mutex<TaskState<'frame, 'data>>>, task: Task<'frame>) -> Self {
+        let cancel = cancel::current();
+        if let Some(ref cancel) = cancel {
+            cancel.register_julia_task(task.as_value().unwrap_non_null(Private));
+        }
+
+        JuliaFuture {
+            shared_state,
+            cancel,
+        }
+    }
+}
+
+impl Drop for JuliaFuture<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(ref cancel) = self.cancel {
+            if let Ok(state) = self.shared_state.lock() {
+                if let Some(task) = state.task {
+                    cancel.unregister_julia_task(task.as_value().unwrap_non_null(Private));
+                }
+            }
+        }
     }
 }
 
@@ -0,0 +1,447 @@
+//! Use a Julia `Channel` as a `Stream` and `Sink`.
+//!
+//! A [`JuliaChannel`] wraps an instance of some subtype of `AbstractChannel` and a frame of an
+//! async task. It implements `futures::Stream`, new items are received by calling `take!`, and
+//! `futures::Sink`, items are sent by calling `put!`. Both functions are called with
+//! [`CallAsync::call_async`], so waiting for an item to become available or for space in the
+//! channel doesn't block the thread the async runtime runs on. The stream ends when the channel
+//! has been closed and all items have been taken from it, i.e. when `take!` throws an
+//! `InvalidStateException`. Closing the sink closes the Julia channel.
+//!
+//! Every item that is received is rooted in the frame the channel was created with. If a large
+//! number of items will be received they should be handled in a loop that creates a new scope
+//! for a batch of items.
+//!
+//! A [`TypedJuliaChannel<T>`] is a channel that has been checked to be an instance of
+//! [`AbstractChannel<T>`], items are sent and received as [`TypedValue<T>`].
+//!
+//! Example:
+//!
+//! ```
+//! use futures::{SinkExt, StreamExt};
+//! use jlrs::{async_util::julia_channel::JuliaChannel, prelude::*};
+//!
+//! struct SumChannel;
+//!
+//! #[async_trait(?Send)]
+//! impl AsyncTask for SumChannel {
+//!     type Output = i64;
+//!     type Affinity = DispatchAny;
+//!
+//!     async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<i64> {
+//!         let channel = unsafe {
+//!             Value::eval_string(&mut frame, "Channel{Int}(32)").into_jlrs_result()?
+//!         };
+//!
+//!         let one = Value::new(&mut frame, 1i64);
+//!         let two = Value::new(&mut frame, 2i64);
+//!
+//!         let mut channel = unsafe { JuliaChannel::new(&mut frame, channel)? };
+//!         channel.send(one).await?;
+//!         channel.send(two).await?;
+//!         channel.close().await?;
+//!
+//!         let mut sum = 0;
+//!         while let Some(item) = channel.next().await {
+//!             sum += item?.unbox::<i64>()?;
+//!         }
+//!
+//!         Ok(sum)
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! [`CallAsync::call_async`]: crate::call::CallAsync::call_async
+//! [`AbstractChannel<T>`]: crate::data::types::abstract_types::AbstractChannel
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{ready, Sink, Stream};
+
+use crate::{
+    call::{Call, CallAsync},
+    convert::into_jlrs_result::{unrooted_exception_error, IntoJlrsResult},
+    data::{
+        managed::{
+            datatype::DataType,
+            private::ManagedPriv,
+            union_all::UnionAll,
+            value::{typed::TypedValue, Value},
+            Managed,
+        },
+        static_data::{define_static_global, static_global},
+        types::{abstract_types::AbstractChannel, construct_type::ConstructType},
+    },
+    error::{JlrsError, JlrsResult, TypeError, CANNOT_DISPLAY_TYPE, CANNOT_DISPLAY_VALUE},
+    memory::target::frame::AsyncGcFrame,
+    private::Private,
+};
+
+define_static_global!(ABSTRACT_CHANNEL, UnionAll<'static>, "Base.AbstractChannel");
+define_static_global!(TAKE, "Base.take!");
+define_static_global!(PUT, "Base.put!");
+define_static_global!(CLOSE, "Base.close");
+define_static_global!(
+    INVALID_STATE_EXCEPTION,
+    DataType<'static>,
+    "Base.InvalidStateException"
+);
+
+type Pending<'borrow, 'target, 'data> = Pin<
+    Box<
+        dyn Future<
+                Output = (
+                    &'borrow mut AsyncGcFrame<'target>,
+                    JlrsResult<Option<Value<'target, 'data>>>,
+                ),
+            > + 'borrow,
+    >,
+>;
+
+enum State<'borrow, 'target, 'data> {
+    Idle(&'borrow mut AsyncGcFrame<'target>),
+    Taking(Pending<'borrow, 'target, 'data>),
+    Putting(Pending<'borrow, 'target, 'data>),
+    Busy,
+}
+
+/// A Julia `AbstractChannel` that can be used as a `Stream` and `Sink` of values.
+///
+/// See the [module-level docs] for more information.
+///
+/// [module-level docs]: self
+pub struct JuliaChannel<'borrow, 'target, 'data> {
+    channel: Value<'borrow, 'data>,
+    state: State<'borrow, 'target, 'data>,
+    taken: Option<JlrsResult<Option<Value<'target, 'data>>>>,
+    put_error: Option<Box<JlrsError>>,
+}
+
+impl<'borrow, 'target, 'data> JuliaChannel<'borrow, 'target, 'data>
+where
+    'data: 'target,
+{
+    /// Wrap `channel`, which must be an instance of some subtype of `AbstractChannel`. Items
+    /// that are received are rooted in `frame`.
+    ///
+    /// Safety: `take!`, `put!` and `close` are called in new tasks, the channel must not
+    /// be accessed concurrently from Rust.
+    pub unsafe fn new(
+        frame: &'borrow mut AsyncGcFrame<'target>,
+        channel: Value<'borrow, 'data>,
+    ) -> JlrsResult<Self> {
+        let abstract_channel = static_global!(ABSTRACT_CHANNEL, frame);
+        if !channel.isa(abstract_channel.as_value()) {
+            Err(TypeError::NotA {
+                value: channel.display_string_or(CANNOT_DISPLAY_VALUE),
+                field_type: abstract_channel.display_string_or(CANNOT_DISPLAY_TYPE),
+            })?
+        }
+
+        Ok(Self::new_unchecked(frame, channel))
+    }
+
+    /// Wrap `channel` without checking if it's an instance of some subtype of
+    /// `AbstractChannel`.
+    ///
+    /// Safety: `channel` must be an `AbstractChannel`, and must not be accessed concurrently from
+    /// Rust.
+    pub unsafe fn new_unchecked(
+        frame: &'borrow mut AsyncGcFrame<'target>,
+        channel: Value<'borrow, 'data>,
+    ) -> Self {
+        JuliaChannel {
+            channel,
+            state: State::Idle(frame),
+            taken: None,
+            put_error: None,
+        }
+    }
+
+    /// Returns the wrapped channel.
+    pub fn channel(&self) -> Value<'borrow, 'data> {
+        self.channel
+    }
+
+    /// Check if the channel is an instance of `AbstractChannel{T}` and convert it to a
+    /// [`TypedJuliaChannel`].
+    ///
+    /// This method waits until no operation is pending.
+    pub async fn try_as_typed<T: ConstructType>(
+        mut self,
+    ) -> JlrsResult<TypedJuliaChannel<'borrow, 'target, 'data, T>> {
+        futures::future::poll_fn(|cx| self.poll_idle(cx)).await;
+
+        let frame = match &mut self.state {
+            State::Idle(frame) => frame,
+            _ => unreachable!(),
+        };
+
+        let channel = self.channel;
+        let is_typed = frame.scope(|mut frame| {
+            let ty = AbstractChannel::<T>::construct_type(frame.as_extended_target());
+            if channel.isa(ty) {
+                Ok(None)
+            } else {
+                Ok(Some(ty.display_string_or(CANNOT_DISPLAY_TYPE)))
+            }
+        })?;
+
+        if let Some(field_type) = is_typed {
+            Err(TypeError::NotA {
+                value: channel.display_string_or(CANNOT_DISPLAY_VALUE),
+                field_type,
+            })?
+        }
+
+        Ok(TypedJuliaChannel {
+            inner: self,
+            _marker: PhantomData,
+        })
+    }
+
+    // Drive the pending operation to completion. The result of a take is stored in `taken`, the
+    // exception thrown by a put is stored in `put_error`.
+    fn poll_idle(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let (frame, res, is_take) = match &mut self.state {
+            State::Idle(_) => return Poll::Ready(()),
+            State::Taking(pending) => {
+                let (frame, res) = ready!(pending.as_mut().poll(cx));
+                (frame, res, true)
+            }
+            State::Putting(pending) => {
+                let (frame, res) = ready!(pending.as_mut().poll(cx));
+                (frame, res, false)
+            }
+            State::Busy => unreachable!(),
+        };
+
+        self.state = State::Idle(frame);
+        if is_take {
+            self.taken = Some(res);
+        } else if let Err(e) = res {
+            self.put_error = Some(e);
+        }
+
+        Poll::Ready(())
+    }
+
+    // Take the frame from the idle state, the state must be replaced with a pending operation.
+    fn take_frame(&mut self) -> &'borrow mut AsyncGcFrame<'target> {
+        match std::mem::replace(&mut self.state, State::Busy) {
+            State::Idle(frame) => frame,
+            _ => panic!("JuliaChannel is not ready, an operation is still pending"),
+        }
+    }
+
+    fn poll_put_done(&mut self, cx: &mut Context<'_>) -> Poll<JlrsResult<()>> {
+        ready!(self.poll_idle(cx));
+        match self.put_error.take() {
+            Some(e) => Poll::Ready(Err(e)),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+async fn take<'borrow, 'target, 'data>(
+    frame: &'borrow mut AsyncGcFrame<'target>,
+    channel: Value<'borrow, 'data>,
+) -> (
+    &'borrow mut AsyncGcFrame<'target>,
+    JlrsResult<Option<Value<'target, 'data>>>,
+)
+where
+    'data: 'target,
+{
+    let output = frame.output();
+    let res = frame
+        .async_scope(|mut frame| async move {
+            // Safety: take! is globally rooted, the caller of JuliaChannel::new guarantees the
+            // channel isn't accessed concurrently.
+            unsafe {
+                let take = static_global!(TAKE, frame);
+                match take.call_async(&mut frame, [channel]).await {
+                    Ok(item) => Ok(Some(item.root(output))),
+                    Err(exc) => {
+                        // take! throws an InvalidStateException if the channel has been closed
+                        // and is empty.
+                        let closed = static_global!(INVALID_STATE_EXCEPTION, frame);
+                        if exc.isa(closed.as_value()) {
+                            Ok(None)
+                        } else {
+                            Err(exc).into_jlrs_result()
+                        }
+                    }
+                }
+            }
+        })
+        .await;
+
+    (frame, res)
+}
+
+async fn put<'borrow, 'target, 'data>(
+    frame: &'borrow mut AsyncGcFrame<'target>,
+    channel: Value<'borrow, 'data>,
+    item: Value<'borrow, 'data>,
+) -> (
+    &'borrow mut AsyncGcFrame<'target>,
+    JlrsResult<Option<Value<'target, 'data>>>,
+)
+where
+    'data: 'target,
+{
+    let res = frame
+        .async_scope(|mut frame| async move {
+            // Safety: put! is globally rooted, the caller of JuliaChannel::new guarantees the
+            // channel isn't accessed concurrently.
+            unsafe {
+                let put = static_global!(PUT, frame);
+                put.call_async(&mut frame, [channel, item])
+                    .await
+                    .into_jlrs_result()?;
+                Ok(None)
+            }
+        })
+        .await;
+
+    (frame, res)
+}
+
+impl<'borrow, 'target, 'data> Stream for JuliaChannel<'borrow, 'target, 'data>
+where
+    'data: 'target,
+{
+    type Item = JlrsResult<Value<'target, 'data>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(res) = this.taken.take() {
+                return Poll::Ready(res.transpose());
+            }
+
+            ready!(this.poll_idle(cx));
+            if this.taken.is_some() {
+                continue;
+            }
+
+            let frame = this.take_frame();
+            this.state = State::Taking(Box::pin(take(frame, this.channel)));
+        }
+    }
+}
+
+impl<'borrow, 'target, 'data> Sink<Value<'borrow, 'data>> for JuliaChannel<'borrow, 'target, 'data>
+where
+    'data: 'target,
+{
+    type Error = Box<JlrsError>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<JlrsResult<()>> {
+        self.get_mut().poll_put_done(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Value<'borrow, 'data>) -> JlrsResult<()> {
+        let this = self.get_mut();
+        let frame = this.take_frame();
+        this.state = State::Putting(Box::pin(put(frame, this.channel, item)));
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<JlrsResult<()>> {
+        self.get_mut().poll_put_done(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<JlrsResult<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_put_done(cx))?;
+
+        let frame = match &this.state {
+            State::Idle(frame) => frame,
+            _ => unreachable!(),
+        };
+
+        // Safety: close is globally rooted, closing a channel doesn't block. The result is
+        // ignored.
+        unsafe {
+            let unrooted = frame.unrooted();
+            static_global!(CLOSE, unrooted)
+                .call1(&unrooted, this.channel)
+                .map_err(unrooted_exception_error)?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A Julia `AbstractChannel{T}` that can be used as a `Stream` and `Sink` of typed values.
+///
+/// Created with [`JuliaChannel::try_as_typed`].
+pub struct TypedJuliaChannel<'borrow, 'target, 'data, T> {
+    inner: JuliaChannel<'borrow, 'target, 'data>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'borrow, 'target, 'data, T> TypedJuliaChannel<'borrow, 'target, 'data, T>
+where
+    T: ConstructType,
+{
+    /// Returns the wrapped channel.
+    pub fn channel(&self) -> Value<'borrow, 'data> {
+        self.inner.channel
+    }
+
+    /// Convert this channel to an untyped [`JuliaChannel`].
+    pub fn into_untyped(self) -> JuliaChannel<'borrow, 'target, 'data> {
+        self.inner
+    }
+}
+
+impl<'borrow, 'target, 'data, T> Stream for TypedJuliaChannel<'borrow, 'target, 'data, T>
+where
+    'data: 'target,
+    T: ConstructType,
+{
+    type Item = JlrsResult<TypedValue<'target, 'data, T>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Safety: the channel is an AbstractChannel{T}, so every item is an instance of T.
+        Pin::new(&mut self.get_mut().inner)
+            .poll_next(cx)
+            .map_ok(|item| unsafe {
+                TypedValue::wrap_non_null(item.unwrap_non_null(Private), Private)
+            })
+    }
+}
+
+impl<'borrow, 'target, 'data, T> Sink<TypedValue<'borrow, 'data, T>>
+    for TypedJuliaChannel<'borrow, 'target, 'data, T>
+where
+    'data: 'target,
+    T: ConstructType,
+{
+    type Error = Box<JlrsError>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<JlrsResult<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: TypedValue<'borrow, 'data, T>) -> JlrsResult<()> {
+        Pin::new(&mut self.get_mut().inner).start_send(item.as_value())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<JlrsResult<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<JlrsResult<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
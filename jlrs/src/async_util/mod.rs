@@ -5,4 +5,5 @@ pub mod channel;
 #[cfg(feature = "async-rt")]
 pub(crate) mod envelope;
 pub(crate) mod future;
+pub mod julia_channel;
 pub mod task;
@@ -14,14 +14,18 @@ use jl_sys::{jl_call, jl_exception_occurred};
 use jlrs_macros::julia_version;
 use smallvec::SmallVec;
 
-#[julia_version(until = "1.8")]
-use crate::data::managed::private::ManagedPriv as _;
 #[cfg(feature = "async")]
 use crate::error::JuliaResult;
 use crate::{
-    data::managed::value::{Value, ValueResult, MAX_SIZE},
+    data::managed::{
+        private::ManagedPriv as _,
+        value::{Value, ValueResult, MAX_SIZE},
+    },
     error::{AccessError, JlrsResult},
-    memory::{context::ledger::Ledger, target::Target},
+    memory::{
+        context::ledger::Ledger,
+        target::{frame::GcFrame, Target},
+    },
     private::Private,
 };
 
@@ -45,6 +49,33 @@ impl<'scope, 'data> WithKeywords<'scope, 'data> {
     pub fn keywords(&self) -> Value<'scope, 'data> {
         self.keywords
     }
+
+    /// Call the function with `args` and the keyword arguments. If an exception is thrown, its
+    /// backtrace is captured and returned along with it, see [`Value::call_with_backtrace`].
+    ///
+    /// Safety: this method has the same safety requirements as [`Call::call`].
+    pub unsafe fn call_with_backtrace<'target, 'value, V>(
+        &self,
+        frame: &mut GcFrame<'target>,
+        args: V,
+    ) -> JlrsResult<Value<'target, 'data>>
+    where
+        V: AsRef<[Value<'value, 'data>]>,
+    {
+        #[cfg(not(any(feature = "julia-1-10", feature = "julia-1-9")))]
+        let func = jl_get_kwsorter(self.func.datatype().unwrap(Private).cast());
+        #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
+        let func = jl_kwcall_func;
+        let func = Value::wrap_non_null(NonNull::new_unchecked(func), Private);
+
+        let args = args.as_ref();
+        let mut vals: SmallVec<[Value; MAX_SIZE]> = SmallVec::with_capacity(2 + args.len());
+        vals.push(self.keywords);
+        vals.push(self.func);
+        vals.extend_from_slice(args);
+
+        func.call_with_backtrace(frame, vals)
+    }
 }
 
 /// Call the implementor as a Julia function.
@@ -234,6 +265,7 @@ impl<'data> Call<'data> for WithKeywords<'_, 'data> {
         let func = jl_get_kwsorter(self.func.datatype().unwrap(Private).cast());
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         let func = jl_kwcall_func;
+
         let args = &mut [self.keywords, self.func];
 
         let res = jl_call(func, args.as_mut_ptr().cast(), 2);
@@ -260,6 +292,7 @@ impl<'data> Call<'data> for WithKeywords<'_, 'data> {
         let func = jl_get_kwsorter(self.func.datatype().unwrap(Private).cast());
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         let func = jl_kwcall_func;
+
         let args = &mut [self.keywords, self.func, arg0];
 
         let res = jl_call(func, args.as_mut_ptr().cast(), 3);
@@ -287,6 +320,7 @@ impl<'data> Call<'data> for WithKeywords<'_, 'data> {
         let func = jl_get_kwsorter(self.func.datatype().unwrap(Private).cast());
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         let func = jl_kwcall_func;
+
         let args = &mut [self.keywords, self.func, arg0, arg1];
 
         let res = jl_call(func, args.as_mut_ptr().cast(), 4);
@@ -315,6 +349,7 @@ impl<'data> Call<'data> for WithKeywords<'_, 'data> {
         let func = jl_get_kwsorter(self.func.datatype().unwrap(Private).cast());
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         let func = jl_kwcall_func;
+
         let args = &mut [self.keywords, self.func, arg0, arg1, arg2];
 
         let res = jl_call(func, args.as_mut_ptr().cast(), 5);
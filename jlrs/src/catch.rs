@@ -7,23 +7,34 @@ use std::{
 };
 
 use jl_sys::{
-    jlrs_catch_t, jlrs_catch_tag_t_JLRS_CATCH_ERR, jlrs_catch_tag_t_JLRS_CATCH_EXCEPTION,
-    jlrs_catch_tag_t_JLRS_CATCH_OK, jlrs_catch_tag_t_JLRS_CATCH_PANIC, jlrs_catch_wrapper,
+    jl_apply_generic, jl_value_t, jlrs_catch_t, jlrs_catch_tag_t_JLRS_CATCH_ERR,
+    jlrs_catch_tag_t_JLRS_CATCH_EXCEPTION, jlrs_catch_tag_t_JLRS_CATCH_OK,
+    jlrs_catch_tag_t_JLRS_CATCH_PANIC, jlrs_catch_wrapper, jlrs_catch_wrapper_with_backtrace,
 };
 use jlrs_macros::julia_version;
+use smallvec::SmallVec;
 
 #[julia_version(windows_lts = true)]
 use crate::{
     call::Call,
     data::managed::{module::Module, value::Value},
-    memory::{gc::Gc, target::unrooted::Unrooted},
+    memory::gc::Gc,
 };
 use crate::{
-    data::managed::value::ValueRef,
+    data::{
+        managed::{
+            private::ManagedPriv,
+            value::{ValueRef, MAX_SIZE},
+        },
+        static_data::{define_static_global, static_global},
+    },
     error::{JlrsResult, JuliaResultRef},
-    memory::target::frame::GcFrame,
+    memory::target::{frame::GcFrame, unrooted::Unrooted},
+    private::Private,
 };
 
+define_static_global!(INVOKELATEST, "Base.invokelatest");
+
 unsafe extern "C" fn trampoline_with_slots<'frame, F, T>(
     func: &mut F,
     frame_slice: &mut GcFrame<'frame>,
@@ -288,3 +299,85 @@ where
         }
     }
 }
+
+// An exception and the backtrace returned by `Base.catch_backtrace`. The backtrace is `None` if
+// it couldn't be captured.
+pub(crate) type ExceptionWithBacktrace<'frame, 'data> =
+    (ValueRef<'frame, 'data>, Option<ValueRef<'frame, 'static>>);
+
+#[julia_version(windows_lts = false)]
+pub(crate) unsafe fn catch_exceptions_with_backtrace<'frame, 'borrow, 'data, G, T>(
+    func: &'borrow mut G,
+) -> JlrsResult<Result<T, ExceptionWithBacktrace<'frame, 'data>>>
+where
+    T: 'frame,
+    G: FnMut(&mut MaybeUninit<T>) -> JlrsResult<()>,
+{
+    let trampoline = trampoline_for(func);
+    let mut result = MaybeUninit::<T>::uninit();
+    let mut backtrace: *mut jl_value_t = null_mut();
+
+    let res = jlrs_catch_wrapper_with_backtrace(
+        func as *mut _ as *mut _,
+        trampoline,
+        (&mut result) as *mut _ as *mut _,
+        null_mut(),
+        &mut backtrace,
+    );
+
+    match res.tag {
+        x if x == jlrs_catch_tag_t_JLRS_CATCH_OK => Ok(Ok(result.assume_init())),
+        x if x == jlrs_catch_tag_t_JLRS_CATCH_ERR => Err(Box::from_raw(res.error.cast())),
+        x if x == jlrs_catch_tag_t_JLRS_CATCH_EXCEPTION => Ok(Err((
+            ValueRef::wrap(NonNull::new_unchecked(res.error.cast())),
+            NonNull::new(backtrace).map(ValueRef::wrap),
+        ))),
+        x if x == jlrs_catch_tag_t_JLRS_CATCH_PANIC => {
+            let err: Box<Box<dyn Any + Send>> = Box::from_raw(res.error.cast());
+            std::panic::resume_unwind(err)
+        }
+        _ => unreachable!(),
+    }
+}
+
+// Exceptions are caught by JlrsCore.call_catch_wrapper, so the backtrace can't be captured.
+#[julia_version(windows_lts = true)]
+pub(crate) unsafe fn catch_exceptions_with_backtrace<'frame, 'borrow, 'data, G, T>(
+    func: &'borrow mut G,
+) -> JlrsResult<Result<T, ExceptionWithBacktrace<'frame, 'data>>>
+where
+    T: 'frame,
+    G: FnMut(&mut MaybeUninit<T>) -> JlrsResult<()>,
+{
+    Ok(catch_exceptions(func)?.map_err(|e| (e, None)))
+}
+
+// Call `func` with `args` in the latest world age with `Base.invokelatest`. If an exception is
+// thrown it's returned along with its backtrace, both are unrooted.
+pub(crate) unsafe fn call_catch_backtrace<'frame, 'data>(
+    func: *mut jl_value_t,
+    args: &[*mut jl_value_t],
+) -> Result<NonNull<jl_value_t>, ExceptionWithBacktrace<'frame, 'data>> {
+    let unrooted = Unrooted::new();
+    let invokelatest = static_global!(INVOKELATEST, unrooted);
+
+    let mut vals: SmallVec<[*mut jl_value_t; MAX_SIZE]> = SmallVec::with_capacity(args.len() + 1);
+    vals.push(func);
+    vals.extend_from_slice(args);
+
+    let mut callback = |result: &mut MaybeUninit<*mut jl_value_t>| {
+        let res = jl_apply_generic(
+            invokelatest.unwrap(Private),
+            vals.as_mut_ptr(),
+            vals.len() as _,
+        );
+        result.write(res);
+        Ok(())
+    };
+
+    // The callback never returns an error.
+    match catch_exceptions_with_backtrace(&mut callback).unwrap() {
+        Ok(res) => Ok(NonNull::new_unchecked(res)),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,396 @@
+//! Typed Julia callbacks for exported functions.
+//!
+//! A function exported with the [`julia_module`] macro can take a Julia function as an argument.
+//! With [`Function`] as the argument type, any function can be passed and it can be called with
+//! arbitrary arguments. If the arguments and return type are known in advance, [`JuliaFn`] can
+//! be used instead:
+//!
+//! ```ignore
+//! fn apply_n(f: JuliaFn<(f64,), f64>, x: f64, n: usize) -> JlrsResult<f64> {
+//!     unsafe {
+//!         CCall::invoke(|mut frame| {
+//!             let f = f.resolve(&mut frame)?;
+//!             Ok((0..n).fold(x, |x, _| f.call((x,))))
+//!         })
+//!     }
+//! }
+//! ```
+//!
+//! On the Julia side the argument is untyped, when `resolve` is called it's checked that a method
+//! that accepts the arguments exists and that the inferred return type is compatible with the
+//! return type. If these checks succeed, a C-callable pointer to that method is created with
+//! `@cfunction`, calling it avoids the overhead of dynamic dispatch and boxing the arguments.
+//! The function pointer is only created once for every signature.
+//!
+//! The pointer is created by passing the function to `@cfunction` as a closure, which requires
+//! LLVM trampolines. These aren't available on every platform, e.g. aarch64, where `resolve`
+//! returns an error and the function must be called with dynamic dispatch instead.
+//!
+//! [`julia_module`]: jlrs_macros::julia_module
+//! [`Function`]: crate::data::managed::function::Function
+
+use std::{ffi::c_void, marker::PhantomData, sync::RwLock};
+
+use once_cell::sync::OnceCell;
+
+use crate::{
+    call::Call,
+    convert::{
+        ccall_types::CCallArg, into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia,
+        unbox::Unbox,
+    },
+    data::{
+        layout::valid_layout::ValidLayout,
+        managed::{
+            datatype::DataType,
+            value::{Value, ValueUnbound},
+            Managed,
+        },
+        static_data::{define_static_global, static_global},
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    error::{JlrsError, JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
+    memory::target::frame::GcFrame,
+};
+
+define_static_global!(HASMETHOD, "Base.hasmethod");
+define_static_global!(RETURN_TYPE, "Core.Compiler.return_type");
+
+// Creates a factory for a signature, the factory creates a `Base.CFunction` for a function that
+// is called with arguments of these types and returns the given return type.
+const CFUNCTION_FACTORY_MAKER: &str = "(rt, argtypes...) -> \
+    Core.eval(Main, :(f -> @cfunction($(Expr(:$, :f)), $rt, ($(argtypes...),))))";
+
+static CFUNCTION_FACTORIES: CFunctionFactories = CFunctionFactories::new();
+
+struct CFunctionFactories {
+    maker: OnceCell<ValueUnbound>,
+    // The key is the signature type `Tuple{Ret, Args...}`.
+    factories: RwLock<Vec<(ValueUnbound, ValueUnbound)>>,
+}
+
+impl CFunctionFactories {
+    const fn new() -> Self {
+        CFunctionFactories {
+            maker: OnceCell::new(),
+            factories: RwLock::new(Vec::new()),
+        }
+    }
+
+    // Safety: must be called from a thread known to Julia. The types must be isbits types.
+    unsafe fn factory<'target>(
+        &self,
+        frame: &mut GcFrame<'target>,
+        ret_type: Value<'_, 'static>,
+        arg_types: &[Value<'_, 'static>],
+    ) -> JlrsResult<ValueUnbound> {
+        let mut sig = Vec::with_capacity(arg_types.len() + 1);
+        sig.push(ret_type);
+        sig.extend_from_slice(arg_types);
+        let key = DataType::anytuple_type(frame)
+            .as_value()
+            .apply_type(&mut *frame, &sig)
+            .into_jlrs_result()?;
+
+        if let Some(factory) = self.find(key) {
+            return Ok(factory);
+        }
+
+        let maker = self.maker.get_or_try_init(|| {
+            Value::eval_string(&mut *frame, CFUNCTION_FACTORY_MAKER)
+                .into_jlrs_result()
+                .map(|maker| maker.leak().as_value())
+        })?;
+
+        // The factory is a function without captured variables that is defined in Main, so it's
+        // globally rooted. The same holds for the signature type, which is cached. The lock must
+        // not be held while calling into Julia, if another thread has created a factory for this
+        // signature in the meantime that one is used instead.
+        let factory = maker
+            .call(&mut *frame, &sig)
+            .into_jlrs_result()?
+            .leak()
+            .as_value();
+
+        let mut factories = self.factories.write().expect("Lock poisoned");
+        if let Some(&(_, factory)) = factories.iter().find(|(k, _)| k.egal(key)) {
+            return Ok(factory);
+        }
+        factories.push((key.leak().as_value(), factory));
+
+        Ok(factory)
+    }
+
+    fn find(&self, key: Value) -> Option<ValueUnbound> {
+        self.factories
+            .read()
+            .expect("Lock poisoned")
+            .iter()
+            .find_map(|&(k, factory)| if k.egal(key) { Some(factory) } else { None })
+    }
+}
+
+unsafe impl Sync for CFunctionFactories {}
+unsafe impl Send for CFunctionFactories {}
+
+/// A Julia function that is called with arguments of type `Args` and returns `Ret`.
+///
+/// `Args` must be a tuple of up to eight elements that implement [`JuliaFnArgs`]. The function
+/// isn't checked until it's resolved with [`JuliaFn::resolve`], it can be called dynamically
+/// without resolving it first with [`JuliaFn::call`].
+#[repr(transparent)]
+pub struct JuliaFn<'scope, 'data, Args, Ret> {
+    func: Value<'scope, 'data>,
+    _marker: PhantomData<fn(Args) -> Ret>,
+}
+
+impl<'scope, 'data, Args, Ret> Clone for JuliaFn<'scope, 'data, Args, Ret> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'scope, 'data, Args, Ret> Copy for JuliaFn<'scope, 'data, Args, Ret> {}
+
+impl<'scope, 'data, Args, Ret> JuliaFn<'scope, 'data, Args, Ret>
+where
+    Args: JuliaFnArgs,
+{
+    /// Treat `func` as a function that is called with arguments of type `Args` and returns
+    /// `Ret`.
+    pub fn new(func: Value<'scope, 'data>) -> Self {
+        JuliaFn {
+            func,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the function as a `Value`.
+    pub fn as_value(self) -> Value<'scope, 'data> {
+        self.func
+    }
+
+    /// Call the function with dynamic dispatch.
+    ///
+    /// The arguments are converted to Julia data, if an exception is thrown it's caught and
+    /// returned. The result is unboxed as `Ret`, an error is returned if its type is
+    /// incompatible.
+    ///
+    /// Safety: this method has the same safety requirements as [`Call::call`].
+    pub unsafe fn call(self, frame: &mut GcFrame, args: Args) -> JlrsResult<Ret::Output>
+    where
+        Ret: Unbox + Typecheck,
+    {
+        frame.scope(|mut frame| {
+            let args = args.into_values(&mut frame);
+            self.func
+                .call(&mut frame, args)
+                .into_jlrs_result()?
+                .unbox::<Ret>()
+        })
+    }
+
+    /// Resolve the method that is called with arguments of type `Args`.
+    ///
+    /// The argument and return types must be isbits types whose layouts are compatible with
+    /// their Julia counterparts, a method that can be called with these arguments must exist, and
+    /// its inferred return type must be compatible with `Ret` if it's a concrete type. If these
+    /// checks succeed, a function pointer to that method is created with `@cfunction`. This
+    /// pointer is rooted in `frame`.
+    ///
+    /// The function is passed to `@cfunction` as a closure, an error is returned on platforms
+    /// without LLVM trampolines like aarch64.
+    ///
+    /// Safety: this method calls into Julia to check the method and infer its return type, it
+    /// has the same safety requirements as [`Call::call`].
+    pub unsafe fn resolve<'target>(
+        self,
+        frame: &mut GcFrame<'target>,
+    ) -> JlrsResult<ResolvedJuliaFn<'target, 'data, Args, Ret>>
+    where
+        Ret: ValidLayout + ConstructType,
+    {
+        let output = frame.output();
+        frame.scope(|mut frame| {
+            let arg_types = Args::arg_types(&mut frame)?;
+            let ret_type = Ret::construct_type(frame.as_extended_target());
+            check_layout::<Ret>(ret_type)?;
+
+            let sig = DataType::anytuple_type(&frame)
+                .as_value()
+                .apply_type(&mut frame, &arg_types)
+                .into_jlrs_result()?;
+
+            let hasmethod = static_global!(HASMETHOD, frame);
+            let has_method = hasmethod
+                .call2(&mut frame, self.func.as_value(), sig)
+                .into_jlrs_result()?
+                .unbox::<bool>()?
+                .as_bool();
+
+            if !has_method {
+                let func = self.func.display_string_or("<Cannot display function>");
+                let sig = sig.display_string_or(CANNOT_DISPLAY_TYPE);
+                Err(JlrsError::exception(format!(
+                    "{} has no method that matches {}",
+                    func, sig
+                )))?;
+            }
+
+            let return_type = static_global!(RETURN_TYPE, frame);
+            let inferred = return_type
+                .call2(&mut frame, self.func.as_value(), sig)
+                .into_jlrs_result()?;
+
+            if let Ok(inferred) = inferred.cast::<DataType>() {
+                if inferred.is_concrete_type() && !Ret::valid_layout(inferred.as_value()) {
+                    Err(TypeError::IncompatibleBaseType {
+                        base_type: inferred.display_string_or(CANNOT_DISPLAY_TYPE),
+                    })?;
+                }
+            }
+
+            let factory = CFUNCTION_FACTORIES.factory(&mut frame, ret_type, &arg_types)?;
+            let cfunction = factory
+                .call1(output, self.func.as_value())
+                .into_jlrs_result()?;
+
+            // The first field of `Base.CFunction` is the function pointer.
+            let ptr = cfunction.data_ptr().cast::<*mut c_void>().as_ptr().read();
+
+            Ok(ResolvedJuliaFn {
+                _cfunction: cfunction,
+                ptr,
+                _marker: PhantomData,
+            })
+        })
+    }
+}
+
+unsafe impl<'scope, 'data, Args, Ret> CCallArg for JuliaFn<'scope, 'data, Args, Ret> {
+    type CCallArgType = Value<'scope, 'data>;
+    type FunctionArgType = Value<'scope, 'data>;
+}
+
+/// A resolved [`JuliaFn`].
+///
+/// The function is called through a function pointer created with `@cfunction`, which is only
+/// valid while the `Base.CFunction` it has been created from is rooted.
+pub struct ResolvedJuliaFn<'scope, 'data, Args, Ret> {
+    _cfunction: Value<'scope, 'data>,
+    ptr: *mut c_void,
+    _marker: PhantomData<fn(Args) -> Ret>,
+}
+
+impl<'scope, 'data, Args, Ret> ResolvedJuliaFn<'scope, 'data, Args, Ret>
+where
+    Args: JuliaFnArgs,
+    Ret: ValidLayout + ConstructType,
+{
+    /// Call the function.
+    ///
+    /// Safety: the function must not throw an exception, the return value is converted to `Ret`
+    /// with `Base.convert` if it has another type which can throw an exception. This method must
+    /// be called from a thread known to Julia.
+    pub unsafe fn call(&self, args: Args) -> Ret {
+        args.call_cfunction(self.ptr)
+    }
+
+    /// Returns the function pointer.
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+}
+
+/// The arguments of a [`JuliaFn`].
+///
+/// This trait is implemented for tuples of up to eight elements, each element must implement
+/// `IntoJulia`, `ValidLayout` and `ConstructType`.
+pub trait JuliaFnArgs: private::JuliaFnArgsPriv {}
+
+fn check_layout<T: ValidLayout>(ty: Value) -> JlrsResult<()> {
+    if !T::valid_layout(ty) {
+        Err(TypeError::IncompatibleBaseType {
+            base_type: ty.display_string_or(CANNOT_DISPLAY_TYPE),
+        })?;
+    }
+
+    match ty.cast::<DataType>() {
+        Ok(dt) if dt.is_bits() => Ok(()),
+        _ => Err(JlrsError::exception(format!(
+            "{} is not an isbits type",
+            ty.display_string_or(CANNOT_DISPLAY_TYPE)
+        )))?,
+    }
+}
+
+macro_rules! impl_julia_fn_args {
+    ($($name:ident),*) => {
+        impl<$($name),*> JuliaFnArgs for ($($name,)*)
+        where
+            $($name: IntoJulia + ValidLayout + ConstructType),*
+        {
+        }
+
+        impl<$($name),*> private::JuliaFnArgsPriv for ($($name,)*)
+        where
+            $($name: IntoJulia + ValidLayout + ConstructType),*
+        {
+            #[allow(unused_mut, unused_variables)]
+            fn arg_types<'target>(
+                frame: &mut GcFrame<'target>,
+            ) -> JlrsResult<Vec<Value<'target, 'static>>> {
+                let mut types = Vec::new();
+                $(
+                    let ty = $name::construct_type(frame.as_extended_target());
+                    check_layout::<$name>(ty)?;
+                    types.push(ty);
+                )*
+                Ok(types)
+            }
+
+            #[allow(non_snake_case, unused_variables)]
+            fn into_values<'target>(
+                self,
+                frame: &mut GcFrame<'target>,
+            ) -> Vec<Value<'target, 'static>> {
+                let ($($name,)*) = self;
+                vec![$(Value::new(&mut *frame, $name)),*]
+            }
+
+            #[allow(non_snake_case)]
+            unsafe fn call_cfunction<Ret>(self, ptr: *mut c_void) -> Ret {
+                let ($($name,)*) = self;
+                let func: extern "C" fn($($name),*) -> Ret = std::mem::transmute(ptr);
+                func($($name),*)
+            }
+        }
+    };
+}
+
+impl_julia_fn_args!();
+impl_julia_fn_args!(A);
+impl_julia_fn_args!(A, B);
+impl_julia_fn_args!(A, B, C);
+impl_julia_fn_args!(A, B, C, D);
+impl_julia_fn_args!(A, B, C, D, E);
+impl_julia_fn_args!(A, B, C, D, E, F);
+impl_julia_fn_args!(A, B, C, D, E, F, G);
+impl_julia_fn_args!(A, B, C, D, E, F, G, H);
+
+mod private {
+    use std::ffi::c_void;
+
+    use crate::{data::managed::value::Value, error::JlrsResult, memory::target::frame::GcFrame};
+
+    pub trait JuliaFnArgsPriv: Sized {
+        fn arg_types<'target>(
+            frame: &mut GcFrame<'target>,
+        ) -> JlrsResult<Vec<Value<'target, 'static>>>;
+
+        fn into_values<'target>(self, frame: &mut GcFrame<'target>)
+            -> Vec<Value<'target, 'static>>;
+
+        unsafe fn call_cfunction<Ret>(self, ptr: *mut c_void) -> Ret;
+    }
+}
@@ -43,6 +43,9 @@ use crate::{
     InstallJlrsCore,
 };
 
+pub mod julia_fn;
+pub mod protocols;
+
 // The pool is lazily created either when it's first used, or when the number of threads is set.
 // ThreadPool is !Sync, but it is safe to clone it (which creates a new handle to the pool) and
 // use that handle to schedule new jobs to avoid having to lock the pool whenever a new job is
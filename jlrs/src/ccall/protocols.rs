@@ -0,0 +1,303 @@
+//! Julia protocol methods for exported types.
+//!
+//! The [`julia_module`] macro can add methods to several functions from `Base` for an exported
+//! opaque or foreign type if that type implements the corresponding Rust trait:
+//!
+//! | Rust trait          | Julia method                                   |
+//! |---------------------|------------------------------------------------|
+//! | `Display`           | `Base.show(io::IO, x)`                         |
+//! | `Debug`             | `Base.show(io::IO, x)`                         |
+//! | `PartialEq`         | `Base.:(==)(x, y)`                             |
+//! | `Hash`              | `Base.hash(x, h::UInt)`                        |
+//! | `Iterator`          | `Base.iterate(x)` and `Base.iterate(x, state)` |
+//! | `ExactSizeIterator` | `Base.length(x)`                               |
+//! | `Index<I>`          | `Base.getindex(x, i::Int)`                     |
+//!
+//! Types that are exported with the `Index<I>` protocol must implement [`CheckedIndex<I>`] too,
+//! the index is checked with it before the type is indexed.
+//!
+//! Fields of the exported type can be exposed as properties, which adds methods to
+//! `Base.getproperty`, `Base.setproperty!` and `Base.propertynames`.
+//!
+//! The functions in this module are called by the functions generated by that macro. They track
+//! the borrow of the exported data with the ledger, a `JlrsCore.BorrowError` is thrown if the
+//! data is already borrowed in an incompatible way.
+//!
+//! [`julia_module`]: jlrs_macros::julia_module
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    convert::TryFrom,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    ops::Index,
+};
+
+use super::CCall;
+use crate::{
+    call::Call,
+    convert::{
+        into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia,
+        into_julia_exception::JlrsException,
+    },
+    data::{
+        layout::tuple::Tuple,
+        managed::{
+            module::Module,
+            rust_result::{RustResult, RustResultRet},
+            string::JuliaString,
+            symbol::Symbol,
+            value::{typed::TypedValue, Value},
+            Managed,
+        },
+        types::{
+            abstract_types::{AnyType, IO},
+            construct_type::ConstructType,
+            foreign_type::OpaqueType,
+        },
+    },
+    error::JlrsResult,
+    memory::target::{frame::GcFrame, Target},
+};
+
+/// Print `this` to `io` with its `Display` implementation.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn show_display<T>(io: TypedValue<IO>, this: TypedValue<T>) -> RustResultRet<AnyType>
+where
+    T: OpaqueType + Display,
+{
+    invoke_shared(this, |this, frame| print(frame, io, this.to_string()))
+}
+
+/// Print `this` to `io` with its `Debug` implementation.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn show_debug<T>(io: TypedValue<IO>, this: TypedValue<T>) -> RustResultRet<AnyType>
+where
+    T: OpaqueType + Debug,
+{
+    invoke_shared(this, |this, frame| print(frame, io, format!("{:?}", this)))
+}
+
+/// Returns `true` if `this` and `other` are equal.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn eq<T>(this: TypedValue<T>, other: TypedValue<T>) -> RustResultRet<bool>
+where
+    T: OpaqueType + PartialEq,
+{
+    match other.track_shared() {
+        Ok(other) => call_shared(this, |this| Ok(*this == *other)),
+        Err(_) => RustResult::borrow_error_internal(),
+    }
+}
+
+/// Hash `this` and `h` with the `DefaultHasher`.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn hash<T>(this: TypedValue<T>, h: usize) -> RustResultRet<usize>
+where
+    T: OpaqueType + Hash,
+{
+    call_shared(this, |this| {
+        let mut hasher = DefaultHasher::new();
+        h.hash(&mut hasher);
+        this.hash(&mut hasher);
+        Ok(hasher.finish() as usize)
+    })
+}
+
+/// Advance the iterator, returns `nothing` if it's exhausted and `(item, nothing)` otherwise.
+///
+/// The exported data is the iterator, so iterating over it consumes its items.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn iterate<T>(mut this: TypedValue<T>) -> RustResultRet<AnyType>
+where
+    T: OpaqueType + Iterator,
+    T::Item: IntoJulia,
+{
+    match this.track_exclusive() {
+        Ok(mut this) => {
+            let item = this.next();
+            invoke(|frame| match item {
+                Some(item) => {
+                    let item = Value::new(&mut *frame, item);
+                    let nothing = Value::nothing(&*frame);
+                    Ok(Tuple::new_unchecked(
+                        frame.as_extended_target(),
+                        [item, nothing],
+                    ))
+                }
+                None => Ok(Value::nothing(&*frame)),
+            })
+        }
+        Err(_) => RustResult::borrow_error_internal(),
+    }
+}
+
+/// Returns the number of remaining items of the iterator.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn length<T>(this: TypedValue<T>) -> RustResultRet<isize>
+where
+    T: OpaqueType + ExactSizeIterator,
+{
+    call_shared(this, |this| Ok(this.len() as isize))
+}
+
+/// Indexing that doesn't panic if the index is out of bounds.
+///
+/// `Index::index` panics if the index is out of bounds, which can't be caught if the library is
+/// compiled with `panic = "abort"`. Types that are exported with the `Index<I>` protocol must
+/// implement this trait so `getindex` can throw a `BoundsError` instead.
+pub trait CheckedIndex<I>: Index<I> {
+    /// Returns the element at `index`, or `None` if `index` is out of bounds.
+    fn checked_index(&self, index: I) -> Option<&Self::Output>;
+}
+
+/// Index `this` with the 1-based `index`, the element is cloned.
+///
+/// A `BoundsError` is thrown if `index - 1` can't be converted to `I`, or if `checked_index`
+/// returns `None`.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn getindex<T, I>(this: TypedValue<T>, index: isize) -> RustResultRet<T::Output>
+where
+    T: OpaqueType + CheckedIndex<I>,
+    T::Output: IntoJulia + ConstructType + Clone + Sized,
+    I: TryFrom<usize>,
+{
+    let value = this.as_value();
+    call_shared(this, |this| {
+        index
+            .checked_sub(1)
+            .and_then(|idx| usize::try_from(idx).ok())
+            .and_then(|idx| I::try_from(idx).ok())
+            .and_then(|idx| this.checked_index(idx))
+            .cloned()
+            .ok_or_else(|| JlrsException::bounds_error(value, &[index]))
+    })
+}
+
+/// Get the property `name` of `this`.
+///
+/// `func` must return the value of the property, or `None` if `this` has no property with that
+/// name.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn get_property<T, F>(
+    this: TypedValue<T>,
+    name: Symbol,
+    func: F,
+) -> RustResultRet<AnyType>
+where
+    T: OpaqueType,
+    F: for<'scope> FnOnce(&T, &str, &mut GcFrame<'scope>) -> Option<Value<'scope, 'static>>,
+{
+    let ty_name = this.as_value().datatype().name();
+    invoke_shared(this, |this, frame| {
+        let name = name.as_str().unwrap_or_default();
+        func(this, name, frame).ok_or_else(|| no_property_error(ty_name, name))
+    })
+}
+
+/// Set the property `name` of `this` to `value`.
+///
+/// `func` must set the property and return `Some(Ok(()))`, `Some(Err(_))` if `value` can't be
+/// converted to the type of the property, or `None` if `this` has no property with that name
+/// which can be set.
+///
+/// Safety: this function must only be called from a function called through `ccall`.
+pub unsafe fn set_property<T, F>(
+    mut this: TypedValue<T>,
+    name: Symbol,
+    value: Value,
+    func: F,
+) -> RustResultRet<AnyType>
+where
+    T: OpaqueType,
+    F: FnOnce(&mut T, &str, Value) -> Option<JlrsResult<()>>,
+{
+    let ty_name = this.as_value().datatype().name();
+    match this.track_exclusive() {
+        Ok(mut this) => {
+            let name = name.as_str().unwrap_or_default();
+            let res = match func(&mut this, name, value) {
+                Some(Ok(())) => Ok(()),
+                Some(Err(e)) => Err(JlrsException::from(e)),
+                None => Err(no_property_error(ty_name, name)),
+            };
+
+            invoke(|frame| res.map(|_| Value::nothing(&*frame)))
+        }
+        Err(_) => RustResult::borrow_error_internal(),
+    }
+}
+
+fn no_property_error(ty_name: &str, name: &str) -> JlrsException {
+    JlrsException::error(format!("type {} has no property {}", ty_name, name))
+}
+
+unsafe fn print<'scope>(
+    frame: &mut GcFrame<'scope>,
+    io: TypedValue<IO>,
+    text: String,
+) -> Result<Value<'scope, 'static>, JlrsException> {
+    let text = JuliaString::new(&mut *frame, text);
+    Module::base(&*frame)
+        .function(&*frame, "print")?
+        .as_managed()
+        .call2(&mut *frame, io.as_value(), text.as_value())
+        .into_jlrs_result()?;
+
+    Ok(Value::nothing(&*frame))
+}
+
+// Tracks `this` immutably, calls `func`, and converts the result to a `RustResult`.
+unsafe fn call_shared<T, R, F>(this: TypedValue<T>, func: F) -> RustResultRet<R>
+where
+    T: OpaqueType,
+    R: IntoJulia + ConstructType,
+    F: FnOnce(&T) -> Result<R, JlrsException>,
+{
+    match this.track_shared() {
+        Ok(this) => RustResult::from_result_internal(func(&this)),
+        Err(_) => RustResult::borrow_error_internal(),
+    }
+}
+
+// Tracks `this` immutably and calls `func` with a frame.
+unsafe fn invoke_shared<T, F>(this: TypedValue<T>, func: F) -> RustResultRet<AnyType>
+where
+    T: OpaqueType,
+    F: for<'scope> FnOnce(
+        &T,
+        &mut GcFrame<'scope>,
+    ) -> Result<Value<'scope, 'static>, JlrsException>,
+{
+    match this.track_shared() {
+        Ok(this) => invoke(|frame| func(&this, frame)),
+        Err(_) => RustResult::borrow_error_internal(),
+    }
+}
+
+// Calls `func` with a frame and converts the result to a `RustResult`.
+unsafe fn invoke<F>(func: F) -> RustResultRet<AnyType>
+where
+    F: for<'scope> FnOnce(&mut GcFrame<'scope>) -> Result<Value<'scope, 'static>, JlrsException>,
+{
+    CCall::invoke(|mut frame| {
+        let unrooted = frame.unrooted();
+        match func(&mut frame) {
+            Ok(value) => {
+                let value = value.as_typed_unchecked::<AnyType>();
+                RustResult::ok(unrooted.into_extended_target(&mut frame), value).leak()
+            }
+            Err(error) => {
+                RustResult::exception(unrooted.into_extended_target(&mut frame), error).leak()
+            }
+        }
+    })
+}
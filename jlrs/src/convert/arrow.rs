@@ -0,0 +1,670 @@
+//! Exchange array data with Apache Arrow through the Arrow C Data Interface.
+//!
+//! The [Arrow C Data Interface] defines two C structs, [`ArrowArray`] and [`ArrowSchema`], that
+//! can be used to share columnar data between libraries without copying it. They are defined
+//! here with the same layout as the structs in the specification, so pointers to them can be
+//! passed to any implementation of the interface, e.g. `FFI_ArrowArray` and `FFI_ArrowSchema`
+//! from arrow-rs.
+//!
+//! Julia arrays can be exported with [`ToArrow`], which is implemented for
+//! [`BitsArrayAccessor`] and [`UnionArrayAccessor`]. Arrays of bits types that implement
+//! [`ArrowPrimitive`] are exported as primitive arrays without nulls, arrays with the element
+//! type `Union{Missing, T}` as nullable primitive arrays whose validity bitmap is computed from
+//! the selector bytes of the array. In both cases the data of the array is not copied, the
+//! exported array borrows it from Julia.
+//!
+//! Arrow arrays can be imported with [`import_primitive`] and [`import_utf8`]. The buffers of the
+//! imported array are used as the backing storage of new Julia arrays, the Arrow array is
+//! released by a finalizer when these Julia arrays have been freed by the GC.
+//!
+//! [Arrow C Data Interface]: https://arrow.apache.org/docs/format/CDataInterface.html
+//! [`BitsArrayAccessor`]: crate::data::managed::array::data::accessor::BitsArrayAccessor
+//! [`UnionArrayAccessor`]: crate::data::managed::array::data::accessor::UnionArrayAccessor
+
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    ffi::{c_void, CStr},
+    marker::PhantomData,
+    mem,
+    os::raw::c_char,
+    ptr::{null, null_mut, NonNull},
+    slice,
+    sync::{Arc, Mutex},
+};
+
+use jl_sys::jl_array_typetagdata;
+use once_cell::sync::Lazy;
+use thiserror::Error;
+
+use super::into_julia::IntoJulia;
+use crate::{
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::{
+        layout::valid_layout::ValidField,
+        managed::{
+            array::{
+                data::accessor::{BitsArrayAccessor, Mutability, UnionArrayAccessor},
+                dimensions::Dims,
+                TypedArray, TypedArrayData,
+            },
+            datatype::DataType,
+            private::ManagedPriv,
+            union::nth_union_component,
+            value::{Value, ValueData},
+            Managed,
+        },
+        static_data::{define_static_global, static_global},
+    },
+    error::{JlrsError, JlrsResult, CANNOT_DISPLAY_TYPE},
+    memory::target::{unrooted::Unrooted, ExtendedTarget, Target},
+    private::Private,
+};
+
+define_static_global!(MISSING, DataType<'static>, "Base.Missing");
+
+/// The `ArrowSchema` is nullable.
+pub const ARROW_FLAG_NULLABLE: i64 = 2;
+
+// The imported Arrow arrays that are kept alive by a Julia array, indexed by the address of that
+// Julia array.
+static IMPORTED: Lazy<Mutex<HashMap<usize, Arc<Imported>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Errors that can occur while exporting or importing Arrow data.
+#[derive(Debug, Error)]
+pub enum ArrowError {
+    #[error("the Arrow array has already been released")]
+    Released,
+    #[error("expected an Arrow array with format {expected}, got {found}")]
+    FormatMismatch { expected: String, found: String },
+    #[error("the Arrow array has {null_count} nulls")]
+    HasNulls { null_count: i64 },
+    #[error("the Arrow array has {n_buffers} buffers, expected {expected}")]
+    BufferCountMismatch { n_buffers: i64, expected: i64 },
+    #[error("buffer {buffer} of the Arrow array is not aligned to {align} bytes")]
+    Misaligned { buffer: usize, align: usize },
+    #[error("the Arrow array has an invalid length or offset")]
+    InvalidLength,
+    #[error("arrays with element type {element_type} can't be exported")]
+    UnsupportedElementType { element_type: String },
+}
+
+impl From<ArrowError> for Box<JlrsError> {
+    fn from(e: ArrowError) -> Self {
+        Box::new(JlrsError::other(e))
+    }
+}
+
+/// The `ArrowSchema` struct of the Arrow C Data Interface.
+///
+/// If the schema hasn't been released when it's dropped, its release callback is called.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowSchema {
+    /// The format string of the data type.
+    pub format: *const c_char,
+    /// The name of the field, can be null.
+    pub name: *const c_char,
+    /// The metadata of the field, can be null.
+    pub metadata: *const c_char,
+    /// Flags that provide additional information about the field, e.g. [`ARROW_FLAG_NULLABLE`].
+    pub flags: i64,
+    /// The number of children.
+    pub n_children: i64,
+    /// The children, null if there are no children.
+    pub children: *mut *mut ArrowSchema,
+    /// The type of the dictionary values, null if the type isn't dictionary-encoded.
+    pub dictionary: *mut ArrowSchema,
+    /// Releases the schema, `None` if it has been released.
+    pub release: Option<unsafe extern "C" fn(schema: *mut ArrowSchema)>,
+    /// Data used by the producer.
+    pub private_data: *mut c_void,
+}
+
+// Safety: the specification requires that a schema can be released from any thread.
+unsafe impl Send for ArrowSchema {}
+
+impl ArrowSchema {
+    /// Returns a released schema, which can be passed to a producer to be filled in.
+    pub fn empty() -> Self {
+        ArrowSchema {
+            format: null(),
+            name: null(),
+            metadata: null(),
+            flags: 0,
+            n_children: 0,
+            children: null_mut(),
+            dictionary: null_mut(),
+            release: None,
+            private_data: null_mut(),
+        }
+    }
+
+    /// Returns `true` if the schema has been released.
+    pub fn is_released(&self) -> bool {
+        self.release.is_none()
+    }
+
+    /// Returns the format string, or `None` if the schema has been released.
+    pub fn format(&self) -> Option<&str> {
+        if self.is_released() || self.format.is_null() {
+            return None;
+        }
+
+        // Safety: the format of a schema that hasn't been released is a valid C string.
+        unsafe { CStr::from_ptr(self.format).to_str().ok() }
+    }
+}
+
+impl Drop for ArrowSchema {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            // Safety: the schema hasn't been released yet.
+            unsafe { release(self) }
+        }
+    }
+}
+
+/// The `ArrowArray` struct of the Arrow C Data Interface.
+///
+/// If the array hasn't been released when it's dropped, its release callback is called.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowArray {
+    /// The number of elements.
+    pub length: i64,
+    /// The number of nulls, -1 if it's unknown.
+    pub null_count: i64,
+    /// The logical offset into the buffers, in number of elements.
+    pub offset: i64,
+    /// The number of buffers.
+    pub n_buffers: i64,
+    /// The number of children.
+    pub n_children: i64,
+    /// The buffers, the first buffer is the validity bitmap which can be null.
+    pub buffers: *mut *const c_void,
+    /// The children, null if there are no children.
+    pub children: *mut *mut ArrowArray,
+    /// The dictionary, null if the array isn't dictionary-encoded.
+    pub dictionary: *mut ArrowArray,
+    /// Releases the array, `None` if it has been released.
+    pub release: Option<unsafe extern "C" fn(array: *mut ArrowArray)>,
+    /// Data used by the producer.
+    pub private_data: *mut c_void,
+}
+
+// Safety: the specification requires that an array can be released from any thread.
+unsafe impl Send for ArrowArray {}
+
+impl ArrowArray {
+    /// Returns a released array, which can be passed to a producer to be filled in.
+    pub fn empty() -> Self {
+        ArrowArray {
+            length: 0,
+            null_count: 0,
+            offset: 0,
+            n_buffers: 0,
+            n_children: 0,
+            buffers: null_mut(),
+            children: null_mut(),
+            dictionary: null_mut(),
+            release: None,
+            private_data: null_mut(),
+        }
+    }
+
+    /// Returns `true` if the array has been released.
+    pub fn is_released(&self) -> bool {
+        self.release.is_none()
+    }
+
+    // Returns the nth buffer, the number of buffers must have been checked.
+    unsafe fn buffer(&self, n: usize) -> *const c_void {
+        *self.buffers.add(n)
+    }
+}
+
+impl Drop for ArrowArray {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            // Safety: the array hasn't been released yet.
+            unsafe { release(self) }
+        }
+    }
+}
+
+/// Primitive types that can be exchanged with Arrow.
+///
+/// Safety: the layout of `Self` must match the layout of the Arrow type with the format string
+/// `FORMAT`, which must be terminated by a null byte.
+pub unsafe trait ArrowPrimitive: ValidField + IntoJulia + Copy {
+    /// The format string of the matching Arrow type.
+    const FORMAT: &'static str;
+}
+
+macro_rules! impl_arrow_primitive {
+    ($type:ty, $format:literal) => {
+        unsafe impl ArrowPrimitive for $type {
+            const FORMAT: &'static str = concat!($format, "\0");
+        }
+    };
+}
+
+impl_arrow_primitive!(i8, "c");
+impl_arrow_primitive!(u8, "C");
+impl_arrow_primitive!(i16, "s");
+impl_arrow_primitive!(u16, "S");
+impl_arrow_primitive!(i32, "i");
+impl_arrow_primitive!(u32, "I");
+impl_arrow_primitive!(i64, "l");
+impl_arrow_primitive!(u64, "L");
+impl_arrow_primitive!(f32, "f");
+impl_arrow_primitive!(f64, "g");
+#[cfg(feature = "f16")]
+impl_arrow_primitive!(half::f16, "e");
+
+/// A Julia array that has been exported as an Arrow array.
+///
+/// The data is borrowed from Julia, so the export can't outlive the accessor it has been created
+/// with. When it's dropped the `ArrowArray` and `ArrowSchema` are released unless a consumer
+/// has taken ownership of them.
+pub struct ArrowExport<'view, T> {
+    array: ArrowArray,
+    schema: ArrowSchema,
+    _marker: PhantomData<&'view [T]>,
+}
+
+impl<'view, T> ArrowExport<'view, T> {
+    /// Returns the exported array.
+    pub fn array(&self) -> &ArrowArray {
+        &self.array
+    }
+
+    /// Returns the schema of the exported array.
+    pub fn schema(&self) -> &ArrowSchema {
+        &self.schema
+    }
+
+    /// Returns a mutable pointer to the exported array, consumers that take ownership of the
+    /// array must move it out and mark the original as released.
+    pub fn array_ptr(&mut self) -> *mut ArrowArray {
+        &mut self.array
+    }
+
+    /// Returns a mutable pointer to the schema, consumers that take ownership of the schema must
+    /// move it out and mark the original as released.
+    pub fn schema_ptr(&mut self) -> *mut ArrowSchema {
+        &mut self.schema
+    }
+
+    /// Convert the export to its `ArrowArray` and `ArrowSchema`.
+    ///
+    /// Safety: the Julia array must not be freed or mutated until the `ArrowArray` has been
+    /// released.
+    pub unsafe fn into_raw(self) -> (ArrowArray, ArrowSchema) {
+        (self.array, self.schema)
+    }
+}
+
+/// Export a Julia array as an Arrow array without copying its data.
+///
+/// The array is exported as a one-dimensional array in column-major order.
+pub trait ToArrow<'view, T>: private::ToArrowPriv {
+    /// Export the array.
+    fn to_arrow(&'view self) -> JlrsResult<ArrowExport<'view, T>>;
+}
+
+impl<'borrow: 'view, 'view, 'array, 'data, T, M> ToArrow<'view, T>
+    for BitsArrayAccessor<'borrow, 'array, 'data, T, M>
+where
+    T: ArrowPrimitive,
+    M: Mutability,
+{
+    fn to_arrow(&'view self) -> JlrsResult<ArrowExport<'view, T>> {
+        let data = self.as_slice();
+        let length = data.len();
+        Ok(export::<T>(length, 0, data.as_ptr().cast(), None))
+    }
+}
+
+impl<'borrow: 'view, 'view, 'array, 'data, T, M> ToArrow<'view, T>
+    for UnionArrayAccessor<'borrow, 'array, 'data, M>
+where
+    T: ArrowPrimitive,
+    M: Mutability,
+{
+    fn to_arrow(&'view self) -> JlrsResult<ArrowExport<'view, T>> {
+        let array = self.array;
+        let elty = array.element_type();
+
+        // Safety: Julia is active, the static global is a DataType.
+        let missing = unsafe {
+            let unrooted = Unrooted::new();
+            static_global!(MISSING, unrooted).as_value()
+        };
+
+        // The element type must be Union{Missing, T}, the tag of Missing is 0 or 1.
+        let components = [0, 1, 2].map(|mut tag| nth_union_component(elty, &mut tag));
+        let missing_tag = match components {
+            [Some(a), Some(b), None] if a == missing && T::valid_field(b) => 0,
+            [Some(a), Some(b), None] if b == missing && T::valid_field(a) => 1,
+            _ => Err(ArrowError::UnsupportedElementType {
+                element_type: elty.display_string_or(CANNOT_DISPLAY_TYPE),
+            })?,
+        };
+
+        if array.element_size() != mem::size_of::<T>() {
+            Err(ArrowError::UnsupportedElementType {
+                element_type: elty.display_string_or(CANNOT_DISPLAY_TYPE),
+            })?
+        }
+
+        let length = self.dimensions().size();
+
+        // Safety: the array is a bits union array, its selector bytes are stored after the data.
+        let selectors = unsafe {
+            slice::from_raw_parts(
+                jl_array_typetagdata(array.unwrap(Private)).cast::<u8>(),
+                length,
+            )
+        };
+
+        let mut validity = vec![0u8; (length + 7) / 8].into_boxed_slice();
+        let mut null_count = 0;
+        for (idx, &selector) in selectors.iter().enumerate() {
+            if selector == missing_tag {
+                null_count += 1;
+            } else {
+                validity[idx / 8] |= 1 << (idx % 8);
+            }
+        }
+
+        Ok(export::<T>(
+            length,
+            null_count,
+            array.data_ptr() as *const c_void,
+            Some(validity),
+        ))
+    }
+}
+
+// Owned by an exported `ArrowArray`.
+struct ExportedData {
+    _buffers: Box<[*const c_void; 2]>,
+    _validity: Option<Box<[u8]>>,
+}
+
+fn export<'view, T: ArrowPrimitive>(
+    length: usize,
+    null_count: usize,
+    data: *const c_void,
+    validity: Option<Box<[u8]>>,
+) -> ArrowExport<'view, T> {
+    let validity_ptr = validity
+        .as_ref()
+        .map(|v| v.as_ptr().cast::<c_void>())
+        .unwrap_or(null());
+
+    let mut buffers = Box::new([validity_ptr, data]);
+    let buffers_ptr = buffers.as_mut_ptr();
+    let private_data = Box::new(ExportedData {
+        _buffers: buffers,
+        _validity: validity,
+    });
+
+    let array = ArrowArray {
+        length: length as i64,
+        null_count: null_count as i64,
+        offset: 0,
+        n_buffers: 2,
+        n_children: 0,
+        buffers: buffers_ptr,
+        children: null_mut(),
+        dictionary: null_mut(),
+        release: Some(release_exported_array),
+        private_data: Box::into_raw(private_data).cast(),
+    };
+
+    let schema = ArrowSchema {
+        format: T::FORMAT.as_ptr().cast(),
+        name: null(),
+        metadata: null(),
+        flags: if validity_ptr.is_null() {
+            0
+        } else {
+            ARROW_FLAG_NULLABLE
+        },
+        n_children: 0,
+        children: null_mut(),
+        dictionary: null_mut(),
+        release: Some(release_exported_schema),
+        private_data: null_mut(),
+    };
+
+    ArrowExport {
+        array,
+        schema,
+        _marker: PhantomData,
+    }
+}
+
+unsafe extern "C" fn release_exported_array(array: *mut ArrowArray) {
+    let array = &mut *array;
+    mem::drop(Box::from_raw(array.private_data.cast::<ExportedData>()));
+    array.private_data = null_mut();
+    array.release = None;
+}
+
+unsafe extern "C" fn release_exported_schema(schema: *mut ArrowSchema) {
+    (*schema).release = None;
+}
+
+// An imported Arrow array, it's released when the last Julia array that uses its buffers has
+// been freed.
+struct Imported {
+    _array: ArrowArray,
+}
+
+// Safety: the array is never accessed, it's only released when it's dropped.
+unsafe impl Sync for Imported {}
+
+unsafe extern "C" fn release_imported(array: *mut c_void) {
+    let imported = IMPORTED.lock().unwrap().remove(&(array as usize));
+    // Release the Arrow array after the lock has been released.
+    mem::drop(imported);
+}
+
+fn check_format(schema: &ArrowSchema, expected: &[&str]) -> JlrsResult<()> {
+    let found = schema.format().ok_or(ArrowError::Released)?;
+    if !expected.iter().any(|f| f.trim_end_matches('\0') == found) {
+        Err(ArrowError::FormatMismatch {
+            expected: expected.join(" or ").replace('\0', ""),
+            found: found.into(),
+        })?
+    }
+
+    Ok(())
+}
+
+fn check_array(array: &ArrowArray, n_buffers: i64) -> JlrsResult<()> {
+    if array.is_released() {
+        Err(ArrowError::Released)?
+    }
+
+    if array.n_buffers != n_buffers {
+        Err(ArrowError::BufferCountMismatch {
+            n_buffers: array.n_buffers,
+            expected: n_buffers,
+        })?
+    }
+
+    if array.length < 0 || array.offset < 0 {
+        Err(ArrowError::InvalidLength)?
+    }
+
+    // Safety: the array has at least one buffer.
+    if array.null_count != 0 && !unsafe { array.buffer(0) }.is_null() {
+        Err(ArrowError::HasNulls {
+            null_count: array.null_count,
+        })?
+    }
+
+    Ok(())
+}
+
+// Returns the nth buffer as a slice of `len` elements starting at `offset`.
+unsafe fn buffer_slice<'a, T>(
+    array: &ArrowArray,
+    n: usize,
+    offset: usize,
+    len: usize,
+) -> JlrsResult<&'a mut [T]> {
+    let ptr = array.buffer(n) as *mut T;
+    if len == 0 {
+        return Ok(slice::from_raw_parts_mut(NonNull::dangling().as_ptr(), 0));
+    }
+
+    if ptr.is_null() {
+        Err(ArrowError::InvalidLength)?
+    }
+
+    if ptr as usize % mem::align_of::<T>() != 0 {
+        Err(ArrowError::Misaligned {
+            buffer: n,
+            align: mem::align_of::<T>(),
+        })?
+    }
+
+    Ok(slice::from_raw_parts_mut(ptr.add(offset), len))
+}
+
+// Create a Julia array that uses `data` as its backing storage and keeps `imported` alive.
+unsafe fn import_slice<'target, T, S>(
+    target: ExtendedTarget<'target, '_, '_, S>,
+    data: &'static mut [T],
+    imported: &Arc<Imported>,
+) -> JlrsResult<TypedArrayData<'target, 'static, S, T>>
+where
+    T: IntoJulia + ValidField,
+    S: Target<'target>,
+{
+    let (output, frame) = target.split();
+    frame.scope(|mut frame| {
+        let len = data.len();
+        let array = TypedArray::<T>::from_slice(frame.as_extended_target(), data, len)?
+            .into_jlrs_result()?;
+
+        IMPORTED
+            .lock()
+            .unwrap()
+            .insert(array.unwrap(Private) as usize, imported.clone());
+        array.as_value().add_ptr_finalizer(release_imported);
+
+        Ok(output.data_from_ptr(array.unwrap_non_null(Private), Private))
+    })
+}
+
+/// Import an Arrow primitive array without nulls as a `Vector{T}`.
+///
+/// The format of the array must be `T::FORMAT`. The data isn't copied, the array is released
+/// when the Julia array has been freed by the GC. If the array can't be imported it's released
+/// immediately.
+///
+/// Safety: `array` and `schema` must be valid according to the Arrow C Data Interface, the data
+/// of the array must not be mutated while the Julia array is in use.
+pub unsafe fn import_primitive<'target, T, S>(
+    target: ExtendedTarget<'target, '_, '_, S>,
+    array: ArrowArray,
+    schema: &ArrowSchema,
+) -> JlrsResult<TypedArrayData<'target, 'static, S, T>>
+where
+    T: ArrowPrimitive,
+    S: Target<'target>,
+{
+    check_format(schema, &[T::FORMAT])?;
+    check_array(&array, 2)?;
+
+    let data = buffer_slice::<T>(&array, 1, array.offset as usize, array.length as usize)?;
+    let imported = Arc::new(Imported { _array: array });
+    import_slice(target, data, &imported)
+}
+
+/// Import an Arrow string array without nulls.
+///
+/// Both `utf8` and `large_utf8` arrays are supported. The array is imported as a `NamedTuple`
+/// with two fields, `offsets` is a `Vector{Int32}` or `Vector{Int64}` that contains the
+/// zero-based offsets of the strings, and `data` is a `Vector{UInt8}` that contains the UTF-8
+/// encoded strings. The `n`th string is `String(data[offsets[n]+1:offsets[n+1]])`.
+///
+/// Neither the offsets nor the data are copied, the array is released when both Julia arrays
+/// have been freed by the GC. If the array can't be imported it's released immediately.
+///
+/// Safety: `array` and `schema` must be valid according to the Arrow C Data Interface, the data
+/// of the array must not be mutated while the Julia arrays are in use.
+pub unsafe fn import_utf8<'target, S>(
+    target: ExtendedTarget<'target, '_, '_, S>,
+    array: ArrowArray,
+    schema: &ArrowSchema,
+) -> JlrsResult<ValueData<'target, 'static, S>>
+where
+    S: Target<'target>,
+{
+    check_format(schema, &["u", "U"])?;
+    check_array(&array, 3)?;
+
+    match schema.format() {
+        Some("u") => import_utf8_with_offsets::<i32, S>(target, array),
+        _ => import_utf8_with_offsets::<i64, S>(target, array),
+    }
+}
+
+unsafe fn import_utf8_with_offsets<'target, O, S>(
+    target: ExtendedTarget<'target, '_, '_, S>,
+    array: ArrowArray,
+) -> JlrsResult<ValueData<'target, 'static, S>>
+where
+    O: ArrowPrimitive + TryInto<usize>,
+    S: Target<'target>,
+{
+    let length = array.length as usize;
+    let offsets = buffer_slice::<O>(&array, 1, array.offset as usize, length + 1)?;
+    let n_bytes = offsets
+        .last()
+        .copied()
+        .map(TryInto::try_into)
+        .unwrap_or(Ok(0))
+        .map_err(|_| ArrowError::InvalidLength)?;
+    let data = buffer_slice::<u8>(&array, 2, 0, n_bytes)?;
+    let imported = Arc::new(Imported { _array: array });
+
+    let (output, frame) = target.split();
+    frame.scope(move |mut frame| {
+        let offsets = import_slice(frame.as_extended_target(), offsets, &imported)?;
+        let data = import_slice(frame.as_extended_target(), data, &imported)?;
+
+        Value::new_named_tuple(
+            frame.extended_target(output),
+            ["offsets", "data"],
+            [offsets.as_value(), data.as_value()],
+        )
+    })
+}
+
+mod private {
+    use crate::data::managed::array::data::accessor::{
+        BitsArrayAccessor, Mutability, UnionArrayAccessor,
+    };
+
+    pub trait ToArrowPriv {}
+
+    impl<'borrow, 'array, 'data, T, M> ToArrowPriv for BitsArrayAccessor<'borrow, 'array, 'data, T, M> where
+        M: Mutability
+    {
+    }
+
+    impl<'borrow, 'array, 'data, M> ToArrowPriv for UnionArrayAccessor<'borrow, 'array, 'data, M> where
+        M: Mutability
+    {
+    }
+}
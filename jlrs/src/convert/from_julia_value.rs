@@ -0,0 +1,205 @@
+//! Convert Julia data to idiomatic Rust data.
+//!
+//! [`Unbox`] requires that the layout of the Rust type matches the layout of the Julia data. The
+//! [`FromJuliaValue`] trait defined in this module is implemented for all types that can be
+//! unboxed as themselves, and additionally for `bool`, `char`, `String`, `Vec<T>`,
+//! `HashMap<K, V>`, `Option<T>` and tuples of types that implement `FromJuliaValue`. It's the
+//! counterpart of [`IntoJuliaValue`].
+//!
+//! [`Unbox`]: crate::convert::unbox::Unbox
+//! [`IntoJuliaValue`]: crate::convert::into_julia_value::IntoJuliaValue
+
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+use super::{into_jlrs_result::IntoJlrsResult, unbox::Unbox};
+use crate::{
+    call::Call,
+    data::{
+        layout::{nothing::Nothing, tuple::Tuple},
+        managed::{
+            array::{dimensions::Dims, Array},
+            module::Module,
+            value::Value,
+            Managed,
+        },
+        types::typecheck::Typecheck,
+    },
+    error::{AccessError, JlrsError, JlrsResult, TypeError, CANNOT_DISPLAY_VALUE},
+    memory::target::frame::GcFrame,
+};
+
+/// Trait implemented by types that can be converted from a Julia value.
+///
+/// Temporary Julia data that is allocated during the conversion, e.g. when an element of an
+/// array of bits-types is accessed, is rooted in a scope created from `frame`.
+pub trait FromJuliaValue: Sized {
+    /// Convert `value` to an instance of `Self`.
+    fn from_julia_value(frame: &mut GcFrame, value: Value) -> JlrsResult<Self>;
+}
+
+impl<U: Unbox<Output = U> + Typecheck> FromJuliaValue for U {
+    fn from_julia_value(_frame: &mut GcFrame, value: Value) -> JlrsResult<Self> {
+        value.unbox::<U>()
+    }
+}
+
+impl FromJuliaValue for bool {
+    fn from_julia_value(_frame: &mut GcFrame, value: Value) -> JlrsResult<Self> {
+        Ok(value.unbox::<bool>()?.as_bool())
+    }
+}
+
+impl FromJuliaValue for char {
+    fn from_julia_value(_frame: &mut GcFrame, value: Value) -> JlrsResult<Self> {
+        let c = value.unbox::<char>()?;
+        let c = c.try_as_char().ok_or_else(|| TypeError::NotA {
+            value: format!("{:#x}", c.as_u32()),
+            field_type: String::from("char"),
+        })?;
+        Ok(c)
+    }
+}
+
+impl FromJuliaValue for String {
+    fn from_julia_value(_frame: &mut GcFrame, value: Value) -> JlrsResult<Self> {
+        match value.unbox::<String>()? {
+            Ok(s) => Ok(s),
+            Err(bytes) => Ok(String::from_utf8(bytes).map_err(JlrsError::other)?),
+        }
+    }
+}
+
+impl<U: FromJuliaValue> FromJuliaValue for Option<U> {
+    fn from_julia_value(frame: &mut GcFrame, value: Value) -> JlrsResult<Self> {
+        if value.is::<Nothing>() {
+            return Ok(None);
+        }
+
+        U::from_julia_value(frame, value).map(Some)
+    }
+}
+
+impl<U: FromJuliaValue> FromJuliaValue for Vec<U> {
+    fn from_julia_value(frame: &mut GcFrame, value: Value) -> JlrsResult<Self> {
+        let array = value.cast::<Array>()?;
+        // Safety: the array is not resized while its elements are converted.
+        let shape = unsafe { array.dimensions().as_slice().to_vec() };
+        let len = shape.iter().product();
+
+        let mut data = Vec::with_capacity(len);
+        // Safety: the array is only accessed immutably.
+        let mut accessor = unsafe { array.indeterminate_data() };
+        let mut index = vec![0; shape.len()];
+        for _ in 0..len {
+            let element = frame.scope(|mut frame| {
+                let element = accessor
+                    .get_value(&mut frame, index.as_slice())?
+                    .ok_or(AccessError::UndefRef)?
+                    .into_jlrs_result()?;
+
+                U::from_julia_value(&mut frame, element)
+            })?;
+            data.push(element);
+
+            // Elements are converted in column-major order.
+            for (idx, dim) in index.iter_mut().zip(shape.iter()) {
+                *idx += 1;
+                if *idx < *dim {
+                    break;
+                }
+                *idx = 0;
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+impl<K, V, S> FromJuliaValue for HashMap<K, V, S>
+where
+    K: FromJuliaValue + Eq + Hash,
+    V: FromJuliaValue,
+    S: BuildHasher + Default,
+{
+    fn from_julia_value(frame: &mut GcFrame, value: Value) -> JlrsResult<Self> {
+        frame.scope(|mut frame| {
+            let base = Module::base(&frame);
+            // Safety: AbstractDict is globally rooted.
+            let abstract_dict = unsafe { base.global(&frame, "AbstractDict")?.as_value() };
+            if !value.isa(abstract_dict) {
+                Err(TypeError::NotA {
+                    value: value.display_string_or(CANNOT_DISPLAY_VALUE),
+                    field_type: String::from("AbstractDict"),
+                })?;
+            }
+
+            // Safety: collecting the pairs of a dictionary is safe.
+            let pairs = unsafe {
+                base.function(&frame, "collect")?
+                    .as_managed()
+                    .call1(&mut frame, value)
+                    .into_jlrs_result()?
+                    .cast::<Array>()?
+            };
+
+            // Safety: the array is only accessed immutably.
+            let len = unsafe { pairs.dimensions().size() };
+            let mut accessor = unsafe { pairs.indeterminate_data() };
+            let mut map = HashMap::with_capacity_and_hasher(len, S::default());
+            for idx in 0..len {
+                let (key, value) = frame.scope(|mut frame| {
+                    let pair = accessor
+                        .get_value(&mut frame, idx)?
+                        .ok_or(AccessError::UndefRef)?
+                        .into_jlrs_result()?;
+
+                    let key = pair.get_nth_field(&mut frame, 0)?;
+                    let key = K::from_julia_value(&mut frame, key)?;
+                    let value = pair.get_nth_field(&mut frame, 1)?;
+                    let value = V::from_julia_value(&mut frame, value)?;
+                    Ok((key, value))
+                })?;
+
+                map.insert(key, value);
+            }
+
+            Ok(map)
+        })
+    }
+}
+
+macro_rules! impl_from_julia_value_tuple {
+    ($n:expr, $($types:ident => $idx:expr),+) => {
+        impl<$($types: FromJuliaValue),+> FromJuliaValue for ($($types,)+) {
+            fn from_julia_value(frame: &mut GcFrame, value: Value) -> JlrsResult<Self> {
+                if !value.is::<Tuple>() || value.n_fields() != $n {
+                    Err(TypeError::NotA {
+                        value: value.display_string_or(CANNOT_DISPLAY_VALUE),
+                        field_type: format!("Tuple with {} fields", $n),
+                    })?;
+                }
+
+                frame.scope(|mut frame| {
+                    Ok(($(
+                        {
+                            let field = value.get_nth_field(&mut frame, $idx)?;
+                            $types::from_julia_value(&mut frame, field)?
+                        },
+                    )+))
+                })
+            }
+        }
+    };
+}
+
+impl_from_julia_value_tuple!(1, A => 0);
+impl_from_julia_value_tuple!(2, A => 0, B => 1);
+impl_from_julia_value_tuple!(3, A => 0, B => 1, C => 2);
+impl_from_julia_value_tuple!(4, A => 0, B => 1, C => 2, D => 3);
+impl_from_julia_value_tuple!(5, A => 0, B => 1, C => 2, D => 3, E => 4);
+impl_from_julia_value_tuple!(6, A => 0, B => 1, C => 2, D => 3, E => 4, F => 5);
+impl_from_julia_value_tuple!(7, A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6);
+impl_from_julia_value_tuple!(8, A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7);
@@ -2,21 +2,35 @@
 //!
 //! A `JuliaResult` contains an exception in its `Err` variant, if you're only interested in
 //! the error message you can convert it to a `JlrsException` with the [`IntoJlrsResult`] trait
-//! defined in this module.
+//! defined in this module. If you need to know what kind of exception has been thrown, the
+//! exception can be decoded instead.
 
 use crate::{
-    data::managed::Managed,
-    error::{JlrsError, JlrsResult, JuliaResult, CANNOT_DISPLAY_VALUE},
+    data::managed::value::ValueRef,
+    error::{Exception, JlrsError, JlrsResult, JuliaResult},
 };
 
 /// Extension trait that lets you convert a `JuliaResult` to a `JlrsResult`.
 ///
-/// If an exception is thrown, this trait's only method converts the exception to an error message
-/// by calling `Base.showerror`.
+/// If an exception is thrown, [`IntoJlrsResult::into_jlrs_result`] converts the exception to an
+/// error message by calling `Base.showerror`. [`IntoJlrsResult::into_decoded_jlrs_result`] also
+/// decodes it with [`Exception::decode`]. The backtrace of the exception isn't available, it
+/// can be captured by calling the function with [`Value::call_with_backtrace`].
+///
+/// [`Value::call_with_backtrace`]: crate::data::managed::value::Value::call_with_backtrace
 pub trait IntoJlrsResult<T>: private::IntoJlrsResultPriv {
     /// Convert `self` to `JlrsResult` by calling `Base.showerror` if an exception has been
     /// thrown.
     fn into_jlrs_result(self) -> JlrsResult<T>;
+
+    /// Convert `self` to `JlrsResult` by decoding the exception with [`Exception::decode`] if an
+    /// exception has been thrown.
+    ///
+    /// Decoding an exception calls several Julia functions to convert its fields to strings, use
+    /// this method only if you need the [`ExceptionKind`] of the exception.
+    ///
+    /// [`ExceptionKind`]: crate::error::exception::ExceptionKind
+    fn into_decoded_jlrs_result(self) -> JlrsResult<T>;
 }
 
 impl<T> IntoJlrsResult<T> for JuliaResult<'_, '_, T> {
@@ -24,11 +38,27 @@ impl<T> IntoJlrsResult<T> for JuliaResult<'_, '_, T> {
     fn into_jlrs_result(self) -> JlrsResult<T> {
         match self {
             Ok(v) => Ok(v),
-            Err(e) => JlrsError::exception_error(e.error_string_or(CANNOT_DISPLAY_VALUE))?,
+            Err(e) => Err(Exception::from_message(e))?,
+        }
+    }
+
+    #[inline]
+    fn into_decoded_jlrs_result(self) -> JlrsResult<T> {
+        match self {
+            Ok(v) => Ok(v),
+            Err(e) => Err(Exception::decode(e))?,
         }
     }
 }
 
+// Convert an unrooted exception to an error. Used to propagate exceptions thrown by calls whose
+// results aren't rooted.
+pub(crate) fn unrooted_exception_error(exception: ValueRef) -> Box<JlrsError> {
+    // Safety: the exception is converted to a string before the GC can free it.
+    let exception = unsafe { Exception::from_message(exception.as_value()) };
+    Box::new(JlrsError::Exception(exception))
+}
+
 mod private {
     use crate::error::JuliaResult;
 
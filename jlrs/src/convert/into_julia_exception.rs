@@ -0,0 +1,221 @@
+//! Convert Rust errors to Julia exceptions.
+//!
+//! Functions exported with the [`julia_module`] macro can return `Result<T, E>` if `T`
+//! implements `IntoJulia` and `E` implements [`IntoJuliaException`]. If an error is returned, it's
+//! converted to a Julia exception which is thrown by the generated Julia function.
+//!
+//! [`JlrsException`] provides constructors for common exception types like `ArgumentError`,
+//! `BoundsError` and `DomainError`, an exception of any other type can be created with
+//! [`JlrsException::new`]. A [`JlrsError`] is converted to a `JlrsCore.JlrsError`.
+//!
+//! ```ignore
+//! fn checked_sqrt(x: f64) -> Result<f64, JlrsException> {
+//!     if x < 0.0 {
+//!         Err(JlrsException::domain_error(x, "sqrt was called with a negative argument"))
+//!     } else {
+//!         Ok(x.sqrt())
+//!     }
+//! }
+//!
+//! julia_module! {
+//!     become checked_sqrt_init;
+//!     fn checked_sqrt(x: f64) -> Result<f64, JlrsException>;
+//! }
+//! ```
+//!
+//! [`julia_module`]: jlrs_macros::julia_module
+
+use std::fmt;
+
+use crate::{
+    convert::{into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia},
+    data::{
+        layout::tuple::Tuple,
+        managed::{
+            datatype::DataType,
+            private::ManagedPriv,
+            string::JuliaString,
+            value::{Value, ValueRef},
+            Managed,
+        },
+        static_data::{define_static_global, static_global},
+    },
+    error::{JlrsError, JlrsResult},
+    memory::target::{frame::GcFrame, Target},
+    private::Private,
+};
+
+define_static_global!(DOMAIN_ERROR, DataType<'static>, "Core.DomainError");
+define_static_global!(JLRS_ERROR, DataType<'static>, "JlrsCore.JlrsError");
+
+/// Trait implemented by errors that can be thrown as a Julia exception.
+pub trait IntoJuliaException {
+    /// Convert `self` to a Julia exception that's rooted in `frame`.
+    fn into_julia_exception<'target>(self, frame: &mut GcFrame<'target>)
+        -> Value<'target, 'static>;
+}
+
+type ConstructException =
+    Box<dyn for<'scope> FnOnce(&mut GcFrame<'scope>) -> JlrsResult<Value<'scope, 'static>>>;
+
+/// A Julia exception that's constructed when it's thrown.
+pub struct JlrsException {
+    construct: ConstructException,
+}
+
+impl JlrsException {
+    /// Create an exception by calling `func`. If `func` returns an error, that error is
+    /// converted to a `JlrsCore.JlrsError` instead.
+    ///
+    /// This can be used to throw an exception of a custom type, e.g. one that has been defined
+    /// in the module that exports the function that returns it:
+    ///
+    /// ```ignore
+    /// JlrsException::new(|frame| unsafe {
+    ///     let msg = JuliaString::new(&mut *frame, "unknown key").as_value();
+    ///     let code = Value::new(&mut *frame, 3i32);
+    ///     Module::main(&frame)
+    ///         .submodule(&frame, "MyModule")?
+    ///         .as_managed()
+    ///         .global(&frame, "MyError")?
+    ///         .as_value()
+    ///         .cast::<DataType>()?
+    ///         .instantiate(frame, [msg, code])?
+    ///         .into_jlrs_result()
+    /// })
+    /// ```
+    pub fn new<F>(func: F) -> Self
+    where
+        F: 'static + for<'scope> FnOnce(&mut GcFrame<'scope>) -> JlrsResult<Value<'scope, 'static>>,
+    {
+        JlrsException {
+            construct: Box::new(func),
+        }
+    }
+
+    /// Create an `ErrorException` with the message `msg`, like `error(msg)` does.
+    pub fn error<S: Into<String>>(msg: S) -> Self {
+        let msg = msg.into();
+        Self::new(move |frame| {
+            let unrooted = frame.unrooted();
+            let msg = JuliaString::new(&mut *frame, msg).as_value();
+            DataType::errorexception_type(&unrooted)
+                .instantiate(frame, [msg])?
+                .into_jlrs_result()
+        })
+    }
+
+    /// Create an `ArgumentError` with the message `msg`.
+    pub fn argument_error<S: Into<String>>(msg: S) -> Self {
+        let msg = msg.into();
+        Self::new(move |frame| {
+            let unrooted = frame.unrooted();
+            let msg = JuliaString::new(&mut *frame, msg).as_value();
+            DataType::argumenterror_type(&unrooted)
+                .instantiate(frame, [msg])?
+                .into_jlrs_result()
+        })
+    }
+
+    /// Create a `BoundsError` that reports that `value` has been accessed at `indices`, which
+    /// are 1-based.
+    ///
+    /// Safety: `value` must not be freed by the GC before the exception has been thrown. This is
+    /// the case if `value` is an argument of the exported function that returns this exception.
+    pub unsafe fn bounds_error(value: Value, indices: &[isize]) -> Self {
+        let value: ValueRef<'static, 'static> = ValueRef::wrap(value.unwrap_non_null(Private));
+        let indices = indices.to_vec();
+        Self::new(move |frame| {
+            let unrooted = frame.unrooted();
+            let value = value.as_value();
+            let output = frame.output();
+            let indices = frame.scope(|mut frame| {
+                let indices = indices
+                    .iter()
+                    .map(|&idx| Value::new(&mut frame, idx))
+                    .collect::<Vec<_>>();
+                Ok(Tuple::new_unchecked(
+                    output.into_extended_target(&mut frame),
+                    indices,
+                ))
+            })?;
+
+            DataType::boundserror_type(&unrooted)
+                .instantiate(frame, [value, indices])?
+                .into_jlrs_result()
+        })
+    }
+
+    /// Create a `DomainError` that reports that `value` is outside the valid domain, with the
+    /// message `msg`.
+    pub fn domain_error<T, S>(value: T, msg: S) -> Self
+    where
+        T: 'static + IntoJulia,
+        S: Into<String>,
+    {
+        let msg = msg.into();
+        Self::new(move |frame| {
+            let value = Value::new(&mut *frame, value);
+            let msg = JuliaString::new(&mut *frame, msg).as_value();
+            static_global!(DOMAIN_ERROR, frame)
+                .instantiate(frame, [value, msg])?
+                .into_jlrs_result()
+        })
+    }
+}
+
+impl From<Box<JlrsError>> for JlrsException {
+    fn from(error: Box<JlrsError>) -> Self {
+        JlrsException::new(move |frame| Ok(error.into_julia_exception(frame)))
+    }
+}
+
+impl fmt::Debug for JlrsException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JlrsException").finish_non_exhaustive()
+    }
+}
+
+impl IntoJuliaException for JlrsException {
+    fn into_julia_exception<'target>(
+        self,
+        frame: &mut GcFrame<'target>,
+    ) -> Value<'target, 'static> {
+        let output = frame.output();
+        let res = frame.scope(|mut frame| {
+            let exception = (self.construct)(&mut frame)?;
+            Ok(exception.root(output))
+        });
+
+        match res {
+            Ok(exception) => exception,
+            Err(e) => e.into_julia_exception(frame),
+        }
+    }
+}
+
+impl IntoJuliaException for JlrsError {
+    fn into_julia_exception<'target>(
+        self,
+        frame: &mut GcFrame<'target>,
+    ) -> Value<'target, 'static> {
+        let output = frame.output();
+        frame
+            .scope(|mut frame| unsafe {
+                let msg = JuliaString::new(&mut frame, format!("{}", self)).as_value();
+                let exception =
+                    static_global!(JLRS_ERROR, frame).instantiate_unchecked(&mut frame, [msg]);
+                Ok(exception.root(output))
+            })
+            .unwrap()
+    }
+}
+
+impl IntoJuliaException for Box<JlrsError> {
+    fn into_julia_exception<'target>(
+        self,
+        frame: &mut GcFrame<'target>,
+    ) -> Value<'target, 'static> {
+        (*self).into_julia_exception(frame)
+    }
+}
@@ -0,0 +1,389 @@
+//! Convert idiomatic Rust data to Julia.
+//!
+//! [`IntoJulia`] only supports bits-types. The [`IntoJuliaValue`] trait defined in this module
+//! is implemented for all types that implement `IntoJulia`, and additionally for `String`,
+//! `Vec<T>`, `HashMap<K, V>`, `Option<T>` and tuples of types that implement `IntoJuliaValue`.
+//! These are converted to a `String`, a `Vector{T}`, a `Dict{K, V}`, an instance of
+//! `Union{Nothing, T}` and a `Tuple` respectively.
+//!
+//! ```
+//! # use jlrs::prelude::*;
+//! # use jlrs::util::test::JULIA;
+//! use jlrs::convert::into_julia_value::IntoJuliaValue;
+//! # fn main() {
+//! # JULIA.with(|j| {
+//! # let mut julia = j.borrow_mut();
+//! # let mut frame = StackFrame::new();
+//! # let mut julia = julia.instance(&mut frame);
+//! julia
+//!     .scope(|mut frame| {
+//!         let data = vec![Some(String::from("foo")), None];
+//!         let value = data.into_julia_value(frame.as_extended_target())?;
+//!
+//!         let func = Module::base(&frame).function(&frame, "length")?.as_managed();
+//!         let len = unsafe { func.call1(&mut frame, value) }
+//!             .into_jlrs_result()?
+//!             .unbox::<isize>()?;
+//!
+//!         assert_eq!(len, 2);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! # });
+//! # }
+//! ```
+//!
+//! [`IntoJulia`]: crate::convert::into_julia::IntoJulia
+
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+use super::{into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia};
+use crate::{
+    call::Call,
+    data::{
+        layout::tuple::Tuple,
+        managed::{
+            array::Array,
+            datatype::DataType,
+            module::Module,
+            string::JuliaString,
+            union::Union,
+            union_all::UnionAll,
+            value::{Value, ValueData},
+            Managed,
+        },
+    },
+    error::JlrsResult,
+    memory::target::{ExtendedTarget, Target},
+};
+
+/// Trait implemented by types that can be converted to a Julia value.
+///
+/// Unlike [`IntoJulia`], this trait is not limited to bits-types. It's implemented for all types
+/// that implement `IntoJulia`, and for `String`, `Vec<T>`, `HashMap<K, V>`, `Option<T>` and
+/// tuples with up to eight elements of types that implement this trait.
+///
+/// [`IntoJulia`]: crate::convert::into_julia::IntoJulia
+pub trait IntoJuliaValue {
+    /// Returns the Julia type associated with the implementor.
+    ///
+    /// The value returned by [`IntoJuliaValue::into_julia_value`] is an instance of this type,
+    /// but its concrete type can be more specific. For example, the type associated with
+    /// `Option<T>` is `Union{Nothing, T}`.
+    fn julia_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>;
+
+    /// Convert `self` to a Julia value.
+    fn into_julia_value<'target, T>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>;
+
+    #[doc(hidden)]
+    fn vec_into_julia_value<'target, T>(
+        data: Vec<Self>,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        Self: Sized,
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+        frame.scope(|mut frame| {
+            let elty = Self::julia_type(frame.as_extended_target())?;
+            let mut array =
+                Array::new_for(frame.as_extended_target(), data.len(), elty).into_jlrs_result()?;
+
+            {
+                // Safety: the array has just been allocated and is not accessed from Julia.
+                let mut accessor = unsafe { array.indeterminate_data_mut() };
+                for (idx, element) in data.into_iter().enumerate() {
+                    frame.scope(|mut frame| {
+                        let element = element.into_julia_value(frame.as_extended_target())?;
+                        accessor
+                            .set_value(&mut frame, idx, Some(element))?
+                            .into_jlrs_result()
+                    })?;
+                }
+            }
+
+            Ok(array.as_value().root(target))
+        })
+    }
+}
+
+impl<U: IntoJulia> IntoJuliaValue for U {
+    fn julia_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, _) = target.split();
+        // Safety: the type is immediately rooted.
+        unsafe { Ok(U::julia_type(&target).as_value().root(target)) }
+    }
+
+    fn into_julia_value<'target, T>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, _) = target.split();
+        Ok(Value::new(target, self))
+    }
+
+    fn vec_into_julia_value<'target, T>(
+        data: Vec<Self>,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        // The element type is a bits-type, so the data can be stored inline.
+        let (target, frame) = target.split();
+        frame.scope(|mut frame| {
+            let n = data.len();
+            let array = Array::from_vec(frame.as_extended_target(), data, n)?.into_jlrs_result()?;
+            Ok(array.as_value().root(target))
+        })
+    }
+}
+
+impl IntoJuliaValue for String {
+    fn julia_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, _) = target.split();
+        Ok(DataType::string_type(&target).as_value().root(target))
+    }
+
+    fn into_julia_value<'target, T>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        self.as_str().into_julia_value(target)
+    }
+}
+
+impl IntoJuliaValue for &str {
+    fn julia_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        String::julia_type(target)
+    }
+
+    fn into_julia_value<'target, T>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, _) = target.split();
+        // Safety: the string is immediately rooted.
+        unsafe { Ok(JuliaString::new(&target, self).as_value().root(target)) }
+    }
+}
+
+impl<U: IntoJuliaValue> IntoJuliaValue for Option<U> {
+    fn julia_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+        frame.scope(|mut frame| {
+            let nothing = DataType::nothing_type(&frame).as_value();
+            let ty = U::julia_type(frame.as_extended_target())?;
+            let ty = Union::new(&mut frame, [nothing, ty]).into_jlrs_result()?;
+            Ok(ty.root(target))
+        })
+    }
+
+    fn into_julia_value<'target, T>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        match self {
+            Some(value) => value.into_julia_value(target),
+            None => {
+                let (target, _) = target.split();
+                Ok(Value::nothing(&target).root(target))
+            }
+        }
+    }
+}
+
+impl<U: IntoJuliaValue> IntoJuliaValue for Vec<U> {
+    fn julia_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+        frame.scope(|mut frame| {
+            let elty = U::julia_type(frame.as_extended_target())?;
+            let rank = Value::new(&mut frame, 1isize);
+            let ty = UnionAll::array_type(&frame)
+                .as_value()
+                .apply_type(&mut frame, [elty, rank])
+                .into_jlrs_result()?;
+
+            Ok(ty.root(target))
+        })
+    }
+
+    fn into_julia_value<'target, T>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        U::vec_into_julia_value(self, target)
+    }
+}
+
+impl<K, V, S> IntoJuliaValue for HashMap<K, V, S>
+where
+    K: IntoJuliaValue + Eq + Hash,
+    V: IntoJuliaValue,
+    S: BuildHasher,
+{
+    fn julia_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+        frame.scope(|mut frame| {
+            let key_type = K::julia_type(frame.as_extended_target())?;
+            let value_type = V::julia_type(frame.as_extended_target())?;
+
+            let ty = Module::base(&frame)
+                .global(&mut frame, "Dict")?
+                .apply_type(&mut frame, [key_type, value_type])
+                .into_jlrs_result()?;
+
+            Ok(ty.root(target))
+        })
+    }
+
+    fn into_julia_value<'target, T>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueData<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+        frame.scope(|mut frame| {
+            let ty = Self::julia_type(frame.as_extended_target())?;
+
+            // Safety: calling the constructor of a Dict and setindex! is safe.
+            unsafe {
+                let dict = ty.call0(&mut frame).into_jlrs_result()?;
+                let setindex = Module::base(&frame)
+                    .function(&frame, "setindex!")?
+                    .as_managed();
+
+                for (key, value) in self {
+                    frame.scope(|mut frame| {
+                        let key = key.into_julia_value(frame.as_extended_target())?;
+                        let value = value.into_julia_value(frame.as_extended_target())?;
+                        setindex
+                            .call3(&mut frame, dict, value, key)
+                            .into_jlrs_result()?;
+                        Ok(())
+                    })?;
+                }
+
+                Ok(dict.root(target))
+            }
+        })
+    }
+}
+
+macro_rules! impl_into_julia_value_tuple {
+    ($n:expr, $($types:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<$($types: IntoJuliaValue),+> IntoJuliaValue for ($($types,)+) {
+            fn julia_type<'target, T>(
+                target: ExtendedTarget<'target, '_, '_, T>,
+            ) -> JlrsResult<ValueData<'target, 'static, T>>
+            where
+                T: Target<'target>,
+            {
+                let (target, frame) = target.split();
+                frame.scope(|mut frame| {
+                    let types: [Value; $n] = [
+                        $($types::julia_type(frame.as_extended_target())?),+
+                    ];
+
+                    let ty = DataType::tuple_type(&frame)
+                        .as_value()
+                        .apply_type(&mut frame, types)
+                        .into_jlrs_result()?;
+
+                    Ok(ty.root(target))
+                })
+            }
+
+            fn into_julia_value<'target, T>(
+                self,
+                target: ExtendedTarget<'target, '_, '_, T>,
+            ) -> JlrsResult<ValueData<'target, 'static, T>>
+            where
+                T: Target<'target>,
+            {
+                let ($($types,)+) = self;
+                let (target, frame) = target.split();
+                frame.scope(|mut frame| {
+                    let values: [Value; $n] = [
+                        $($types.into_julia_value(frame.as_extended_target())?),+
+                    ];
+
+                    let tuple = Tuple::new(frame.as_extended_target(), values)
+                        .into_jlrs_result()?;
+
+                    Ok(tuple.root(target))
+                })
+            }
+        }
+    };
+}
+
+impl_into_julia_value_tuple!(1, A);
+impl_into_julia_value_tuple!(2, A, B);
+impl_into_julia_value_tuple!(3, A, B, C);
+impl_into_julia_value_tuple!(4, A, B, C, D);
+impl_into_julia_value_tuple!(5, A, B, C, D, E);
+impl_into_julia_value_tuple!(6, A, B, C, D, E, F);
+impl_into_julia_value_tuple!(7, A, B, C, D, E, F, G);
+impl_into_julia_value_tuple!(8, A, B, C, D, E, F, G, H);
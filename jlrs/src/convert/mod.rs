@@ -1,12 +1,19 @@
 //! Traits for converting data.
 
+#[cfg(feature = "jlrs-arrow")]
+pub mod arrow;
 pub mod ccall_types;
 pub mod compatible;
+pub mod from_julia_value;
 pub mod into_jlrs_result;
 pub mod into_julia;
+pub mod into_julia_exception;
+pub mod into_julia_value;
 #[cfg(feature = "async-rt")]
 pub mod into_result;
 #[cfg(feature = "jlrs-ndarray")]
 pub mod ndarray;
+#[cfg(feature = "jlrs-serde")]
+pub mod serde;
 pub mod to_symbol;
 pub mod unbox;
@@ -1,13 +1,61 @@
-//! Borrow data from Julia arrays as `ndarray`'s `ArrayView` and `ArrayViewMut`.
+//! Borrow data from Julia arrays as `ndarray`'s `ArrayView` and `ArrayViewMut`, and convert
+//! `ndarray`'s arrays to Julia arrays.
+//!
+//! Julia arrays are stored in column-major order, while `ndarray` supports arbitrary strides. An
+//! owned `Array` or a borrowed `ArrayViewMut` can be converted to a Julia array with
+//! [`IntoJuliaArray`]. If its data is stored contiguously in column-major order, the buffer is
+//! moved or borrowed as the backing storage of the Julia array. Otherwise, the layout is
+//! preserved by wrapping the buffer: data in row-major order is wrapped in a
+//! `PermutedDimsArray`, and strided data in a view of a reshaped array, which is wrapped in a
+//! `PermutedDimsArray` if the axes aren't ordered by increasing stride. The data is only copied
+//! if the strides can't be expressed this way, e.g. if elements overlap or if the buffer of a
+//! view ends before the last block of the axis with the largest stride.
+//!
+//! A mutable view is only borrowed if it contains every element between its first and last
+//! element, otherwise it's copied. The elements it skips may be borrowed by other views, e.g.
+//! those returned by `multi_slice_mut`, and would be accessible from Julia through the parent of
+//! the wrapping view.
 
-use ndarray::{ArrayView, ArrayViewMut, Dim, IntoDimension, IxDynImpl, ShapeBuilder};
+use ndarray::{
+    Array as NdArray, ArrayView, ArrayViewMut, Dim, Dimension, IntoDimension, IxDynImpl,
+    ShapeBuilder,
+};
 
-use super::compatible::{Compatible, CompatibleCast};
-use crate::data::managed::array::data::{
-    accessor::{BitsArrayAccessor, InlinePtrArrayAccessor, Mutability, Mutable},
-    copied::CopiedArray,
+use super::{
+    compatible::{Compatible, CompatibleCast},
+    into_jlrs_result::IntoJlrsResult,
+    into_julia::IntoJulia,
+};
+use crate::{
+    call::Call,
+    data::{
+        layout::valid_layout::ValidField,
+        managed::{
+            array::{
+                data::{
+                    accessor::{BitsArrayAccessor, InlinePtrArrayAccessor, Mutability, Mutable},
+                    copied::CopiedArray,
+                },
+                dimensions::Dims,
+                Array, TypedArray, TypedArrayData, TypedRankedArray, TypedRankedArrayData,
+            },
+            private::ManagedPriv,
+            value::{Value, ValueData},
+            Managed,
+        },
+        static_data::{define_static_global, static_global},
+    },
+    error::{ArrayLayoutError, JlrsResult},
+    memory::target::{frame::GcFrame, ExtendedTarget, Target},
+    private::Private,
 };
 
+define_static_global!(PERMUTED_DIMS_ARRAY, "Base.PermutedDimsArray");
+define_static_global!(RESHAPE, "Base.reshape");
+define_static_global!(VIEW, "Base.view");
+define_static_global!(STEP_RANGE, "Base.StepRange");
+define_static_global!(UNIT_RANGE, "Base.UnitRange");
+
 /// Trait to borrow Julia arrays with inline data as `ndarray`'s `ArrayView`.
 pub trait NdArrayView<'view, T>: private::NdArrayPriv {
     /// Borrow the data in the array as an `ArrayView`.
@@ -119,12 +167,632 @@ impl<'view, T> NdArrayViewMut<'view, T> for CopiedArray<T> {
     }
 }
 
+/// Trait to convert `ndarray`'s owned arrays and mutable views to Julia arrays.
+///
+/// Owned arrays are converted to arrays that own their data, mutable views to arrays that borrow
+/// their data. See the [module-level docs] for more information about how the layout of the data
+/// is preserved.
+///
+/// [module-level docs]: self
+pub trait IntoJuliaArray<'data, T>: private::IntoJuliaArrayPriv
+where
+    T: IntoJulia + ValidField,
+{
+    /// Convert `self` to a Julia array without copying its data.
+    ///
+    /// The result is an `Array{T, N}` if the data is stored contiguously in column-major order,
+    /// otherwise it's a `PermutedDimsArray` or a view that wraps the data. The data is copied to
+    /// a new `Array{T, N}` if its layout can't be preserved, or if `self` is a mutable view that
+    /// doesn't contain every element between its first and last element.
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<ValueData<'target, 'data, S>>
+    where
+        S: Target<'target>;
+
+    /// Convert `self` to a `TypedArray`.
+    ///
+    /// The data is moved or borrowed if it's stored contiguously in column-major order,
+    /// otherwise it's copied to a new array in column-major order.
+    fn into_typed_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayData<'target, 'data, S, T>>
+    where
+        S: Target<'target>;
+
+    /// Convert `self` to a `TypedRankedArray` of rank `N`.
+    ///
+    /// This method behaves like [`IntoJuliaArray::into_typed_array`], but returns
+    /// `ArrayLayoutError::RankMismatch` if the rank of `self` is not `N`.
+    fn into_typed_ranked_array<'target, S, const N: isize>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedRankedArrayData<'target, 'data, S, T, N>>
+    where
+        S: Target<'target>;
+}
+
+impl<T, D> IntoJuliaArray<'static, T> for NdArray<T, D>
+where
+    T: IntoJulia + ValidField + Clone,
+    D: Dimension,
+{
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<ValueData<'target, 'static, S>>
+    where
+        S: Target<'target>,
+    {
+        StridedData::from_owned(self).into_julia_array(target)
+    }
+
+    fn into_typed_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayData<'target, 'static, S, T>>
+    where
+        S: Target<'target>,
+    {
+        StridedData::from_owned(self).into_typed_array(target)
+    }
+
+    fn into_typed_ranked_array<'target, S, const N: isize>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedRankedArrayData<'target, 'static, S, T, N>>
+    where
+        S: Target<'target>,
+    {
+        check_rank::<N>(self.ndim())?;
+        StridedData::from_owned(self).into_typed_ranked_array(target)
+    }
+}
+
+impl<'data, T, D> IntoJuliaArray<'data, T> for ArrayViewMut<'data, T, D>
+where
+    T: IntoJulia + ValidField + Clone,
+    D: Dimension,
+{
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<ValueData<'target, 'data, S>>
+    where
+        S: Target<'target>,
+    {
+        StridedData::from_view(self).into_julia_array(target)
+    }
+
+    fn into_typed_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayData<'target, 'data, S, T>>
+    where
+        S: Target<'target>,
+    {
+        StridedData::from_view(self).into_typed_array(target)
+    }
+
+    fn into_typed_ranked_array<'target, S, const N: isize>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedRankedArrayData<'target, 'data, S, T, N>>
+    where
+        S: Target<'target>,
+    {
+        check_rank::<N>(self.ndim())?;
+        StridedData::from_view(self).into_typed_ranked_array(target)
+    }
+}
+
+fn check_rank<const N: isize>(rank: usize) -> JlrsResult<()> {
+    if rank as isize != N {
+        Err(ArrayLayoutError::RankMismatch {
+            found: rank as isize,
+            provided: N,
+        })?
+    }
+
+    Ok(())
+}
+
+// The buffer that contains the data of an ndarray array.
+enum Buffer<'data, T> {
+    Owned(Vec<T>),
+    Borrowed(&'data mut [T]),
+}
+
+impl<'data, T: IntoJulia + ValidField> Buffer<'data, T> {
+    fn len(&self) -> usize {
+        match self {
+            Buffer::Owned(data) => data.len(),
+            Buffer::Borrowed(data) => data.len(),
+        }
+    }
+
+    fn as_slice(&self) -> &[T] {
+        match self {
+            Buffer::Owned(data) => data,
+            Buffer::Borrowed(data) => data,
+        }
+    }
+
+    // Only keep the `len` elements starting at `offset`.
+    fn trim(self, offset: usize, len: usize) -> Self {
+        match self {
+            Buffer::Owned(mut data) => {
+                data.truncate(offset + len);
+                data.drain(..offset);
+                Buffer::Owned(data)
+            }
+            Buffer::Borrowed(data) => Buffer::Borrowed(&mut data[offset..offset + len]),
+        }
+    }
+
+    // Create a Julia array with dimensions `dims` backed by this buffer.
+    fn into_array<'target>(
+        self,
+        frame: &mut GcFrame<'target>,
+        dims: &[usize],
+    ) -> JlrsResult<TypedArray<'target, 'data, T>> {
+        match self {
+            Buffer::Owned(data) => {
+                TypedArray::from_vec(frame.as_extended_target(), data, dims)?.into_jlrs_result()
+            }
+            Buffer::Borrowed(data) => {
+                TypedArray::from_slice(frame.as_extended_target(), data, dims)?.into_jlrs_result()
+            }
+        }
+    }
+}
+
+// The layout of a view of a reshaped array that preserves the strides of the data.
+struct StridedView {
+    // The dimensions of the reshaped array.
+    parent_dims: Vec<usize>,
+    // The number of elements in the reshaped array.
+    parent_len: usize,
+    // The one-based index in the first dimension of the reshaped array.
+    first: usize,
+    // The start, step and stop of the ranges in the other dimensions.
+    ranges: Vec<(isize, isize, isize)>,
+    // The one-based permutation that restores the order of the axes.
+    perm: Vec<isize>,
+}
+
+// The data of an ndarray array, its dimensions and strides in number of elements, and the
+// position of its first element in the buffer.
+struct StridedData<'data, T> {
+    buffer: Buffer<'data, T>,
+    offset: usize,
+    dims: Vec<usize>,
+    strides: Vec<isize>,
+}
+
+impl<T, D> From<NdArray<T, D>> for StridedData<'static, T>
+where
+    D: Dimension,
+{
+    fn from(array: NdArray<T, D>) -> Self {
+        let dims = array.shape().to_vec();
+        let strides = array.strides().to_vec();
+        let first = array.as_ptr();
+        let data = array.into_raw_vec();
+
+        let offset = match std::mem::size_of::<T>() {
+            0 => 0,
+            sz => (first as usize - data.as_ptr() as usize) / sz,
+        };
+
+        StridedData {
+            buffer: Buffer::Owned(data),
+            offset,
+            dims,
+            strides,
+        }
+    }
+}
+
+impl<'data, T: IntoJulia + ValidField + Clone> StridedData<'data, T> {
+    fn from_owned<D: Dimension>(array: NdArray<T, D>) -> StridedData<'static, T> {
+        StridedData::from(array)
+    }
+
+    fn from_view<D: Dimension>(mut view: ArrayViewMut<'data, T, D>) -> Self {
+        let dims = view.shape().to_vec();
+        let strides = view.strides().to_vec();
+        let len = dims.iter().product::<usize>();
+
+        if dims.contains(&0) {
+            return StridedData {
+                buffer: Buffer::Owned(Vec::new()),
+                offset: 0,
+                dims,
+                strides,
+            };
+        }
+
+        // The range of offsets of all elements relative to the first element.
+        let (min, max) = dims
+            .iter()
+            .zip(strides.iter())
+            .fold((0, 0), |(min, max), (&n, &s)| {
+                let extent = s * (n as isize - 1);
+                (min + extent.min(0), max + extent.max(0))
+            });
+
+        // The elements of a mutable view don't alias, if the range contains more elements than
+        // the view it doesn't own all of them and the data is copied in column-major order.
+        let span = (max - min) as usize + 1;
+        if span != len {
+            let data = view.reversed_axes().iter().cloned().collect::<Vec<_>>();
+            let strides = dims
+                .iter()
+                .scan(1, |stride, &n| {
+                    let s = *stride;
+                    *stride *= n as isize;
+                    Some(s)
+                })
+                .collect();
+
+            return StridedData {
+                buffer: Buffer::Owned(data),
+                offset: 0,
+                dims,
+                strides,
+            };
+        }
+
+        // Safety: the view contains every element in this range, the buffer is borrowed
+        // mutably for `'data`.
+        let buffer = unsafe {
+            let start = view.as_mut_ptr().offset(min);
+            std::slice::from_raw_parts_mut(start, span)
+        };
+
+        StridedData {
+            buffer: Buffer::Borrowed(buffer),
+            offset: -min as usize,
+            dims,
+            strides,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    // Returns `true` if the data is stored contiguously with the first axis varying fastest.
+    fn is_contiguous<'a>(dims: impl Iterator<Item = (&'a usize, &'a isize)>) -> bool {
+        let mut expected = 1;
+        for (&n, &s) in dims {
+            if n != 1 && s != expected {
+                return false;
+            }
+            expected *= n as isize;
+        }
+
+        true
+    }
+
+    fn is_column_major(&self) -> bool {
+        Self::is_contiguous(self.dims.iter().zip(self.strides.iter()))
+    }
+
+    fn is_row_major(&self) -> bool {
+        Self::is_contiguous(self.dims.iter().zip(self.strides.iter()).rev())
+    }
+
+    // Copy the data to a new buffer in column-major order.
+    fn copy_column_major(&self) -> Vec<T> {
+        let len = self.len();
+        let data = self.buffer.as_slice();
+        let mut copied = Vec::with_capacity(len);
+        let mut index = vec![0; self.dims.len()];
+
+        for _ in 0..len {
+            let offset = index
+                .iter()
+                .zip(self.strides.iter())
+                .fold(self.offset as isize, |offset, (&i, &s)| {
+                    offset + i as isize * s
+                });
+            copied.push(data[offset as usize].clone());
+
+            for (i, &n) in index.iter_mut().zip(self.dims.iter()) {
+                *i += 1;
+                if *i < n {
+                    break;
+                }
+                *i = 0;
+            }
+        }
+
+        copied
+    }
+
+    // Compute the layout of a view of a reshaped array that preserves the strides, or `None` if
+    // the strides can't be preserved that way.
+    fn strided_view(&self) -> Option<StridedView> {
+        let rank = self.dims.len();
+
+        // The axes that have more than one element, sorted by increasing stride.
+        let mut axes = (0..rank).filter(|&i| self.dims[i] > 1).collect::<Vec<_>>();
+        axes.sort_by_key(|&i| self.strides[i].unsigned_abs());
+        if axes.is_empty() || axes.iter().any(|&i| self.strides[i] == 0) {
+            return None;
+        }
+
+        // The index of the element with the lowest address.
+        let low = self
+            .dims
+            .iter()
+            .zip(self.strides.iter())
+            .filter(|(_, &s)| s < 0)
+            .fold(self.offset as isize, |low, (&n, &s)| {
+                low + s * (n as isize - 1)
+            }) as usize;
+
+        // The reshaped array has the dimensions (s1, s2/s1, ..., sn/sn-1, rest), where si is the
+        // stride of the ith axis in `axes`. Moving one step in dimension i + 1 of the reshaped
+        // array moves si elements in the buffer.
+        let mut parent_dims = Vec::with_capacity(axes.len() + 1);
+        let mut prev = 1;
+        for &axis in axes.iter() {
+            let stride = self.strides[axis].unsigned_abs();
+            if stride % prev != 0 {
+                return None;
+            }
+            parent_dims.push(stride / prev);
+            prev = stride;
+        }
+
+        let rest = self.buffer.len() / prev;
+        parent_dims.push(rest);
+
+        // Decompose the index of the element with the lowest address into an index in the
+        // reshaped array, every axis must fit in its dimension.
+        let mut remaining = low;
+        let first = remaining % parent_dims[0];
+        remaining /= parent_dims[0];
+
+        let mut ranges = Vec::with_capacity(rank);
+        for (i, &axis) in axes.iter().enumerate() {
+            let dim = parent_dims[i + 1];
+            let start = if i + 1 == axes.len() {
+                remaining
+            } else {
+                let start = remaining % dim;
+                remaining /= dim;
+                start
+            };
+
+            let n = self.dims[axis];
+            if start + n > dim {
+                return None;
+            }
+
+            let (start, stop) = (start as isize + 1, (start + n) as isize);
+            if self.strides[axis] > 0 {
+                ranges.push((start, 1, stop));
+            } else {
+                ranges.push((stop, -1, start));
+            }
+        }
+
+        // Axes with a single element are added as trailing dimensions of length 1.
+        let mut order = axes;
+        for axis in (0..rank).filter(|&i| self.dims[i] <= 1) {
+            parent_dims.push(1);
+            ranges.push((1, 1, 1));
+            order.push(axis);
+        }
+
+        let mut perm = vec![0; rank];
+        for (position, &axis) in order.iter().enumerate() {
+            perm[axis] = position as isize + 1;
+        }
+
+        Some(StridedView {
+            parent_len: parent_dims.iter().product(),
+            parent_dims,
+            first: first + 1,
+            ranges,
+            perm,
+        })
+    }
+
+    fn into_typed_array_in<'target>(
+        self,
+        frame: &mut GcFrame<'target>,
+    ) -> JlrsResult<TypedArray<'target, 'data, T>> {
+        let len = self.len();
+        if len == 0 {
+            return Buffer::Owned(Vec::new()).into_array(frame, &self.dims);
+        }
+
+        if self.is_column_major() {
+            self.buffer
+                .trim(self.offset, len)
+                .into_array(frame, &self.dims)
+        } else {
+            Buffer::Owned(self.copy_column_major()).into_array(frame, &self.dims)
+        }
+    }
+
+    fn into_typed_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayData<'target, 'data, S, T>>
+    where
+        S: Target<'target>,
+    {
+        let (output, frame) = target.split();
+        frame.scope(|mut frame| {
+            let array = self.into_typed_array_in(&mut frame)?;
+            // Safety: the array has just been allocated.
+            unsafe { Ok(output.data_from_ptr(array.unwrap_non_null(Private), Private)) }
+        })
+    }
+
+    fn into_typed_ranked_array<'target, S, const N: isize>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedRankedArrayData<'target, 'data, S, T, N>>
+    where
+        S: Target<'target>,
+    {
+        let (output, frame) = target.split();
+        frame.scope(|mut frame| {
+            let array = self.into_typed_array_in(&mut frame)?;
+            // Safety: the array has just been allocated, its rank has been checked.
+            unsafe {
+                Ok(output.data_from_ptr::<TypedRankedArray<T, N>>(
+                    array.unwrap_non_null(Private),
+                    Private,
+                ))
+            }
+        })
+    }
+
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<ValueData<'target, 'data, S>>
+    where
+        S: Target<'target>,
+    {
+        let (output, frame) = target.split();
+        frame.scope(|mut frame| {
+            let len = self.len();
+            if len == 0 || self.is_column_major() {
+                let array = self.into_typed_array_in(&mut frame)?;
+                // Safety: the array has just been allocated.
+                return unsafe {
+                    Ok(output.data_from_ptr(array.as_value().unwrap_non_null(Private), Private))
+                };
+            }
+
+            let wrapped = if self.is_row_major() {
+                let reversed = self.dims.iter().copied().rev().collect::<Vec<_>>();
+                let perm = (1..=self.dims.len() as isize).rev().collect::<Vec<_>>();
+                let array = self
+                    .buffer
+                    .trim(self.offset, len)
+                    .into_array(&mut frame, &reversed)?;
+                permute_dims(&mut frame, array.as_value(), perm)?
+            } else if let Some(view) = self.strided_view() {
+                let len = self.buffer.len();
+                let array = self.buffer.into_array(&mut frame, &[len])?;
+                strided_view(&mut frame, array.as_value(), view)?
+            } else {
+                let array =
+                    Buffer::Owned(self.copy_column_major()).into_array(&mut frame, &self.dims)?;
+                array.as_value()
+            };
+
+            // Safety: the value has just been allocated.
+            unsafe { Ok(output.data_from_ptr(wrapped.unwrap_non_null(Private), Private)) }
+        })
+    }
+}
+
+// Wrap `array` in a `PermutedDimsArray` with the one-based permutation `perm`.
+fn permute_dims<'target, 'data>(
+    frame: &mut GcFrame<'target>,
+    array: Value<'_, 'data>,
+    perm: Vec<isize>,
+) -> JlrsResult<Value<'target, 'data>> {
+    // Safety: PermutedDimsArray is called with an array and a valid permutation.
+    unsafe {
+        let n = perm.len();
+        let perm = Array::from_vec(frame.as_extended_target(), perm, n)?
+            .into_jlrs_result()?
+            .as_value();
+
+        static_global!(PERMUTED_DIMS_ARRAY, frame)
+            .call2(frame, array, perm)
+            .into_jlrs_result()
+    }
+}
+
+// Wrap the one-dimensional `array` in a view of a reshaped array with the layout `view`.
+fn strided_view<'target, 'data>(
+    frame: &mut GcFrame<'target>,
+    array: Value<'_, 'data>,
+    view: StridedView,
+) -> JlrsResult<Value<'target, 'data>> {
+    // Safety: the functions are called with arguments of the correct types, the ranges are in
+    // bounds.
+    unsafe {
+        let unrooted = frame.unrooted();
+        let mut parent = array;
+        if view.parent_len != array.cast::<Array>()?.dimensions().size() {
+            let start = Value::new(&mut *frame, 1isize);
+            let stop = Value::new(&mut *frame, view.parent_len as isize);
+            let range = static_global!(UNIT_RANGE, unrooted)
+                .call2(&mut *frame, start, stop)
+                .into_jlrs_result()?;
+            parent = static_global!(VIEW, unrooted)
+                .call2(&mut *frame, parent, range)
+                .into_jlrs_result()?;
+        }
+
+        let mut args = Vec::with_capacity(view.parent_dims.len() + 1);
+        args.push(parent);
+        for &dim in view.parent_dims.iter() {
+            args.push(Value::new(&mut *frame, dim as isize));
+        }
+        let reshaped = static_global!(RESHAPE, unrooted)
+            .call(&mut *frame, args)
+            .into_jlrs_result()?;
+
+        let mut args = Vec::with_capacity(view.ranges.len() + 2);
+        args.push(reshaped);
+        args.push(Value::new(&mut *frame, view.first as isize));
+        for &(start, step, stop) in view.ranges.iter() {
+            let start = Value::new(&mut *frame, start);
+            let step = Value::new(&mut *frame, step);
+            let stop = Value::new(&mut *frame, stop);
+            let range = static_global!(STEP_RANGE, unrooted)
+                .call3(&mut *frame, start, step, stop)
+                .into_jlrs_result()?;
+            args.push(range);
+        }
+        let viewed = static_global!(VIEW, unrooted)
+            .call(&mut *frame, args)
+            .into_jlrs_result()?;
+
+        if view
+            .perm
+            .iter()
+            .enumerate()
+            .all(|(i, &p)| p == i as isize + 1)
+        {
+            Ok(viewed)
+        } else {
+            permute_dims(frame, viewed, view.perm)
+        }
+    }
+}
+
 mod private {
+    use ndarray::{Array as NdArray, ArrayViewMut, Dimension};
+
     use crate::data::managed::array::data::{
         accessor::{BitsArrayAccessor, InlinePtrArrayAccessor, Mutability},
         copied::CopiedArray,
     };
 
+    pub trait IntoJuliaArrayPriv {}
+
+    impl<T, D: Dimension> IntoJuliaArrayPriv for NdArray<T, D> {}
+
+    impl<'data, T, D: Dimension> IntoJuliaArrayPriv for ArrayViewMut<'data, T, D> {}
+
     pub trait NdArrayPriv {}
     impl<'borrow, 'array, 'data, T, M> NdArrayPriv
         for InlinePtrArrayAccessor<'borrow, 'array, 'data, T, M>
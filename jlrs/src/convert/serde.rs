@@ -0,0 +1,1047 @@
+//! Convert Rust data to Julia data and back with `serde`.
+//!
+//! Any type that implements `Serialize` can be converted to a Julia value with [`to_value`]. The
+//! data model of `serde` is mapped to Julia types as follows:
+//!
+//! - booleans, characters, integers and floating point numbers are converted to the matching
+//!   primitive Julia type.
+//! - strings are converted to a `String`, byte slices to a `Vector{UInt8}`.
+//! - `None`, `()` and unit structs are converted to `nothing`.
+//! - sequences are converted to a `Vector`. If all elements have the same type, that type is
+//!   used as the element type, otherwise the element type is `Any`.
+//! - tuples and tuple structs are converted to a `Tuple`.
+//! - maps are converted to a `Dict`. The key and value types are determined the same way as the
+//!   element type of a sequence.
+//! - structs are converted to a `NamedTuple`. With [`to_value_as`] a struct can be converted to
+//!   an instance of an arbitrary `DataType` instead, its fields are matched by name.
+//! - unit variants of enums are converted to a `Symbol`, all other variants to a `Tuple` whose
+//!   first element is the name of the variant as a `Symbol`. For newtype and struct variants the
+//!   second element is the content of the variant, for tuple variants the remaining elements are
+//!   the fields of the variant.
+//!
+//! Julia data can be converted to a type that implements `Deserialize` with [`from_value`]. The
+//! [`Deserializer`] walks the value with a [`FieldAccessor`] and supports the same types as the
+//! serializer, and additionally any type with named fields. Strings and symbols can be borrowed
+//! from Julia.
+//!
+//! [`FieldAccessor`]: crate::data::managed::value::field_accessor::FieldAccessor
+
+use std::fmt::Display;
+
+use ::serde::{
+    de::{self, DeserializeSeed, IntoDeserializer, Visitor},
+    forward_to_deserialize_any,
+    ser::{self, Serialize},
+    Deserialize,
+};
+use thiserror::Error;
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::{
+        layout::{nothing::Nothing, tuple::Tuple},
+        managed::{
+            array::{dimensions::Dims, Array, ArrayRef, TypedArray},
+            datatype::DataType,
+            module::Module,
+            string::{JuliaString, StringRef},
+            symbol::{Symbol, SymbolRef},
+            value::{
+                field_accessor::{FieldAccessor, FieldIndex},
+                Value, ValueData,
+            },
+            Managed,
+        },
+        types::typecheck::NamedTuple,
+    },
+    error::{JlrsError, JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
+    memory::target::{frame::GcFrame, unrooted::Unrooted, ExtendedTarget, Target},
+};
+
+/// Errors that can occur while serializing or deserializing Julia data.
+#[derive(Debug, Error)]
+pub enum SerdeError {
+    #[error("{0}")]
+    Custom(String),
+    #[error("cannot deserialize data of type {ty}")]
+    Unsupported { ty: String },
+    #[error("expected an enum variant, got data of type {ty}")]
+    NotAVariant { ty: String },
+    #[error("map key is missing")]
+    MissingKey,
+    #[error(transparent)]
+    Jlrs(Box<JlrsError>),
+}
+
+impl From<Box<JlrsError>> for SerdeError {
+    fn from(e: Box<JlrsError>) -> Self {
+        SerdeError::Jlrs(e)
+    }
+}
+
+impl From<SerdeError> for Box<JlrsError> {
+    fn from(e: SerdeError) -> Self {
+        match e {
+            SerdeError::Jlrs(e) => e,
+            e => Box::new(JlrsError::other(e)),
+        }
+    }
+}
+
+impl ser::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Custom(msg.to_string())
+    }
+}
+
+/// Serialize `value` to a new Julia value.
+pub fn to_value<'target, S, T>(
+    target: ExtendedTarget<'target, '_, '_, T>,
+    value: &S,
+) -> JlrsResult<ValueData<'target, 'static, T>>
+where
+    S: Serialize + ?Sized,
+    T: Target<'target>,
+{
+    let (output, frame) = target.split();
+    frame.scope(|mut frame| {
+        let value = value.serialize(Serializer::new(&mut frame))?;
+        Ok(value.root(output))
+    })
+}
+
+/// Serialize `value` to a new instance of `ty`.
+///
+/// `value` must serialize to a `NamedTuple`, i.e. it must be a struct. The fields of `ty` are
+/// looked up by name in the serialized data and passed to the constructor of `ty`, so the field
+/// values are converted to the field types of `ty` if necessary.
+pub fn to_value_as<'target, S, T>(
+    target: ExtendedTarget<'target, '_, '_, T>,
+    value: &S,
+    ty: DataType,
+) -> JlrsResult<ValueData<'target, 'static, T>>
+where
+    S: Serialize + ?Sized,
+    T: Target<'target>,
+{
+    let (output, frame) = target.split();
+    frame.scope(|mut frame| {
+        let named_tuple = value.serialize(Serializer::new(&mut frame))?;
+        if !named_tuple.is::<NamedTuple>() {
+            let ty = named_tuple
+                .datatype()
+                .display_string_or(CANNOT_DISPLAY_TYPE);
+            Err(TypeError::NotANamedTuple { ty })?;
+        }
+
+        let n_fields = ty.n_fields().unwrap_or(0) as usize;
+        let mut values = Vec::with_capacity(n_fields);
+        for idx in 0..n_fields {
+            // Safety: idx is in bounds
+            let name = unsafe { ty.field_name(idx).unwrap_unchecked() };
+            values.push(named_tuple.get_field(&mut frame, name)?);
+        }
+
+        // Safety: calling a constructor is safe.
+        let value = unsafe { ty.as_value().call(&mut frame, values) }.into_jlrs_result()?;
+        Ok(value.root(output))
+    })
+}
+
+/// Deserialize `value` as an instance of `T`.
+pub fn from_value<'scope, 'data, T>(value: Value<'scope, 'data>) -> JlrsResult<T>
+where
+    T: Deserialize<'scope>,
+{
+    Ok(T::deserialize(Deserializer::new(value))?)
+}
+
+/// Serializer that converts Rust data to Julia data.
+///
+/// All values that are created while serializing data are rooted in the frame.
+pub struct Serializer<'frame, 'borrow> {
+    frame: &'borrow mut GcFrame<'frame>,
+}
+
+impl<'frame, 'borrow> Serializer<'frame, 'borrow> {
+    /// Create a new serializer that roots its data in `frame`.
+    pub fn new(frame: &'borrow mut GcFrame<'frame>) -> Self {
+        Serializer { frame }
+    }
+
+    fn symbol(&mut self, name: &str) -> Value<'frame, 'static> {
+        Symbol::new(&*self.frame, name).as_value()
+    }
+}
+
+// Returns the type of the elements if they're all the same, `Any` otherwise.
+fn common_type<'frame>(
+    frame: &GcFrame<'frame>,
+    values: &[Value<'frame, 'static>],
+) -> Value<'frame, 'static> {
+    let any = DataType::any_type(frame).as_value();
+    let mut types = values.iter().map(|v| v.datatype());
+    match types.next() {
+        Some(first) if types.all(|ty| ty.as_value() == first) => first.as_value(),
+        _ => any,
+    }
+}
+
+fn new_vector<'frame>(
+    frame: &mut GcFrame<'frame>,
+    values: Vec<Value<'frame, 'static>>,
+) -> Result<Value<'frame, 'static>, SerdeError> {
+    let elty = common_type(frame, &values);
+    let mut array =
+        Array::new_for(frame.as_extended_target(), values.len(), elty).into_jlrs_result()?;
+
+    // Safety: the array has just been allocated and is not accessed from Julia.
+    let mut accessor = unsafe { array.indeterminate_data_mut() };
+    for (idx, value) in values.into_iter().enumerate() {
+        accessor
+            .set_value(&mut *frame, idx, Some(value))?
+            .into_jlrs_result()?;
+    }
+
+    Ok(array.as_value())
+}
+
+impl<'frame, 'borrow> ser::Serializer for Serializer<'frame, 'borrow> {
+    type Ok = Value<'frame, 'static>;
+    type Error = SerdeError;
+    type SerializeSeq = SerializeVector<'frame, 'borrow>;
+    type SerializeTuple = SerializeTuple<'frame, 'borrow>;
+    type SerializeTupleStruct = SerializeTuple<'frame, 'borrow>;
+    type SerializeTupleVariant = SerializeTuple<'frame, 'borrow>;
+    type SerializeMap = SerializeDict<'frame, 'borrow>;
+    type SerializeStruct = SerializeNamedTuple<'frame, 'borrow>;
+    type SerializeStructVariant = SerializeNamedTuple<'frame, 'borrow>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(JuliaString::new(self.frame, v).as_value())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        let array =
+            TypedArray::<u8>::from_vec(self.frame.as_extended_target(), v.to_vec(), v.len())?
+                .into_jlrs_result()?;
+        Ok(array.as_value())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::nothing(&*self.frame))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::nothing(&*self.frame))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::nothing(&*self.frame))
+    }
+
+    fn serialize_unit_variant(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(self.symbol(variant))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let variant = self.symbol(variant);
+        let value = value.serialize(Serializer::new(&mut *self.frame))?;
+        Ok(Tuple::new(self.frame.as_extended_target(), [variant, value]).into_jlrs_result()?)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeVector {
+            frame: self.frame,
+            values: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(SerializeTuple {
+            frame: self.frame,
+            values: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let mut values = Vec::with_capacity(len + 1);
+        values.push(self.symbol(variant));
+        Ok(SerializeTuple {
+            frame: self.frame,
+            values,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let len = len.unwrap_or(0);
+        Ok(SerializeDict {
+            frame: self.frame,
+            keys: Vec::with_capacity(len),
+            values: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(SerializeNamedTuple {
+            frame: self.frame,
+            variant: None,
+            names: Vec::with_capacity(len),
+            values: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeNamedTuple {
+            frame: self.frame,
+            variant: Some(variant),
+            names: Vec::with_capacity(len),
+            values: Vec::with_capacity(len),
+        })
+    }
+}
+
+/// Serializes a sequence to a `Vector`.
+pub struct SerializeVector<'frame, 'borrow> {
+    frame: &'borrow mut GcFrame<'frame>,
+    values: Vec<Value<'frame, 'static>>,
+}
+
+impl<'frame, 'borrow> ser::SerializeSeq for SerializeVector<'frame, 'borrow> {
+    type Ok = Value<'frame, 'static>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let value = value.serialize(Serializer::new(&mut *self.frame))?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        new_vector(self.frame, self.values)
+    }
+}
+
+/// Serializes tuples, tuple structs and tuple variants to a `Tuple`.
+pub struct SerializeTuple<'frame, 'borrow> {
+    frame: &'borrow mut GcFrame<'frame>,
+    values: Vec<Value<'frame, 'static>>,
+}
+
+impl<'frame, 'borrow> SerializeTuple<'frame, 'borrow> {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let value = value.serialize(Serializer::new(&mut *self.frame))?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value<'frame, 'static>, SerdeError> {
+        Ok(Tuple::new(self.frame.as_extended_target(), self.values).into_jlrs_result()?)
+    }
+}
+
+impl<'frame, 'borrow> ser::SerializeTuple for SerializeTuple<'frame, 'borrow> {
+    type Ok = Value<'frame, 'static>;
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'frame, 'borrow> ser::SerializeTupleStruct for SerializeTuple<'frame, 'borrow> {
+    type Ok = Value<'frame, 'static>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'frame, 'borrow> ser::SerializeTupleVariant for SerializeTuple<'frame, 'borrow> {
+    type Ok = Value<'frame, 'static>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+/// Serializes a map to a `Dict`.
+pub struct SerializeDict<'frame, 'borrow> {
+    frame: &'borrow mut GcFrame<'frame>,
+    keys: Vec<Value<'frame, 'static>>,
+    values: Vec<Value<'frame, 'static>>,
+}
+
+impl<'frame, 'borrow> ser::SerializeMap for SerializeDict<'frame, 'borrow> {
+    type Ok = Value<'frame, 'static>;
+    type Error = SerdeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        let key = key.serialize(Serializer::new(&mut *self.frame))?;
+        self.keys.push(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if self.keys.len() != self.values.len() + 1 {
+            Err(SerdeError::MissingKey)?;
+        }
+
+        let value = value.serialize(Serializer::new(&mut *self.frame))?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        if self.keys.len() != self.values.len() {
+            Err(SerdeError::MissingKey)?;
+        }
+
+        let frame = self.frame;
+        let key_type = common_type(frame, &self.keys);
+        let value_type = common_type(frame, &self.values);
+
+        // Safety: Base.Dict and Base.setindex! are globally rooted, calling them is safe.
+        unsafe {
+            let base = Module::base(&*frame);
+            let dict = base
+                .global(&mut *frame, "Dict")?
+                .apply_type(&mut *frame, [key_type, value_type])
+                .into_jlrs_result()?
+                .call0(&mut *frame)
+                .into_jlrs_result()?;
+
+            let setindex = base.function(&*frame, "setindex!")?.as_managed();
+            for (key, value) in self.keys.into_iter().zip(self.values) {
+                setindex
+                    .call3(&mut *frame, dict, value, key)
+                    .into_jlrs_result()?;
+            }
+
+            Ok(dict)
+        }
+    }
+}
+
+/// Serializes structs and struct variants to a `NamedTuple`.
+pub struct SerializeNamedTuple<'frame, 'borrow> {
+    frame: &'borrow mut GcFrame<'frame>,
+    variant: Option<&'static str>,
+    names: Vec<&'static str>,
+    values: Vec<Value<'frame, 'static>>,
+}
+
+impl<'frame, 'borrow> SerializeNamedTuple<'frame, 'borrow> {
+    fn push<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        let value = value.serialize(Serializer::new(&mut *self.frame))?;
+        self.names.push(key);
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value<'frame, 'static>, SerdeError> {
+        let frame = self.frame;
+        let named_tuple =
+            Value::new_named_tuple(frame.as_extended_target(), self.names, self.values)?;
+
+        match self.variant {
+            Some(variant) => {
+                let variant = Symbol::new(&*frame, variant).as_value();
+                Ok(
+                    Tuple::new(frame.as_extended_target(), [variant, named_tuple])
+                        .into_jlrs_result()?,
+                )
+            }
+            None => Ok(named_tuple),
+        }
+    }
+}
+
+impl<'frame, 'borrow> ser::SerializeStruct for SerializeNamedTuple<'frame, 'borrow> {
+    type Ok = Value<'frame, 'static>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl<'frame, 'borrow> ser::SerializeStructVariant for SerializeNamedTuple<'frame, 'borrow> {
+    type Ok = Value<'frame, 'static>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+/// Deserializer that converts Julia data to Rust data.
+///
+/// The data is accessed with a [`FieldAccessor`], so no temporary Julia data is allocated while
+/// deserializing it.
+///
+/// [`FieldAccessor`]: crate::data::managed::value::field_accessor::FieldAccessor
+pub struct Deserializer<'de, 'data> {
+    accessor: FieldAccessor<'de, 'data>,
+}
+
+impl<'de, 'data> Deserializer<'de, 'data> {
+    /// Create a new deserializer for `value`.
+    pub fn new(value: Value<'de, 'data>) -> Self {
+        Deserializer {
+            accessor: value.field_accessor(),
+        }
+    }
+
+    /// Create a new deserializer for the field an accessor currently points to.
+    pub fn from_accessor(accessor: FieldAccessor<'de, 'data>) -> Self {
+        Deserializer { accessor }
+    }
+
+    fn datatype(&self) -> Option<DataType<'de>> {
+        self.accessor.value()?;
+        // Safety: the accessor only points to reachable data.
+        self.accessor
+            .current_field_type()
+            .map(|ty| unsafe { ty.as_managed() })
+    }
+
+    fn field<F: FieldIndex>(&self, field: F) -> Result<Self, SerdeError> {
+        let accessor = self.accessor.try_clone()?.field(field)?;
+        Ok(Deserializer { accessor })
+    }
+
+    fn type_string(&self) -> String {
+        match self.datatype() {
+            Some(ty) => ty.display_string_or(CANNOT_DISPLAY_TYPE),
+            None => String::from("Nothing"),
+        }
+    }
+
+    fn is_dict(ty: DataType) -> bool {
+        let type_name = ty.type_name();
+        // Safety: Base is globally rooted
+        let base = Module::base(unsafe { &Unrooted::new() });
+        type_name.name().as_bytes() == b"Dict" && type_name.module().as_value() == base
+    }
+
+    fn n_fields(ty: DataType) -> usize {
+        ty.n_fields().unwrap_or(0) as usize
+    }
+}
+
+impl<'de, 'data> de::Deserializer<'de> for Deserializer<'de, 'data> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let ty = match self.datatype() {
+            Some(ty) => ty,
+            None => return visitor.visit_none(),
+        };
+
+        let accessor = self.accessor;
+        if ty.is::<bool>() {
+            visitor.visit_bool(accessor.access::<bool>()?)
+        } else if ty.is::<char>() {
+            visitor.visit_char(accessor.access::<char>()?)
+        } else if ty.is::<i8>() {
+            visitor.visit_i8(accessor.access::<i8>()?)
+        } else if ty.is::<i16>() {
+            visitor.visit_i16(accessor.access::<i16>()?)
+        } else if ty.is::<i32>() {
+            visitor.visit_i32(accessor.access::<i32>()?)
+        } else if ty.is::<i64>() {
+            visitor.visit_i64(accessor.access::<i64>()?)
+        } else if ty.is::<u8>() {
+            visitor.visit_u8(accessor.access::<u8>()?)
+        } else if ty.is::<u16>() {
+            visitor.visit_u16(accessor.access::<u16>()?)
+        } else if ty.is::<u32>() {
+            visitor.visit_u32(accessor.access::<u32>()?)
+        } else if ty.is::<u64>() {
+            visitor.visit_u64(accessor.access::<u64>()?)
+        } else if ty.is::<f32>() {
+            visitor.visit_f32(accessor.access::<f32>()?)
+        } else if ty.is::<f64>() {
+            visitor.visit_f64(accessor.access::<f64>()?)
+        } else if ty.is::<Nothing>() {
+            visitor.visit_unit()
+        } else if ty.is::<JuliaString>() {
+            // Safety: the string is reachable
+            let s = unsafe { accessor.access::<StringRef>()?.as_managed() };
+            visitor.visit_borrowed_str(s.as_str()?)
+        } else if ty.is::<Symbol>() {
+            // Safety: symbols are globally rooted
+            let s = unsafe { accessor.access::<SymbolRef>()?.as_managed() };
+            visitor.visit_borrowed_str(s.as_str()?)
+        } else if ty.is::<Array>() {
+            // Safety: the array is reachable and can't be resized while it's deserialized.
+            let dims = unsafe {
+                let array = accessor.try_clone()?.access::<ArrayRef>()?.as_managed();
+                array.dimensions().as_slice().to_vec()
+            };
+
+            visitor.visit_seq(ArraySeq {
+                accessor,
+                len: dims.iter().product(),
+                dims,
+                index: 0,
+            })
+        } else if ty.is::<Tuple>() {
+            visitor.visit_seq(TupleSeq {
+                de: Deserializer { accessor },
+                index: 0,
+                len: Self::n_fields(ty),
+            })
+        } else if Self::is_dict(ty) {
+            let de = Deserializer { accessor };
+            let slots = de.field("slots")?;
+            let len = match slots.datatype() {
+                // Safety: the array is reachable
+                Some(_) => unsafe {
+                    slots
+                        .accessor
+                        .access::<ArrayRef>()?
+                        .as_managed()
+                        .dimensions()
+                        .size()
+                },
+                None => 0,
+            };
+
+            visitor.visit_map(DictMap { de, index: 0, len })
+        } else if Self::n_fields(ty) > 0 {
+            visitor.visit_map(StructMap {
+                de: Deserializer { accessor },
+                ty,
+                index: 0,
+                len: Self::n_fields(ty),
+            })
+        } else if ty.instance().is_some() {
+            visitor.visit_unit()
+        } else {
+            Err(SerdeError::Unsupported {
+                ty: ty.display_string_or(CANNOT_DISPLAY_TYPE),
+            })
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.datatype() {
+            Some(ty) if !ty.is::<Nothing>() => visitor.visit_some(self),
+            _ => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let ty = match self.datatype() {
+            Some(ty) => ty,
+            None => Err(SerdeError::NotAVariant {
+                ty: self.type_string(),
+            })?,
+        };
+
+        if ty.is::<Symbol>() {
+            // Safety: symbols are globally rooted
+            let variant = unsafe { self.accessor.access::<SymbolRef>()?.as_managed() };
+            return visitor.visit_enum(IntoDeserializer::<SerdeError>::into_deserializer(
+                variant.as_str()?,
+            ));
+        }
+
+        if ty.is::<Tuple>() && Self::n_fields(ty) > 0 {
+            let tag = self.field(0)?;
+            if tag.datatype().map(|ty| ty.is::<Symbol>()).unwrap_or(false) {
+                // Safety: symbols are globally rooted
+                let variant = unsafe { tag.accessor.access::<SymbolRef>()?.as_managed() };
+                return visitor.visit_enum(EnumVariant {
+                    variant: variant.as_str()?,
+                    len: Self::n_fields(ty),
+                    de: self,
+                });
+            }
+        }
+
+        Err(SerdeError::NotAVariant {
+            ty: self.type_string(),
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+// Converts a linear index to a column-major n-dimensional index.
+fn cartesian_index(dims: &[usize], mut index: usize) -> Vec<usize> {
+    dims.iter()
+        .map(|&dim| {
+            let i = index % dim;
+            index /= dim;
+            i
+        })
+        .collect()
+}
+
+struct ArraySeq<'de, 'data> {
+    accessor: FieldAccessor<'de, 'data>,
+    dims: Vec<usize>,
+    index: usize,
+    len: usize,
+}
+
+impl<'de, 'data> de::SeqAccess<'de> for ArraySeq<'de, 'data> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        if self.index == self.len {
+            return Ok(None);
+        }
+
+        let index = cartesian_index(&self.dims, self.index);
+        let accessor = self.accessor.try_clone()?.field(index.as_slice())?;
+        self.index += 1;
+        seed.deserialize(Deserializer { accessor }).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+struct TupleSeq<'de, 'data> {
+    de: Deserializer<'de, 'data>,
+    index: usize,
+    len: usize,
+}
+
+impl<'de, 'data> de::SeqAccess<'de> for TupleSeq<'de, 'data> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        if self.index == self.len {
+            return Ok(None);
+        }
+
+        let field = self.de.field(self.index)?;
+        self.index += 1;
+        seed.deserialize(field).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+struct StructMap<'de, 'data> {
+    de: Deserializer<'de, 'data>,
+    ty: DataType<'de>,
+    index: usize,
+    len: usize,
+}
+
+impl<'de, 'data> de::MapAccess<'de> for StructMap<'de, 'data> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        if self.index == self.len {
+            return Ok(None);
+        }
+
+        match self.ty.field_name_str(self.index) {
+            Some(name) => seed
+                .deserialize(IntoDeserializer::<SerdeError>::into_deserializer(name))
+                .map(Some),
+            None => Err(SerdeError::Unsupported {
+                ty: self.de.type_string(),
+            }),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let field = self.de.field(self.index)?;
+        self.index += 1;
+        seed.deserialize(field)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+// Iterates over the filled slots of a `Dict`.
+struct DictMap<'de, 'data> {
+    de: Deserializer<'de, 'data>,
+    index: usize,
+    len: usize,
+}
+
+impl<'de, 'data> DictMap<'de, 'data> {
+    // A slot is filled if it's 0x01 (Julia 1.8 and earlier) or its high bit is set (Julia 1.9
+    // and later).
+    fn next_filled_slot(&mut self) -> Result<Option<usize>, SerdeError> {
+        while self.index < self.len {
+            let index = self.index;
+            let slot = self
+                .de
+                .accessor
+                .try_clone()?
+                .field("slots")?
+                .field(index)?
+                .access::<u8>()?;
+
+            if slot == 0x01 || slot & 0x80 != 0 {
+                return Ok(Some(index));
+            }
+
+            self.index += 1;
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'de, 'data> de::MapAccess<'de> for DictMap<'de, 'data> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.next_filled_slot()? {
+            Some(index) => {
+                let key = self.de.field("keys")?.field(index)?;
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let value = self.de.field("vals")?.field(self.index)?;
+        self.index += 1;
+        seed.deserialize(value)
+    }
+}
+
+struct EnumVariant<'de, 'data> {
+    variant: &'de str,
+    de: Deserializer<'de, 'data>,
+    len: usize,
+}
+
+impl<'de, 'data> de::EnumAccess<'de> for EnumVariant<'de, 'data> {
+    type Error = SerdeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(IntoDeserializer::<SerdeError>::into_deserializer(
+            self.variant,
+        ))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'data> de::VariantAccess<'de> for EnumVariant<'de, 'data> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self.de.field(1)?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(TupleSeq {
+            de: self.de,
+            index: 1,
+            len: self.len,
+        })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_any(self.de.field(1)?, visitor)
+    }
+}
@@ -0,0 +1,82 @@
+//! Layout type for `Complex{T}`.
+//!
+//! Julia's `Complex{T}` is a struct with two fields of type `T`, the real and imaginary part. It
+//! can be represented as [`Complex<T>`] if `T` implements [`RealLayout`], e.g. a `ComplexF64`
+//! is a `Complex<f64>`.
+//!
+//! If the `num-complex` feature is enabled, `num_complex::Complex<T>` can be used as a layout
+//! for `Complex{T}` too, both types have the same layout and can be converted into each other.
+
+#[cfg(feature = "num-complex")]
+use num_complex::Complex as NumComplex;
+
+use crate::{
+    convert::into_julia::IntoJulia,
+    data::{
+        layout::valid_layout::ValidField, managed::union_all::UnionAll,
+        static_data::define_static_global, types::construct_type::ConstructType,
+    },
+};
+
+define_static_global!(COMPLEX, UnionAll<'static>, "Base.Complex");
+
+/// Layouts of subtypes of `Real` that can be used as the type parameter of `Complex` and other
+/// numeric types.
+///
+/// Safety: this trait must only be implemented by layouts of isbits subtypes of `Real` that have
+/// the same alignment in Rust and Julia.
+pub unsafe trait RealLayout: IntoJulia + ValidField + ConstructType + Copy {}
+
+unsafe impl RealLayout for i8 {}
+unsafe impl RealLayout for i16 {}
+unsafe impl RealLayout for i32 {}
+unsafe impl RealLayout for i64 {}
+unsafe impl RealLayout for isize {}
+unsafe impl RealLayout for u8 {}
+unsafe impl RealLayout for u16 {}
+unsafe impl RealLayout for u32 {}
+unsafe impl RealLayout for u64 {}
+unsafe impl RealLayout for usize {}
+unsafe impl RealLayout for f32 {}
+unsafe impl RealLayout for f64 {}
+#[cfg(feature = "f16")]
+unsafe impl RealLayout for half::f16 {}
+
+/// A Julia `Complex{T}`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Complex<T> {
+    /// The real part.
+    pub re: T,
+    /// The imaginary part.
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    /// Create a new complex number.
+    #[inline]
+    pub const fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+}
+
+impl_parametric_layout!(Complex, RealLayout, COMPLEX);
+
+#[cfg(feature = "num-complex")]
+impl_parametric_layout!(NumComplex, RealLayout, COMPLEX);
+
+#[cfg(feature = "num-complex")]
+impl<T> From<NumComplex<T>> for Complex<T> {
+    #[inline]
+    fn from(value: NumComplex<T>) -> Self {
+        Complex::new(value.re, value.im)
+    }
+}
+
+#[cfg(feature = "num-complex")]
+impl<T> From<Complex<T>> for NumComplex<T> {
+    #[inline]
+    fn from(value: Complex<T>) -> Self {
+        NumComplex::new(value.re, value.im)
+    }
+}
@@ -0,0 +1,462 @@
+//! Layout types for `Dates.DateTime`, `Dates.Date`, `Dates.Time` and periods.
+//!
+//! The types defined in Julia's `Dates` module are thin wrappers around an `Int64`: a
+//! `DateTime` is the number of milliseconds since `0000-12-31T00:00:00`, a `Date` is the number
+//! of days since `0000-12-31`, a `Time` is the number of nanoseconds since midnight, and periods
+//! like `Millisecond` and `Day` store their value. The layouts in this module expose this
+//! integer directly, they can be used as field types and as element types of arrays.
+//!
+//! The `Dates` module must have been loaded before any of these types can be converted to Julia
+//! data, e.g. by evaluating `using Dates`.
+//!
+//! If the `chrono` feature is enabled, `DateTime`, `Date` and `Time` can be converted to and
+//! from `NaiveDateTime`, `NaiveDate` and `NaiveTime`. If the `time` feature is enabled, they can
+//! be converted to and from `OffsetDateTime`, `Date` and `Time`. A `DateTime` has no time zone,
+//! it's treated as UTC when it's converted to or from an `OffsetDateTime`. Sub-millisecond
+//! precision is lost when a `DateTime` is created from either of these types. Conversions that
+//! can fail because the value is out of range return a [`DatesError`].
+
+use thiserror::Error;
+
+use crate::{
+    convert::{into_julia::IntoJulia, unbox::Unbox},
+    data::{
+        managed::{
+            datatype::{DataType, DataTypeData},
+            module::Module,
+            private::ManagedPriv,
+            value::{Value, ValueData},
+            Managed,
+        },
+        static_data::{define_static_global, static_global},
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    error::JlrsError,
+    impl_valid_layout,
+    memory::target::{unrooted::Unrooted, ExtendedTarget, Target},
+    private::Private,
+};
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+
+/// The instant of `Dates.UNIXEPOCH`, `1970-01-01T00:00:00`.
+pub const UNIX_EPOCH_MILLIS: i64 = 62_135_683_200_000;
+
+define_static_global!(DATE_TIME, DataType<'static>, "Dates.DateTime");
+define_static_global!(DATE, DataType<'static>, "Dates.Date");
+define_static_global!(TIME, DataType<'static>, "Dates.Time");
+define_static_global!(NANOSECOND, DataType<'static>, "Dates.Nanosecond");
+define_static_global!(MICROSECOND, DataType<'static>, "Dates.Microsecond");
+define_static_global!(MILLISECOND, DataType<'static>, "Dates.Millisecond");
+define_static_global!(SECOND, DataType<'static>, "Dates.Second");
+define_static_global!(MINUTE, DataType<'static>, "Dates.Minute");
+define_static_global!(HOUR, DataType<'static>, "Dates.Hour");
+define_static_global!(DAY, DataType<'static>, "Dates.Day");
+define_static_global!(WEEK, DataType<'static>, "Dates.Week");
+define_static_global!(MONTH, DataType<'static>, "Dates.Month");
+define_static_global!(QUARTER, DataType<'static>, "Dates.Quarter");
+define_static_global!(YEAR, DataType<'static>, "Dates.Year");
+
+/// Errors that can occur when converting dates and times.
+#[derive(Debug, Error)]
+pub enum DatesError {
+    #[error("{ty} with instant {instant} is out of range")]
+    OutOfRange { ty: &'static str, instant: i64 },
+}
+
+impl From<DatesError> for Box<JlrsError> {
+    fn from(e: DatesError) -> Self {
+        Box::new(JlrsError::other(e))
+    }
+}
+
+macro_rules! impl_dates_layout {
+    ($ty:ident, $global:ident) => {
+        unsafe impl IntoJulia for $ty {
+            fn julia_type<'scope, T>(target: T) -> DataTypeData<'scope, T>
+            where
+                T: Target<'scope>,
+            {
+                let dt = static_global!($global, target);
+                unsafe { target.data_from_ptr(dt.unwrap_non_null(Private), Private) }
+            }
+        }
+
+        unsafe impl Typecheck for $ty {
+            fn typecheck(t: DataType) -> bool {
+                if t.name() != stringify!($ty) {
+                    return false;
+                }
+
+                // Safety: the types are globally rooted. If the Dates module hasn't been loaded,
+                // `t` is some other type with the same name.
+                let unrooted = unsafe { Unrooted::new() };
+                Module::package_root_module(&unrooted, "Dates").is_some()
+                    && t == static_global!($global, unrooted)
+            }
+        }
+
+        impl_valid_layout!($ty);
+
+        unsafe impl Unbox for $ty {
+            type Output = Self;
+        }
+
+        unsafe impl ConstructType for $ty {
+            fn construct_type<'target, T>(
+                target: ExtendedTarget<'target, '_, '_, T>,
+            ) -> ValueData<'target, 'static, T>
+            where
+                T: Target<'target>,
+            {
+                let (target, _) = target.split();
+                static_global!($global, target).as_value().root(target)
+            }
+
+            fn base_type<'target, T>(target: &T) -> Option<Value<'target, 'static>>
+            where
+                T: Target<'target>,
+            {
+                Some(static_global!($global, target).as_value())
+            }
+        }
+
+        unsafe impl crate::convert::ccall_types::CCallArg for $ty {
+            type CCallArgType = Self;
+            type FunctionArgType = Self;
+        }
+
+        unsafe impl crate::convert::ccall_types::CCallReturn for $ty {
+            type CCallReturnType = Self;
+            type FunctionReturnType = Self;
+        }
+    };
+}
+
+macro_rules! define_period {
+    ($(#[$doc:meta])* $ty:ident, $global:ident) => {
+        $(#[$doc])*
+        #[repr(C)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $ty {
+            pub value: i64,
+        }
+
+        impl $ty {
+            #[inline]
+            pub const fn new(value: i64) -> Self {
+                $ty { value }
+            }
+        }
+
+        impl_dates_layout!($ty, $global);
+    };
+}
+
+/// A `Dates.DateTime`, the number of milliseconds since `0000-12-31T00:00:00`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub instant: i64,
+}
+
+impl DateTime {
+    /// Create a new `DateTime` from the number of milliseconds since `0000-12-31T00:00:00`.
+    #[inline]
+    pub const fn new(instant: i64) -> Self {
+        DateTime { instant }
+    }
+
+    /// Create a new `DateTime` from the number of milliseconds since the Unix epoch.
+    #[inline]
+    pub const fn from_unix_millis(millis: i64) -> Self {
+        DateTime::new(millis + UNIX_EPOCH_MILLIS)
+    }
+
+    /// Returns the number of milliseconds since the Unix epoch.
+    #[inline]
+    pub const fn unix_millis(self) -> i64 {
+        self.instant - UNIX_EPOCH_MILLIS
+    }
+}
+
+impl_dates_layout!(DateTime, DATE_TIME);
+
+/// A `Dates.Date`, the number of days since `0000-12-31`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub instant: i64,
+}
+
+impl Date {
+    /// Create a new `Date` from the number of days since `0000-12-31`.
+    #[inline]
+    pub const fn new(instant: i64) -> Self {
+        Date { instant }
+    }
+}
+
+impl_dates_layout!(Date, DATE);
+
+/// A `Dates.Time`, the number of nanoseconds since midnight.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub instant: i64,
+}
+
+impl Time {
+    /// Create a new `Time` from the number of nanoseconds since midnight.
+    #[inline]
+    pub const fn new(instant: i64) -> Self {
+        Time { instant }
+    }
+}
+
+impl_dates_layout!(Time, TIME);
+
+define_period!(
+    /// A `Dates.Nanosecond`.
+    Nanosecond,
+    NANOSECOND
+);
+define_period!(
+    /// A `Dates.Microsecond`.
+    Microsecond,
+    MICROSECOND
+);
+define_period!(
+    /// A `Dates.Millisecond`.
+    Millisecond,
+    MILLISECOND
+);
+define_period!(
+    /// A `Dates.Second`.
+    Second,
+    SECOND
+);
+define_period!(
+    /// A `Dates.Minute`.
+    Minute,
+    MINUTE
+);
+define_period!(
+    /// A `Dates.Hour`.
+    Hour,
+    HOUR
+);
+define_period!(
+    /// A `Dates.Day`.
+    Day,
+    DAY
+);
+define_period!(
+    /// A `Dates.Week`.
+    Week,
+    WEEK
+);
+define_period!(
+    /// A `Dates.Month`.
+    Month,
+    MONTH
+);
+define_period!(
+    /// A `Dates.Quarter`.
+    Quarter,
+    QUARTER
+);
+define_period!(
+    /// A `Dates.Year`.
+    Year,
+    YEAR
+);
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use std::convert::{TryFrom, TryInto};
+
+    use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+    use super::{Date, DateTime, DatesError, Time, MILLIS_PER_DAY, NANOS_PER_DAY};
+
+    impl From<NaiveDateTime> for DateTime {
+        fn from(value: NaiveDateTime) -> Self {
+            let days = value.date().num_days_from_ce() as i64;
+            let time = value.time();
+            let millis = time.num_seconds_from_midnight() as i64 * 1000
+                + (time.nanosecond() / 1_000_000) as i64;
+            DateTime::new(days * MILLIS_PER_DAY + millis)
+        }
+    }
+
+    impl TryFrom<DateTime> for NaiveDateTime {
+        type Error = DatesError;
+
+        fn try_from(value: DateTime) -> Result<Self, Self::Error> {
+            let err = || DatesError::OutOfRange {
+                ty: "DateTime",
+                instant: value.instant,
+            };
+
+            let days = value.instant.div_euclid(MILLIS_PER_DAY);
+            let millis = value.instant.rem_euclid(MILLIS_PER_DAY) as u32;
+            let date = days
+                .try_into()
+                .ok()
+                .and_then(NaiveDate::from_num_days_from_ce_opt)
+                .ok_or_else(err)?;
+            let time = NaiveTime::from_num_seconds_from_midnight_opt(
+                millis / 1000,
+                (millis % 1000) * 1_000_000,
+            )
+            .ok_or_else(err)?;
+
+            Ok(date.and_time(time))
+        }
+    }
+
+    impl From<NaiveDate> for Date {
+        fn from(value: NaiveDate) -> Self {
+            Date::new(value.num_days_from_ce() as i64)
+        }
+    }
+
+    impl TryFrom<Date> for NaiveDate {
+        type Error = DatesError;
+
+        fn try_from(value: Date) -> Result<Self, Self::Error> {
+            value
+                .instant
+                .try_into()
+                .ok()
+                .and_then(NaiveDate::from_num_days_from_ce_opt)
+                .ok_or(DatesError::OutOfRange {
+                    ty: "Date",
+                    instant: value.instant,
+                })
+        }
+    }
+
+    impl From<NaiveTime> for Time {
+        fn from(value: NaiveTime) -> Self {
+            Time::new(
+                value.num_seconds_from_midnight() as i64 * 1_000_000_000
+                    + value.nanosecond() as i64,
+            )
+        }
+    }
+
+    impl TryFrom<Time> for NaiveTime {
+        type Error = DatesError;
+
+        fn try_from(value: Time) -> Result<Self, Self::Error> {
+            let err = DatesError::OutOfRange {
+                ty: "Time",
+                instant: value.instant,
+            };
+
+            if !(0..NANOS_PER_DAY).contains(&value.instant) {
+                return Err(err);
+            }
+
+            NaiveTime::from_num_seconds_from_midnight_opt(
+                (value.instant / 1_000_000_000) as u32,
+                (value.instant % 1_000_000_000) as u32,
+            )
+            .ok_or(err)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_impls {
+    use std::convert::{TryFrom, TryInto};
+
+    use time::{Date as TimeDate, OffsetDateTime, Time as TimeTime};
+
+    use super::{Date, DateTime, DatesError, Time, NANOS_PER_DAY, UNIX_EPOCH_MILLIS};
+
+    // The Julian day number of `0000-12-31`.
+    const JULIAN_DAY_OFFSET: i64 = 1_721_425;
+
+    impl From<OffsetDateTime> for DateTime {
+        fn from(value: OffsetDateTime) -> Self {
+            let millis = value.unix_timestamp_nanos().div_euclid(1_000_000) as i64;
+            DateTime::new(millis + UNIX_EPOCH_MILLIS)
+        }
+    }
+
+    impl TryFrom<DateTime> for OffsetDateTime {
+        type Error = DatesError;
+
+        fn try_from(value: DateTime) -> Result<Self, Self::Error> {
+            let nanos = value.unix_millis() as i128 * 1_000_000;
+            OffsetDateTime::from_unix_timestamp_nanos(nanos).map_err(|_| DatesError::OutOfRange {
+                ty: "DateTime",
+                instant: value.instant,
+            })
+        }
+    }
+
+    impl From<TimeDate> for Date {
+        fn from(value: TimeDate) -> Self {
+            Date::new(value.to_julian_day() as i64 - JULIAN_DAY_OFFSET)
+        }
+    }
+
+    impl TryFrom<Date> for TimeDate {
+        type Error = DatesError;
+
+        fn try_from(value: Date) -> Result<Self, Self::Error> {
+            let err = DatesError::OutOfRange {
+                ty: "Date",
+                instant: value.instant,
+            };
+
+            let julian_day = value
+                .instant
+                .checked_add(JULIAN_DAY_OFFSET)
+                .and_then(|day| day.try_into().ok());
+
+            match julian_day {
+                Some(day) => TimeDate::from_julian_day(day).map_err(|_| err),
+                None => Err(err),
+            }
+        }
+    }
+
+    impl From<TimeTime> for Time {
+        fn from(value: TimeTime) -> Self {
+            let (h, m, s, ns) = value.as_hms_nano();
+            let secs = h as i64 * 3600 + m as i64 * 60 + s as i64;
+            Time::new(secs * 1_000_000_000 + ns as i64)
+        }
+    }
+
+    impl TryFrom<Time> for TimeTime {
+        type Error = DatesError;
+
+        fn try_from(value: Time) -> Result<Self, Self::Error> {
+            let err = DatesError::OutOfRange {
+                ty: "Time",
+                instant: value.instant,
+            };
+
+            if !(0..NANOS_PER_DAY).contains(&value.instant) {
+                return Err(err);
+            }
+
+            let secs = value.instant / 1_000_000_000;
+            let ns = (value.instant % 1_000_000_000) as u32;
+            TimeTime::from_hms_nano(
+                (secs / 3600) as u8,
+                (secs / 60 % 60) as u8,
+                (secs % 60) as u8,
+                ns,
+            )
+            .map_err(|_| err)
+        }
+    }
+}
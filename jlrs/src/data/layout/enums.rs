@@ -0,0 +1,62 @@
+//! Layouts for Julia enums.
+//!
+//! A fieldless Rust enum with an integer representation can be used as the layout of a Julia
+//! enum type, i.e. a type created with the `@enum` macro, by deriving `ValidLayout` and the other
+//! layout traits. The base type of the Julia enum must match the representation of the Rust
+//! enum:
+//!
+//! ```ignore
+//! // @enum Fruit::Int32 apple=1 orange=2 kiwi=4
+//! #[derive(Copy, Clone, Debug, PartialEq, Eq, ValidLayout, Typecheck, Unbox, IntoJulia,
+//!          ConstructType, CCallReturn)]
+//! #[repr(i32)]
+//! #[jlrs(julia_type = "Main.Fruit")]
+//! enum Fruit {
+//!     Apple = 1,
+//!     Orange = 2,
+//!     Kiwi = 4,
+//! }
+//! ```
+//!
+//! The derived `ValidLayout` implementation only checks the layout with [`is_enum_layout`]: the
+//! type must be a Julia enum type whose base type matches the representation of the Rust enum.
+//! A Julia enum can have instances that aren't variants of the Rust enum, so the value is checked
+//! when it's unboxed. The derived `Unbox` implementation has the output type
+//! `Result<Self, Repr>`, an instance whose value isn't a discriminant of the Rust enum is
+//! returned as `Err(value)`. For the same reason `ValidField` and `CCallArg` can't be derived
+//! for enums, the integer representation must be used as the field or argument type instead.
+//! Enums with fields are not supported.
+
+use std::mem::size_of;
+
+use super::valid_layout::ValidLayout;
+use crate::data::{
+    managed::{datatype::DataType, union_all::UnionAll, Managed},
+    static_data::{define_static_global, static_global},
+};
+
+define_static_global!(ENUM, UnionAll<'static>, "Base.Enum");
+
+/// Returns `true` if `ty` is a Julia enum type whose base type has the layout `T`.
+///
+/// The values of the instances of `ty` are not checked.
+pub fn is_enum_layout<T>(ty: DataType) -> bool
+where
+    T: ValidLayout,
+{
+    if !ty.is_bits() || ty.n_fields() != Some(0) || ty.size() != Some(size_of::<T>() as u32) {
+        return false;
+    }
+
+    let unrooted = ty.unrooted_target();
+    let super_type = ty.super_type();
+    if super_type.type_name() != static_global!(ENUM, unrooted).base_type().type_name() {
+        return false;
+    }
+
+    // Safety: the base type is a type parameter of the supertype, which is rooted by ty.
+    match super_type.parameter(unrooted, 0) {
+        Some(base_type) => unsafe { T::valid_layout(base_type.as_value()) },
+        None => false,
+    }
+}
@@ -0,0 +1,128 @@
+//! Layouts for `Int128` and `UInt128`.
+//!
+//! Julia's `Int128` and `UInt128` are represented as `i128` and `u128` respectively. These types
+//! aren't exposed by the C API, they're looked up in the `Core` module when they're used for the
+//! first time.
+//!
+//! Rust and Julia don't necessarily agree on the alignment of 128-bit integers: since Rust 1.77
+//! `i128` and `u128` are aligned to 16 bytes on x86_64, while older versions of Julia align
+//! `Int128` and `UInt128` to 8 bytes. Because the size of these types is the same, their layouts
+//! are considered valid if only the alignment differs.
+//!
+//! Boxed values are always unboxed with an unaligned read. The data of a Julia array is aligned
+//! to at least 16 bytes, so the elements of an array of 128-bit integers are aligned for Rust.
+//! The offsets of the fields of a struct depend on the alignment of their types, so a Rust
+//! struct with an `i128` or `u128` field can't be used as the layout of a Julia struct if the
+//! alignments differ. `ValidField` checks the alignment, so such layouts are rejected. For this
+//! reason these types can't be used as the type parameter of `Complex` and `Rational`.
+
+use std::mem::align_of;
+
+use crate::{
+    convert::{into_julia::IntoJulia, unbox::Unbox},
+    data::{
+        layout::valid_layout::{ValidField, ValidLayout},
+        managed::{
+            datatype::{DataType, DataTypeData},
+            private::ManagedPriv,
+            value::{Value, ValueData},
+            Managed,
+        },
+        static_data::{define_static_global, static_global},
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    memory::target::{unrooted::Unrooted, ExtendedTarget, Target},
+    private::Private,
+};
+
+define_static_global!(INT128, DataType<'static>, "Core.Int128");
+define_static_global!(UINT128, DataType<'static>, "Core.UInt128");
+
+macro_rules! impl_int128 {
+    ($ty:ty, $global:ident) => {
+        unsafe impl IntoJulia for $ty {
+            fn julia_type<'scope, T>(target: T) -> DataTypeData<'scope, T>
+            where
+                T: Target<'scope>,
+            {
+                let dt = static_global!($global, target);
+                unsafe { target.data_from_ptr(dt.unwrap_non_null(Private), Private) }
+            }
+        }
+
+        unsafe impl Typecheck for $ty {
+            #[inline]
+            fn typecheck(t: DataType) -> bool {
+                // Safety: the type is globally rooted
+                let unrooted = unsafe { Unrooted::new() };
+                t == static_global!($global, unrooted)
+            }
+        }
+
+        unsafe impl ValidLayout for $ty {
+            #[inline]
+            fn valid_layout(v: Value) -> bool {
+                if let Ok(dt) = v.cast::<DataType>() {
+                    dt.is::<$ty>()
+                } else {
+                    false
+                }
+            }
+
+            const IS_REF: bool = false;
+        }
+
+        unsafe impl ValidField for $ty {
+            #[inline]
+            fn valid_field(v: Value) -> bool {
+                // The offsets of the fields of the containing struct depend on the alignment.
+                if let Ok(dt) = v.cast::<DataType>() {
+                    dt.is::<$ty>() && dt.align() == Some(align_of::<$ty>() as u16)
+                } else {
+                    false
+                }
+            }
+        }
+
+        unsafe impl Unbox for $ty {
+            type Output = Self;
+
+            #[inline]
+            unsafe fn unbox(value: Value) -> Self {
+                value.data_ptr().cast::<Self>().as_ptr().read_unaligned()
+            }
+        }
+
+        unsafe impl ConstructType for $ty {
+            fn construct_type<'target, T>(
+                target: ExtendedTarget<'target, '_, '_, T>,
+            ) -> ValueData<'target, 'static, T>
+            where
+                T: Target<'target>,
+            {
+                let (target, _) = target.split();
+                static_global!($global, target).as_value().root(target)
+            }
+
+            fn base_type<'target, T>(target: &T) -> Option<Value<'target, 'static>>
+            where
+                T: Target<'target>,
+            {
+                Some(static_global!($global, target).as_value())
+            }
+        }
+
+        unsafe impl crate::convert::ccall_types::CCallArg for $ty {
+            type CCallArgType = Self;
+            type FunctionArgType = Self;
+        }
+
+        unsafe impl crate::convert::ccall_types::CCallReturn for $ty {
+            type CCallReturnType = Self;
+            type FunctionReturnType = Self;
+        }
+    };
+}
+
+impl_int128!(i128, INT128);
+impl_int128!(u128, UINT128);
@@ -57,11 +57,135 @@ macro_rules! impl_construct_julia_type {
     };
 }
 
+// Implements all layout traits for `$ty<T>`, a `#[repr(C)]` type that has the same layout as
+// the Julia type `$global{T}`. `$global` must be a static global that contains that `UnionAll`.
+macro_rules! impl_parametric_layout {
+    ($ty:ident, $bound:ident, $global:ident) => {
+        unsafe impl<T: $bound> $crate::data::layout::valid_layout::ValidLayout for $ty<T> {
+            #[inline]
+            fn valid_layout(v: $crate::data::managed::value::Value) -> bool {
+                if let Ok(dt) = v.cast::<$crate::data::managed::datatype::DataType>() {
+                    dt.is::<Self>()
+                } else {
+                    false
+                }
+            }
+
+            const IS_REF: bool = false;
+        }
+
+        unsafe impl<T: $bound> $crate::data::layout::valid_layout::ValidField for $ty<T> {
+            #[inline]
+            fn valid_field(v: $crate::data::managed::value::Value) -> bool {
+                <Self as $crate::data::layout::valid_layout::ValidLayout>::valid_layout(v)
+            }
+        }
+
+        unsafe impl<T: $bound> $crate::data::types::typecheck::Typecheck for $ty<T> {
+            fn typecheck(t: $crate::data::managed::datatype::DataType) -> bool {
+                unsafe {
+                    let unrooted = $crate::memory::target::unrooted::Unrooted::new();
+                    let base_type = $crate::data::static_data::static_global!($global, unrooted);
+                    if t.type_name() != base_type.base_type().type_name() {
+                        return false;
+                    }
+
+                    let params = t.parameters();
+                    let params = params.data().as_slice();
+                    match params[0] {
+                        Some(param) => param.as_value() == T::julia_type(unrooted).as_value(),
+                        None => false,
+                    }
+                }
+            }
+        }
+
+        unsafe impl<T: $bound> $crate::convert::unbox::Unbox for $ty<T> {
+            type Output = Self;
+        }
+
+        unsafe impl<T: $bound> $crate::convert::into_julia::IntoJulia for $ty<T> {
+            fn julia_type<'scope, Tgt>(
+                target: Tgt,
+            ) -> $crate::data::managed::datatype::DataTypeData<'scope, Tgt>
+            where
+                Tgt: $crate::memory::target::Target<'scope>,
+            {
+                use $crate::data::managed::{private::ManagedPriv as _, Managed as _};
+
+                let base_type = $crate::data::static_data::static_global!($global, target);
+
+                // Safety: Not rooting the result should be fine. The result is a concrete type,
+                // which is globally rooted.
+                unsafe {
+                    let param = T::julia_type(&target).as_value();
+                    let ty = base_type
+                        .as_value()
+                        .apply_type_unchecked(&target, [param])
+                        .as_value()
+                        .cast_unchecked::<$crate::data::managed::datatype::DataType>();
+                    debug_assert!(ty.is_concrete_type());
+                    target.data_from_ptr(
+                        ty.unwrap_non_null($crate::private::Private),
+                        $crate::private::Private,
+                    )
+                }
+            }
+        }
+
+        unsafe impl<T: $bound> $crate::data::types::construct_type::ConstructType for $ty<T> {
+            fn construct_type<'target, Tgt>(
+                target: $crate::memory::target::ExtendedTarget<'target, '_, '_, Tgt>,
+            ) -> $crate::data::managed::value::ValueData<'target, 'static, Tgt>
+            where
+                Tgt: $crate::memory::target::Target<'target>,
+            {
+                use $crate::data::managed::Managed as _;
+
+                let (target, frame) = target.split();
+                frame
+                    .scope(|mut frame| {
+                        let param = T::construct_type(frame.as_extended_target());
+                        let base_type = $crate::data::static_data::static_global!($global, frame);
+                        unsafe { Ok(base_type.as_value().apply_type_unchecked(target, [param])) }
+                    })
+                    .unwrap()
+            }
+
+            fn base_type<'target, Tgt>(
+                target: &Tgt,
+            ) -> Option<$crate::data::managed::value::Value<'target, 'static>>
+            where
+                Tgt: $crate::memory::target::Target<'target>,
+            {
+                use $crate::data::managed::Managed as _;
+
+                Some($crate::data::static_data::static_global!($global, target).as_value())
+            }
+        }
+
+        unsafe impl<T: $bound> $crate::convert::ccall_types::CCallArg for $ty<T> {
+            type CCallArgType = Self;
+            type FunctionArgType = Self;
+        }
+
+        unsafe impl<T: $bound> $crate::convert::ccall_types::CCallReturn for $ty<T> {
+            type CCallReturnType = Self;
+            type FunctionReturnType = Self;
+        }
+    };
+}
+
 pub mod bool;
 pub mod char;
+pub mod complex;
+pub mod dates;
+pub mod enums;
 #[cfg(feature = "f16")]
 pub mod f16;
+pub mod int128;
 pub mod nothing;
+pub mod rational;
 #[cfg(feature = "internal-types")]
 pub mod ssa_value;
 pub mod tuple;
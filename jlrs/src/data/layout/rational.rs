@@ -0,0 +1,70 @@
+//! Layout type for `Rational{T}`.
+//!
+//! Julia's `Rational{T}` is a struct with two fields of type `T`, the numerator and denominator.
+//! It can be represented as [`Rational<T>`] if `T` implements [`IntegerLayout`]. Julia expects
+//! rational numbers to be normalized: the numerator and denominator must be coprime, and the
+//! denominator must be non-negative.
+//!
+//! If the `num-rational` feature is enabled, `num_rational::Ratio<T>` and `Rational<T>` can be
+//! converted into each other.
+
+#[cfg(feature = "num-rational")]
+use num_rational::Ratio;
+
+use super::complex::RealLayout;
+use crate::data::{managed::union_all::UnionAll, static_data::define_static_global};
+
+define_static_global!(RATIONAL, UnionAll<'static>, "Base.Rational");
+
+/// Layouts of subtypes of `Integer` that can be used as the type parameter of `Rational`.
+///
+/// Safety: this trait must only be implemented by layouts of isbits subtypes of `Integer`.
+pub unsafe trait IntegerLayout: RealLayout {}
+
+unsafe impl IntegerLayout for i8 {}
+unsafe impl IntegerLayout for i16 {}
+unsafe impl IntegerLayout for i32 {}
+unsafe impl IntegerLayout for i64 {}
+unsafe impl IntegerLayout for isize {}
+unsafe impl IntegerLayout for u8 {}
+unsafe impl IntegerLayout for u16 {}
+unsafe impl IntegerLayout for u32 {}
+unsafe impl IntegerLayout for u64 {}
+unsafe impl IntegerLayout for usize {}
+
+/// A Julia `Rational{T}`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rational<T> {
+    /// The numerator.
+    pub num: T,
+    /// The denominator.
+    pub den: T,
+}
+
+impl<T> Rational<T> {
+    /// Create a new rational number without normalizing it.
+    #[inline]
+    pub const fn new_raw(num: T, den: T) -> Self {
+        Rational { num, den }
+    }
+}
+
+impl_parametric_layout!(Rational, IntegerLayout, RATIONAL);
+
+#[cfg(feature = "num-rational")]
+impl<T> From<Ratio<T>> for Rational<T> {
+    #[inline]
+    fn from(value: Ratio<T>) -> Self {
+        let (num, den) = value.into_raw();
+        Rational::new_raw(num, den)
+    }
+}
+
+#[cfg(feature = "num-rational")]
+impl<T> From<Rational<T>> for Ratio<T> {
+    #[inline]
+    fn from(value: Rational<T>) -> Self {
+        Ratio::new_raw(value.num, value.den)
+    }
+}
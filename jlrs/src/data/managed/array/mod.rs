@@ -2647,6 +2647,10 @@ pub type TypedArrayData<'target, 'data, T, U> =
 pub type TypedArrayResult<'target, 'data, T, U> =
     <T as TargetType<'target>>::Result<'data, TypedArray<'target, 'data, U>>;
 
+/// `TypedRankedArray<U, N>` or `TypedRankedArrayRef<U, N>`, depending on the target type `T`.
+pub type TypedRankedArrayData<'target, 'data, T, U, const N: isize> =
+    <T as TargetType<'target>>::Data<'data, TypedRankedArray<'target, 'data, U, N>>;
+
 unsafe impl<'scope, 'data, T: ValidField + ConstructType> CCallArg
     for TypedArray<'scope, 'data, T>
 {
@@ -0,0 +1,801 @@
+//! Managed types for `Dict`.
+//!
+//! A [`Dict`] is a Julia dictionary with arbitrary key and value types, [`TypedDict`] is also
+//! available which can be used if the key and value types are known. The key and value types of
+//! a `TypedDict<K, V>` are constructed from `K` and `V` with [`ConstructType`].
+//!
+//! Keys are looked up by calling `Base.haskey`, `Base.get`, `Base.setindex!`, and `Base.pop!`.
+//! These functions call `hash` and `isequal`, if an exception is thrown it's caught. The
+//! iterators returned by [`Dict::iter`] and [`TypedDict::iter`] read the key-value pairs
+//! directly from the dictionary and root them in two slots that are reused for every pair.
+
+use std::{marker::PhantomData, ptr::NonNull};
+
+use jl_sys::jl_value_t;
+use once_cell::sync::OnceCell;
+
+use super::{
+    array::Array,
+    symbol::{Symbol, SymbolUnbound},
+    union_all::UnionAll,
+    value::{
+        typed::{TypedValue, TypedValueData, TypedValueRef},
+        ValueData, ValueRef,
+    },
+    Ref,
+};
+use crate::{
+    call::Call,
+    convert::{
+        ccall_types::{CCallArg, CCallReturn},
+        into_jlrs_result::unrooted_exception_error,
+    },
+    data::{
+        layout::valid_layout::{ValidField, ValidLayout},
+        managed::{datatype::DataType, private::ManagedPriv, value::Value, Managed},
+        static_data::{define_static_global, static_global},
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    error::{AccessError, JlrsResult},
+    memory::target::{
+        frame::GcFrame, reusable_slot::ReusableSlot, target_type::TargetType, unrooted::Unrooted,
+        ExtendedTarget, Target,
+    },
+    private::Private,
+};
+
+define_static_global!(DICT, UnionAll<'static>, "Base.Dict");
+define_static_global!(HASKEY, "Base.haskey");
+define_static_global!(GET, "Base.get");
+define_static_global!(SETINDEX, "Base.setindex!");
+define_static_global!(POP, "Base.pop!");
+
+// A generated symbol that is used as the default value of `Base.get` and `Base.pop!`. Generated
+// symbols are unique, so it can't be stored in a dictionary unless it's leaked.
+static NOT_FOUND: OnceCell<NotFound> = OnceCell::new();
+
+struct NotFound(SymbolUnbound);
+unsafe impl Send for NotFound {}
+unsafe impl Sync for NotFound {}
+
+fn not_found() -> Value<'static, 'static> {
+    // Safety: symbols are globally rooted.
+    let unrooted = unsafe { Unrooted::new() };
+    NOT_FOUND
+        .get_or_init(|| NotFound(Symbol::generate_tagged(&unrooted, "jlrs_not_found")))
+        .0
+        .as_value()
+}
+
+/// A Julia `Dict`.
+#[repr(transparent)]
+pub struct Dict<'scope, 'data>(
+    NonNull<jl_value_t>,
+    PhantomData<&'scope ()>,
+    PhantomData<&'data ()>,
+);
+
+impl<'data> Dict<'_, 'data> {
+    /// Create a new, empty `Dict{K, V}` where `K` is `key_type` and `V` is `value_type`.
+    ///
+    /// If the type can't be constructed the exception is caught and returned.
+    pub fn new<'target, T>(
+        target: T,
+        key_type: Value<'_, 'data>,
+        value_type: Value<'_, 'data>,
+    ) -> DictResult<'target, 'data, T>
+    where
+        T: Target<'target>,
+    {
+        // Safety: the applied type is rooted in the type cache, exceptions are caught and the
+        // result is immediately rooted.
+        unsafe {
+            let res = match static_global!(DICT, target)
+                .as_value()
+                .apply_type(&target, [key_type, value_type])
+            {
+                Ok(ty) => match ty.as_value().call0(&target) {
+                    Ok(dict) => Ok(dict.ptr()),
+                    Err(e) => Err(e.ptr()),
+                },
+                Err(e) => Err(e.ptr()),
+            };
+
+            target.result_from_ptr(res, Private)
+        }
+    }
+}
+
+impl<'scope, 'data> Dict<'scope, 'data> {
+    /// Returns the number of key-value pairs in this dictionary.
+    ///
+    /// # Safety
+    ///
+    /// The dictionary must not be mutated concurrently.
+    pub unsafe fn len(self) -> JlrsResult<usize> {
+        let count = self
+            .as_value()
+            .field_accessor()
+            .field("count")?
+            .access::<isize>()?;
+
+        Ok(count as usize)
+    }
+
+    /// Returns `true` if this dictionary contains no key-value pairs.
+    ///
+    /// # Safety
+    ///
+    /// The dictionary must not be mutated concurrently.
+    pub unsafe fn is_empty(self) -> JlrsResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Returns `true` if this dictionary contains `key`.
+    ///
+    /// If an exception is thrown it's converted to an error.
+    ///
+    /// # Safety
+    ///
+    /// This method calls `Base.haskey`, which calls the `hash` and `isequal` methods of the key
+    /// type. These methods can run arbitrary Julia code, and the dictionary must not be mutated
+    /// concurrently.
+    pub unsafe fn contains_key(self, key: Value<'_, 'data>) -> JlrsResult<bool> {
+        // haskey is globally rooted, the result is unboxed before it can be freed.
+        let unrooted = self.unrooted_target();
+        let contains = static_global!(HASKEY, unrooted)
+            .call2(&unrooted, self.as_value(), key)
+            .map_err(unrooted_exception_error)?
+            .as_value()
+            .unbox::<bool>()?
+            .as_bool();
+
+        Ok(contains)
+    }
+
+    /// Returns the value associated with `key`, or `None` if this dictionary doesn't contain
+    /// `key`.
+    ///
+    /// If an exception is thrown it's converted to an error.
+    ///
+    /// # Safety
+    ///
+    /// This method calls `Base.get`, which calls the `hash` and `isequal` methods of the key
+    /// type. These methods can run arbitrary Julia code, and the dictionary must not be mutated
+    /// concurrently.
+    pub unsafe fn get<'target, T>(
+        self,
+        target: T,
+        key: Value<'_, 'data>,
+    ) -> JlrsResult<Option<ValueData<'target, 'data, T>>>
+    where
+        T: Target<'target>,
+    {
+        // get is globally rooted, the value is reachable from the dictionary and immediately
+        // rooted.
+        let not_found = not_found();
+        let value = static_global!(GET, target)
+            .call3(&target, self.as_value(), key, not_found)
+            .map_err(unrooted_exception_error)?;
+
+        if value.as_value().egal(not_found) {
+            return Ok(None);
+        }
+
+        Ok(Some(value.root(target)))
+    }
+
+    /// Insert `value` with the key `key`. If the key and value can't be converted to the key and
+    /// value type of this dictionary, the exception is caught and returned.
+    ///
+    /// # Safety
+    ///
+    /// The dictionary must not be accessed concurrently, this includes iterating over it with
+    /// [`Dict::iter`].
+    pub unsafe fn insert<'target, T>(
+        self,
+        target: T,
+        key: Value<'_, 'data>,
+        value: Value<'_, 'data>,
+    ) -> T::Exception<'data, ()>
+    where
+        T: Target<'target>,
+    {
+        let res = match static_global!(SETINDEX, target).call3(&target, self.as_value(), value, key)
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.ptr()),
+        };
+
+        target.exception_from_ptr(res, Private)
+    }
+
+    /// Remove `key` from this dictionary and return its associated value, or `None` if this
+    /// dictionary doesn't contain `key`.
+    ///
+    /// If an exception is thrown it's converted to an error.
+    ///
+    /// # Safety
+    ///
+    /// The dictionary must not be accessed concurrently, this includes iterating over it with
+    /// [`Dict::iter`].
+    pub unsafe fn remove<'target, T>(
+        self,
+        target: T,
+        key: Value<'_, 'data>,
+    ) -> JlrsResult<Option<ValueData<'target, 'data, T>>>
+    where
+        T: Target<'target>,
+    {
+        let not_found = not_found();
+        let value = static_global!(POP, target)
+            .call3(&target, self.as_value(), key, not_found)
+            .map_err(unrooted_exception_error)?;
+
+        if value.as_value().egal(not_found) {
+            return Ok(None);
+        }
+
+        Ok(Some(value.root(target)))
+    }
+
+    /// Returns an iterator over the key-value pairs of this dictionary.
+    ///
+    /// Two slots are reserved in `frame`, a pair remains rooted until the next pair is returned.
+    /// The dictionary must not be mutated while it's iterated over.
+    pub fn iter<'target>(self, frame: &GcFrame<'target>) -> DictIter<'scope, 'data, 'target> {
+        DictIter {
+            dict: self,
+            key_slot: frame.reusable_slot(),
+            value_slot: frame.reusable_slot(),
+            index: 0,
+        }
+    }
+
+    /// Try to convert this dictionary to a [`TypedDict`].
+    pub fn try_as_typed<K, V>(self) -> JlrsResult<TypedDict<'scope, 'data, K, V>>
+    where
+        K: ValidLayout + ConstructType,
+        V: ValidLayout + ConstructType,
+    {
+        self.as_value().cast::<TypedDict<K, V>>()
+    }
+
+    /// Convert this dictionary to a [`TypedDict`] without checking if the key and value types
+    /// are correct.
+    ///
+    /// # Safety
+    ///
+    /// The layouts of `K` and `V` must be valid for the key and value types of this
+    /// dictionary.
+    pub unsafe fn as_typed_unchecked<K, V>(self) -> TypedDict<'scope, 'data, K, V>
+    where
+        K: ValidLayout + ConstructType,
+        V: ValidLayout + ConstructType,
+    {
+        TypedDict::wrap_non_null(self.unwrap_non_null(Private), Private)
+    }
+
+    // Returns the array stored in the field `name`.
+    fn field_array(self, name: &str) -> JlrsResult<Array<'scope, 'data>> {
+        let array = self
+            .as_value()
+            .get_field_ref(name)?
+            .ok_or(AccessError::UndefRef)?;
+
+        // Safety: the array is reachable from the dictionary.
+        unsafe { array.as_value().cast::<Array>() }
+    }
+
+    // Returns the index of the first filled slot at or after `index`. A slot is filled if it's
+    // 0x01 (Julia 1.8 and earlier) or its high bit is set (Julia 1.9 and later).
+    fn next_filled_slot(self, index: usize) -> JlrsResult<Option<usize>> {
+        let slots = self.field_array("slots")?;
+
+        // Safety: the slots are only read.
+        let slots = unsafe { slots.bits_data::<u8>()? };
+        let next = slots.as_slice()[index.min(slots.as_slice().len())..]
+            .iter()
+            .position(|&slot| slot == 0x01 || slot & 0x80 != 0)
+            .map(|offset| index + offset);
+
+        Ok(next)
+    }
+}
+
+unsafe impl<'scope, 'data> Typecheck for Dict<'scope, 'data> {
+    fn typecheck(t: DataType) -> bool {
+        // Safety: Base.Dict is globally rooted
+        let unrooted = unsafe { Unrooted::new() };
+        t.type_name() == static_global!(DICT, unrooted).base_type().type_name()
+    }
+}
+
+impl_debug!(Dict<'_, '_>);
+
+impl<'scope, 'data> Clone for Dict<'scope, 'data> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'scope, 'data> Copy for Dict<'scope, 'data> {}
+
+impl<'scope, 'data> ManagedPriv<'scope, 'data> for Dict<'scope, 'data> {
+    type Wraps = jl_value_t;
+    type TypeConstructorPriv<'target, 'da> = Dict<'target, 'da>;
+    const NAME: &'static str = "Dict";
+
+    // Safety: `inner` must not have been freed yet, the result must never be
+    // used after the GC might have freed it.
+    unsafe fn wrap_non_null(inner: NonNull<Self::Wraps>, _: Private) -> Self {
+        Self(inner, PhantomData, PhantomData)
+    }
+
+    fn unwrap_non_null(self, _: Private) -> NonNull<Self::Wraps> {
+        self.0
+    }
+}
+
+unsafe impl ConstructType for Dict<'_, '_> {
+    fn construct_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> ValueData<'target, 'static, T>
+    where
+        T: Target<'target>,
+    {
+        let (target, _) = target.split();
+        static_global!(DICT, target).as_value().root(target)
+    }
+
+    fn base_type<'target, Tgt>(target: &Tgt) -> Option<Value<'target, 'static>>
+    where
+        Tgt: Target<'target>,
+    {
+        Some(static_global!(DICT, target).as_value())
+    }
+}
+
+impl_ccall_arg_managed!(Dict, 2);
+
+/// Exactly the same as [`Dict`], except it has an explicit key type `K` and value type `V`.
+#[repr(transparent)]
+pub struct TypedDict<'scope, 'data, K, V>(
+    NonNull<jl_value_t>,
+    PhantomData<&'scope ()>,
+    PhantomData<&'data ()>,
+    PhantomData<K>,
+    PhantomData<V>,
+);
+
+impl<'data, K, V> TypedDict<'_, 'data, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    /// Create a new, empty `Dict{K, V}`.
+    ///
+    /// If the constructor throws an exception it's caught and returned.
+    pub fn new<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<TypedDictResult<'target, 'data, K, V, T>>
+    where
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+
+        frame.scope(|mut frame| {
+            let dict_type = Self::construct_type(frame.as_extended_target());
+
+            // Safety: the constructed type is a concrete Dict type, the result is immediately
+            // rooted.
+            unsafe {
+                let res = match dict_type.call0(&frame) {
+                    Ok(dict) => Ok(dict.ptr()),
+                    Err(e) => Err(e.ptr()),
+                };
+
+                Ok(target.result_from_ptr(res, Private))
+            }
+        })
+    }
+}
+
+impl<'scope, 'data, K, V> TypedDict<'scope, 'data, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    /// Returns the number of key-value pairs in this dictionary.
+    ///
+    /// # Safety
+    ///
+    /// The dictionary must not be mutated concurrently.
+    pub unsafe fn len(self) -> JlrsResult<usize> {
+        self.as_dict().len()
+    }
+
+    /// Returns `true` if this dictionary contains no key-value pairs.
+    ///
+    /// # Safety
+    ///
+    /// The dictionary must not be mutated concurrently.
+    pub unsafe fn is_empty(self) -> JlrsResult<bool> {
+        self.as_dict().is_empty()
+    }
+
+    /// Returns `true` if this dictionary contains `key`.
+    ///
+    /// If an exception is thrown it's converted to an error.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as [`Dict::contains_key`] apply.
+    pub unsafe fn contains_key(self, key: TypedValue<'_, 'data, K>) -> JlrsResult<bool> {
+        self.as_dict().contains_key(key.as_value())
+    }
+
+    /// Returns the value associated with `key`, or `None` if this dictionary doesn't contain
+    /// `key`.
+    ///
+    /// If an exception is thrown it's converted to an error.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as [`Dict::get`] apply.
+    pub unsafe fn get<'target, T>(
+        self,
+        target: T,
+        key: TypedValue<'_, 'data, K>,
+    ) -> JlrsResult<Option<TypedValueData<'target, 'data, V, T>>>
+    where
+        T: Target<'target>,
+    {
+        // The value is an instance of V, it's reachable from the dictionary and immediately
+        // rooted.
+        let value = self.as_dict().get(&target, key.as_value())?;
+        Ok(value.map(|v| target.data_from_ptr(v.ptr().cast(), Private)))
+    }
+
+    /// Insert `value` with the key `key`.
+    ///
+    /// # Safety
+    ///
+    /// The dictionary must not be accessed concurrently, this includes iterating over it with
+    /// [`TypedDict::iter`].
+    pub unsafe fn insert<'target, T>(
+        self,
+        target: T,
+        key: TypedValue<'_, 'data, K>,
+        value: TypedValue<'_, 'data, V>,
+    ) -> T::Exception<'data, ()>
+    where
+        T: Target<'target>,
+    {
+        self.as_dict()
+            .insert(target, key.as_value(), value.as_value())
+    }
+
+    /// Remove `key` from this dictionary and return its associated value, or `None` if this
+    /// dictionary doesn't contain `key`.
+    ///
+    /// If an exception is thrown it's converted to an error.
+    ///
+    /// # Safety
+    ///
+    /// The dictionary must not be accessed concurrently, this includes iterating over it with
+    /// [`TypedDict::iter`].
+    pub unsafe fn remove<'target, T>(
+        self,
+        target: T,
+        key: TypedValue<'_, 'data, K>,
+    ) -> JlrsResult<Option<TypedValueData<'target, 'data, V, T>>>
+    where
+        T: Target<'target>,
+    {
+        let value = self.as_dict().remove(&target, key.as_value())?;
+        Ok(value.map(|v| target.data_from_ptr(v.ptr().cast(), Private)))
+    }
+
+    /// Returns an iterator over the key-value pairs of this dictionary.
+    ///
+    /// Two slots are reserved in `frame`, a pair remains rooted until the next pair is returned.
+    /// The dictionary must not be mutated while it's iterated over.
+    pub fn iter<'target>(
+        self,
+        frame: &GcFrame<'target>,
+    ) -> TypedDictIter<'scope, 'data, 'target, K, V> {
+        TypedDictIter {
+            iter: self.as_dict().iter(frame),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Convert this dictionary to a [`Dict`].
+    pub fn as_dict(self) -> Dict<'scope, 'data> {
+        // Safety: a TypedDict is always a Dict
+        unsafe { Dict::wrap_non_null(self.unwrap_non_null(Private), Private) }
+    }
+}
+
+unsafe impl<'scope, 'data, K, V> Typecheck for TypedDict<'scope, 'data, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    fn typecheck(t: DataType) -> bool {
+        if !t.is::<Dict>() {
+            return false;
+        }
+
+        // Safety: borrow is only temporary
+        unsafe {
+            let params = t.parameters();
+            let params = params.data().as_slice();
+            K::valid_layout(params[0].unwrap().as_value())
+                && V::valid_layout(params[1].unwrap().as_value())
+        }
+    }
+}
+
+impl<K, V> ::std::fmt::Debug for TypedDict<'_, '_, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        match self.display_string() {
+            Ok(s) => f.write_str(&s),
+            Err(e) => f.write_fmt(format_args!("<Cannot display value: {}>", e)),
+        }
+    }
+}
+
+impl<'scope, 'data, K, V> Clone for TypedDict<'scope, 'data, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'scope, 'data, K, V> Copy for TypedDict<'scope, 'data, K, V> {}
+
+impl<'scope, 'data, K, V> ManagedPriv<'scope, 'data> for TypedDict<'scope, 'data, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    type Wraps = jl_value_t;
+    type TypeConstructorPriv<'target, 'da> = TypedDict<'target, 'da, K, V>;
+    const NAME: &'static str = "Dict";
+
+    // Safety: `inner` must not have been freed yet, the result must never be
+    // used after the GC might have freed it.
+    unsafe fn wrap_non_null(inner: NonNull<Self::Wraps>, _: Private) -> Self {
+        Self(inner, PhantomData, PhantomData, PhantomData, PhantomData)
+    }
+
+    fn unwrap_non_null(self, _: Private) -> NonNull<Self::Wraps> {
+        self.0
+    }
+}
+
+unsafe impl<K, V> ConstructType for TypedDict<'_, '_, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    fn construct_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> ValueData<'target, 'static, T>
+    where
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+
+        frame
+            .scope(|mut frame| {
+                let key_param = K::construct_type(frame.as_extended_target());
+                let value_param = V::construct_type(frame.as_extended_target());
+                let params = [key_param, value_param];
+                unsafe {
+                    let applied = static_global!(DICT, frame)
+                        .as_value()
+                        .apply_type_unchecked(&mut frame, params);
+                    Ok(UnionAll::rewrap(
+                        target.into_extended_target(&mut frame),
+                        applied.cast_unchecked::<DataType>(),
+                    ))
+                }
+            })
+            .unwrap()
+    }
+
+    fn base_type<'target, Tgt>(target: &Tgt) -> Option<Value<'target, 'static>>
+    where
+        Tgt: Target<'target>,
+    {
+        Some(static_global!(DICT, target).as_value())
+    }
+}
+
+unsafe impl<'scope, 'data, K, V> CCallArg for TypedDict<'scope, 'data, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    type CCallArgType = Value<'static, 'static>;
+    type FunctionArgType = TypedDict<'scope, 'data, K, V>;
+}
+
+unsafe impl<K, V> CCallReturn for TypedDictRet<K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    type CCallReturnType = Value<'static, 'static>;
+    type FunctionReturnType = TypedDict<'static, 'static, K, V>;
+}
+
+/// Iterator over the key-value pairs of a [`Dict`].
+///
+/// The pairs are returned as `ValueRef`s because they're rooted in slots that are reused, a pair
+/// must not be used after the next one has been returned.
+pub struct DictIter<'scope, 'data, 'target> {
+    dict: Dict<'scope, 'data>,
+    key_slot: ReusableSlot<'target>,
+    value_slot: ReusableSlot<'target>,
+    index: usize,
+}
+
+impl<'scope, 'data, 'target> DictIter<'scope, 'data, 'target> {
+    fn next_pair(
+        &mut self,
+    ) -> JlrsResult<Option<(ValueRef<'target, 'data>, ValueRef<'target, 'data>)>> {
+        let index = match self.dict.next_filled_slot(self.index)? {
+            Some(index) => index,
+            None => {
+                self.index = usize::MAX;
+                return Ok(None);
+            }
+        };
+        self.index = index + 1;
+
+        let keys = self.dict.field_array("keys")?;
+        let vals = self.dict.field_array("vals")?;
+
+        // Safety: the keys and values are only read.
+        unsafe {
+            let key = keys
+                .indeterminate_data()
+                .get_value(&mut self.key_slot, index)?
+                .ok_or(AccessError::UndefRef)?
+                .map_err(unrooted_exception_error)?;
+
+            let value = vals
+                .indeterminate_data()
+                .get_value(&mut self.value_slot, index)?
+                .ok_or(AccessError::UndefRef)?
+                .map_err(unrooted_exception_error)?;
+
+            Ok(Some((key, value)))
+        }
+    }
+}
+
+impl<'scope, 'data, 'target> Iterator for DictIter<'scope, 'data, 'target> {
+    type Item = JlrsResult<(ValueRef<'target, 'data>, ValueRef<'target, 'data>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == usize::MAX {
+            return None;
+        }
+
+        self.next_pair().transpose()
+    }
+}
+
+/// Iterator over the key-value pairs of a [`TypedDict`].
+///
+/// The pairs are returned as `TypedValueRef`s for the same reason as [`DictIter`].
+pub struct TypedDictIter<'scope, 'data, 'target, K, V> {
+    iter: DictIter<'scope, 'data, 'target>,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<'scope, 'data, 'target, K, V> Iterator for TypedDictIter<'scope, 'data, 'target, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    type Item = JlrsResult<(
+        TypedValueRef<'target, 'data, K>,
+        TypedValueRef<'target, 'data, V>,
+    )>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The keys and values are instances of K and V respectively.
+        self.iter.next().map(|pair| {
+            pair.map(|(key, value)| {
+                (
+                    TypedValueRef::wrap(key.ptr()),
+                    TypedValueRef::wrap(value.ptr()),
+                )
+            })
+        })
+    }
+}
+
+/// A reference to a [`Dict`] that has not been explicitly rooted.
+pub type DictRef<'scope, 'data> = Ref<'scope, 'data, Dict<'scope, 'data>>;
+
+/// A [`DictRef`] with static lifetimes. This is a useful shorthand for signatures of
+/// `ccall`able functions that return a [`Dict`].
+pub type DictRet = Ref<'static, 'static, Dict<'static, 'static>>;
+
+unsafe impl ValidLayout for DictRef<'_, '_> {
+    fn valid_layout(v: Value) -> bool {
+        if let Ok(dt) = v.cast::<DataType>() {
+            dt.is::<Dict>()
+        } else {
+            false
+        }
+    }
+
+    const IS_REF: bool = true;
+}
+
+unsafe impl ValidField for Option<DictRef<'_, '_>> {
+    fn valid_field(v: Value) -> bool {
+        DictRef::valid_layout(v)
+    }
+}
+
+/// `Dict` or `DictRef`, depending on the target type `T`.
+pub type DictData<'target, 'data, T> =
+    <T as TargetType<'target>>::Data<'data, Dict<'target, 'data>>;
+
+/// `JuliaResult<Dict>` or `JuliaResultRef<DictRef>`, depending on the target type `T`.
+pub type DictResult<'target, 'data, T> =
+    <T as TargetType<'target>>::Result<'data, Dict<'target, 'data>>;
+
+/// A reference to a [`TypedDict`] that has not been explicitly rooted.
+pub type TypedDictRef<'scope, 'data, K, V> = Ref<'scope, 'data, TypedDict<'scope, 'data, K, V>>;
+
+/// A [`TypedDictRef`] with static lifetimes. This is a useful shorthand for signatures of
+/// `ccall`able functions that return a [`TypedDict`].
+pub type TypedDictRet<K, V> = Ref<'static, 'static, TypedDict<'static, 'static, K, V>>;
+
+unsafe impl<K, V> ValidLayout for TypedDictRef<'_, '_, K, V>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    fn valid_layout(v: Value) -> bool {
+        if let Ok(dt) = v.cast::<DataType>() {
+            dt.is::<TypedDict<K, V>>()
+        } else {
+            false
+        }
+    }
+
+    const IS_REF: bool = true;
+}
+
+unsafe impl<K, V> ValidField for Option<TypedDictRef<'_, '_, K, V>>
+where
+    K: ValidLayout + ConstructType,
+    V: ValidLayout + ConstructType,
+{
+    fn valid_field(v: Value) -> bool {
+        TypedDictRef::<K, V>::valid_layout(v)
+    }
+}
+
+/// `TypedDict<K, V>` or `TypedDictRef<K, V>`, depending on the target type `T`.
+pub type TypedDictData<'target, 'data, K, V, T> =
+    <T as TargetType<'target>>::Data<'data, TypedDict<'target, 'data, K, V>>;
+
+/// `JuliaResult<TypedDict<K, V>>` or `JuliaResultRef<TypedDictRef<K, V>>`, depending on the
+/// target type `T`.
+pub type TypedDictResult<'target, 'data, K, V, T> =
+    <T as TargetType<'target>>::Result<'data, TypedDict<'target, 'data, K, V>>;
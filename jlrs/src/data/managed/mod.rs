@@ -180,6 +180,7 @@ macro_rules! impl_debug {
 pub mod array;
 pub mod ccall_ref;
 pub mod datatype;
+pub mod dict;
 pub mod function;
 #[cfg(feature = "internal-types")]
 pub mod internal;
@@ -190,6 +191,7 @@ pub mod simple_vector;
 pub mod string;
 pub mod symbol;
 pub mod task;
+pub mod tuple_value;
 pub mod type_name;
 pub mod type_var;
 pub mod union;
@@ -20,7 +20,9 @@ use super::{
 #[cfg(feature = "ccall")]
 use crate::{ccall::CCall, memory::stack_frame::StackFrame};
 use crate::{
-    convert::ccall_types::CCallReturn,
+    convert::{
+        ccall_types::CCallReturn, into_julia::IntoJulia, into_julia_exception::IntoJuliaException,
+    },
     data::{
         layout::bool::Bool,
         managed::{
@@ -160,6 +162,54 @@ impl<'target, 'data, U: ConstructType> RustResult<'target, 'data, U> {
             .unwrap()
     }
 
+    /// Constructs a `RustResult` that contains `error`, which is converted to a Julia exception
+    /// with [`IntoJuliaException`].
+    pub fn exception<T: Target<'target>, E: IntoJuliaException>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+        error: E,
+    ) -> RustResultData<'target, 'data, U, T> {
+        let (target, frame) = target.split();
+
+        frame
+            .scope(|mut frame| {
+                let unrooted = frame.unrooted();
+                let error = error.into_julia_exception(&mut frame);
+                unsafe {
+                    let instance = Self::construct_type(frame.as_extended_target())
+                        .cast_unchecked::<DataType>()
+                        .instantiate_unchecked(&frame, [error, Value::true_v(&unrooted)])
+                        .as_value()
+                        .cast_unchecked::<RustResult<U>>()
+                        .root(target);
+
+                    Ok(instance)
+                }
+            })
+            .unwrap()
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "ccall")]
+    pub unsafe fn from_result_internal<E>(
+        result: Result<U, E>,
+    ) -> RustResultRef<'static, 'static, U>
+    where
+        U: IntoJulia,
+        E: IntoJuliaException,
+    {
+        CCall::invoke(|mut frame| match result {
+            Ok(data) => {
+                let unrooted = frame.unrooted();
+                let data = TypedValue::new(&mut frame, data);
+                RustResult::ok(unrooted.into_extended_target(&mut frame), data).leak()
+            }
+            Err(error) => {
+                let unrooted = frame.unrooted();
+                RustResult::exception(unrooted.into_extended_target(&mut frame), error).leak()
+            }
+        })
+    }
+
     #[doc(hidden)]
     #[cfg(feature = "ccall")]
     pub unsafe fn borrow_error_internal() -> RustResultRef<'static, 'static, U> {
@@ -307,6 +357,15 @@ unsafe impl<U: ConstructType> CCallReturn
     type FunctionReturnType = U;
 }
 
+unsafe impl<U, E> CCallReturn for Result<U, E>
+where
+    U: IntoJulia + ConstructType,
+    E: IntoJuliaException,
+{
+    type CCallReturnType = AnyType;
+    type FunctionReturnType = U;
+}
+
 /// The layout of a [`RustResult`].
 #[repr(C)]
 pub struct RustResultLayout<'scope, 'data, U: ConstructType> {
@@ -0,0 +1,462 @@
+//! Managed type for `Tuple`, create and destructure tuples with heterogeneous elements.
+//!
+//! The generic tuple layouts in [`data::layout::tuple`] can only be used if all fields are
+//! bits-types. A [`TupleValue`] is a tuple with arbitrary elements, it can be created from a Rust
+//! tuple whose elements are managed data or implement [`IntoJulia`], and destructured into a
+//! Rust tuple whose elements are managed types or can be unboxed:
+//!
+//! ```
+//! # use jlrs::prelude::*;
+//! # use jlrs::util::test::JULIA;
+//! use jlrs::data::managed::tuple_value::TupleValue;
+//! # fn main() {
+//! # JULIA.with(|j| {
+//! # let mut julia = j.borrow_mut();
+//! # let mut frame = StackFrame::new();
+//! # let mut julia = julia.instance(&mut frame);
+//! julia
+//!     .scope(|mut frame| {
+//!         let s = JuliaString::new(&mut frame, "foo");
+//!         let tup = TupleValue::new(frame.as_extended_target(), (1i32, s, 2.0f64))
+//!             .into_jlrs_result()?;
+//!
+//!         let (a, b, c) = tup.destructure::<(i32, JuliaString, f64)>(&mut frame)?;
+//!         assert_eq!(a, 1);
+//!         assert_eq!(b.as_str()?, "foo");
+//!         assert_eq!(c, 2.0);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! # });
+//! # }
+//! ```
+//!
+//! [`data::layout::tuple`]: crate::data::layout::tuple
+//! [`IntoJulia`]: crate::convert::into_julia::IntoJulia
+
+use std::{marker::PhantomData, ptr::NonNull};
+
+use jl_sys::jl_value_t;
+
+use super::{
+    array::{Array, TypedArray},
+    datatype::DataType,
+    dict::Dict,
+    module::Module,
+    string::JuliaString,
+    symbol::Symbol,
+    union_all::UnionAll,
+    value::{ValueData, MAX_SIZE},
+    Ref,
+};
+use crate::{
+    convert::{into_julia::IntoJulia, unbox::Unbox},
+    data::{
+        layout::{tuple::Tuple, valid_layout::ValidField},
+        managed::{private::ManagedPriv, value::Value, Managed},
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    error::{JlrsResult, TypeError, CANNOT_DISPLAY_TYPE, CANNOT_DISPLAY_VALUE},
+    memory::target::{frame::GcFrame, target_type::TargetType, ExtendedTarget, Target},
+    private::Private,
+};
+
+/// A Julia tuple with arbitrary elements.
+#[repr(transparent)]
+pub struct TupleValue<'scope, 'data>(
+    NonNull<jl_value_t>,
+    PhantomData<&'scope ()>,
+    PhantomData<&'data ()>,
+);
+
+impl<'data> TupleValue<'_, 'data> {
+    /// Create a new tuple from the elements of `elements`. Elements that implement `IntoJulia`
+    /// are converted to Julia data, managed data is used as is.
+    pub fn new<'target, E, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+        elements: E,
+    ) -> TupleValueResult<'target, 'data, T>
+    where
+        E: IntoTupleValue<'data>,
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+
+        frame
+            .scope(|mut frame| {
+                let values = elements.into_values(&mut frame);
+                let tuple = Tuple::new(frame.as_extended_target(), values);
+
+                // Safety: the result is a tuple, it's immediately rooted.
+                unsafe {
+                    let res = match tuple {
+                        Ok(tuple) => Ok(tuple.unwrap_non_null(Private)),
+                        Err(e) => Err(e.unwrap_non_null(Private)),
+                    };
+
+                    Ok(target.result_from_ptr(res, Private))
+                }
+            })
+            .unwrap()
+    }
+}
+
+impl<'scope, 'data> TupleValue<'scope, 'data> {
+    /// Returns the number of elements of this tuple.
+    pub fn len(self) -> usize {
+        self.as_value().n_fields()
+    }
+
+    /// Returns `true` if this tuple is empty.
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the type of the element at `idx`.
+    pub fn element_type(self, idx: usize) -> Option<DataType<'scope>> {
+        if idx >= self.len() {
+            return None;
+        }
+
+        // Safety: the field types of a tuple type are globally rooted, the elements of a tuple
+        // always have a concrete type.
+        unsafe {
+            let ty = self
+                .as_value()
+                .datatype()
+                .field_types(self.unrooted_target())
+                .as_managed()
+                .data()
+                .as_slice()[idx]?
+                .as_value()
+                .cast_unchecked::<DataType>();
+
+            Some(ty)
+        }
+    }
+
+    /// Roots the element at index `idx` if it exists and returns it, or an error if the index is
+    /// out of bounds.
+    pub fn get<'target, T>(self, target: T, idx: usize) -> JlrsResult<ValueData<'target, 'data, T>>
+    where
+        T: Target<'target>,
+    {
+        self.as_value().get_nth_field(target, idx)
+    }
+
+    /// Destructure this tuple into a Rust tuple.
+    ///
+    /// The number of elements and the type of each element are checked before any element is
+    /// converted, elements that are not bits-types are rooted in `frame`.
+    pub fn destructure<'target, D>(self, frame: &mut GcFrame<'target>) -> JlrsResult<D>
+    where
+        D: FromTupleValue<'target, 'data>,
+    {
+        if self.len() != D::LEN {
+            Err(TypeError::NotA {
+                value: self.display_string_or(CANNOT_DISPLAY_VALUE),
+                field_type: format!("Tuple with {} elements", D::LEN),
+            })?;
+        }
+
+        D::check_element_types(self)?;
+        D::from_tuple_value(frame, self)
+    }
+}
+
+unsafe impl<'scope, 'data> Typecheck for TupleValue<'scope, 'data> {
+    fn typecheck(t: DataType) -> bool {
+        t.is::<Tuple>()
+    }
+}
+
+impl_debug!(TupleValue<'_, '_>);
+
+impl<'scope, 'data> Clone for TupleValue<'scope, 'data> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'scope, 'data> Copy for TupleValue<'scope, 'data> {}
+
+impl<'scope, 'data> ManagedPriv<'scope, 'data> for TupleValue<'scope, 'data> {
+    type Wraps = jl_value_t;
+    type TypeConstructorPriv<'target, 'da> = TupleValue<'target, 'da>;
+    const NAME: &'static str = "Tuple";
+
+    // Safety: `inner` must not have been freed yet, the result must never be
+    // used after the GC might have freed it.
+    unsafe fn wrap_non_null(inner: NonNull<Self::Wraps>, _: Private) -> Self {
+        Self(inner, PhantomData, PhantomData)
+    }
+
+    fn unwrap_non_null(self, _: Private) -> NonNull<Self::Wraps> {
+        self.0
+    }
+}
+
+unsafe impl ConstructType for TupleValue<'_, '_> {
+    fn construct_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> ValueData<'target, 'static, T>
+    where
+        T: Target<'target>,
+    {
+        let (target, _) = target.split();
+        DataType::tuple_type(&target).as_value().root(target)
+    }
+
+    fn base_type<'target, Tgt>(target: &Tgt) -> Option<Value<'target, 'static>>
+    where
+        Tgt: Target<'target>,
+    {
+        Some(DataType::tuple_type(target).as_value())
+    }
+}
+
+impl_ccall_arg_managed!(TupleValue, 2);
+
+/// A reference to a [`TupleValue`] that has not been explicitly rooted.
+pub type TupleValueRef<'scope, 'data> = Ref<'scope, 'data, TupleValue<'scope, 'data>>;
+
+/// A [`TupleValueRef`] with static lifetimes. This is a useful shorthand for signatures of
+/// `ccall`able functions that return a [`TupleValue`].
+pub type TupleValueRet = Ref<'static, 'static, TupleValue<'static, 'static>>;
+
+/// `TupleValue` or `TupleValueRef`, depending on the target type `T`.
+pub type TupleValueData<'target, 'data, T> =
+    <T as TargetType<'target>>::Data<'data, TupleValue<'target, 'data>>;
+
+/// `JuliaResult<TupleValue>` or `JuliaResultRef<TupleValueRef>`, depending on the target type
+/// `T`.
+pub type TupleValueResult<'target, 'data, T> =
+    <T as TargetType<'target>>::Result<'data, TupleValue<'target, 'data>>;
+
+/// Trait implemented by types that can be extracted from a tuple with
+/// [`TupleValue::destructure`].
+///
+/// This trait is implemented for all types that can be unboxed as themselves, and for several
+/// managed types. If a managed type is extracted, the element is rooted in a frame.
+pub trait TupleElement<'target, 'data>: Sized {
+    /// Returns `true` if an element of type `ty` can be converted to `Self`.
+    fn is_element_type(ty: DataType) -> bool;
+
+    /// Convert `value` to `Self`.
+    fn from_element(value: Value<'target, 'data>) -> JlrsResult<Self>;
+}
+
+impl<'target, 'data, U> TupleElement<'target, 'data> for U
+where
+    U: Unbox<Output = U> + Typecheck,
+{
+    fn is_element_type(ty: DataType) -> bool {
+        ty.is::<U>()
+    }
+
+    fn from_element(value: Value<'target, 'data>) -> JlrsResult<Self> {
+        value.unbox::<U>()
+    }
+}
+
+impl<'target, 'data> TupleElement<'target, 'data> for Value<'target, 'data> {
+    fn is_element_type(_ty: DataType) -> bool {
+        true
+    }
+
+    fn from_element(value: Value<'target, 'data>) -> JlrsResult<Self> {
+        Ok(value)
+    }
+}
+
+impl<'target, 'data, U: ValidField> TupleElement<'target, 'data> for TypedArray<'target, 'data, U> {
+    fn is_element_type(ty: DataType) -> bool {
+        ty.is::<Self>()
+    }
+
+    fn from_element(value: Value<'target, 'data>) -> JlrsResult<Self> {
+        value.cast::<Self>()
+    }
+}
+
+macro_rules! impl_tuple_element_managed {
+    ($type:ident, 1) => {
+        impl<'target, 'data> TupleElement<'target, 'data> for $type<'target> {
+            fn is_element_type(ty: DataType) -> bool {
+                ty.is::<Self>()
+            }
+
+            fn from_element(value: Value<'target, 'data>) -> JlrsResult<Self> {
+                value.cast::<Self>()
+            }
+        }
+    };
+    ($type:ident, 2) => {
+        impl<'target, 'data> TupleElement<'target, 'data> for $type<'target, 'data> {
+            fn is_element_type(ty: DataType) -> bool {
+                ty.is::<Self>()
+            }
+
+            fn from_element(value: Value<'target, 'data>) -> JlrsResult<Self> {
+                value.cast::<Self>()
+            }
+        }
+    };
+}
+
+impl_tuple_element_managed!(Array, 2);
+impl_tuple_element_managed!(DataType, 1);
+impl_tuple_element_managed!(Dict, 2);
+impl_tuple_element_managed!(JuliaString, 1);
+impl_tuple_element_managed!(Module, 1);
+impl_tuple_element_managed!(Symbol, 1);
+impl_tuple_element_managed!(TupleValue, 2);
+impl_tuple_element_managed!(UnionAll, 1);
+
+/// Trait implemented by types that can be used as an element of a new [`TupleValue`].
+///
+/// This trait is implemented for all types that implement [`IntoJulia`], and for several managed
+/// types.
+///
+/// [`IntoJulia`]: crate::convert::into_julia::IntoJulia
+pub trait IntoTupleElement<'data> {
+    /// Convert `self` to a `Value` rooted in `frame`.
+    fn into_element<'target>(self, frame: &mut GcFrame<'target>) -> Value<'target, 'data>;
+}
+
+impl<'data, U: IntoJulia> IntoTupleElement<'data> for U {
+    fn into_element<'target>(self, frame: &mut GcFrame<'target>) -> Value<'target, 'data> {
+        Value::new(frame, self)
+    }
+}
+
+impl<'scope, 'data> IntoTupleElement<'data> for Value<'scope, 'data> {
+    fn into_element<'target>(self, frame: &mut GcFrame<'target>) -> Value<'target, 'data> {
+        self.root(frame)
+    }
+}
+
+impl<'scope, 'data, U: ValidField> IntoTupleElement<'data> for TypedArray<'scope, 'data, U> {
+    fn into_element<'target>(self, frame: &mut GcFrame<'target>) -> Value<'target, 'data> {
+        self.as_value().root(frame)
+    }
+}
+
+macro_rules! impl_into_tuple_element_managed {
+    ($type:ident, 1) => {
+        impl<'scope, 'data> IntoTupleElement<'data> for $type<'scope> {
+            fn into_element<'target>(self, frame: &mut GcFrame<'target>) -> Value<'target, 'data> {
+                self.as_value().root(frame)
+            }
+        }
+    };
+    ($type:ident, 2) => {
+        impl<'scope, 'data> IntoTupleElement<'data> for $type<'scope, 'data> {
+            fn into_element<'target>(self, frame: &mut GcFrame<'target>) -> Value<'target, 'data> {
+                self.as_value().root(frame)
+            }
+        }
+    };
+}
+
+impl_into_tuple_element_managed!(Array, 2);
+impl_into_tuple_element_managed!(DataType, 1);
+impl_into_tuple_element_managed!(Dict, 2);
+impl_into_tuple_element_managed!(JuliaString, 1);
+impl_into_tuple_element_managed!(Module, 1);
+impl_into_tuple_element_managed!(Symbol, 1);
+impl_into_tuple_element_managed!(TupleValue, 2);
+impl_into_tuple_element_managed!(UnionAll, 1);
+
+/// Trait implemented by Rust tuples whose elements implement [`TupleElement`].
+pub trait FromTupleValue<'target, 'data>: Sized {
+    /// The number of elements.
+    const LEN: usize;
+
+    #[doc(hidden)]
+    fn check_element_types(tuple: TupleValue) -> JlrsResult<()>;
+
+    #[doc(hidden)]
+    fn from_tuple_value(
+        frame: &mut GcFrame<'target>,
+        tuple: TupleValue<'_, 'data>,
+    ) -> JlrsResult<Self>;
+}
+
+/// Trait implemented by Rust tuples whose elements implement [`IntoTupleElement`].
+pub trait IntoTupleValue<'data> {
+    #[doc(hidden)]
+    fn into_values<'target>(
+        self,
+        frame: &mut GcFrame<'target>,
+    ) -> smallvec::SmallVec<[Value<'target, 'data>; MAX_SIZE]>;
+}
+
+// Returns an error if the element at `idx` can't be converted to `E`.
+fn check_element_type<'target, 'data, E: TupleElement<'target, 'data>>(
+    tuple: TupleValue,
+    idx: usize,
+) -> JlrsResult<()> {
+    match tuple.element_type(idx) {
+        Some(ty) if E::is_element_type(ty) => Ok(()),
+        ty => Err(TypeError::IncompatibleType {
+            element_type: ty
+                .map(|ty| ty.display_string_or(CANNOT_DISPLAY_TYPE))
+                .unwrap_or_else(|| String::from(CANNOT_DISPLAY_TYPE)),
+            value_type: String::from(std::any::type_name::<E>()),
+        })?,
+    }
+}
+
+macro_rules! impl_tuple_value_conversions {
+    ($n:expr, $($types:ident => $idx:expr),+) => {
+        impl<'target, 'data, $($types),+> FromTupleValue<'target, 'data> for ($($types,)+)
+        where
+            $($types: TupleElement<'target, 'data>),+
+        {
+            const LEN: usize = $n;
+
+            fn check_element_types(tuple: TupleValue) -> JlrsResult<()> {
+                $(check_element_type::<$types>(tuple, $idx)?;)+
+                Ok(())
+            }
+
+            fn from_tuple_value(
+                frame: &mut GcFrame<'target>,
+                tuple: TupleValue<'_, 'data>,
+            ) -> JlrsResult<Self> {
+                Ok(($(
+                    {
+                        let element = tuple.get(&mut *frame, $idx)?;
+                        $types::from_element(element)?
+                    },
+                )+))
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<'data, $($types),+> IntoTupleValue<'data> for ($($types,)+)
+        where
+            $($types: IntoTupleElement<'data>),+
+        {
+            fn into_values<'target>(
+                self,
+                frame: &mut GcFrame<'target>,
+            ) -> smallvec::SmallVec<[Value<'target, 'data>; MAX_SIZE]> {
+                let ($($types,)+) = self;
+                let mut values = smallvec::SmallVec::new();
+                $(values.push($types.into_element(frame));)+
+                values
+            }
+        }
+    };
+}
+
+impl_tuple_value_conversions!(1, A => 0);
+impl_tuple_value_conversions!(2, A => 0, B => 1);
+impl_tuple_value_conversions!(3, A => 0, B => 1, C => 2);
+impl_tuple_value_conversions!(4, A => 0, B => 1, C => 2, D => 3);
+impl_tuple_value_conversions!(5, A => 0, B => 1, C => 2, D => 3, E => 4);
+impl_tuple_value_conversions!(6, A => 0, B => 1, C => 2, D => 3, E => 4, F => 5);
+impl_tuple_value_conversions!(7, A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6);
+impl_tuple_value_conversions!(8, A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7);
@@ -74,13 +74,19 @@ pub struct FieldAccessor<'scope, 'data> {
 
 impl<'scope, 'data> FieldAccessor<'scope, 'data> {
     pub(crate) fn new(value: Value<'scope, 'data>) -> Self {
+        let state = if value.is::<Array>() {
+            ViewState::Array
+        } else {
+            ViewState::Unlocked
+        };
+
         FieldAccessor {
             value: Some(value.as_ref()),
             current_field_type: Some(value.datatype().as_ref()),
             offset: 0,
             #[cfg(not(feature = "julia-1-6"))]
             buffer: AtomicBuffer::new(),
-            state: ViewState::Unlocked,
+            state,
         }
     }
     /// Access the field the accessor is currenty pointing to as a value of type `T`.
@@ -0,0 +1,251 @@
+//! Iterate over Julia values from Rust.
+//!
+//! Any Julia value that implements the iteration protocol, e.g. a `Vector`, a `Generator`, the
+//! result of `eachline`, or a `Channel`, can be iterated over from Rust with [`Value::iter`]. The
+//! returned [`ValueIter`] calls `Base.iterate` until it returns `nothing`, the iteration state
+//! is kept alive in a [`ReusableSlot`] so it doesn't consume a new slot every iteration.
+//!
+//! `ValueIter` implements `Iterator`, every item is rooted in an [`Output`] that targets the
+//! frame the iterator was created with. If the items don't need to outlive the iteration,
+//! [`ValueIter::next_with`] can be used with any target instead. With a mutable reference to a
+//! `ReusableSlot` a single slot can be used for all items.
+//!
+//! When the async runtime is used, [`Value::iter_async`] returns an [`AsyncValueIter`]. It calls
+//! `Base.iterate` in a new task, so other tasks can make progress while the next item isn't
+//! available yet. This is useful for long-running streams like a `Channel` that's filled by
+//! another task.
+//!
+//! Example:
+//!
+//! ```
+//! # use jlrs::prelude::*;
+//! # use jlrs::util::test::JULIA;
+//! # fn main() {
+//! # JULIA.with(|j| {
+//! # let mut julia = j.borrow_mut();
+//! # let mut frame = StackFrame::new();
+//! # let mut julia = julia.instance(&mut frame);
+//! julia
+//!     .scope(|mut frame| unsafe {
+//!         let gen = Value::eval_string(&mut frame, "(x^2 for x in 1:4)").into_jlrs_result()?;
+//!
+//!         let mut slot = frame.reusable_slot();
+//!         let mut iter = gen.iter(&frame);
+//!         let mut sum = 0;
+//!         while let Some(item) = iter.next_with(&mut slot)? {
+//!             sum += item.as_value().unbox::<i64>()?;
+//!         }
+//!
+//!         assert_eq!(sum, 30);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! # });
+//! # }
+//! ```
+//!
+//! [`Output`]: crate::memory::target::output::Output
+
+use super::{Value, ValueData, ValueRef};
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::unrooted_exception_error,
+    data::{
+        layout::nothing::Nothing,
+        static_data::{define_static_global, static_global},
+    },
+    error::JlrsResult,
+    memory::target::{frame::GcFrame, reusable_slot::ReusableSlot, Target},
+};
+
+define_static_global!(ITERATE, "Base.iterate");
+
+/// Iterator over a Julia value that implements the iteration protocol.
+///
+/// Created with [`Value::iter`].
+pub struct ValueIter<'target, 'data, 'borrow> {
+    iterable: Value<'borrow, 'data>,
+    frame: &'borrow GcFrame<'target>,
+    pair: ReusableSlot<'target>,
+    state_slot: ReusableSlot<'target>,
+    state: Option<ValueRef<'target, 'data>>,
+    done: bool,
+}
+
+impl<'target, 'data, 'borrow> ValueIter<'target, 'data, 'borrow> {
+    pub(crate) fn new(iterable: Value<'borrow, 'data>, frame: &'borrow GcFrame<'target>) -> Self {
+        ValueIter {
+            iterable,
+            frame,
+            pair: frame.reusable_slot(),
+            state_slot: frame.reusable_slot(),
+            state: None,
+            done: false,
+        }
+    }
+
+    /// Returns `true` if `Base.iterate` has returned `nothing` or thrown an exception.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Get the next item and root it in `target`, or `None` if the iterator is exhausted.
+    ///
+    /// If `Base.iterate` throws an exception it's converted to an error, the iterator is
+    /// exhausted afterwards.
+    pub fn next_with<'t, T>(&mut self, target: T) -> JlrsResult<Option<ValueData<'t, 'data, T>>>
+    where
+        T: Target<'t>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+
+        // Safety: iterate is globally rooted, the (item, state) tuple is rooted in pair and the
+        // state in state_slot until the next iteration. The item is immediately rooted.
+        unsafe {
+            let unrooted = self.frame.unrooted();
+            let iterate = static_global!(ITERATE, unrooted);
+
+            let res = match self.state {
+                Some(state) => iterate.call2(&mut self.pair, self.iterable, state.as_value()),
+                None => iterate.call1(&mut self.pair, self.iterable),
+            };
+
+            let pair = match res {
+                Ok(pair) => pair.as_value(),
+                Err(e) => {
+                    self.done = true;
+                    Err(unrooted_exception_error(e))?
+                }
+            };
+
+            if pair.is::<Nothing>() {
+                self.done = true;
+                return Ok(None);
+            }
+
+            self.state = Some(pair.get_nth_field(&mut self.state_slot, 1)?);
+            Ok(Some(pair.get_nth_field(target, 0)?))
+        }
+    }
+}
+
+impl<'target, 'data, 'borrow> Iterator for ValueIter<'target, 'data, 'borrow> {
+    type Item = JlrsResult<Value<'target, 'data>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let output = self.frame.output();
+        self.next_with(output).transpose()
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "async")] {
+        use crate::{
+            call::CallAsync,
+            convert::into_jlrs_result::IntoJlrsResult,
+            memory::target::frame::AsyncGcFrame,
+        };
+
+        /// Async iterator over a Julia value that implements the iteration protocol.
+        ///
+        /// Created with [`Value::iter_async`]. Every call to `Base.iterate` happens in a new
+        /// task, the current task yields until that task has completed.
+        pub struct AsyncValueIter<'target, 'data, 'borrow> {
+            iterable: Value<'borrow, 'data>,
+            frame: &'borrow mut AsyncGcFrame<'target>,
+            state_slot: ReusableSlot<'target>,
+            state: Option<ValueRef<'target, 'data>>,
+            done: bool,
+        }
+
+        impl<'target, 'data, 'borrow> AsyncValueIter<'target, 'data, 'borrow>
+        where
+            'data: 'target,
+        {
+            pub(crate) fn new(
+                iterable: Value<'borrow, 'data>,
+                frame: &'borrow mut AsyncGcFrame<'target>,
+            ) -> Self {
+                let state_slot = frame.reusable_slot();
+                AsyncValueIter {
+                    iterable,
+                    frame,
+                    state_slot,
+                    state: None,
+                    done: false,
+                }
+            }
+
+            /// Returns `true` if `Base.iterate` has returned `nothing` or thrown an exception.
+            pub fn is_done(&self) -> bool {
+                self.done
+            }
+
+            /// Get the next item, or `None` if the iterator is exhausted. The item is rooted
+            /// in the frame this iterator was created with.
+            ///
+            /// If `Base.iterate` throws an exception it's converted to an error, the iterator is
+            /// exhausted afterwards.
+            ///
+            /// Safety: `Base.iterate` is called in a new task, the iterable must not be accessed
+            /// concurrently.
+            pub async unsafe fn next(&mut self) -> Option<JlrsResult<Value<'target, 'data>>> {
+                if self.done {
+                    return None;
+                }
+
+                let output = self.frame.output();
+                let iterable = self.iterable;
+                let state = self.state;
+                let state_slot = &mut self.state_slot;
+
+                let res = self
+                    .frame
+                    .async_scope(|mut frame| async move {
+                        let iterate = static_global!(ITERATE, frame);
+
+                        let res = match state {
+                            Some(state) => {
+                                iterate
+                                    .call_async(&mut frame, [iterable, state.as_value()])
+                                    .await
+                            }
+                            None => iterate.call_async(&mut frame, [iterable]).await,
+                        };
+
+                        let pair = res.into_jlrs_result()?;
+
+                        if pair.is::<Nothing>() {
+                            return Ok(None);
+                        }
+
+                        let state = pair.get_nth_field(state_slot, 1)?;
+                        let item = pair.get_nth_field(output, 0)?;
+                        Ok(Some((item, state)))
+                    })
+                    .await;
+
+                match res {
+                    Ok(Some((item, state))) => {
+                        self.state = Some(state);
+                        Some(Ok(item))
+                    }
+                    Ok(None) => {
+                        self.done = true;
+                        None
+                    }
+                    Err(e) => {
+                        self.done = true;
+                        Some(Err(e))
+                    }
+                }
+            }
+        }
+    }
+}
@@ -32,6 +32,7 @@ jl_atomic_swap_bits
 */
 
 pub mod field_accessor;
+pub mod iter;
 pub mod tracked;
 pub mod typed;
 
@@ -134,10 +135,11 @@ use jl_sys::{
 };
 use jlrs_macros::julia_version;
 
-use self::{field_accessor::FieldAccessor, typed::TypedValue};
+use self::{field_accessor::FieldAccessor, iter::ValueIter, typed::TypedValue};
 use super::Ref;
 use crate::{
     call::{Call, ProvideKeywords, WithKeywords},
+    catch::call_catch_backtrace,
     convert::{into_julia::IntoJulia, to_symbol::ToSymbol, unbox::Unbox},
     data::{
         layout::valid_layout::{ValidField, ValidLayout},
@@ -159,7 +161,7 @@ use crate::{
         },
     },
     error::{
-        AccessError, IOError, InstantiationError, JlrsError, JlrsResult, TypeError,
+        AccessError, Exception, IOError, InstantiationError, JlrsError, JlrsResult, TypeError,
         CANNOT_DISPLAY_TYPE,
     },
     memory::{
@@ -1166,6 +1168,90 @@ impl Value<'_, '_> {
     }
 }
 
+/// # Call with backtrace
+impl<'scope, 'data> Value<'scope, 'data> {
+    /// Call this value with `args`. If an exception is thrown, the backtrace is captured along
+    /// with it. Both are decoded into an [`Exception`] which is returned as
+    /// `JlrsError::Exception`, the backtrace is available with [`Exception::backtrace`].
+    ///
+    /// The function is called with `Base.invokelatest` so, like [`Call::call`], it's called in
+    /// the latest world age. The backtrace can't be captured when Julia 1.6 is used on Windows.
+    /// The methods of [`Call`] don't capture backtraces, use this method or
+    /// [`WithKeywords::call_with_backtrace`] if the backtrace is needed.
+    ///
+    /// Safety: this method has the same safety requirements as [`Call::call`].
+    ///
+    /// [`Exception`]: crate::error::Exception
+    /// [`Exception::backtrace`]: crate::error::Exception::backtrace
+    pub unsafe fn call_with_backtrace<'target, 'value, V>(
+        self,
+        frame: &mut GcFrame<'target>,
+        args: V,
+    ) -> JlrsResult<Value<'target, 'data>>
+    where
+        V: AsRef<[Value<'value, 'data>]>,
+    {
+        let args = args.as_ref();
+        let mut vals = smallvec::SmallVec::<[*mut jl_value_t; MAX_SIZE]>::with_capacity(args.len());
+        vals.extend(args.iter().map(|arg| arg.unwrap(Private)));
+
+        match call_catch_backtrace(self.unwrap(Private), &vals) {
+            Ok(res) => Ok(ValueRef::wrap(res).root(frame)),
+            Err((exception, backtrace)) => frame.scope(|mut frame| {
+                // Root the exception and backtrace before anything is allocated.
+                let exception = exception.root(&mut frame);
+                let decoded = match backtrace {
+                    Some(backtrace) => {
+                        let backtrace = backtrace.root(&mut frame);
+                        Exception::decode_with_backtrace(&mut frame, exception, backtrace)?
+                    }
+                    None => Exception::decode(exception),
+                };
+                Err(decoded)?
+            }),
+        }
+    }
+}
+
+/// # Iteration
+impl<'scope, 'data> Value<'scope, 'data> {
+    /// Returns an iterator over this value that calls `Base.iterate` until it returns `nothing`.
+    ///
+    /// Items returned by the `Iterator` implementation are rooted in `frame`, which can't be
+    /// used to create a new scope while the iterator is alive. See [`iter`] for more information.
+    ///
+    /// Safety: `Base.iterate` can execute arbitrary Julia code, the iterator has the same safety
+    /// requirements as [`Call::call`].
+    ///
+    /// [`iter`]: crate::data::managed::value::iter
+    pub unsafe fn iter<'target, 'borrow>(
+        self,
+        frame: &'borrow GcFrame<'target>,
+    ) -> ValueIter<'target, 'data, 'borrow>
+    where
+        'scope: 'borrow,
+    {
+        ValueIter::new(self, frame)
+    }
+
+    #[cfg(feature = "async")]
+    /// Returns an async iterator over this value that calls `Base.iterate` in a new task until
+    /// it returns `nothing`. Items are rooted in `frame`.
+    ///
+    /// Safety: `Base.iterate` can execute arbitrary Julia code, the iterator has the same safety
+    /// requirements as [`Call::call`].
+    pub unsafe fn iter_async<'target, 'borrow>(
+        self,
+        frame: &'borrow mut crate::memory::target::frame::AsyncGcFrame<'target>,
+    ) -> iter::AsyncValueIter<'target, 'data, 'borrow>
+    where
+        'scope: 'borrow,
+        'data: 'target,
+    {
+        iter::AsyncValueIter::new(self, frame)
+    }
+}
+
 /// # Finalization
 impl Value<'_, '_> {
     /// Add a finalizer `f` to this value. The finalizer must be a Julia function, it will be
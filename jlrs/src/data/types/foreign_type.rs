@@ -52,7 +52,45 @@
 //! `julia_module` automatically takes care of this, otherwise you must manually call
 //! `OpaqueType::create_type` or `OpaqueType::reinit_type`. The first must be called if the
 //! type doesn't exist yet, the second if the module that defines the type has been precompiled.
-use std::{any::TypeId, ffi::c_void, mem::MaybeUninit, ptr::NonNull, sync::RwLock};
+//!
+//! The instantiations of a generic type can be exposed as a single parametric type by
+//! implementing `ParametricBase` and `ParametricVariant` in addition to `OpaqueType`. The
+//! parametric type is created with `ParametricBase::create_type`, each instantiation with
+//! `ParametricVariant::create_variant`:
+//!
+//! ```ignore
+//! struct Grid<T> {
+//!     data: Vec<T>,
+//! }
+//!
+//! unsafe impl<T: Send + 'static> OpaqueType for Grid<T> {}
+//!
+//! unsafe impl<T: Send + 'static> ParametricBase for Grid<T> {
+//!     type Key = Grid<()>;
+//!
+//!     fn type_parameters() -> &'static [&'static str] {
+//!         &["T"]
+//!     }
+//! }
+//!
+//! unsafe impl<T: ConstructType + Send + 'static> ParametricVariant for Grid<T> {
+//!     type Parameters = Tuple1<T>;
+//! }
+//!
+//! julia_module! {
+//!     become module_jl_init;
+//!
+//!     for T in [f32, f64]
+//!     struct Grid<T> as Grid <: AbstractMatrix{T};
+//! }
+//! ```
+use std::{
+    any::{type_name, TypeId},
+    ffi::c_void,
+    mem::MaybeUninit,
+    ptr::NonNull,
+    sync::RwLock,
+};
 
 #[julia_version(except = ["1.7"])]
 use jl_sys::jl_gc_schedule_foreign_sweepfunc;
@@ -63,19 +101,24 @@ use jlrs_macros::julia_version;
 
 use super::typecheck::Typecheck;
 use crate::{
-    convert::{into_julia::IntoJulia, unbox::Unbox},
+    call::Call,
+    convert::{into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia, unbox::Unbox},
     data::{
         layout::valid_layout::ValidLayout,
         managed::{
             datatype::{DataType, DataTypeData},
             module::Module,
             private::ManagedPriv,
+            simple_vector::SimpleVector,
             symbol::Symbol,
+            type_var::TypeVar,
+            union_all::UnionAll,
             value::{Value, ValueData, ValueRef},
             Managed,
         },
         types::construct_type::ConstructType,
     },
+    error::{AccessError, JlrsResult, TypeError},
     memory::{
         get_tls,
         target::{ExtendedTarget, Target},
@@ -85,6 +128,7 @@ use crate::{
 };
 
 static FOREIGN_TYPE_REGISTRY: ForeignTypes = ForeignTypes::new();
+static PARAMETRIC_TYPE_REGISTRY: ForeignTypes = ForeignTypes::new();
 
 struct ForeignTypes {
     data: RwLock<Vec<(TypeId, DataType<'static>)>>,
@@ -152,7 +196,29 @@ pub unsafe trait OpaqueType: Sized + Send + 'static {
     where
         Tgt: Target<'target>,
     {
-        create_opaque_type::<Self, Tgt>(target, name, module)
+        create_opaque_type::<Self, Tgt>(target, name, module, None)
+    }
+
+    /// Creates a new opaque type named `name` in `module` with the supertype `super_type`.
+    ///
+    /// This method can be used instead of `OpaqueType::create_type` to override the supertype
+    /// returned by `OpaqueType::super_type`. It's called by init functions generated with the
+    /// `julia_module` macro if a supertype has been declared.
+    ///
+    /// Safety:
+    ///
+    /// The new type is not set as a constant in `module`, you must do this manually after calling
+    /// this function. You must not override the default implementation.
+    unsafe fn create_type_with_super_type<'target, Tgt>(
+        target: Tgt,
+        name: Symbol,
+        module: Module,
+        super_type: DataType,
+    ) -> DataTypeData<'target, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        create_opaque_type::<Self, Tgt>(target, name, module, Some(super_type))
     }
 
     /// Reinitializes the previously created type `datatype`.
@@ -260,7 +326,19 @@ unsafe impl<T: ForeignType> OpaqueType for T {
     where
         Tgt: Target<'target>,
     {
-        create_foreign_type::<Self, Tgt>(target, name, module)
+        create_foreign_type::<Self, Tgt>(target, name, module, None)
+    }
+
+    unsafe fn create_type_with_super_type<'target, Tgt>(
+        target: Tgt,
+        name: Symbol,
+        module: Module,
+        super_type: DataType,
+    ) -> DataTypeData<'target, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        create_foreign_type::<Self, Tgt>(target, name, module, Some(super_type))
     }
 
     unsafe fn reinit_type(datatype: DataType) -> bool {
@@ -268,10 +346,135 @@ unsafe impl<T: ForeignType> OpaqueType for T {
     }
 }
 
+/// A generic type whose instantiations are exposed to Julia as a parametric opaque type.
+///
+/// While every instantiation of a generic type can implement `OpaqueType`, each of them would be
+/// exposed as a distinct type. Instead, a single parametric type can be created, e.g. `Grid{T}`,
+/// and an instantiation like `Grid<f64>` can be exposed as `Grid{Float64}`. The parametric type
+/// is created with `ParametricBase::create_type`, every instantiation that's used must be
+/// created with `ParametricVariant::create_variant` afterwards.
+///
+/// Safety:
+///
+/// All instantiations must have the same `Key` and type parameters. The implementor can't
+/// contain any references to Julia data.
+pub unsafe trait ParametricBase: Sized + Send + 'static {
+    /// The type that identifies the parametric type, it must be the same for all
+    /// instantiations, e.g. `Grid<()>`.
+    type Key: 'static;
+
+    /// The names of the type parameters.
+    fn type_parameters() -> &'static [&'static str];
+
+    /// The supertype of the parametric type, `Core.Any` by default. The type parameters of the
+    /// parametric type are provided as `TypeVar`s.
+    fn super_type<'target, Tgt>(
+        target: Tgt,
+        _params: &[TypeVar],
+    ) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        DataType::any_type(&target).as_value().root(target)
+    }
+
+    /// Creates a new parametric opaque type named `name` in `module`, the `UnionAll` is returned.
+    ///
+    /// A parametric type must be created if it doesn't exist yet in `module`. This method is
+    /// called automatically by init functions generated with the `julia_module` macro. An error
+    /// is returned if the supertype isn't a `DataType`.
+    ///
+    /// Safety:
+    ///
+    /// The new type is not set as a constant in `module`, you must do this manually after calling
+    /// this function. You must not override the default implementation.
+    unsafe fn create_type<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+        name: Symbol,
+        module: Module,
+    ) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        create_parametric_opaque_type::<Self, Tgt>(target, name, module, None)
+    }
+
+    /// Creates a new parametric opaque type named `name` in `module` with the supertype returned
+    /// by the Julia function `super_type`, the `UnionAll` is returned.
+    ///
+    /// `super_type` is called with the type parameters as `TypeVar`s, e.g. `T -> AbstractVector{T}`.
+    /// This method can be used instead of `ParametricBase::create_type` to override the supertype
+    /// returned by `ParametricBase::super_type`. It's called by init functions generated with the
+    /// `julia_module` macro if a supertype has been declared. An error is returned if
+    /// `super_type` throws or doesn't return a `DataType`.
+    ///
+    /// Safety:
+    ///
+    /// The new type is not set as a constant in `module`, you must do this manually after calling
+    /// this function. You must not override the default implementation.
+    unsafe fn create_type_with_super_type<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+        name: Symbol,
+        module: Module,
+        super_type: Value,
+    ) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        create_parametric_opaque_type::<Self, Tgt>(target, name, module, Some(super_type))
+    }
+
+    /// Reinitializes the previously created parametric type `ty`.
+    ///
+    /// A parametric type must be reinitialized if it has been created in a precompiled module
+    /// and this module is loaded. This method is called automatically by init functions
+    /// generated with the `julia_module` macro.
+    ///
+    /// Safety:
+    ///
+    /// The type must have been originally created by calling `ParametricBase::create_type`. You
+    /// must not override the default implementation.
+    unsafe fn reinit_type(ty: Value) -> bool {
+        reinit_parametric_opaque_type::<Self>(ty)
+    }
+}
+
+/// An instantiation of a parametric opaque type.
+///
+/// Safety:
+///
+/// The number of type parameters must match `ParametricBase::type_parameters`. The
+/// implementation of `OpaqueType` must not be used to create a new type.
+pub unsafe trait ParametricVariant: ParametricBase + OpaqueType {
+    /// The type parameters of this instantiation as a tuple type, e.g. `Tuple1<T>`.
+    ///
+    /// The Julia type parameters are the parameters of the constructed tuple type.
+    type Parameters: ConstructType;
+
+    /// Creates this instantiation of the parametric type.
+    ///
+    /// The parametric type must have been created or reinitialized before calling this method,
+    /// which is called automatically by init functions generated with the `julia_module` macro.
+    /// An error is returned if the type can't be applied to `Parameters`.
+    ///
+    /// Safety:
+    ///
+    /// You must not override the default implementation.
+    unsafe fn create_variant<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+    ) -> JlrsResult<DataTypeData<'target, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        create_parametric_variant::<Self, Tgt>(target)
+    }
+}
+
 unsafe fn create_foreign_type<'target, U, T>(
     target: T,
     name: Symbol,
     module: Module,
+    super_type: Option<DataType>,
 ) -> DataTypeData<'target, T>
 where
     U: ForeignType,
@@ -292,7 +495,10 @@ where
         do_sweep::<T>(&mut *value.cast())
     }
 
-    let super_type = U::super_type(&target).ptr().as_ptr();
+    let super_type = match super_type {
+        Some(super_type) => super_type.unwrap(Private),
+        None => U::super_type(&target).ptr().as_ptr(),
+    };
 
     let ty = jl_new_foreign_type(
         name.unwrap(Private),
@@ -321,6 +527,7 @@ unsafe fn create_opaque_type<'target, U, T>(
     target: T,
     name: Symbol,
     module: Module,
+    super_type: Option<DataType>,
 ) -> DataTypeData<'target, T>
 where
     U: OpaqueType,
@@ -330,7 +537,10 @@ where
         return target.data_from_ptr(ty.unwrap_non_null(Private), Private);
     }
 
-    let super_type = U::super_type(&target).ptr().as_ptr();
+    let super_type = match super_type {
+        Some(super_type) => super_type.unwrap(Private),
+        None => U::super_type(&target).ptr().as_ptr(),
+    };
 
     #[cfg(feature = "julia-1-6")]
     let ty = jl_new_datatype(
@@ -468,6 +678,172 @@ where
     true
 }
 
+unsafe fn create_parametric_opaque_type<'target, U, Tgt>(
+    target: ExtendedTarget<'target, '_, '_, Tgt>,
+    name: Symbol,
+    module: Module,
+    super_type: Option<Value>,
+) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+where
+    U: ParametricBase,
+    Tgt: Target<'target>,
+{
+    let (target, frame) = target.split();
+    if let Some(ty) = PARAMETRIC_TYPE_REGISTRY.find::<U::Key>() {
+        return Ok(wrapper(ty).root(target));
+    }
+
+    frame.scope(|mut frame| {
+        let names = U::type_parameters();
+        let mut params = SimpleVector::with_capacity(&mut frame, names.len());
+        let mut tvars = Vec::with_capacity(names.len());
+        {
+            let mut data = params.data_mut();
+            for (i, name) in names.iter().copied().enumerate() {
+                let tvar = TypeVar::new_unchecked(&mut frame, name, None, None);
+                data.set(i, Some(tvar.as_value()))?;
+                tvars.push(tvar);
+            }
+        }
+
+        let super_type = match super_type {
+            Some(func) => {
+                let tvars = tvars.iter().map(|tvar| tvar.as_value()).collect::<Vec<_>>();
+                func.call(&mut frame, tvars).into_jlrs_result()?
+            }
+            None => U::super_type(&mut frame, &tvars),
+        };
+        let super_type = super_type.cast::<DataType>()?;
+
+        #[cfg(feature = "julia-1-6")]
+        let ty = jl_new_datatype(
+            name.unwrap(Private),
+            module.unwrap(Private),
+            super_type.unwrap(Private),
+            params.unwrap(Private),
+            jl_emptysvec,
+            jl_emptysvec,
+            0,
+            1,
+            0,
+        );
+
+        #[cfg(not(feature = "julia-1-6"))]
+        let ty = jl_new_datatype(
+            name.unwrap(Private),
+            module.unwrap(Private),
+            super_type.unwrap(Private),
+            params.unwrap(Private),
+            jl_emptysvec,
+            jl_emptysvec,
+            jl_emptysvec,
+            0,
+            1,
+            0,
+        );
+
+        debug_assert!(!ty.is_null());
+        let ty = DataType::wrap_non_null(NonNull::new_unchecked(ty), Private);
+        PARAMETRIC_TYPE_REGISTRY
+            .data
+            .write()
+            .expect("Foreign type lock was poisoned")
+            .push((TypeId::of::<U::Key>(), ty));
+
+        Ok(wrapper(ty).root(target))
+    })
+}
+
+unsafe fn reinit_parametric_opaque_type<U>(ty: Value) -> bool
+where
+    U: ParametricBase,
+{
+    if PARAMETRIC_TYPE_REGISTRY.find::<U::Key>().is_some() {
+        return true;
+    }
+
+    let ty = match ty.cast::<UnionAll>() {
+        Ok(ty) => ty.base_type(),
+        Err(_) => return false,
+    };
+
+    PARAMETRIC_TYPE_REGISTRY
+        .data
+        .write()
+        .expect("Foreign type lock was poisoned")
+        .push((
+            TypeId::of::<U::Key>(),
+            DataType::wrap_non_null(ty.unwrap_non_null(Private), Private),
+        ));
+    true
+}
+
+unsafe fn create_parametric_variant<'target, U, Tgt>(
+    target: ExtendedTarget<'target, '_, '_, Tgt>,
+) -> JlrsResult<DataTypeData<'target, Tgt>>
+where
+    U: ParametricVariant,
+    Tgt: Target<'target>,
+{
+    let (target, frame) = target.split();
+    if let Some(ty) = FOREIGN_TYPE_REGISTRY.find::<U>() {
+        return Ok(target.data_from_ptr(ty.unwrap_non_null(Private), Private));
+    }
+
+    frame.scope(|mut frame| {
+        let base = PARAMETRIC_TYPE_REGISTRY.find::<U::Key>().ok_or_else(|| {
+            TypeError::NoParametricType {
+                variant: type_name::<U>().into(),
+            }
+        })?;
+
+        let params = U::Parameters::construct_type(frame.as_extended_target())
+            .cast::<DataType>()?
+            .parameters();
+        let params = params
+            .data()
+            .as_slice()
+            .iter()
+            .map(|param| {
+                param
+                    .map(|param| param.as_value())
+                    .ok_or(AccessError::UndefRef)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let n_type_params = U::type_parameters().len();
+        if params.len() != n_type_params {
+            Err(TypeError::VariantParametersMismatch {
+                variant: type_name::<U>().into(),
+                n_params: params.len(),
+                n_type_params,
+            })?;
+        }
+
+        let ty = wrapper(base)
+            .apply_type(&mut frame, params)
+            .into_jlrs_result()?
+            .cast::<DataType>()?;
+
+        FOREIGN_TYPE_REGISTRY
+            .data
+            .write()
+            .expect("Foreign type lock was poisoned")
+            .push((
+                TypeId::of::<U>(),
+                DataType::wrap_non_null(ty.unwrap_non_null(Private), Private),
+            ));
+
+        Ok(ty.root(target))
+    })
+}
+
+// The `UnionAll` of a parametric type.
+unsafe fn wrapper<'scope>(ty: DataType<'scope>) -> Value<'scope, 'static> {
+    let wrapper = ty.type_name().unwrap_non_null(Private).as_ref().wrapper;
+    Value::wrap_non_null(NonNull::new_unchecked(wrapper), Private)
+}
+
 #[julia_version(since = "1.7", until = "1.7")]
 #[inline(always)]
 unsafe fn do_sweep<T>(_: &mut ForeignValue<T>)
@@ -0,0 +1,462 @@
+//! Decode Julia exceptions.
+//!
+//! When an exception is converted to an [`Exception`] with [`Exception::decode`], e.g. when a
+//! `JuliaResult` is converted to a `JlrsResult` with
+//! [`IntoJlrsResult::into_decoded_jlrs_result`], the type of the exception and its most relevant
+//! fields are decoded into an [`ExceptionKind`]. This lets you branch on the kind of exception
+//! that has been thrown rather than its error message:
+//!
+//! ```
+//! # use jlrs::prelude::*;
+//! # use jlrs::util::test::JULIA;
+//! use jlrs::error::{exception::ExceptionKind, JlrsError};
+//!
+//! # fn main() {
+//! # JULIA.with(|j| {
+//! # let mut julia = j.borrow_mut();
+//! # let mut frame = StackFrame::new();
+//! # let mut julia = julia.instance(&mut frame);
+//! julia
+//!     .scope(|mut frame| unsafe {
+//!         let res = Value::eval_string(&mut frame, "[1, 2, 3][4]").into_decoded_jlrs_result();
+//!
+//!         match res {
+//!             Err(e) => match e.as_ref() {
+//!                 JlrsError::Exception(e) => match e.kind() {
+//!                     Some(ExceptionKind::BoundsError { indices, .. }) => {
+//!                         assert_eq!(indices.as_deref(), Some(&[4][..]))
+//!                     }
+//!                     _ => panic!("expected a BoundsError"),
+//!                 },
+//!                 _ => panic!("expected an exception"),
+//!             },
+//!             Ok(_) => panic!("expected an exception"),
+//!         }
+//!
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! # });
+//! # }
+//! ```
+//!
+//! [`IntoJlrsResult::into_decoded_jlrs_result`]: crate::convert::into_jlrs_result::IntoJlrsResult::into_decoded_jlrs_result
+
+use std::fmt;
+
+use super::{Exception, JlrsResult, CANNOT_DISPLAY_TYPE, CANNOT_DISPLAY_VALUE};
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::unrooted_exception_error,
+    data::{
+        layout::tuple::Tuple,
+        managed::{
+            array::TypedArray,
+            datatype::DataType,
+            function::Function,
+            string::JuliaString,
+            symbol::Symbol,
+            value::{Value, ValueRef},
+            Managed,
+        },
+        static_data::{define_static_global, static_global},
+    },
+    memory::target::frame::GcFrame,
+};
+
+define_static_global!(INEXACT_ERROR, DataType<'static>, "Core.InexactError");
+define_static_global!(DOMAIN_ERROR, DataType<'static>, "Core.DomainError");
+define_static_global!(KEY_ERROR, DataType<'static>, "Base.KeyError");
+define_static_global!(NAMEOF, "Base.nameof");
+define_static_global!(STACKTRACE, "Base.stacktrace");
+define_static_global!(SHOW_BACKTRACE, "Base.show_backtrace");
+define_static_global!(IOBUFFER, "Base.IOBuffer");
+define_static_global!(IOCONTEXT, "Base.IOContext");
+define_static_global!(PAIR, "Base.Pair");
+define_static_global!(TAKE, "Base.take!");
+define_static_global!(COLOR, "JlrsCore.color");
+
+/// A decoded Julia exception.
+///
+/// Values are converted to strings with `Base.show`, types are converted to their name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExceptionKind {
+    /// A `BoundsError`.
+    BoundsError {
+        /// The type of the value that was indexed, if it's known.
+        value_type: Option<String>,
+        /// The offending indices, if they're known and are integers.
+        indices: Option<Vec<isize>>,
+    },
+    /// A `MethodError`.
+    MethodError {
+        /// The name of the function.
+        function: String,
+        /// The types of the arguments the function was called with.
+        arg_types: Vec<String>,
+    },
+    /// An `ArgumentError`.
+    ArgumentError {
+        /// The error message.
+        msg: String,
+    },
+    /// An `InexactError`.
+    InexactError {
+        /// The name of the function that threw the error.
+        function: String,
+        /// The type the value couldn't be converted to.
+        target_type: String,
+        /// The value that couldn't be converted.
+        value: String,
+    },
+    /// A `DomainError`.
+    DomainError {
+        /// The value that is outside the valid domain.
+        value: String,
+        /// The error message.
+        msg: String,
+    },
+    /// A `KeyError`.
+    KeyError {
+        /// The key that wasn't found.
+        key: String,
+    },
+    /// An `InterruptException`.
+    InterruptException,
+    /// An `ErrorException`, which is thrown by `error`.
+    ErrorException {
+        /// The error message.
+        msg: String,
+    },
+    /// An `UndefVarError`.
+    UndefVarError {
+        /// The name of the undefined variable.
+        var: String,
+    },
+    /// A `TypeError`.
+    TypeError {
+        /// The name of the function that threw the error.
+        function: String,
+        /// The expected type.
+        expected: String,
+        /// The value that was found instead.
+        got: String,
+    },
+    /// Any other exception.
+    Other {
+        /// The type of the exception.
+        exception_type: String,
+    },
+}
+
+/// The backtrace of an exception.
+///
+/// Its `Display` implementation prints the backtrace like `showerror` does, so
+/// `format!("{}{}", exception, backtrace)` matches the output of `showerror(io, e, bt)`. The
+/// output is colored if colored errors have been enabled with `Julia::error_color`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backtrace {
+    frames: Vec<BacktraceFrame>,
+    formatted: String,
+}
+
+/// A single frame of a backtrace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BacktraceFrame {
+    /// The name of the function.
+    pub function: String,
+    /// The file the function is defined in.
+    pub file: String,
+    /// The line number.
+    pub line: usize,
+    /// `true` if the code has been inlined.
+    pub inlined: bool,
+    /// `true` if this frame is part of a C function.
+    pub from_c: bool,
+}
+
+impl Exception {
+    /// Convert `exception` to an `Exception`. Its error message is the message that is shown
+    /// when the exception is thrown, its type and fields are decoded into an [`ExceptionKind`].
+    ///
+    /// The backtrace isn't available, use [`Exception::decode_with_backtrace`] or
+    /// [`Value::call_with_backtrace`] to capture it.
+    pub fn decode(exception: Value) -> Self {
+        Exception {
+            msg: exception.error_string_or(CANNOT_DISPLAY_VALUE),
+            kind: Some(Box::new(ExceptionKind::decode(exception))),
+            backtrace: None,
+        }
+    }
+
+    /// Convert `exception` to an `Exception` and decode its `backtrace`, which must be the
+    /// result of `Base.catch_backtrace`.
+    ///
+    /// Temporary data is rooted in a new scope of `frame`.
+    pub fn decode_with_backtrace(
+        frame: &mut GcFrame,
+        exception: Value,
+        backtrace: Value,
+    ) -> JlrsResult<Self> {
+        let mut decoded = Self::decode(exception);
+        decoded.backtrace = Some(Backtrace::decode(frame, backtrace)?);
+        Ok(decoded)
+    }
+
+    // Convert `exception` to an `Exception` without decoding it.
+    pub(crate) fn from_message(exception: Value) -> Self {
+        Exception {
+            msg: exception.error_string_or(CANNOT_DISPLAY_VALUE),
+            kind: None,
+            backtrace: None,
+        }
+    }
+}
+
+impl ExceptionKind {
+    /// Decode `exception`.
+    pub fn decode(exception: Value) -> Self {
+        let unrooted = exception.unrooted_target();
+        let ty = exception.datatype();
+
+        if ty == DataType::boundserror_type(&unrooted) {
+            ExceptionKind::BoundsError {
+                value_type: field(exception, "a").map(|a| {
+                    a.datatype()
+                        .as_value()
+                        .display_string_or(CANNOT_DISPLAY_TYPE)
+                }),
+                indices: field(exception, "i").and_then(decode_indices),
+            }
+        } else if ty == DataType::methoderror_type(&unrooted) {
+            ExceptionKind::MethodError {
+                function: field(exception, "f").map_or_else(undef, function_name),
+                arg_types: field(exception, "args").map_or_else(Vec::new, |args| {
+                    // Safety: the parameters of a tuple type are its element types.
+                    unsafe {
+                        args.datatype()
+                            .parameters()
+                            .data()
+                            .as_slice()
+                            .iter()
+                            .map(|ty| match ty {
+                                Some(ty) => ty.as_value().display_string_or(CANNOT_DISPLAY_TYPE),
+                                None => undef(),
+                            })
+                            .collect()
+                    }
+                }),
+            }
+        } else if ty == DataType::argumenterror_type(&unrooted) {
+            ExceptionKind::ArgumentError {
+                msg: string_field(exception, "msg"),
+            }
+        } else if ty == static_global!(INEXACT_ERROR, unrooted) {
+            ExceptionKind::InexactError {
+                function: string_field(exception, "func"),
+                target_type: display_field(exception, "T"),
+                value: display_field(exception, "val"),
+            }
+        } else if ty == static_global!(DOMAIN_ERROR, unrooted) {
+            ExceptionKind::DomainError {
+                value: display_field(exception, "val"),
+                msg: string_field(exception, "msg"),
+            }
+        } else if ty == static_global!(KEY_ERROR, unrooted) {
+            ExceptionKind::KeyError {
+                key: display_field(exception, "key"),
+            }
+        } else if ty == Value::interrupt_exception(&unrooted).datatype() {
+            ExceptionKind::InterruptException
+        } else if ty == DataType::errorexception_type(&unrooted) {
+            ExceptionKind::ErrorException {
+                msg: string_field(exception, "msg"),
+            }
+        } else if ty == DataType::undefvarerror_type(&unrooted) {
+            ExceptionKind::UndefVarError {
+                var: string_field(exception, "var"),
+            }
+        } else if ty == DataType::typeerror_type(&unrooted) {
+            ExceptionKind::TypeError {
+                function: string_field(exception, "func"),
+                expected: display_field(exception, "expected"),
+                got: display_field(exception, "got"),
+            }
+        } else {
+            ExceptionKind::Other {
+                exception_type: ty.as_value().display_string_or(CANNOT_DISPLAY_TYPE),
+            }
+        }
+    }
+}
+
+impl Backtrace {
+    /// Decode `backtrace`, which must be the result of `Base.catch_backtrace`.
+    ///
+    /// Temporary data is rooted in a new scope of `frame`.
+    pub fn decode(frame: &mut GcFrame, backtrace: Value) -> JlrsResult<Self> {
+        frame.scope(|mut frame| {
+            let frames = decode_frames(&mut frame, backtrace)?;
+            let formatted = format_backtrace(&mut frame, backtrace)?;
+            Ok(Backtrace { frames, formatted })
+        })
+    }
+
+    /// Returns the frames of this backtrace, the innermost frame comes first.
+    pub fn frames(&self) -> &[BacktraceFrame] {
+        &self.frames
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.formatted)
+    }
+}
+
+// Convert the backtrace to a vector of StackFrames with Base.stacktrace and decode them.
+fn decode_frames(frame: &mut GcFrame, backtrace: Value) -> JlrsResult<Vec<BacktraceFrame>> {
+    frame.scope(|mut frame| {
+        // Safety: stacktrace is globally rooted, and only converts the backtrace to a vector
+        // of StackFrames.
+        let frames = unsafe {
+            let stacktrace = static_global!(STACKTRACE, frame);
+            stacktrace
+                .call1(&mut frame, backtrace)
+                .map_err(|e| Exception::decode(e))?
+        };
+
+        let mut slot = frame.reusable_slot();
+        // Safety: iterating over a vector of StackFrames doesn't execute arbitrary code.
+        let mut iter = unsafe { frames.iter(&frame) };
+        let mut decoded = Vec::new();
+
+        while let Some(sf) = iter.next_with(&mut slot)? {
+            // Safety: the StackFrame is rooted in slot until the next iteration.
+            let sf = unsafe { sf.as_value() };
+            decoded.push(BacktraceFrame {
+                function: string_field(sf, "func"),
+                file: string_field(sf, "file"),
+                line: sf.field_accessor().field("line")?.access::<i64>()? as usize,
+                inlined: sf.field_accessor().field("inlined")?.access::<bool>()?,
+                from_c: sf.field_accessor().field("from_c")?.access::<bool>()?,
+            });
+        }
+
+        Ok(decoded)
+    })
+}
+
+// Print the backtrace to a buffer with Base.show_backtrace. The buffer is wrapped in an IOContext
+// so the output is colored if JlrsCore.color is set.
+fn format_backtrace(frame: &mut GcFrame, backtrace: Value) -> JlrsResult<String> {
+    frame.scope(|mut frame| unsafe {
+        // Safety: all called functions are globally rooted and only write to the new buffer.
+        let color = static_global!(COLOR, frame)
+            .field_accessor()
+            .field("x")?
+            .access::<bool>()?;
+        let color = if color {
+            Value::true_v(&frame)
+        } else {
+            Value::false_v(&frame)
+        };
+
+        let key = Symbol::new(&frame, "color").as_value();
+        let pair = static_global!(PAIR, frame)
+            .call2(&mut frame, key, color)
+            .map_err(|e| Exception::decode(e))?;
+        let buffer = static_global!(IOBUFFER, frame)
+            .call0(&mut frame)
+            .map_err(|e| Exception::decode(e))?;
+        let io = static_global!(IOCONTEXT, frame)
+            .call2(&mut frame, buffer, pair)
+            .map_err(|e| Exception::decode(e))?;
+
+        static_global!(SHOW_BACKTRACE, frame)
+            .call2(&frame, io, backtrace)
+            .map_err(|e| unrooted_exception_error(e))?;
+
+        let bytes = static_global!(TAKE, frame)
+            .call1(&mut frame, buffer)
+            .map_err(|e| Exception::decode(e))?
+            .cast::<TypedArray<u8>>()?;
+        let bytes = bytes.bits_data()?;
+
+        Ok(String::from_utf8_lossy(bytes.as_slice()).into_owned())
+    })
+}
+
+// Returns the field `name` of `value`, or `None` if the field doesn't exist or is undefined.
+fn field<'scope, 'data>(value: Value<'scope, 'data>, name: &str) -> Option<Value<'scope, 'data>> {
+    // Safety: the field is reachable from value.
+    value
+        .get_field_ref(name)
+        .ok()
+        .flatten()
+        .map(|f: ValueRef| unsafe { f.as_value() })
+}
+
+// Converts the field `name` of `value` to a string with `Base.show`.
+fn display_field(value: Value, name: &str) -> String {
+    field(value, name).map_or_else(undef, |f| f.display_string_or(CANNOT_DISPLAY_VALUE))
+}
+
+// Converts the field `name` of `value` to a string, strings and symbols are converted directly.
+fn string_field(value: Value, name: &str) -> String {
+    match field(value, name) {
+        Some(f) if f.is::<JuliaString>() => {
+            // Safety: f is a String.
+            let s = unsafe { f.cast_unchecked::<JuliaString>() };
+            s.as_str()
+                .map_or_else(|_| f.display_string_or(CANNOT_DISPLAY_VALUE), String::from)
+        }
+        Some(f) if f.is::<Symbol>() => {
+            // Safety: f is a Symbol.
+            let s = unsafe { f.cast_unchecked::<Symbol>() };
+            s.as_string()
+                .unwrap_or_else(|_| f.display_string_or(CANNOT_DISPLAY_VALUE))
+        }
+        Some(f) => f.display_string_or(CANNOT_DISPLAY_VALUE),
+        None => undef(),
+    }
+}
+
+// Returns the name of a function, or the value converted to a string if it's not a function.
+fn function_name(f: Value) -> String {
+    if f.is::<Function>() {
+        let unrooted = f.unrooted_target();
+        // Safety: nameof is globally rooted, the result is a symbol.
+        let name = unsafe { static_global!(NAMEOF, unrooted).call1(&unrooted, f) };
+        if let Ok(name) = name {
+            // Safety: symbols are globally rooted.
+            let name = unsafe { name.as_value() };
+            if let Ok(name) = name.cast::<Symbol>() {
+                if let Ok(name) = name.as_string() {
+                    return name;
+                }
+            }
+        }
+    }
+
+    f.display_string_or(CANNOT_DISPLAY_VALUE)
+}
+
+// Converts the index of a BoundsError to a vector of integers.
+fn decode_indices(i: Value) -> Option<Vec<isize>> {
+    if let Ok(idx) = i.unbox::<isize>() {
+        return Some(vec![idx]);
+    }
+
+    if !i.is::<Tuple>() {
+        return None;
+    }
+
+    (0..i.n_fields())
+        .map(|idx| i.field_accessor().field(idx).ok()?.access::<isize>().ok())
+        .collect()
+}
+
+fn undef() -> String {
+    String::from("#undef")
+}
@@ -1,9 +1,12 @@
 //! Everything related to errors.
 
+pub mod exception;
+
 use std::error::Error as StdErr;
 
 use thiserror::Error;
 
+use self::exception::{Backtrace, ExceptionKind};
 use crate::data::managed::{
     array::dimensions::Dimensions,
     value::{Value, ValueRef},
@@ -36,6 +39,22 @@ pub enum RuntimeError {
     ChannelClosed,
     #[error("channel full")]
     ChannelFull,
+    #[error("invalid value for {option}: {reason}")]
+    InvalidOption {
+        option: &'static str,
+        reason: String,
+    },
+}
+
+/// The reason a task sent to the async runtime has been cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Cancelled {
+    #[error("cancellation was requested")]
+    Requested,
+    #[error("deadline exceeded")]
+    DeadlineExceeded,
+    #[error("the runtime is shutting down")]
+    Shutdown,
 }
 
 /// IO errors.
@@ -72,6 +91,14 @@ pub enum TypeError {
     NoBaseType,
     #[error("The layout of this type is incompatible with {base_type}")]
     IncompatibleBaseType { base_type: String },
+    #[error("the parametric type of {variant} has not been created")]
+    NoParametricType { variant: String },
+    #[error("{variant} has {n_params} parameters, the parametric type has {n_type_params} type parameters")]
+    VariantParametersMismatch {
+        variant: String,
+        n_params: usize,
+        n_type_params: usize,
+    },
 }
 
 /// Array layout errors.
@@ -144,10 +171,15 @@ pub enum InstantiationError {
 }
 
 /// Julia exception converted to a string.
+///
+/// If the exception has been decoded with [`Exception::decode`], its type and relevant fields
+/// are available as an [`ExceptionKind`].
 #[derive(Debug, Error)]
 #[error("{msg}")]
 pub struct Exception {
     msg: String,
+    kind: Option<Box<ExceptionKind>>,
+    backtrace: Option<Backtrace>,
 }
 
 impl Exception {
@@ -155,6 +187,17 @@ impl Exception {
     pub fn get_message(&self) -> &str {
         &self.msg
     }
+
+    /// Returns the decoded exception, or `None` if this exception has only been converted to a
+    /// string.
+    pub fn kind(&self) -> Option<&ExceptionKind> {
+        self.kind.as_deref()
+    }
+
+    /// Returns the backtrace of this exception, or `None` if it hasn't been captured.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
 }
 
 /// All different errors.
@@ -176,6 +219,8 @@ pub enum JlrsError {
     InstantiationError(InstantiationError),
     #[error("Array layout error: {0}")]
     ArrayLayoutError(ArrayLayoutError),
+    #[error("Cancelled: {0}")]
+    Cancelled(Cancelled),
 }
 
 impl JlrsError {
@@ -186,7 +231,11 @@ impl JlrsError {
 
     /// Convert an error message to `JlrsError::Exception`.
     pub fn exception<S: Into<String>>(msg: S) -> Self {
-        JlrsError::Exception(Exception { msg: msg.into() })
+        JlrsError::Exception(Exception {
+            msg: msg.into(),
+            kind: None,
+            backtrace: None,
+        })
     }
 
     /// Convert an arbitrary error to `Err(JlrsError::Other)`.
@@ -222,3 +271,5 @@ impl_from!(IOError);
 impl_from!(AccessError);
 impl_from!(InstantiationError);
 impl_from!(ArrayLayoutError);
+impl_from!(Exception);
+impl_from!(Cancelled);
@@ -161,10 +161,44 @@
 //!
 //!   Access the content of a Julia array as an `ArrayView` or `ArrayViewMut` from ndarray.
 //!
+//! - `jlrs-serde`
+//!
+//!   Convert Rust data that implements `Serialize` to Julia data, and Julia data to Rust data
+//!   that implements `Deserialize`, with serde.
+//!
+//! - `jlrs-arrow`
+//!
+//!   Export Julia arrays as Arrow arrays and import Arrow arrays as Julia arrays through the
+//!   Arrow C Data Interface without copying their data.
+//!
+//! - `jlrs-tracing`
+//!
+//!   Emit spans and events with tracing when tasks are dispatched to and called by the async
+//!   runtime.
+//!
 //! - `f16`
 //!
 //!   Adds support for working with Julia's `Float16` type from Rust using half's `f16` type.
 //!
+//! - `num-complex`
+//!
+//!   Adds support for using num-complex's `Complex` type as a layout for Julia's `Complex` type.
+//!
+//! - `num-rational`
+//!
+//!   Adds conversions between num-rational's `Ratio` type and the layout for Julia's `Rational`
+//!   type.
+//!
+//! - `chrono`
+//!
+//!   Adds conversions between the layouts for `DateTime`, `Date` and `Time` from Julia's Dates
+//!   module and chrono's `NaiveDateTime`, `NaiveDate` and `NaiveTime`.
+//!
+//! - `time`
+//!
+//!   Adds conversions between the layouts for `DateTime`, `Date` and `Time` from Julia's Dates
+//!   module and time's `OffsetDateTime`, `Date` and `Time`.
+//!
 //! - `ccall`
 //!
 //!   Julia's `ccall` interface can be used to call functions written in Rust from Julia. No
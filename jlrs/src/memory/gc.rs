@@ -1,26 +1,50 @@
 //! Manage the garbage collector.
 
+use std::{
+    os::raw::c_int,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Mutex,
+    time::Duration,
+};
+
 #[julia_version(since = "1.10")]
 use jl_sys::jl_gc_set_max_memory;
 use jl_sys::{
-    jl_gc_collect, jl_gc_collection_t, jl_gc_enable, jl_gc_is_enabled, jl_gc_mark_queue_obj,
-    jl_gc_mark_queue_objarray, jl_gc_safepoint, jl_gc_wb,
+    jl_gc_collect, jl_gc_collection_t, jl_gc_enable, jl_gc_is_enabled, jl_gc_live_bytes,
+    jl_gc_mark_queue_obj, jl_gc_mark_queue_objarray, jl_gc_safepoint, jl_gc_set_cb_post_gc,
+    jl_gc_wb,
 };
 use jlrs_macros::julia_version;
 
-use super::{target::Target, PTls};
+use super::{
+    target::{unrooted::Unrooted, Target},
+    PTls,
+};
+#[julia_version(since = "1.7")]
+use crate::data::managed::module::Module;
 #[cfg(feature = "sync-rt")]
 use crate::runtime::sync_rt::Julia;
-#[julia_version(since = "1.7")]
-use crate::{call::Call, data::managed::module::Module};
 use crate::{
-    data::managed::{
-        private::ManagedPriv,
-        value::{Value, ValueRef},
+    call::Call,
+    convert::into_jlrs_result::unrooted_exception_error,
+    data::{
+        managed::{
+            private::ManagedPriv,
+            value::{Value, ValueRef},
+        },
+        static_data::{define_static_global, static_global},
     },
+    error::JlrsResult,
     private::Private,
 };
 
+define_static_global!(GC_NUM, "Base.gc_num");
+
+type PressureCallback = Box<dyn Fn(GcPressure) + Send + Sync>;
+
+// The threshold in bytes and the callback set with `Gc::gc_set_pressure_callback`.
+static PRESSURE_CALLBACK: Mutex<Option<(u64, PressureCallback)>> = Mutex::new(None);
+
 /// The different collection modes.
 #[derive(Debug, Copy, Clone)]
 pub enum GcCollection {
@@ -29,11 +53,40 @@ pub enum GcCollection {
     Incremental = 2,
 }
 
+/// Statistics about the GC, returned by [`Gc::gc_stats`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// The total number of bytes that have been allocated.
+    pub total_allocated: u64,
+    /// The number of bytes that were live after the last collection, plus the number of bytes
+    /// that have been allocated since.
+    pub live_bytes: u64,
+    /// The number of full collections.
+    pub full_collections: u64,
+    /// The number of incremental collections.
+    pub incremental_collections: u64,
+    /// The total time the GC has paused execution.
+    pub total_pause: Duration,
+    /// The longest pause, `None` if the used version of Julia doesn't track it.
+    pub max_pause: Option<Duration>,
+}
+
+/// Passed to the callback set with [`Gc::gc_set_pressure_callback`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GcPressure {
+    /// The number of live bytes after the collection.
+    pub live_bytes: u64,
+    /// The threshold the callback was set with.
+    pub threshold: u64,
+    /// `true` if the collection was a full collection.
+    pub full: bool,
+}
+
 /// Manage the GC.
 ///
 /// This trait provides several methods that can be used to enable or disable the GC, force a
-/// collection, insert a safepoint, and to enable and disable GC logging. It's implemented for
-/// [`Julia`] and all implementations of [`Target`]
+/// collection, insert a safepoint, to enable and disable GC logging, and to monitor the heap.
+/// It's implemented for [`Julia`] and all implementations of [`Target`]
 pub trait Gc: private::GcPriv {
     /// Enable or disable the GC.
     fn enable_gc(&self, on: bool) -> bool {
@@ -98,13 +151,105 @@ pub trait Gc: private::GcPriv {
         }
     }
 
+    /// Returns statistics about the GC.
+    ///
+    /// The statistics are read from `Base.gc_num` and `jl_gc_live_bytes`. If `Base.gc_num`
+    /// throws an exception it's converted to an error.
+    fn gc_stats(&self) -> JlrsResult<GcStats> {
+        // Safety: Julia is active, this method is called from a thread known to Julia, and no
+        // Julia data is returned by this method.
+        let unrooted = unsafe { Unrooted::new() };
+
+        // Safety: Base.gc_num is safe to call. The result isn't rooted, but it's only used
+        // before anything else is allocated.
+        unsafe {
+            let num = static_global!(GC_NUM, unrooted)
+                .call0(unrooted)
+                .map_err(unrooted_exception_error)?
+                .as_value();
+
+            // The fields and their types depend on the version of Julia.
+            let int_field = |name: &str| {
+                let accessor = || num.field_accessor().field(name).ok();
+                accessor()?
+                    .access::<i64>()
+                    .ok()
+                    .or_else(|| accessor()?.access::<u64>().ok().map(|v| v as i64))
+                    .or_else(|| accessor()?.access::<i32>().ok().map(|v| v as i64))
+            };
+            let int = |name: &str| int_field(name).unwrap_or(0);
+
+            let allocated = int("allocd") + int("deferred_alloc") + int("total_allocd");
+            let n_collections = int("pause");
+            let n_full = int("full_sweep");
+
+            Ok(GcStats {
+                total_allocated: allocated.max(0) as u64,
+                live_bytes: jl_gc_live_bytes().max(0) as u64,
+                full_collections: n_full.max(0) as u64,
+                incremental_collections: (n_collections - n_full).max(0) as u64,
+                total_pause: Duration::from_nanos(int("total_time").max(0) as u64),
+                max_pause: int_field("max_pause").map(|ns| Duration::from_nanos(ns.max(0) as u64)),
+            })
+        }
+    }
+
+    /// Call `callback` after every collection that leaves at least `threshold` live bytes.
+    ///
+    /// This can be used to react to memory pressure before the process runs out of memory,
+    /// e.g. by dropping caches or rejecting new work. Only one callback can be set, a previously
+    /// set callback is replaced.
+    ///
+    /// The callback is called by the thread that ran the collection while the GC is still
+    /// active. It must not call into Julia, and should return quickly. If it panics, the panic
+    /// is caught and ignored.
+    fn gc_set_pressure_callback<F>(&self, threshold: u64, callback: F)
+    where
+        F: 'static + Send + Sync + Fn(GcPressure),
+    {
+        *PRESSURE_CALLBACK.lock().unwrap() = Some((threshold, Box::new(callback)));
+        // Safety: the callback doesn't call into Julia.
+        unsafe { jl_gc_set_cb_post_gc(Some(pressure_callback), 1) }
+    }
+
+    /// Remove the callback set with [`Gc::gc_set_pressure_callback`].
+    fn gc_remove_pressure_callback(&self) {
+        // Safety: the callback doesn't call into Julia.
+        unsafe { jl_gc_set_cb_post_gc(Some(pressure_callback), 0) }
+        *PRESSURE_CALLBACK.lock().unwrap() = None;
+    }
+
     #[julia_version(since = "1.10")]
-    /// Set GC memory trigger in bytes for greedy memory collecting
-    fn gc_set_max_memory(max_mem: u64) {
+    /// Set the maximum size of the heap in bytes.
+    ///
+    /// The GC collects more aggressively when the heap approaches this limit.
+    fn gc_set_max_memory(&self, max_mem: u64) {
+        // Safety: this function can only be called while Julia is active.
         unsafe { jl_gc_set_max_memory(max_mem) }
     }
 }
 
+unsafe extern "C" fn pressure_callback(full: c_int) {
+    // The lock is only held briefly outside of collections, don't wait for it.
+    let guard = match PRESSURE_CALLBACK.try_lock() {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+
+    if let Some((threshold, ref callback)) = *guard {
+        let live_bytes = jl_gc_live_bytes().max(0) as u64;
+        if live_bytes >= threshold {
+            let pressure = GcPressure {
+                live_bytes,
+                threshold,
+                full: full != 0,
+            };
+
+            catch_unwind(AssertUnwindSafe(|| callback(pressure))).ok();
+        }
+    }
+}
+
 /// Mark `obj`, returns `true` if `obj` points to young data.
 ///
 /// This method can be used to implement custom mark functions. If a foreign type contains
@@ -2,10 +2,17 @@ use std::{cell::RefCell, collections::VecDeque, rc::Rc, time::Duration};
 
 use jl_sys::{jl_adopt_thread, jl_gc_safepoint};
 
-use super::{queue::Receiver, AsyncRuntime, Message, MessageInner};
+use std::sync::Arc;
+
+#[cfg(feature = "jlrs-tracing")]
+use super::stats::task_span;
+use super::{
+    cancel::check_deadlines, queue::Receiver, shutdown::ShutdownState, stats::StatsCollector,
+    AsyncRuntime, Message, MessageInner,
+};
 use crate::{
     async_util::task::sleep,
-    error::JlrsResult,
+    error::{Cancelled, JlrsError, JlrsResult},
     memory::{stack_frame::StackFrame, target::unrooted::Unrooted},
 };
 
@@ -13,25 +20,41 @@ pub(crate) unsafe fn init_worker<R: AsyncRuntime, const N: usize>(
     worker_id: usize,
     recv_timeout: Duration,
     receiver: Receiver<Message>,
+    shutdown: Arc<ShutdownState>,
+    stats: Arc<StatsCollector>,
 ) -> std::thread::JoinHandle<JlrsResult<()>> {
-    R::spawn_thread(move || run_async::<R, N>(worker_id, recv_timeout, receiver))
+    R::spawn_thread(move || run_async::<R, N>(worker_id, recv_timeout, receiver, shutdown, stats))
 }
 
 fn run_async<R: AsyncRuntime, const N: usize>(
     worker_id: usize,
     recv_timeout: Duration,
     receiver: Receiver<Message>,
+    shutdown: Arc<ShutdownState>,
+    stats: Arc<StatsCollector>,
 ) -> JlrsResult<()> {
     let mut base_frame = StackFrame::<N>::new_n();
     R::block_on(
-        unsafe { run_inner::<R, N>(recv_timeout, receiver, &mut base_frame) },
+        unsafe {
+            run_inner::<R, N>(
+                worker_id,
+                recv_timeout,
+                receiver,
+                shutdown,
+                stats,
+                &mut base_frame,
+            )
+        },
         Some(worker_id),
     )
 }
 
 async unsafe fn run_inner<R: AsyncRuntime, const N: usize>(
+    worker_id: usize,
     recv_timeout: Duration,
     receiver: Receiver<Message>,
+    shutdown: Arc<ShutdownState>,
+    stats: Arc<StatsCollector>,
     base_frame: &mut StackFrame<N>,
 ) -> JlrsResult<()> {
     let _ = jl_adopt_thread();
@@ -58,7 +81,21 @@ async unsafe fn run_inner<R: AsyncRuntime, const N: usize>(
         Rc::new(RefCell::new(running_tasks.into_boxed_slice()))
     };
 
+    let running_cancels = {
+        let mut running_cancels = Vec::with_capacity(N);
+        for _ in 0..N {
+            running_cancels.push(None);
+        }
+
+        Rc::new(RefCell::new(running_cancels.into_boxed_slice()))
+    };
+
     loop {
+        check_deadlines(&running_cancels);
+        if shutdown.should_cancel() {
+            shutdown.cancel_running(&running_cancels);
+        }
+
         if free_stacks.borrow().len() == 0 {
             sleep(&Unrooted::new(), recv_timeout);
             R::yield_now().await;
@@ -68,54 +105,96 @@ async unsafe fn run_inner<R: AsyncRuntime, const N: usize>(
 
         match R::timeout(recv_timeout, receiver.recv_worker()).await {
             None => jl_gc_safepoint(),
-            Some(Ok(msg)) => match msg.inner {
-                MessageInner::Task(task) => {
-                    let idx = free_stacks.borrow_mut().pop_front().unwrap();
-                    let stack = base_frame.nth_stack(idx);
-
-                    let task = {
-                        let free_stacks = free_stacks.clone();
-                        let running_tasks = running_tasks.clone();
-
-                        R::spawn_local(async move {
-                            task.call(stack).await;
-                            free_stacks.borrow_mut().push_back(idx);
-                            running_tasks.borrow_mut()[idx] = None;
-                        })
-                    };
-
-                    running_tasks.borrow_mut()[idx] = Some(task);
-                }
-                MessageInner::BlockingTask(task) => {
-                    let stack = base_frame.sync_stack();
-                    task.call(stack);
-                }
-                MessageInner::PostBlockingTask(task) => {
-                    let idx = free_stacks.borrow_mut().pop_front().unwrap();
-                    let stack = base_frame.nth_stack(idx);
-
-                    let task = {
-                        let free_stacks = free_stacks.clone();
-                        let running_tasks = running_tasks.clone();
-
-                        R::spawn_local(async move {
-                            task.post(stack).await;
-                            free_stacks.borrow_mut().push_back(idx);
-                            running_tasks.borrow_mut()[idx] = None;
-                        })
-                    };
-
-                    running_tasks.borrow_mut()[idx] = Some(task);
-                }
-                MessageInner::Include(task) => {
-                    let stack = base_frame.sync_stack();
-                    task.call(stack);
+            Some(Ok(msg)) => {
+                if shutdown.should_cancel() {
+                    if msg.reject(Box::new(JlrsError::Cancelled(Cancelled::Shutdown))) {
+                        shutdown.task_dropped();
+                    }
+                    stats.task_rejected();
+                    continue;
                 }
-                MessageInner::ErrorColor(task) => {
-                    let stack = base_frame.sync_stack();
-                    task.call(stack);
+
+                let task = match msg.receive() {
+                    Some(task) => task,
+                    None => {
+                        stats.task_rejected();
+                        continue;
+                    }
+                };
+                let timer = stats.task_started(worker_id + 1, msg.kind(), msg.queued_at);
+                let cancel = msg.cancel;
+                #[cfg(feature = "jlrs-tracing")]
+                let span = task_span(&timer, msg.queued_at);
+
+                match task {
+                    MessageInner::Task(task) => {
+                        let idx = free_stacks.borrow_mut().pop_front().unwrap();
+                        let stack = base_frame.nth_stack(idx);
+
+                        let task = {
+                            let free_stacks = free_stacks.clone();
+                            let running_tasks = running_tasks.clone();
+                            let running_cancels = running_cancels.clone();
+                            let cancel = cancel.clone();
+
+                            let fut = async move {
+                                task.call(stack, cancel, timer).await;
+                                free_stacks.borrow_mut().push_back(idx);
+                                running_tasks.borrow_mut()[idx] = None;
+                                running_cancels.borrow_mut()[idx] = None;
+                            };
+
+                            #[cfg(feature = "jlrs-tracing")]
+                            let fut = tracing::Instrument::instrument(fut, span);
+
+                            R::spawn_local(fut)
+                        };
+
+                        running_tasks.borrow_mut()[idx] = Some(task);
+                        running_cancels.borrow_mut()[idx] = Some(cancel);
+                    }
+                    MessageInner::BlockingTask(task) => {
+                        #[cfg(feature = "jlrs-tracing")]
+                        let _entered = span.enter();
+                        let stack = base_frame.sync_stack();
+                        task.call(stack, timer);
+                    }
+                    MessageInner::PostBlockingTask(task) => {
+                        let idx = free_stacks.borrow_mut().pop_front().unwrap();
+                        let stack = base_frame.nth_stack(idx);
+
+                        let task = {
+                            let free_stacks = free_stacks.clone();
+                            let running_tasks = running_tasks.clone();
+
+                            let fut = async move {
+                                task.post(stack, timer).await;
+                                free_stacks.borrow_mut().push_back(idx);
+                                running_tasks.borrow_mut()[idx] = None;
+                            };
+
+                            #[cfg(feature = "jlrs-tracing")]
+                            let fut = tracing::Instrument::instrument(fut, span);
+
+                            R::spawn_local(fut)
+                        };
+
+                        running_tasks.borrow_mut()[idx] = Some(task);
+                    }
+                    MessageInner::Include(task) => {
+                        #[cfg(feature = "jlrs-tracing")]
+                        let _entered = span.enter();
+                        let stack = base_frame.sync_stack();
+                        task.call(stack, timer);
+                    }
+                    MessageInner::ErrorColor(task) => {
+                        #[cfg(feature = "jlrs-tracing")]
+                        let _entered = span.enter();
+                        let stack = base_frame.sync_stack();
+                        task.call(stack, timer);
+                    }
                 }
-            },
+            }
             _ => break,
         }
     }
@@ -124,6 +203,10 @@ async unsafe fn run_inner<R: AsyncRuntime, const N: usize>(
     for i in 0..N {
         loop {
             if running_tasks.borrow()[i].is_some() {
+                if shutdown.should_cancel() {
+                    shutdown.cancel_running(&running_cancels);
+                }
+
                 R::yield_now().await;
                 sleep(&Unrooted::new(), recv_timeout);
                 jl_gc_safepoint();
@@ -0,0 +1,333 @@
+//! Cancel tasks and enforce deadlines.
+//!
+//! Every task that's sent to the async runtime can be cancelled with the [`CancelHandle`]
+//! returned by [`Dispatch::cancel_handle`], and a deadline can be set with
+//! [`Dispatch::deadline`] or [`Dispatch::timeout`]. If a task is cancelled before the runtime
+//! has received it, [`JlrsError::Cancelled`] is sent to its result sender immediately and the
+//! task is dropped without being called. The message that remains in the queue is empty, but it
+//! still takes up capacity in the channel until the runtime receives and discards it. A deadline
+//! that passes while the task is queued is detected when the runtime receives the task, or
+//! when [`CancelHandle::is_cancelled`] is called.
+//!
+//! If an async task is already running, the Julia tasks it's waiting on are interrupted by
+//! throwing an `InterruptException` into them with `schedule(t, InterruptException();
+//! error=true)`. If it isn't waiting on a Julia task, it's dropped at the next `.await` and
+//! [`JlrsError::Cancelled`] is sent to the result sender. If the task completes before it has
+//! been dropped, its result is sent instead. Blocking tasks can't be interrupted after they've
+//! started. A persistent task that has been initialized stops receiving calls when it's
+//! cancelled, its `exit` method is called after the call that's running has completed.
+//!
+//! Deadlines are checked every time the runtime receives a new message or the receive timeout
+//! set with [`AsyncRuntimeBuilder::recv_timeout`] expires, so a task can run slightly longer
+//! than its deadline.
+//!
+//! [`Dispatch::cancel_handle`]: crate::runtime::async_rt::dispatch::Dispatch::cancel_handle
+//! [`Dispatch::deadline`]: crate::runtime::async_rt::dispatch::Dispatch::deadline
+//! [`Dispatch::timeout`]: crate::runtime::async_rt::dispatch::Dispatch::timeout
+//! [`JlrsError::Cancelled`]: crate::error::JlrsError::Cancelled
+//! [`AsyncRuntimeBuilder::recv_timeout`]: crate::runtime::builder::AsyncRuntimeBuilder::recv_timeout
+
+use std::{
+    cell::RefCell,
+    fmt,
+    pin::Pin,
+    ptr::NonNull,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Instant,
+};
+
+use futures::Future;
+use jl_sys::jl_value_t;
+
+use crate::{
+    call::Call,
+    data::managed::{module::Module, private::ManagedPriv, value::Value},
+    error::{Cancelled, JlrsError},
+    memory::target::unrooted::Unrooted,
+    private::Private,
+};
+
+use super::MessageInner;
+
+thread_local! {
+    // The cancellation state of the async task that's currently being polled on this thread.
+    static CURRENT: RefCell<Option<Arc<CancelState>>> = const { RefCell::new(None) };
+}
+
+/// A handle that can be used to cancel a task that has been sent to the async runtime.
+///
+/// Created with [`Dispatch::cancel_handle`], it can be cloned and sent to other threads.
+///
+/// [`Dispatch::cancel_handle`]: crate::runtime::async_rt::dispatch::Dispatch::cancel_handle
+#[derive(Clone)]
+pub struct CancelHandle {
+    state: Arc<CancelState>,
+}
+
+impl CancelHandle {
+    pub(crate) fn new(state: Arc<CancelState>) -> Self {
+        CancelHandle { state }
+    }
+
+    /// Request the task to be cancelled.
+    ///
+    /// If the runtime hasn't received the task yet, the result sender of the task receives
+    /// [`JlrsError::Cancelled`] before this method returns. Otherwise this method doesn't wait
+    /// until the running task has been cancelled, the result sender receives
+    /// [`JlrsError::Cancelled`] when it has been dropped, or its result if it completes first.
+    ///
+    /// [`JlrsError::Cancelled`]: crate::error::JlrsError::Cancelled
+    pub fn cancel(&self) {
+        self.state.cancel(Cancelled::Requested);
+    }
+
+    /// Returns `true` if the task has been cancelled or its deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.check_deadline();
+        self.state.cancelled().is_some()
+    }
+}
+
+impl fmt::Debug for CancelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelHandle")
+            .field("cancelled", &self.state.cancelled())
+            .finish()
+    }
+}
+
+struct TaskPtr(NonNull<jl_value_t>);
+
+// Safety: the pointer is only dereferenced by the thread that polls the task.
+unsafe impl Send for TaskPtr {}
+
+struct CancelStateInner {
+    cancelled: Option<Cancelled>,
+    deadline: Option<Instant>,
+    waker: Option<Waker>,
+    // The Julia tasks the async task is waiting on, and whether they have been interrupted.
+    julia_tasks: Vec<(TaskPtr, bool)>,
+    // Set when a Julia task has been interrupted, the output of the async task is a consequence
+    // of the interruption.
+    interrupted: bool,
+}
+
+pub(crate) struct CancelState {
+    inner: Mutex<CancelStateInner>,
+    // The task until it's received by the runtime or rejected because it has been cancelled.
+    queued: Mutex<Option<MessageInner>>,
+}
+
+impl CancelState {
+    pub(crate) fn new(task: MessageInner) -> Self {
+        CancelState {
+            inner: Mutex::new(CancelStateInner {
+                cancelled: None,
+                deadline: None,
+                waker: None,
+                julia_tasks: Vec::new(),
+                interrupted: false,
+            }),
+            queued: Mutex::new(Some(task)),
+        }
+    }
+
+    // Take the task out of the queued message, returns `None` if it has already been taken.
+    pub(crate) fn dequeue(&self) -> Option<MessageInner> {
+        self.queued.lock().unwrap().take()
+    }
+
+    pub(crate) fn set_deadline(&self, deadline: Instant) {
+        self.inner.lock().unwrap().deadline = Some(deadline);
+    }
+
+    pub(crate) fn cancelled(&self) -> Option<Cancelled> {
+        self.inner.lock().unwrap().cancelled
+    }
+
+    // Returns `false` if the task had already been cancelled. If the runtime hasn't received the
+    // task yet, it's rejected immediately.
+    pub(crate) fn cancel(&self, reason: Cancelled) -> bool {
+        let waker = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.cancelled.is_some() {
+                return false;
+            }
+
+            inner.cancelled = Some(reason);
+            inner.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        // The runtime checks if the task has been cancelled after taking it, so exactly one of
+        // them rejects it.
+        if let Some(task) = self.dequeue() {
+            task.reject(Box::new(JlrsError::Cancelled(reason)));
+        }
+
+        true
+    }
+
+    // Cancel the task if its deadline has passed.
+    pub(crate) fn check_deadline(&self) {
+        let deadline = self.inner.lock().unwrap().deadline;
+        match deadline {
+            Some(deadline) if deadline <= Instant::now() => {
+                self.cancel(Cancelled::DeadlineExceeded);
+            }
+            _ => (),
+        }
+    }
+
+    // Returns the reason if the task has been cancelled or its deadline has passed.
+    pub(crate) fn check(&self) -> Option<Cancelled> {
+        self.check_deadline();
+        self.cancelled()
+    }
+
+    // Called when a `JuliaFuture` is created while this task is being polled.
+    pub(crate) fn register_julia_task(&self, task: NonNull<jl_value_t>) {
+        self.inner
+            .lock()
+            .unwrap()
+            .julia_tasks
+            .push((TaskPtr(task), false));
+    }
+
+    // Called when a `JuliaFuture` is dropped.
+    pub(crate) fn unregister_julia_task(&self, task: NonNull<jl_value_t>) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(idx) = inner.julia_tasks.iter().position(|(t, _)| t.0 == task) {
+            inner.julia_tasks.swap_remove(idx);
+        }
+    }
+
+    fn set_waker(&self, waker: &Waker) {
+        self.inner.lock().unwrap().waker = Some(waker.clone());
+    }
+
+    // Interrupt all Julia tasks that haven't been interrupted yet, returns `false` if the async
+    // task isn't waiting on any Julia task.
+    //
+    // Safety: must be called from the thread that polls the async task.
+    unsafe fn interrupt_julia_tasks(&self) -> bool {
+        let to_interrupt = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.julia_tasks.is_empty() {
+                return false;
+            }
+
+            inner.interrupted = true;
+            inner
+                .julia_tasks
+                .iter_mut()
+                .filter(|(_, interrupted)| !*interrupted)
+                .map(|(task, interrupted)| {
+                    *interrupted = true;
+                    task.0
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The lock must not be held while Julia code runs, the interrupted task can complete
+        // and wake the async task immediately.
+        let unrooted = Unrooted::new();
+        if let Ok(func) = Module::main(&unrooted).function(&unrooted, "jlrsinterrupttask") {
+            let func = func.as_managed();
+            for task in to_interrupt {
+                let task = Value::wrap_non_null(task, Private);
+                func.call1(unrooted, task).ok();
+            }
+        }
+
+        true
+    }
+}
+
+// Cancel the running tasks whose deadline has passed.
+pub(crate) fn check_deadlines(running: &RefCell<Box<[Option<Arc<CancelState>>]>>) {
+    let running = running
+        .borrow()
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+    for state in running {
+        state.check_deadline();
+    }
+}
+
+// Returns the cancellation state of the async task that's currently being polled on this
+// thread.
+pub(crate) fn current() -> Option<Arc<CancelState>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+// Wraps the future of an async task. The future is dropped at the next `.await` if the task is
+// cancelled while it isn't waiting on a Julia task, otherwise the Julia tasks it's waiting on are
+// interrupted. Resolves to `None` if the future has been dropped or a Julia task has been
+// interrupted, a future that completes after the task has been cancelled otherwise still
+// resolves to its output.
+pub(crate) struct Cancellable<F> {
+    future: Option<Pin<Box<F>>>,
+    state: Arc<CancelState>,
+}
+
+impl<F: Future> Cancellable<F> {
+    pub(crate) fn new(future: F, state: Arc<CancelState>) -> Self {
+        Cancellable {
+            future: Some(Box::pin(future)),
+            state,
+        }
+    }
+}
+
+impl<F: Future> Future for Cancellable<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.future.is_none() {
+            return Poll::Ready(None);
+        }
+
+        let state = self.state.clone();
+        state.set_waker(cx.waker());
+
+        let prev = CURRENT.with(|current| current.replace(Some(state.clone())));
+        let res = self.future.as_mut().unwrap().as_mut().poll(cx);
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+
+        if let Poll::Ready(res) = res {
+            self.future = None;
+            if state.inner.lock().unwrap().interrupted {
+                return Poll::Ready(None);
+            }
+
+            return Poll::Ready(Some(res));
+        }
+
+        // Safety: async tasks are polled by a thread that can call into Julia.
+        if state.check().is_some() && !unsafe { state.interrupt_julia_tasks() } {
+            self.future = None;
+            return Poll::Ready(None);
+        }
+
+        Poll::Pending
+    }
+}
+
+// Helper function that is evaluated when the async runtime is initialized. The exception is only
+// thrown into tasks that are waiting, which is checked by `schedule`.
+pub(crate) const INTERRUPT_TASK_FN: &str = "function jlrsinterrupttask(t::Task)
+    if istaskstarted(t) && !istaskdone(t)
+        try
+            schedule(t, InterruptException(); error=true)
+        catch
+        end
+    end
+    nothing
+end\0";
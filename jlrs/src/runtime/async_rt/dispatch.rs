@@ -1,16 +1,31 @@
 //! Dispatch a task to the async runtime.
+//!
+//! If the runtime is shutting down, a dispatched task is not sent to the runtime, its result
+//! sender receives [`RuntimeError::ChannelClosed`] instead.
+//!
+//! [`RuntimeError::ChannelClosed`]: crate::error::RuntimeError::ChannelClosed
 
-use std::{fmt::Debug, marker::PhantomData};
+use std::{
+    fmt::Debug,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    async_util::affinity::{Affinity, ToAny, ToMain, ToWorker},
-    runtime::async_rt::{queue::Sender, Message},
+    async_util::{
+        affinity::{Affinity, ToAny, ToMain, ToWorker},
+        channel::{SendError, TrySendError},
+    },
+    error::{JlrsError, RuntimeError},
+    runtime::async_rt::{cancel::CancelHandle, queue::Sender, Message},
 };
 
 /// Dispatch a task to the async runtime.
 pub struct Dispatch<'a, D> {
     msg: Message,
     sender: &'a Sender<Message>,
+    // Set after the first attempt to dispatch the task.
+    prepared: bool,
     _dispatch: PhantomData<D>,
 }
 
@@ -25,9 +40,84 @@ impl<'a, D: Affinity> Dispatch<'a, D> {
         Dispatch {
             msg,
             sender,
+            prepared: false,
             _dispatch: PhantomData,
         }
     }
+
+    /// Returns a handle that can be used to cancel the task after it has been dispatched.
+    ///
+    /// See the [`cancel`] module for more information.
+    ///
+    /// [`cancel`]: crate::runtime::async_rt::cancel
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle::new(self.msg.cancel.clone())
+    }
+
+    /// Cancel the task if it hasn't completed before `deadline`.
+    ///
+    /// The result sender of the task receives [`JlrsError::Cancelled`] if the deadline has
+    /// passed.
+    ///
+    /// [`JlrsError::Cancelled`]: crate::error::JlrsError::Cancelled
+    pub fn deadline(self, deadline: Instant) -> Self {
+        self.msg.cancel.set_deadline(deadline);
+        self
+    }
+
+    /// Cancel the task if it hasn't completed within `timeout`, measured from the moment this
+    /// method is called.
+    ///
+    /// The result sender of the task receives [`JlrsError::Cancelled`] if the timeout has
+    /// expired.
+    ///
+    /// [`JlrsError::Cancelled`]: crate::error::JlrsError::Cancelled
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    // Update the time the task is queued. If the queue was full and dispatching is retried,
+    // the task has been waiting since the first attempt.
+    #[cfg_attr(not(feature = "jlrs-tracing"), allow(unused_variables))]
+    fn prepare(mut self, queue: &'static str) -> Self {
+        if self.prepared {
+            return self;
+        }
+
+        #[cfg(feature = "jlrs-tracing")]
+        tracing::debug!(kind = ?self.msg.kind(), queue, "dispatching task");
+
+        self.msg.queued_at = Instant::now();
+        self.prepared = true;
+        self
+    }
+
+    // Handle the result of trying to send the task, the dispatcher is returned if the queue is
+    // full.
+    fn try_sent(
+        sender: &'a Sender<Message>,
+        res: Result<(), TrySendError<Message>>,
+    ) -> Result<(), Self> {
+        match res {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(msg)) => Err(Dispatch {
+                msg,
+                sender,
+                prepared: true,
+                _dispatch: PhantomData,
+            }),
+            Err(TrySendError::Closed(msg)) => {
+                reject_closed(msg);
+                Ok(())
+            }
+        }
+    }
+}
+
+// The runtime is shutting down, the result sender receives an error.
+fn reject_closed(msg: Message) {
+    let error = Box::new(JlrsError::RuntimeError(RuntimeError::ChannelClosed));
+    msg.reject(error);
 }
 
 impl<'a, D: ToAny> Dispatch<'a, D> {
@@ -36,7 +126,10 @@ impl<'a, D: ToAny> Dispatch<'a, D> {
     /// The dispatched task can be handled by either the main thread or any of the worker threads.
     /// This method doesn't resolve until the task has been successfully dispatched.
     pub async fn dispatch_any(self) {
-        self.sender.send(self.msg).await
+        let this = self.prepare("any");
+        if let Err(SendError(msg)) = this.sender.send(this.msg).await {
+            reject_closed(msg)
+        }
     }
 
     /// Try to dispatch the task to any thread.
@@ -44,15 +137,9 @@ impl<'a, D: ToAny> Dispatch<'a, D> {
     /// The dispatched task can be handled by either the main thread or any of the worker threads.
     /// If the backing queue is full, the dispatcher is returned to allow retrying.
     pub fn try_dispatch_any(self) -> Result<(), Self> {
-        if let Some(msg) = self.sender.try_send(self.msg) {
-            Err(Dispatch {
-                msg,
-                sender: self.sender,
-                _dispatch: PhantomData,
-            })
-        } else {
-            Ok(())
-        }
+        let this = self.prepare("any");
+        let res = this.sender.try_send(this.msg);
+        Self::try_sent(this.sender, res)
     }
 }
 
@@ -62,7 +149,10 @@ impl<'a, D: ToMain> Dispatch<'a, D> {
     /// The dispatched task is guaranteed to be handled by the main thread. This method doesn't
     /// resolve until the task has been successfully dispatched.
     pub async fn dispatch_main(self) {
-        self.sender.send_main(self.msg).await
+        let this = self.prepare("main");
+        if let Err(SendError(msg)) = this.sender.send_main(this.msg).await {
+            reject_closed(msg)
+        }
     }
 
     /// Try to dispatch the task to the main thread.
@@ -70,15 +160,9 @@ impl<'a, D: ToMain> Dispatch<'a, D> {
     /// The dispatched task is guaranteed to be handled by the main thread. If the backing queue
     /// is full, the dispatcher is returned to allow retrying.
     pub fn try_dispatch_main(self) -> Result<(), Self> {
-        if let Some(msg) = self.sender.try_send_main(self.msg) {
-            Err(Dispatch {
-                msg,
-                sender: self.sender,
-                _dispatch: PhantomData,
-            })
-        } else {
-            Ok(())
-        }
+        let this = self.prepare("main");
+        let res = this.sender.try_send_main(this.msg);
+        Self::try_sent(this.sender, res)
     }
 }
 
@@ -89,7 +173,10 @@ impl<'a, D: ToWorker> Dispatch<'a, D> {
     /// otherwise it's handled by the main thread. This method doesn't resolve until the task has
     /// been successfully dispatched.
     pub async fn dispatch_worker(self) {
-        self.sender.send_worker(self.msg).await
+        let this = self.prepare("worker");
+        if let Err(SendError(msg)) = this.sender.send_worker(this.msg).await {
+            reject_closed(msg)
+        }
     }
 
     /// Try to dispatch the task to a worker thread.
@@ -98,14 +185,8 @@ impl<'a, D: ToWorker> Dispatch<'a, D> {
     /// otherwise it's handled by the main thread.  If the backing queue is full, the dispatcher
     /// is returned to allow retrying.
     pub fn try_dispatch_worker(self) -> Result<(), Self> {
-        if let Some(msg) = self.sender.try_send_worker(self.msg) {
-            Err(Dispatch {
-                msg,
-                sender: self.sender,
-                _dispatch: PhantomData,
-            })
-        } else {
-            Ok(())
-        }
+        let this = self.prepare("worker");
+        let res = this.sender.try_send_worker(this.msg);
+        Self::try_sent(this.sender, res)
     }
 }
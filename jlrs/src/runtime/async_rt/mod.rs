@@ -24,8 +24,11 @@
 pub mod adopted;
 #[cfg(feature = "async-std-rt")]
 pub mod async_std_rt;
+pub mod cancel;
 pub mod dispatch;
 pub mod queue;
+pub mod shutdown;
+pub mod stats;
 #[cfg(feature = "tokio-rt")]
 pub mod tokio_rt;
 
@@ -38,7 +41,7 @@ use std::{
     path::Path,
     rc::Rc,
     sync::{atomic::Ordering, Arc},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use async_trait::async_trait;
@@ -53,9 +56,14 @@ use jlrs_macros::julia_version;
 
 #[julia_version(since = "1.9")]
 use self::adopted::init_worker;
+#[cfg(feature = "jlrs-tracing")]
+use self::stats::task_span;
 use self::{
+    cancel::{check_deadlines, CancelState, INTERRUPT_TASK_FN},
     dispatch::Dispatch,
     queue::{channel, Receiver, Sender},
+    shutdown::{ExitGuard, ShutdownFuture, ShutdownPolicy, ShutdownState},
+    stats::{RuntimeStats, StatsCollector, TaskKind},
 };
 use crate::{
     async_util::{
@@ -72,14 +80,14 @@ use crate::{
     },
     convert::into_result::IntoResult,
     data::managed::{module::Module, value::Value},
-    error::{IOError, JlrsError, JlrsResult, RuntimeError},
+    error::{Cancelled, IOError, JlrsError, JlrsResult, RuntimeError},
     init_jlrs,
     memory::{
         context::stack::Stack,
         stack_frame::StackFrame,
         target::{frame::GcFrame, unrooted::Unrooted},
     },
-    runtime::{builder::AsyncRuntimeBuilder, INIT},
+    runtime::{builder::AsyncRuntimeBuilder, package_manager::AsyncPackageManager, INIT},
 };
 
 /// Functionality that is necessary to use an async runtime with jlrs.
@@ -142,9 +150,12 @@ pub trait AsyncRuntime: Send + Sync + 'static {
 /// A handle to the async runtime.
 ///
 /// This handle can be used to include files and send new tasks to the runtime. The runtime shuts
-/// down when the last handle is dropped and all active tasks have completed.
+/// down when the last handle is dropped and all active tasks have completed, or when
+/// [`AsyncJulia::shutdown`] is called.
 pub struct AsyncJulia<R> {
     sender: Sender<Message>,
+    shutdown: Arc<ShutdownState>,
+    stats: Arc<StatsCollector>,
     _runtime: PhantomData<R>,
 }
 
@@ -367,6 +378,55 @@ where
         Ok(Dispatch::new(&self.sender, msg))
     }
 
+    /// Returns an [`AsyncPackageManager`] that can be used to manage the active project
+    /// environment and its packages.
+    pub fn package_manager(&self) -> AsyncPackageManager<'_, R> {
+        AsyncPackageManager::new(self)
+    }
+
+    /// Shut down the runtime.
+    ///
+    /// The runtime stops accepting new tasks immediately, `policy` determines whether tasks that
+    /// have already been dispatched are drained or cancelled. After all tasks have completed the
+    /// shutdown hooks are called and Julia exits. The returned future resolves to a summary of
+    /// the tasks that have been dropped or cancelled when Julia has exited.
+    ///
+    /// If the runtime is already shutting down, the original policy is used. See the
+    /// [`shutdown`] module for more information.
+    ///
+    /// [`shutdown`]: crate::runtime::async_rt::shutdown
+    pub fn shutdown(&self, policy: ShutdownPolicy) -> ShutdownFuture {
+        let future = self.shutdown.request(policy);
+        self.sender.close();
+        future
+    }
+
+    /// Register a hook that's called on the main runtime thread when the runtime shuts down.
+    ///
+    /// Hooks are called in the order they've been registered after all tasks have completed,
+    /// right before Julia exits. They can be used to clean up global state, e.g. by flushing
+    /// Julia's IO buffers. Hooks are also called if the runtime shuts down because the last
+    /// handle has been dropped.
+    pub fn on_shutdown<F>(&self, hook: F)
+    where
+        for<'base> F: 'static + Send + FnOnce(GcFrame<'base>) -> JlrsResult<()>,
+    {
+        self.shutdown.add_hook(Box::new(hook));
+    }
+
+    /// Returns a snapshot of the statistics of the runtime.
+    ///
+    /// See the [`stats`] module for more information.
+    ///
+    /// [`stats`]: crate::runtime::async_rt::stats
+    pub fn stats(&self) -> RuntimeStats {
+        self.stats.snapshot(
+            self.sender.main_queue_len(),
+            self.sender.any_queue_len(),
+            self.sender.worker_queue_len(),
+        )
+    }
+
     /// Enable or disable colored error messages originating from Julia as a blocking task.
     ///
     /// This method waits if there's no room in the channel. It takes two arguments, a `bool` to
@@ -386,12 +446,21 @@ where
     pub(crate) unsafe fn init<const N: usize>(
         builder: AsyncRuntimeBuilder<R>,
     ) -> JlrsResult<(Self, std::thread::JoinHandle<JlrsResult<()>>)> {
+        builder.builder.options.validate()?;
+
         let has_workers = builder.has_workers();
         let (sender, receiver) = channel(builder.channel_capacity.get(), has_workers);
-        let handle = R::spawn_thread(move || Self::run_async::<N>(builder, receiver));
+        let shutdown = Arc::new(ShutdownState::new());
+        let stats = Arc::new(StatsCollector::new(builder.n_runtime_threads()));
+        let (shutdown_state, collector) = (shutdown.clone(), stats.clone());
+        let handle = R::spawn_thread(move || {
+            Self::run_async::<N>(builder, receiver, shutdown_state, collector)
+        });
 
         let julia = AsyncJulia {
             sender,
+            shutdown,
+            stats,
             _runtime: PhantomData,
         };
 
@@ -402,12 +471,21 @@ where
     pub(crate) unsafe fn init_async<const N: usize>(
         builder: AsyncRuntimeBuilder<R>,
     ) -> JlrsResult<(Self, R::RuntimeHandle)> {
+        builder.builder.options.validate()?;
+
         let has_workers = builder.has_workers();
         let (sender, receiver) = channel(builder.channel_capacity.get(), has_workers);
-        let handle = R::spawn_blocking(move || Self::run_async::<N>(builder, receiver));
+        let shutdown = Arc::new(ShutdownState::new());
+        let stats = Arc::new(StatsCollector::new(builder.n_runtime_threads()));
+        let (shutdown_state, collector) = (shutdown.clone(), stats.clone());
+        let handle = R::spawn_blocking(move || {
+            Self::run_async::<N>(builder, receiver, shutdown_state, collector)
+        });
 
         let julia = AsyncJulia {
             sender,
+            shutdown,
+            stats,
             _runtime: PhantomData,
         };
 
@@ -417,11 +495,18 @@ where
     fn run_async<const N: usize>(
         builder: AsyncRuntimeBuilder<R>,
         receiver: Receiver<Message>,
+        shutdown: Arc<ShutdownState>,
+        stats: Arc<StatsCollector>,
     ) -> JlrsResult<()> {
+        let _exit_guard = ExitGuard::new(shutdown.clone());
+
         unsafe {
             if jl_is_initialized() != 0 || INIT.swap(true, Ordering::Relaxed) {
                 Err(RuntimeError::AlreadyInitialized)?;
             }
+
+            builder.builder.options.apply();
+
             #[cfg(not(any(feature = "julia-1-10", feature = "julia-1-9")))]
             {
                 if builder.n_threads == 0 {
@@ -484,11 +569,13 @@ where
             } else {
                 jl_init();
             }
+
+            builder.builder.options.apply_depot_path()?;
         }
 
         let mut base_frame = StackFrame::<N>::new_n();
         R::block_on(
-            unsafe { Self::run_inner(builder, receiver, &mut base_frame) },
+            unsafe { Self::run_inner(builder, receiver, shutdown, stats, &mut base_frame) },
             None,
         )
     }
@@ -496,6 +583,8 @@ where
     async unsafe fn run_inner<'ctx, const N: usize>(
         builder: AsyncRuntimeBuilder<R>,
         receiver: Receiver<Message>,
+        shutdown: Arc<ShutdownState>,
+        stats: Arc<StatsCollector>,
         base_frame: &'ctx mut StackFrame<N>,
     ) -> Result<(), Box<JlrsError>> {
         let base_frame: &'static mut StackFrame<N> = std::mem::transmute(base_frame);
@@ -525,13 +614,28 @@ where
             Rc::new(RefCell::new(running_tasks.into_boxed_slice()))
         };
 
+        let running_cancels = {
+            let mut running_cancels = Vec::with_capacity(N);
+            for _ in 0..N {
+                running_cancels.push(None);
+            }
+
+            Rc::new(RefCell::new(running_cancels.into_boxed_slice()))
+        };
+
         let recv_timeout = builder.recv_timeout;
 
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         let mut workers = Vec::with_capacity(builder.n_workers);
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         for i in 0..builder.n_workers {
-            let worker = init_worker::<R, N>(i, recv_timeout, receiver.clone());
+            let worker = init_worker::<R, N>(
+                i,
+                recv_timeout,
+                receiver.clone(),
+                shutdown.clone(),
+                stats.clone(),
+            );
             workers.push(worker)
         }
 
@@ -539,6 +643,11 @@ where
         jl_enter_threaded_region();
 
         loop {
+            check_deadlines(&running_cancels);
+            if shutdown.should_cancel() {
+                shutdown.cancel_running(&running_cancels);
+            }
+
             if free_stacks.borrow().len() == 0 {
                 jl_process_events();
                 R::yield_now().await;
@@ -551,54 +660,96 @@ where
                     jl_process_events();
                     jl_yield();
                 }
-                Some(Ok(msg)) => match msg.inner {
-                    MessageInner::Task(task) => {
-                        let idx = free_stacks.borrow_mut().pop_front().unwrap();
-                        let stack = base_frame.nth_stack(idx);
-
-                        let task = {
-                            let free_stacks = free_stacks.clone();
-                            let running_tasks = running_tasks.clone();
-
-                            R::spawn_local(async move {
-                                task.call(stack).await;
-                                free_stacks.borrow_mut().push_back(idx);
-                                running_tasks.borrow_mut()[idx] = None;
-                            })
-                        };
-
-                        running_tasks.borrow_mut()[idx] = Some(task);
+                Some(Ok(msg)) => {
+                    if shutdown.should_cancel() {
+                        if msg.reject(Box::new(JlrsError::Cancelled(Cancelled::Shutdown))) {
+                            shutdown.task_dropped();
+                        }
+                        stats.task_rejected();
+                        continue;
                     }
-                    MessageInner::BlockingTask(task) => {
-                        let stack = base_frame.sync_stack();
-                        task.call(stack);
-                    }
-                    MessageInner::PostBlockingTask(task) => {
-                        let idx = free_stacks.borrow_mut().pop_front().unwrap();
-                        let stack = base_frame.nth_stack(idx);
-
-                        let task = {
-                            let free_stacks = free_stacks.clone();
-                            let running_tasks = running_tasks.clone();
-
-                            R::spawn_local(async move {
-                                task.post(stack).await;
-                                free_stacks.borrow_mut().push_back(idx);
-                                running_tasks.borrow_mut()[idx] = None;
-                            })
-                        };
-
-                        running_tasks.borrow_mut()[idx] = Some(task);
-                    }
-                    MessageInner::Include(task) => {
-                        let stack = base_frame.sync_stack();
-                        task.call(stack);
-                    }
-                    MessageInner::ErrorColor(task) => {
-                        let stack = base_frame.sync_stack();
-                        task.call(stack);
+
+                    let task = match msg.receive() {
+                        Some(task) => task,
+                        None => {
+                            stats.task_rejected();
+                            continue;
+                        }
+                    };
+                    let timer = stats.task_started(0, msg.kind(), msg.queued_at);
+                    let cancel = msg.cancel;
+                    #[cfg(feature = "jlrs-tracing")]
+                    let span = task_span(&timer, msg.queued_at);
+
+                    match task {
+                        MessageInner::Task(task) => {
+                            let idx = free_stacks.borrow_mut().pop_front().unwrap();
+                            let stack = base_frame.nth_stack(idx);
+
+                            let task = {
+                                let free_stacks = free_stacks.clone();
+                                let running_tasks = running_tasks.clone();
+                                let running_cancels = running_cancels.clone();
+                                let cancel = cancel.clone();
+
+                                let fut = async move {
+                                    task.call(stack, cancel, timer).await;
+                                    free_stacks.borrow_mut().push_back(idx);
+                                    running_tasks.borrow_mut()[idx] = None;
+                                    running_cancels.borrow_mut()[idx] = None;
+                                };
+
+                                #[cfg(feature = "jlrs-tracing")]
+                                let fut = tracing::Instrument::instrument(fut, span);
+
+                                R::spawn_local(fut)
+                            };
+
+                            running_tasks.borrow_mut()[idx] = Some(task);
+                            running_cancels.borrow_mut()[idx] = Some(cancel);
+                        }
+                        MessageInner::BlockingTask(task) => {
+                            #[cfg(feature = "jlrs-tracing")]
+                            let _entered = span.enter();
+                            let stack = base_frame.sync_stack();
+                            task.call(stack, timer);
+                        }
+                        MessageInner::PostBlockingTask(task) => {
+                            let idx = free_stacks.borrow_mut().pop_front().unwrap();
+                            let stack = base_frame.nth_stack(idx);
+
+                            let task = {
+                                let free_stacks = free_stacks.clone();
+                                let running_tasks = running_tasks.clone();
+
+                                let fut = async move {
+                                    task.post(stack, timer).await;
+                                    free_stacks.borrow_mut().push_back(idx);
+                                    running_tasks.borrow_mut()[idx] = None;
+                                };
+
+                                #[cfg(feature = "jlrs-tracing")]
+                                let fut = tracing::Instrument::instrument(fut, span);
+
+                                R::spawn_local(fut)
+                            };
+
+                            running_tasks.borrow_mut()[idx] = Some(task);
+                        }
+                        MessageInner::Include(task) => {
+                            #[cfg(feature = "jlrs-tracing")]
+                            let _entered = span.enter();
+                            let stack = base_frame.sync_stack();
+                            task.call(stack, timer);
+                        }
+                        MessageInner::ErrorColor(task) => {
+                            #[cfg(feature = "jlrs-tracing")]
+                            let _entered = span.enter();
+                            let stack = base_frame.sync_stack();
+                            task.call(stack, timer);
+                        }
                     }
-                },
+                }
                 Some(Err(_)) => break,
             }
         }
@@ -606,6 +757,10 @@ where
         for i in 0..N {
             loop {
                 if running_tasks.borrow()[i].is_some() {
+                    if shutdown.should_cancel() {
+                        shutdown.cancel_running(&running_cancels);
+                    }
+
                     R::yield_now().await;
                     sleep(&Unrooted::new(), recv_timeout);
                     jl_process_events();
@@ -631,14 +786,27 @@ where
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         jl_exit_threaded_region();
 
+        let mut failed_hooks = 0;
+        for hook in shutdown.take_hooks() {
+            let (owner, frame) = GcFrame::base(base_frame.sync_stack());
+            if hook(frame).is_err() {
+                failed_hooks += 1;
+            }
+            std::mem::drop(owner);
+        }
+
         jl_atexit_hook(0);
+        shutdown.complete(failed_hooks);
         Ok(())
     }
 }
 
 /// The message type used by the async runtime for communication.
 pub struct Message {
-    inner: MessageInner,
+    kind: TaskKind,
+    // Owns the task until it's received or rejected.
+    cancel: Arc<CancelState>,
+    queued_at: Instant,
 }
 
 pub(crate) enum MessageInner {
@@ -655,11 +823,65 @@ impl fmt::Debug for Message {
     }
 }
 
-unsafe impl Sync for Message {}
-
 impl MessageInner {
     pub(crate) fn wrap(self) -> Message {
-        Message { inner: self }
+        Message {
+            kind: self.kind(),
+            cancel: Arc::new(CancelState::new(self)),
+            queued_at: Instant::now(),
+        }
+    }
+
+    fn kind(&self) -> TaskKind {
+        match self {
+            MessageInner::Task(task) => task.kind(),
+            MessageInner::BlockingTask(_) => TaskKind::Blocking,
+            MessageInner::PostBlockingTask(_) => TaskKind::PostBlocking,
+            MessageInner::Include(_) => TaskKind::Include,
+            MessageInner::ErrorColor(_) => TaskKind::ErrorColor,
+        }
+    }
+
+    // Send `error` to the result sender without calling the task.
+    pub(crate) fn reject(self, error: Box<JlrsError>) {
+        match self {
+            MessageInner::Task(task) => task.reject(error),
+            MessageInner::BlockingTask(task) => task.reject(error),
+            MessageInner::PostBlockingTask(task) => task.reject(error),
+            MessageInner::Include(task) => task.reject(error),
+            MessageInner::ErrorColor(task) => task.reject(error),
+        }
+    }
+}
+
+impl Message {
+    pub(crate) fn kind(&self) -> TaskKind {
+        self.kind
+    }
+
+    // Take the task out of the message when it's received. If the task has been cancelled, the
+    // result sender has received an error and `None` is returned.
+    pub(crate) fn receive(&self) -> Option<MessageInner> {
+        let task = self.cancel.dequeue()?;
+        match self.cancel.check() {
+            Some(reason) => {
+                task.reject(Box::new(JlrsError::Cancelled(reason)));
+                None
+            }
+            None => Some(task),
+        }
+    }
+
+    // Send `error` to the result sender without calling the task, returns `false` if the task
+    // has already been rejected because it has been cancelled.
+    pub(crate) fn reject(self, error: Box<JlrsError>) -> bool {
+        match self.cancel.dequeue() {
+            Some(task) => {
+                task.reject(error);
+                true
+            }
+            None => false,
+        }
     }
 }
 
@@ -670,6 +892,9 @@ fn set_custom_fns(stack: &Stack) -> JlrsResult<()> {
         let cmd = CStr::from_bytes_with_nul_unchecked(b"const JlrsThreads = JlrsCore.Threads\0");
         Value::eval_cstring(&mut frame, cmd).expect("using JlrsCore threw an exception");
 
+        let cmd = CStr::from_bytes_with_nul_unchecked(INTERRUPT_TASK_FN.as_bytes());
+        Value::eval_cstring(&mut frame, cmd).expect("defining jlrsinterrupttask failed");
+
         let wake_rust = Value::new(&mut frame, wake_task as *mut c_void);
         Module::main(&frame)
             .submodule(&frame, "JlrsThreads")?
@@ -1,5 +1,5 @@
 use std::sync::{
-    atomic::{AtomicUsize, Ordering},
+    atomic::{AtomicBool, AtomicUsize, Ordering},
     Arc,
 };
 
@@ -8,7 +8,10 @@ use futures::Future;
 use futures_concurrency::future::Race;
 use jlrs_macros::julia_version;
 
-use crate::error::{JlrsResult, RuntimeError};
+use crate::{
+    async_util::channel::{SendError, TrySendError},
+    error::{JlrsResult, RuntimeError},
+};
 
 struct Queues<T> {
     main_queue: Queue<T>,
@@ -16,6 +19,11 @@ struct Queues<T> {
     worker_queue: Option<Queue<T>>,
     // there's no method that closes the queue, so the number of senders must be tracked.
     n_senders: AtomicUsize,
+    // set when the runtime is shut down while there are still senders.
+    closed: AtomicBool,
+    // the number of sends that are in progress, the receiver must not consider a closed queue
+    // to be exhausted until they have completed.
+    pending_sends: AtomicUsize,
 }
 
 impl<T> Queues<T> {
@@ -31,8 +39,27 @@ impl<T> Queues<T> {
             any_queue,
             worker_queue,
             n_senders: AtomicUsize::new(1),
+            closed: AtomicBool::new(false),
+            pending_sends: AtomicUsize::new(0),
         })
     }
+
+    fn is_closed(&self) -> bool {
+        self.n_senders.load(Ordering::Acquire) == 0 || self.closed.load(Ordering::SeqCst)
+    }
+
+    fn has_pending_sends(&self) -> bool {
+        self.pending_sends.load(Ordering::SeqCst) != 0
+    }
+}
+
+// Tracks a send that is in progress.
+struct PendingSend<'a>(&'a AtomicUsize);
+
+impl Drop for PendingSend<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 pub(crate) struct Sender<T> {
@@ -57,21 +84,45 @@ impl<T> Clone for Sender<T> {
 }
 
 impl<T: Send> Sender<T> {
-    pub(crate) async fn send(&self, item: T) {
-        if let Some(ref q) = self.queues.any_queue {
-            q.push(item).await
-        } else {
-            self.send_main(item).await
-        }
+    pub(crate) fn close(&self) {
+        self.queues.closed.store(true, Ordering::SeqCst);
     }
 
-    pub(crate) fn try_send(&self, item: T) -> Option<T> {
-        if let Some(ref q) = self.queues.any_queue {
-            q.try_push(item).err()?;
-        } else {
-            self.try_send_main(item)?;
+    // Returns `None` if the queue has been closed. The send must be completed before the
+    // returned guard is dropped.
+    fn start_send(&self) -> Option<PendingSend<'_>> {
+        // The pending send must be registered before checking if the queue has been closed: if
+        // the receiver observes that the queue has been closed and that there are no pending
+        // sends, no item can be pushed afterwards.
+        self.queues.pending_sends.fetch_add(1, Ordering::SeqCst);
+        let pending = PendingSend(&self.queues.pending_sends);
+        if self.queues.closed.load(Ordering::SeqCst) {
+            return None;
         }
-        None
+
+        Some(pending)
+    }
+
+    pub(crate) fn main_queue_len(&self) -> usize {
+        self.queues.main_queue.len()
+    }
+
+    pub(crate) fn any_queue_len(&self) -> Option<usize> {
+        self.queues.any_queue.as_ref().map(|q| q.len())
+    }
+
+    pub(crate) fn worker_queue_len(&self) -> Option<usize> {
+        self.queues.worker_queue.as_ref().map(|q| q.len())
+    }
+
+    pub(crate) async fn send(&self, item: T) -> Result<(), SendError<T>> {
+        let q = self.queues.any_queue.as_ref();
+        self.send_to(q, item).await
+    }
+
+    pub(crate) fn try_send(&self, item: T) -> Result<(), TrySendError<T>> {
+        let q = self.queues.any_queue.as_ref();
+        self.try_send_to(q, item)
     }
 
     pub(crate) fn resize_queue<'own>(
@@ -81,13 +132,12 @@ impl<T: Send> Sender<T> {
         self.queues.any_queue.as_ref().map(|q| q.resize(capacity))
     }
 
-    pub(crate) async fn send_main(&self, item: T) {
-        self.queues.main_queue.push(item).await
+    pub(crate) async fn send_main(&self, item: T) -> Result<(), SendError<T>> {
+        self.send_to(None, item).await
     }
 
-    pub(crate) fn try_send_main(&self, item: T) -> Option<T> {
-        self.queues.main_queue.try_push(item).err()?;
-        None
+    pub(crate) fn try_send_main(&self, item: T) -> Result<(), TrySendError<T>> {
+        self.try_send_to(None, item)
     }
 
     pub(crate) fn resize_main_queue<'own>(
@@ -97,21 +147,14 @@ impl<T: Send> Sender<T> {
         self.queues.main_queue.resize(capacity)
     }
 
-    pub(crate) async fn send_worker(&self, item: T) {
-        if let Some(ref q) = self.queues.worker_queue {
-            q.push(item).await
-        } else {
-            self.send_main(item).await
-        }
+    pub(crate) async fn send_worker(&self, item: T) -> Result<(), SendError<T>> {
+        let q = self.queues.worker_queue.as_ref();
+        self.send_to(q, item).await
     }
 
-    pub(crate) fn try_send_worker(&self, item: T) -> Option<T> {
-        if let Some(ref q) = self.queues.worker_queue {
-            q.try_push(item).err()?;
-        } else {
-            self.try_send_main(item)?;
-        }
-        None
+    pub(crate) fn try_send_worker(&self, item: T) -> Result<(), TrySendError<T>> {
+        let q = self.queues.worker_queue.as_ref();
+        self.try_send_to(q, item)
     }
 
     pub(crate) fn resize_worker_queue<'own>(
@@ -123,6 +166,29 @@ impl<T: Send> Sender<T> {
             .as_ref()
             .map(|q| q.resize(capacity))
     }
+
+    // Push `item` to `q`, or to the main queue if `q` is `None`.
+    async fn send_to(&self, q: Option<&Queue<T>>, item: T) -> Result<(), SendError<T>> {
+        let _pending = match self.start_send() {
+            Some(pending) => pending,
+            None => return Err(SendError(item)),
+        };
+
+        q.unwrap_or(&self.queues.main_queue).push(item).await;
+        Ok(())
+    }
+
+    // Try to push `item` to `q`, or to the main queue if `q` is `None`.
+    fn try_send_to(&self, q: Option<&Queue<T>>, item: T) -> Result<(), TrySendError<T>> {
+        let _pending = match self.start_send() {
+            Some(pending) => pending,
+            None => return Err(TrySendError::Closed(item)),
+        };
+
+        q.unwrap_or(&self.queues.main_queue)
+            .try_push(item)
+            .map_err(TrySendError::Full)
+    }
 }
 
 pub(crate) struct Receiver<T> {
@@ -142,11 +208,15 @@ impl<T> Clone for Receiver<T> {
 
 impl<T: Send> Receiver<T> {
     pub(crate) async fn recv_main(&self) -> JlrsResult<T> {
-        if self.queue.n_senders.load(Ordering::Acquire) == 0 {
-            return match self.try_recv_main() {
-                Some(t) => Ok(t),
-                None => Err(RuntimeError::ChannelClosed)?,
-            };
+        if self.queue.is_closed() {
+            // Pending sends must be checked before the queue, an item that's pushed in between
+            // would be lost otherwise. If there are pending sends, wait for them to complete.
+            let exhausted = !self.queue.has_pending_sends();
+            match self.try_recv_main() {
+                Some(t) => return Ok(t),
+                None if exhausted => Err(RuntimeError::ChannelClosed)?,
+                None => (),
+            }
         }
 
         if self.queue.any_queue.is_some() {
@@ -171,11 +241,15 @@ impl<T: Send> Receiver<T> {
 
     #[julia_version(since = "1.9")]
     pub(crate) async fn recv_worker(&self) -> JlrsResult<T> {
-        if self.queue.n_senders.load(Ordering::Acquire) == 0 {
-            return match self.try_recv_worker() {
-                Some(t) => Ok(t),
-                None => Err(RuntimeError::ChannelClosed)?,
-            };
+        if self.queue.is_closed() {
+            // Pending sends must be checked before the queue, an item that's pushed in between
+            // would be lost otherwise. If there are pending sends, wait for them to complete.
+            let exhausted = !self.queue.has_pending_sends();
+            match self.try_recv_worker() {
+                Some(t) => return Ok(t),
+                None if exhausted => Err(RuntimeError::ChannelClosed)?,
+                None => (),
+            }
         }
 
         Ok((
@@ -0,0 +1,234 @@
+//! Shut down the async runtime.
+//!
+//! The async runtime shuts down when the last [`AsyncJulia`] handle has been dropped and all
+//! tasks have completed. It can also be shut down explicitly with [`AsyncJulia::shutdown`],
+//! which immediately stops the runtime from accepting new tasks, the result sender of a task that
+//! is dispatched after the shutdown has been requested receives
+//! [`RuntimeError::ChannelClosed`].
+//!
+//! The [`ShutdownPolicy`] determines what happens with tasks that have already been dispatched.
+//! They can be drained, i.e. all queued tasks are called and the runtime waits until all
+//! running tasks have completed, or be cancelled like they are when a [`CancelHandle`] is used.
+//! Note that a persistent task only completes when all of its handles have been dropped, so
+//! draining waits indefinitely if a handle is never dropped.
+//!
+//! After all tasks have completed the shutdown hooks registered with
+//! [`AsyncJulia::on_shutdown`] are called, e.g. to flush Julia's IO buffers, before Julia exits.
+//! The [`ShutdownFuture`] returned by `AsyncJulia::shutdown` resolves to a [`ShutdownSummary`]
+//! when Julia has exited. If the runtime thread panics or returns an error before it has
+//! completed the shutdown, the future resolves to [`RuntimeError::ChannelClosed`] instead.
+//!
+//! [`AsyncJulia`]: crate::runtime::async_rt::AsyncJulia
+//! [`AsyncJulia::shutdown`]: crate::runtime::async_rt::AsyncJulia::shutdown
+//! [`AsyncJulia::on_shutdown`]: crate::runtime::async_rt::AsyncJulia::on_shutdown
+//! [`RuntimeError::ChannelClosed`]: crate::error::RuntimeError::ChannelClosed
+//! [`CancelHandle`]: crate::runtime::async_rt::cancel::CancelHandle
+
+use std::{
+    cell::RefCell,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::{channel::oneshot, Future};
+
+use super::cancel::CancelState;
+use crate::{
+    error::{Cancelled, JlrsResult, RuntimeError},
+    memory::target::frame::GcFrame,
+};
+
+/// What happens with tasks that have been dispatched when the runtime is shut down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownPolicy {
+    /// Call all queued tasks and wait until all running tasks have completed.
+    Drain,
+    /// Drain the runtime for at most the given duration, remaining tasks are cancelled when it
+    /// has elapsed.
+    DrainWithTimeout(Duration),
+    /// Cancel all queued and running tasks.
+    Cancel,
+}
+
+/// Summary of a completed shutdown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    /// The number of queued tasks that have been dropped without being called.
+    pub dropped: usize,
+    /// The number of running tasks that have been cancelled.
+    pub cancelled: usize,
+    /// The number of shutdown hooks that returned an error.
+    pub failed_hooks: usize,
+}
+
+/// Future returned by [`AsyncJulia::shutdown`], resolves when Julia has exited.
+///
+/// [`AsyncJulia::shutdown`]: crate::runtime::async_rt::AsyncJulia::shutdown
+pub struct ShutdownFuture {
+    receiver: oneshot::Receiver<ShutdownSummary>,
+}
+
+impl Future for ShutdownFuture {
+    type Output = JlrsResult<ShutdownSummary>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(summary)) => Poll::Ready(Ok(summary)),
+            // The runtime thread has stopped without completing the shutdown.
+            Poll::Ready(Err(_)) => Poll::Ready(Err(RuntimeError::ChannelClosed)?),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub(crate) type ShutdownHook = Box<dyn Send + for<'base> FnOnce(GcFrame<'base>) -> JlrsResult<()>>;
+
+struct Requested {
+    policy: ShutdownPolicy,
+    requested_at: Instant,
+}
+
+struct Waiters {
+    senders: Vec<oneshot::Sender<ShutdownSummary>>,
+    // Set when the runtime thread has exited, `summary` is `None` if it hasn't completed the
+    // shutdown.
+    exited: bool,
+    summary: Option<ShutdownSummary>,
+}
+
+// Shared between all handles and the threads of the runtime.
+pub(crate) struct ShutdownState {
+    requested: Mutex<Option<Requested>>,
+    hooks: Mutex<Vec<ShutdownHook>>,
+    waiters: Mutex<Waiters>,
+    dropped: AtomicUsize,
+    cancelled: AtomicUsize,
+}
+
+impl ShutdownState {
+    pub(crate) fn new() -> Self {
+        ShutdownState {
+            requested: Mutex::new(None),
+            hooks: Mutex::new(Vec::new()),
+            waiters: Mutex::new(Waiters {
+                senders: Vec::new(),
+                exited: false,
+                summary: None,
+            }),
+            dropped: AtomicUsize::new(0),
+            cancelled: AtomicUsize::new(0),
+        }
+    }
+
+    // Request a shutdown, if one has already been requested the original policy is used.
+    pub(crate) fn request(&self, policy: ShutdownPolicy) -> ShutdownFuture {
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut waiters = self.waiters.lock().unwrap();
+            if !waiters.exited {
+                waiters.senders.push(sender);
+            } else if let Some(summary) = waiters.summary {
+                sender.send(summary).ok();
+            }
+        }
+
+        let mut requested = self.requested.lock().unwrap();
+        if requested.is_none() {
+            *requested = Some(Requested {
+                policy,
+                requested_at: Instant::now(),
+            });
+        }
+
+        ShutdownFuture { receiver }
+    }
+
+    pub(crate) fn add_hook(&self, hook: ShutdownHook) {
+        self.hooks.lock().unwrap().push(hook);
+    }
+
+    // Returns `true` if queued and running tasks must be cancelled.
+    pub(crate) fn should_cancel(&self) -> bool {
+        match *self.requested.lock().unwrap() {
+            Some(Requested {
+                policy: ShutdownPolicy::Cancel,
+                ..
+            }) => true,
+            Some(Requested {
+                policy: ShutdownPolicy::DrainWithTimeout(timeout),
+                requested_at,
+            }) => requested_at.elapsed() >= timeout,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn task_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Cancel all running tasks that haven't been cancelled yet.
+    pub(crate) fn cancel_running(&self, running: &RefCell<Box<[Option<Arc<CancelState>>]>>) {
+        let running = running
+            .borrow()
+            .iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+        for state in running {
+            if state.cancel(Cancelled::Shutdown) {
+                self.cancelled.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub(crate) fn take_hooks(&self) -> Vec<ShutdownHook> {
+        std::mem::take(&mut *self.hooks.lock().unwrap())
+    }
+
+    // Notify all waiters that Julia has exited.
+    pub(crate) fn complete(&self, failed_hooks: usize) {
+        let summary = ShutdownSummary {
+            dropped: self.dropped.load(Ordering::Relaxed),
+            cancelled: self.cancelled.load(Ordering::Relaxed),
+            failed_hooks,
+        };
+
+        let mut waiters = self.waiters.lock().unwrap();
+        waiters.exited = true;
+        waiters.summary = Some(summary);
+        for waiter in waiters.senders.drain(..) {
+            waiter.send(summary).ok();
+        }
+    }
+
+    // Called when the runtime thread exits, the senders are dropped if the shutdown hasn't been
+    // completed so the waiters resolve to an error.
+    fn exit(&self) {
+        let mut waiters = self.waiters.lock().unwrap();
+        waiters.exited = true;
+        waiters.senders.clear();
+    }
+}
+
+// Created when the runtime thread starts, the waiters are notified when it's dropped even if
+// the runtime thread panics or returns early with an error.
+pub(crate) struct ExitGuard {
+    state: Arc<ShutdownState>,
+}
+
+impl ExitGuard {
+    pub(crate) fn new(state: Arc<ShutdownState>) -> Self {
+        ExitGuard { state }
+    }
+}
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        self.state.exit();
+    }
+}
@@ -0,0 +1,301 @@
+//! Collect statistics about the async runtime.
+//!
+//! The async runtime keeps track of the tasks it has handled. A snapshot of these statistics
+//! can be taken with [`AsyncJulia::stats`] from any thread, it contains the current lengths of
+//! the task queues, the number of tasks that are in flight on each runtime thread, how many tasks
+//! have completed, failed or been cancelled, and percentiles of the time tasks have waited in the
+//! queue and of the execution time of each [`TaskKind`]. Percentiles are computed over the
+//! most recent 1024 tasks.
+//!
+//! If the `jlrs-tracing` feature is enabled, events are emitted with [`tracing`] when a task is
+//! dispatched, and every task is called in a `task` span which records its kind, the thread that
+//! handles it and how long it has waited in the queue.
+//!
+//! [`AsyncJulia::stats`]: crate::runtime::async_rt::AsyncJulia::stats
+//! [`tracing`]: https://docs.rs/tracing
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::error::{JlrsError, JlrsResult};
+
+const N_SAMPLES: usize = 1024;
+const N_TASK_KINDS: usize = 8;
+
+/// The kind of a task that has been sent to the async runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TaskKind {
+    /// An async task.
+    Async,
+    /// The registration of an async task.
+    RegisterAsync,
+    /// A persistent task, its execution time is the time between its initialization and exit.
+    Persistent,
+    /// The registration of a persistent task.
+    RegisterPersistent,
+    /// A blocking task.
+    Blocking,
+    /// A blocking task that's called in a new Julia task.
+    PostBlocking,
+    /// Including a file.
+    Include,
+    /// Setting the error color.
+    ErrorColor,
+}
+
+impl TaskKind {
+    fn index(self) -> usize {
+        match self {
+            TaskKind::Async => 0,
+            TaskKind::RegisterAsync => 1,
+            TaskKind::Persistent => 2,
+            TaskKind::RegisterPersistent => 3,
+            TaskKind::Blocking => 4,
+            TaskKind::PostBlocking => 5,
+            TaskKind::Include => 6,
+            TaskKind::ErrorColor => 7,
+        }
+    }
+}
+
+/// Percentiles of a set of durations.
+///
+/// All durations are zero if no samples have been collected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Percentiles {
+    /// The number of samples.
+    pub count: usize,
+    /// The median.
+    pub p50: Duration,
+    /// The 90th percentile.
+    pub p90: Duration,
+    /// The 99th percentile.
+    pub p99: Duration,
+    /// The maximum.
+    pub max: Duration,
+}
+
+/// A snapshot of the statistics of the async runtime.
+#[derive(Clone, Debug)]
+pub struct RuntimeStats {
+    /// The number of tasks in the queue of the main runtime thread.
+    pub main_queue_len: usize,
+    /// The number of tasks in the queue shared by all runtime threads, `None` if the runtime has
+    /// no worker threads.
+    pub any_queue_len: Option<usize>,
+    /// The number of tasks in the queue of the worker threads, `None` if the runtime has no
+    /// worker threads.
+    pub worker_queue_len: Option<usize>,
+    /// The number of tasks that are in flight on each runtime thread, the first element is the
+    /// main runtime thread.
+    pub in_flight_per_thread: Vec<usize>,
+    /// The number of tasks that have completed successfully.
+    pub completed: u64,
+    /// The number of tasks that have returned an error.
+    pub failed: u64,
+    /// The number of tasks that have been cancelled, including tasks that were cancelled before
+    /// they were called.
+    pub cancelled: u64,
+    /// The time tasks have waited in the queue before they were called.
+    pub wait_time: Percentiles,
+    execution_time: [Percentiles; N_TASK_KINDS],
+}
+
+impl RuntimeStats {
+    /// Returns the total number of tasks that are in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight_per_thread.iter().sum()
+    }
+
+    /// Returns the percentiles of the execution time of tasks of kind `kind`.
+    pub fn execution_time(&self, kind: TaskKind) -> Percentiles {
+        self.execution_time[kind.index()]
+    }
+}
+
+// The outcome of a task that has been called.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TaskOutcome {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl TaskOutcome {
+    pub(crate) fn of<T>(res: &JlrsResult<T>) -> Self {
+        match res {
+            Ok(_) => TaskOutcome::Completed,
+            Err(e) => match **e {
+                JlrsError::Cancelled(_) => TaskOutcome::Cancelled,
+                _ => TaskOutcome::Failed,
+            },
+        }
+    }
+}
+
+// The most recent samples.
+struct Samples {
+    samples: VecDeque<Duration>,
+}
+
+impl Samples {
+    fn new() -> Self {
+        Samples {
+            samples: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, sample: Duration) {
+        if self.samples.len() == N_SAMPLES {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(sample);
+    }
+
+    fn percentiles(&self) -> Percentiles {
+        if self.samples.is_empty() {
+            return Percentiles::default();
+        }
+
+        let mut sorted = self.samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+
+        let n = sorted.len();
+        let nth = |q: f64| sorted[((n - 1) as f64 * q).round() as usize];
+
+        Percentiles {
+            count: n,
+            p50: nth(0.5),
+            p90: nth(0.9),
+            p99: nth(0.99),
+            max: sorted[n - 1],
+        }
+    }
+}
+
+// Returned when a task is called, used to record its execution time when it has finished.
+pub(crate) struct TaskTimer {
+    thread: usize,
+    kind: TaskKind,
+    started_at: Instant,
+    stats: Arc<StatsCollector>,
+}
+
+impl TaskTimer {
+    // Record the execution time and outcome of the task. This must happen before its result is
+    // sent, so a snapshot taken after receiving the result includes the task.
+    pub(crate) fn finish(self, outcome: TaskOutcome) {
+        self.stats.task_finished(&self, outcome)
+    }
+}
+
+// Shared between all handles and the threads of the runtime.
+pub(crate) struct StatsCollector {
+    in_flight: Box<[AtomicUsize]>,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    cancelled: AtomicU64,
+    wait_time: Mutex<Samples>,
+    execution_time: Mutex<[Samples; N_TASK_KINDS]>,
+}
+
+impl StatsCollector {
+    // `n_threads` is the total number of runtime threads, including the main runtime thread.
+    pub(crate) fn new(n_threads: usize) -> Self {
+        StatsCollector {
+            in_flight: (0..n_threads).map(|_| AtomicUsize::new(0)).collect(),
+            completed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            cancelled: AtomicU64::new(0),
+            wait_time: Mutex::new(Samples::new()),
+            execution_time: Mutex::new([(); N_TASK_KINDS].map(|_| Samples::new())),
+        }
+    }
+
+    pub(crate) fn task_started(
+        self: &Arc<Self>,
+        thread: usize,
+        kind: TaskKind,
+        queued_at: Instant,
+    ) -> TaskTimer {
+        let started_at = Instant::now();
+        self.wait_time
+            .lock()
+            .unwrap()
+            .push(started_at.saturating_duration_since(queued_at));
+        self.in_flight[thread].fetch_add(1, Ordering::Relaxed);
+
+        TaskTimer {
+            thread,
+            kind,
+            started_at,
+            stats: self.clone(),
+        }
+    }
+
+    fn task_finished(&self, timer: &TaskTimer, outcome: TaskOutcome) {
+        self.execution_time.lock().unwrap()[timer.kind.index()].push(timer.started_at.elapsed());
+        self.in_flight[timer.thread].fetch_sub(1, Ordering::Relaxed);
+
+        let counter = match outcome {
+            TaskOutcome::Completed => &self.completed,
+            TaskOutcome::Failed => &self.failed,
+            TaskOutcome::Cancelled => &self.cancelled,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Called when a task is cancelled before it has been called.
+    pub(crate) fn task_rejected(&self) {
+        self.cancelled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(
+        &self,
+        main_queue_len: usize,
+        any_queue_len: Option<usize>,
+        worker_queue_len: Option<usize>,
+    ) -> RuntimeStats {
+        let execution_time = {
+            let samples = self.execution_time.lock().unwrap();
+            let mut execution_time = [Percentiles::default(); N_TASK_KINDS];
+            for (percentiles, samples) in execution_time.iter_mut().zip(samples.iter()) {
+                *percentiles = samples.percentiles();
+            }
+            execution_time
+        };
+
+        RuntimeStats {
+            main_queue_len,
+            any_queue_len,
+            worker_queue_len,
+            in_flight_per_thread: self
+                .in_flight
+                .iter()
+                .map(|n| n.load(Ordering::Relaxed))
+                .collect(),
+            completed: self.completed.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            cancelled: self.cancelled.load(Ordering::Relaxed),
+            wait_time: self.wait_time.lock().unwrap().percentiles(),
+            execution_time,
+        }
+    }
+}
+
+// The span a task is called in.
+#[cfg(feature = "jlrs-tracing")]
+pub(crate) fn task_span(timer: &TaskTimer, queued_at: Instant) -> tracing::Span {
+    let wait_us = timer
+        .started_at
+        .saturating_duration_since(queued_at)
+        .as_micros() as u64;
+    tracing::debug_span!("task", kind = ?timer.kind, thread = timer.thread, wait_us)
+}
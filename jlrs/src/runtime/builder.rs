@@ -1,32 +1,238 @@
 //! Build a runtime.
 //!
 //! Before Julia can be used it must be initialized. The builders provided by this module must be
-//! used to initialize Julia and set custom parameters. The [`RuntimeBuilder`] only lets you
-//! provide a custom system image, [`AsyncRuntimeBuilder`] provides additional methods to set the
+//! used to initialize Julia and set custom parameters. The [`RuntimeBuilder`] lets you provide a
+//! custom system image and set several of Julia's command line options, e.g. the active project
+//! and the optimization level. [`AsyncRuntimeBuilder`] provides additional methods to set the
 //! number of threads available to Julia among others.
+//!
+//! Options are validated when the runtime is started, an invalid option results in a
+//! [`RuntimeError::InvalidOption`] or [`IOError::NotFound`] and Julia is not initialized.
+//!
+//! [`RuntimeError::InvalidOption`]: crate::error::RuntimeError::InvalidOption
+//! [`IOError::NotFound`]: crate::error::IOError::NotFound
 
 #[cfg(feature = "async-rt")]
 use std::num::NonZeroUsize;
-use std::path::{Path, PathBuf};
+use std::{
+    ffi::CString,
+    path::{Path, PathBuf},
+};
+
+use jl_sys::jl_options;
 
-#[cfg(feature = "async-rt")]
 use jlrs_macros::julia_version;
 
 #[cfg(feature = "sync-rt")]
 use super::sync_rt::PendingJulia;
-#[cfg(any(feature = "sync-rt", feature = "async-rt"))]
-use crate::error::JlrsResult;
-use crate::InstallJlrsCore;
+use crate::{
+    convert::into_jlrs_result::unrooted_exception_error,
+    data::managed::value::Value,
+    error::{IOError, JlrsResult, RuntimeError},
+    memory::target::unrooted::Unrooted,
+    InstallJlrsCore,
+};
+
+const JL_OPTIONS_CHECK_BOUNDS_DEFAULT: i8 = 0;
+const JL_OPTIONS_CHECK_BOUNDS_ON: i8 = 1;
+const JL_OPTIONS_CHECK_BOUNDS_OFF: i8 = 2;
+const JL_OPTIONS_STARTUPFILE_ON: i8 = 1;
+const JL_OPTIONS_STARTUPFILE_OFF: i8 = 2;
+const JL_OPTIONS_HANDLE_SIGNALS_ON: i8 = 1;
+const JL_OPTIONS_HANDLE_SIGNALS_OFF: i8 = 0;
+const JL_OPTIONS_USE_COMPILED_MODULES_YES: i8 = 1;
+const JL_OPTIONS_USE_COMPILED_MODULES_NO: i8 = 0;
+
+/// Bounds checking mode, equivalent to the `--check-bounds` command line option.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckBounds {
+    /// Respect `@inbounds` annotations.
+    Default,
+    /// Always check bounds, `--check-bounds=yes`.
+    On,
+    /// Never check bounds, `--check-bounds=no`.
+    Off,
+}
 
 /// Build a sync runtime.
 ///
-/// With this builder you can set a custom system image by calling [`RuntimeBuilder::image`],
-/// the builder can be upgraded to an [`AsyncRuntimeBuilder`] by calling
+/// With this builder you can set a custom system image by calling [`RuntimeBuilder::image`] and
+/// set several command line options that Julia would otherwise parse when it's started, the
+/// builder can be upgraded to an [`AsyncRuntimeBuilder`] by calling
 /// [`RuntimeBuilder::async_runtime`] and providing a backing runtime. To start the runtime you
 /// must call [`RuntimeBuilder::start`].
 pub struct RuntimeBuilder {
     pub(crate) image: Option<(PathBuf, PathBuf)>,
     pub(crate) install_jlrs_core: InstallJlrsCore,
+    pub(crate) options: JuliaOptions,
+}
+
+// Command line options that are stored in jl_options before Julia is initialized.
+#[derive(Default)]
+pub(crate) struct JuliaOptions {
+    project: Option<PathBuf>,
+    depot_path: Option<Vec<PathBuf>>,
+    opt_level: Option<u8>,
+    check_bounds: Option<CheckBounds>,
+    compiled_modules: Option<bool>,
+    startup_file: Option<bool>,
+    handle_signals: Option<bool>,
+    #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
+    heap_size_hint: Option<u64>,
+}
+
+#[cfg_attr(not(any(feature = "sync-rt", feature = "async-rt")), allow(dead_code))]
+impl JuliaOptions {
+    pub(crate) fn validate(&self) -> JlrsResult<()> {
+        if let Some(ref project) = self.project {
+            // Project specs that start with `@`, e.g. `@.`, are resolved by Julia.
+            let is_spec = matches!(project.to_str(), Some(p) if p.starts_with('@'));
+            if !is_spec && !project.exists() {
+                Err(IOError::NotFound {
+                    path: project.to_string_lossy().to_string(),
+                })?;
+            }
+
+            if project.to_str().is_none() {
+                Err(RuntimeError::InvalidOption {
+                    option: "project",
+                    reason: "path is not valid UTF-8".into(),
+                })?;
+            }
+
+            if project.to_string_lossy().contains('\0') {
+                Err(RuntimeError::InvalidOption {
+                    option: "project",
+                    reason: "path contains a null byte".into(),
+                })?;
+            }
+        }
+
+        if let Some(ref depot_path) = self.depot_path {
+            if depot_path.is_empty() {
+                Err(RuntimeError::InvalidOption {
+                    option: "depot_path",
+                    reason: "no depots have been provided".into(),
+                })?;
+            }
+
+            for depot in depot_path {
+                match depot.to_str() {
+                    None => Err(RuntimeError::InvalidOption {
+                        option: "depot_path",
+                        reason: "path is not valid UTF-8".into(),
+                    })?,
+                    Some(depot) if depot.contains('\0') => Err(RuntimeError::InvalidOption {
+                        option: "depot_path",
+                        reason: "path contains a null byte".into(),
+                    })?,
+                    _ => (),
+                }
+            }
+        }
+
+        if let Some(opt_level) = self.opt_level {
+            if opt_level > 3 {
+                Err(RuntimeError::InvalidOption {
+                    option: "optimization_level",
+                    reason: format!("expected a level between 0 and 3, got {}", opt_level),
+                })?;
+            }
+        }
+
+        #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
+        if let Some(heap_size_hint) = self.heap_size_hint {
+            if heap_size_hint == 0 {
+                Err(RuntimeError::InvalidOption {
+                    option: "heap_size_hint",
+                    reason: "the hint must be larger than 0 bytes".into(),
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Safety: must be called before Julia is initialized, from the thread that initializes it.
+    // The options must have been validated.
+    pub(crate) unsafe fn apply(&self) {
+        if let Some(ref project) = self.project {
+            let project = CString::new(project.to_string_lossy().as_bytes()).unwrap();
+            // The string must live as long as Julia does.
+            jl_options.project = project.into_raw();
+        }
+
+        if let Some(opt_level) = self.opt_level {
+            jl_options.opt_level = opt_level as i8;
+        }
+
+        if let Some(check_bounds) = self.check_bounds {
+            jl_options.check_bounds = match check_bounds {
+                CheckBounds::Default => JL_OPTIONS_CHECK_BOUNDS_DEFAULT,
+                CheckBounds::On => JL_OPTIONS_CHECK_BOUNDS_ON,
+                CheckBounds::Off => JL_OPTIONS_CHECK_BOUNDS_OFF,
+            };
+        }
+
+        if let Some(compiled_modules) = self.compiled_modules {
+            jl_options.use_compiled_modules = if compiled_modules {
+                JL_OPTIONS_USE_COMPILED_MODULES_YES
+            } else {
+                JL_OPTIONS_USE_COMPILED_MODULES_NO
+            };
+        }
+
+        if let Some(startup_file) = self.startup_file {
+            jl_options.startupfile = if startup_file {
+                JL_OPTIONS_STARTUPFILE_ON
+            } else {
+                JL_OPTIONS_STARTUPFILE_OFF
+            };
+        }
+
+        if let Some(handle_signals) = self.handle_signals {
+            jl_options.handle_signals = if handle_signals {
+                JL_OPTIONS_HANDLE_SIGNALS_ON
+            } else {
+                JL_OPTIONS_HANDLE_SIGNALS_OFF
+            };
+        }
+
+        #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
+        if let Some(heap_size_hint) = self.heap_size_hint {
+            jl_options.heap_size_hint = heap_size_hint;
+        }
+    }
+
+    // Replace the contents of `Base.DEPOT_PATH`, which Julia populates from the
+    // `JULIA_DEPOT_PATH` environment variable during initialization. Modifying the environment
+    // of the process isn't thread-safe, so the depots are set after Julia has been initialized.
+    //
+    // Safety: must be called after Julia has been initialized, before JlrsCore is loaded, from
+    // the thread that initialized it.
+    pub(crate) unsafe fn apply_depot_path(&self) -> JlrsResult<()> {
+        let depot_path = match self.depot_path {
+            Some(ref depot_path) => depot_path,
+            None => return Ok(()),
+        };
+
+        let mut cmd = String::from("empty!(Base.DEPOT_PATH); append!(Base.DEPOT_PATH, String[");
+        for depot in depot_path {
+            // The paths have been validated.
+            cmd.push('"');
+            for c in depot.to_str().unwrap().chars() {
+                if matches!(c, '\\' | '"' | '$') {
+                    cmd.push('\\');
+                }
+                cmd.push(c);
+            }
+            cmd.push_str("\", ");
+        }
+        cmd.push_str("])");
+
+        Value::eval_string(Unrooted::new(), cmd).map_err(unrooted_exception_error)?;
+        Ok(())
+    }
 }
 
 cfg_if::cfg_if! {
@@ -140,6 +346,62 @@ cfg_if::cfg_if! {
                 self
             }
 
+            /// Set the active project, see [`RuntimeBuilder::project`].
+            pub fn project<P>(mut self, project: P) -> Self
+            where
+                P: AsRef<Path>,
+            {
+                self.builder = self.builder.project(project);
+                self
+            }
+
+            /// Set the depot path, see [`RuntimeBuilder::depot_path`].
+            pub fn depot_path<I, P>(mut self, depots: I) -> Self
+            where
+                I: IntoIterator<Item = P>,
+                P: AsRef<Path>,
+            {
+                self.builder = self.builder.depot_path(depots);
+                self
+            }
+
+            /// Set the optimization level, see [`RuntimeBuilder::optimization_level`].
+            pub fn optimization_level(mut self, level: u8) -> Self {
+                self.builder = self.builder.optimization_level(level);
+                self
+            }
+
+            /// Set the bounds checking mode, see [`RuntimeBuilder::check_bounds`].
+            pub fn check_bounds(mut self, check_bounds: CheckBounds) -> Self {
+                self.builder = self.builder.check_bounds(check_bounds);
+                self
+            }
+
+            /// Enable or disable using compiled modules, see [`RuntimeBuilder::compiled_modules`].
+            pub fn compiled_modules(mut self, enable: bool) -> Self {
+                self.builder = self.builder.compiled_modules(enable);
+                self
+            }
+
+            /// Enable or disable loading the startup file, see [`RuntimeBuilder::startup_file`].
+            pub fn startup_file(mut self, enable: bool) -> Self {
+                self.builder = self.builder.startup_file(enable);
+                self
+            }
+
+            /// Enable or disable Julia's signal handlers, see [`RuntimeBuilder::handle_signals`].
+            pub fn handle_signals(mut self, enable: bool) -> Self {
+                self.builder = self.builder.handle_signals(enable);
+                self
+            }
+
+            #[julia_version(since = "1.9")]
+            /// Set the heap size hint, see [`RuntimeBuilder::heap_size_hint`].
+            pub fn heap_size_hint(mut self, bytes: u64) -> Self {
+                self.builder = self.builder.heap_size_hint(bytes);
+                self
+            }
+
             /// Initialize Julia on another thread.
             ///
             /// You must set the maximum number of concurrent tasks with the `N` const generic.
@@ -165,16 +427,36 @@ cfg_if::cfg_if! {
                     false
                 }
             }
+
+            // The number of threads used by the runtime, including the main runtime thread.
+            pub(crate) fn n_runtime_threads(&self) -> usize {
+                #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
+                {
+                    self.n_workers + 1
+                }
+
+                #[cfg(not(any(feature = "julia-1-10", feature = "julia-1-9")))]
+                {
+                    1
+                }
+            }
         }
     }
 }
 
+impl Default for RuntimeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RuntimeBuilder {
     /// Create a new `RuntimeBuilder`.
     pub fn new() -> Self {
         RuntimeBuilder {
             image: None,
             install_jlrs_core: InstallJlrsCore::Default,
+            options: JuliaOptions::default(),
         }
     }
 
@@ -266,4 +548,79 @@ impl RuntimeBuilder {
         self.install_jlrs_core = install;
         self
     }
+
+    /// Set the active project, equivalent to the `--project` command line option.
+    ///
+    /// `project` must be the path to an existing project directory or `Project.toml` file, or a
+    /// project spec that starts with `@`, e.g. `@.` to search for a project in the current
+    /// directory and its parents.
+    pub fn project<P>(mut self, project: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.options.project = Some(project.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the depot path, which is otherwise read from the `JULIA_DEPOT_PATH` environment
+    /// variable.
+    ///
+    /// The first depot is used to install packages and store compiled modules. The environment
+    /// of the process isn't modified, `Base.DEPOT_PATH` is replaced with these depots right
+    /// after Julia has been initialized.
+    pub fn depot_path<I, P>(mut self, depots: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let depots = depots
+            .into_iter()
+            .map(|depot| depot.as_ref().to_path_buf())
+            .collect();
+        self.options.depot_path = Some(depots);
+        self
+    }
+
+    /// Set the optimization level, equivalent to the `-O` command line option.
+    ///
+    /// The level must be between 0 and 3, the default level is 2.
+    pub fn optimization_level(mut self, level: u8) -> Self {
+        self.options.opt_level = Some(level);
+        self
+    }
+
+    /// Set the bounds checking mode, equivalent to the `--check-bounds` command line option.
+    pub fn check_bounds(mut self, check_bounds: CheckBounds) -> Self {
+        self.options.check_bounds = Some(check_bounds);
+        self
+    }
+
+    /// Enable or disable using and caching compiled modules, equivalent to the
+    /// `--compiled-modules` command line option.
+    pub fn compiled_modules(mut self, enable: bool) -> Self {
+        self.options.compiled_modules = Some(enable);
+        self
+    }
+
+    /// Enable or disable loading `~/.julia/config/startup.jl`, equivalent to the
+    /// `--startup-file` command line option.
+    pub fn startup_file(mut self, enable: bool) -> Self {
+        self.options.startup_file = Some(enable);
+        self
+    }
+
+    /// Enable or disable Julia's default signal handlers, equivalent to the `--handle-signals`
+    /// command line option.
+    pub fn handle_signals(mut self, enable: bool) -> Self {
+        self.options.handle_signals = Some(enable);
+        self
+    }
+
+    #[julia_version(since = "1.9")]
+    /// Set a hint in bytes for the size of the heap, equivalent to the `--heap-size-hint`
+    /// command line option. The GC collects more aggressively when the hint is approached.
+    pub fn heap_size_hint(mut self, bytes: u64) -> Self {
+        self.options.heap_size_hint = Some(bytes);
+        self
+    }
 }
@@ -0,0 +1,453 @@
+//! Manage project environments and packages.
+//!
+//! Julia's package manager, `Pkg`, can be used from Rust with a [`PackageManager`] when the sync
+//! runtime is used, and with an [`AsyncPackageManager`] when the async runtime is used. They
+//! wrap `Pkg.activate`, `Pkg.instantiate`, `Pkg.develop` and `Pkg.status`, the packages in the
+//! active environment are reported as [`PackageInfo`]s. Exceptions thrown by `Pkg` are returned
+//! as [`JlrsError::Exception`].
+//!
+//! Only local packages are needed to activate, develop and instantiate an environment, with
+//! [`PackageManager::offline`] `Pkg` won't try to access the network:
+//!
+//! ```no_run
+//! # use jlrs::prelude::*;
+//! # use jlrs::util::test::JULIA;
+//! # fn main() {
+//! # JULIA.with(|j| {
+//! # let mut julia = j.borrow_mut();
+//! # let mut frame = StackFrame::new();
+//! # let mut julia = julia.instance(&mut frame);
+//! let mut pkg = julia.package_manager();
+//! pkg.offline(true).unwrap();
+//! pkg.activate("path/to/MyProject").unwrap();
+//!
+//! unsafe {
+//!     pkg.develop("path/to/MyLocalPackage").unwrap();
+//!     pkg.instantiate().unwrap();
+//! }
+//!
+//! for package in pkg.status().unwrap() {
+//!     println!("{} {:?}", package.name, package.version);
+//! }
+//! # });
+//! # }
+//! ```
+//!
+//! [`JlrsError::Exception`]: crate::error::JlrsError::Exception
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    call::{Call, ProvideKeywords},
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::{
+        layout::nothing::Nothing,
+        managed::{module::Module, string::JuliaString, value::Value, Managed},
+    },
+    error::{IOError, JlrsResult},
+    memory::target::frame::GcFrame,
+};
+
+/// The version of a package, prerelease and build annotations are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackageVersion {
+    /// Major version.
+    pub major: u32,
+    /// Minor version.
+    pub minor: u32,
+    /// Patch version.
+    pub patch: u32,
+}
+
+impl fmt::Display for PackageVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Information about a package in the active environment, as returned by `Pkg.dependencies`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackageInfo {
+    /// The name of the package.
+    pub name: String,
+    /// The UUID of the package.
+    pub uuid: String,
+    /// The installed version, `None` for packages without a version like standard libraries.
+    pub version: Option<PackageVersion>,
+    /// `true` if the package is a direct dependency of the active project.
+    pub is_direct_dep: bool,
+    /// `true` if the package has been added with `Pkg.develop` or by path.
+    pub is_tracking_path: bool,
+    /// The directory that contains the source code of the package.
+    pub source: PathBuf,
+}
+
+/// The package manager of the sync runtime.
+///
+/// Created with [`Julia::package_manager`].
+///
+/// [`Julia::package_manager`]: crate::runtime::sync_rt::Julia::package_manager
+#[cfg(feature = "sync-rt")]
+pub struct PackageManager<'borrow, 'context> {
+    julia: &'borrow mut crate::runtime::sync_rt::Julia<'context>,
+}
+
+#[cfg(feature = "sync-rt")]
+impl<'borrow, 'context> PackageManager<'borrow, 'context> {
+    pub(crate) fn new(julia: &'borrow mut crate::runtime::sync_rt::Julia<'context>) -> Self {
+        PackageManager { julia }
+    }
+
+    /// Activate the project at `path` by calling `Pkg.activate(path)`. If the directory doesn't
+    /// contain a project yet, it's created when the first package is added.
+    pub fn activate<P: AsRef<Path>>(&mut self, path: P) -> JlrsResult<()> {
+        self.julia
+            .scope(|mut frame| unsafe { activate(&mut frame, path.as_ref()) })
+    }
+
+    /// Install all dependencies of the active project by calling `Pkg.instantiate()`.
+    ///
+    /// Safety: installing a package can run its build script.
+    pub unsafe fn instantiate(&mut self) -> JlrsResult<()> {
+        self.julia.scope(|mut frame| instantiate(&mut frame))
+    }
+
+    /// Add the local package at `path` to the active project by calling
+    /// `Pkg.develop(path=path)`.
+    ///
+    /// Safety: developing a package can run its build script.
+    pub unsafe fn develop<P: AsRef<Path>>(&mut self, path: P) -> JlrsResult<()> {
+        self.julia
+            .scope(|mut frame| develop(&mut frame, path.as_ref()))
+    }
+
+    /// Enable or disable offline mode by calling `Pkg.offline(enable)`. In offline mode `Pkg`
+    /// doesn't access the network, only installed and local packages can be used.
+    pub fn offline(&mut self, enable: bool) -> JlrsResult<()> {
+        self.julia
+            .scope(|mut frame| unsafe { offline(&mut frame, enable) })
+    }
+
+    /// Returns the direct dependencies of the active project, i.e. the packages listed by
+    /// `Pkg.status()`.
+    pub fn status(&mut self) -> JlrsResult<Vec<PackageInfo>> {
+        self.julia
+            .scope(|mut frame| unsafe { dependencies(&mut frame, true) })
+    }
+
+    /// Returns all packages in the active environment, including indirect dependencies.
+    pub fn dependencies(&mut self) -> JlrsResult<Vec<PackageInfo>> {
+        self.julia
+            .scope(|mut frame| unsafe { dependencies(&mut frame, false) })
+    }
+
+    /// Returns the path to the `Project.toml` file of the active project, if there is one.
+    pub fn active_project(&mut self) -> JlrsResult<Option<PathBuf>> {
+        self.julia
+            .scope(|mut frame| unsafe { active_project(&mut frame) })
+    }
+
+    /// Returns the names of all packages that have been loaded.
+    pub fn loaded_packages(&mut self) -> JlrsResult<Vec<String>> {
+        self.julia
+            .scope(|mut frame| unsafe { loaded_packages(&mut frame) })
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "async-rt")] {
+        use crate::{
+            async_util::{affinity::DispatchMain, channel::OneshotSender},
+            runtime::async_rt::{dispatch::Dispatch, AsyncJulia, AsyncRuntime},
+        };
+
+        /// The package manager of the async runtime.
+        ///
+        /// Created with [`AsyncJulia::package_manager`]. Every operation is sent to the main
+        /// runtime thread as a blocking task, the result is sent back with the provided
+        /// `OneshotSender`.
+        pub struct AsyncPackageManager<'borrow, R: AsyncRuntime> {
+            julia: &'borrow AsyncJulia<R>,
+        }
+
+        impl<'borrow, R: AsyncRuntime> AsyncPackageManager<'borrow, R> {
+            pub(crate) fn new(julia: &'borrow AsyncJulia<R>) -> Self {
+                AsyncPackageManager { julia }
+            }
+
+            /// Activate the project at `path` by calling `Pkg.activate(path)`.
+            pub fn activate<P, O>(&self, path: P, res_sender: O) -> Dispatch<'borrow, DispatchMain>
+            where
+                P: AsRef<Path>,
+                O: OneshotSender<JlrsResult<()>>,
+            {
+                let path = path.as_ref().to_path_buf();
+                self.julia.blocking_task_with_affinity(
+                    move |mut frame| unsafe { activate(&mut frame, &path) },
+                    res_sender,
+                )
+            }
+
+            /// Install all dependencies of the active project by calling `Pkg.instantiate()`.
+            ///
+            /// Safety: installing a package can run its build script.
+            pub unsafe fn instantiate<O>(&self, res_sender: O) -> Dispatch<'borrow, DispatchMain>
+            where
+                O: OneshotSender<JlrsResult<()>>,
+            {
+                self.julia.blocking_task_with_affinity(
+                    |mut frame| instantiate(&mut frame),
+                    res_sender,
+                )
+            }
+
+            /// Add the local package at `path` to the active project by calling
+            /// `Pkg.develop(path=path)`.
+            ///
+            /// Safety: developing a package can run its build script.
+            pub unsafe fn develop<P, O>(&self, path: P, res_sender: O) -> Dispatch<'borrow, DispatchMain>
+            where
+                P: AsRef<Path>,
+                O: OneshotSender<JlrsResult<()>>,
+            {
+                let path = path.as_ref().to_path_buf();
+                self.julia.blocking_task_with_affinity(
+                    move |mut frame| develop(&mut frame, &path),
+                    res_sender,
+                )
+            }
+
+            /// Enable or disable offline mode by calling `Pkg.offline(enable)`.
+            pub fn offline<O>(&self, enable: bool, res_sender: O) -> Dispatch<'borrow, DispatchMain>
+            where
+                O: OneshotSender<JlrsResult<()>>,
+            {
+                self.julia.blocking_task_with_affinity(
+                    move |mut frame| unsafe { offline(&mut frame, enable) },
+                    res_sender,
+                )
+            }
+
+            /// Returns the direct dependencies of the active project, i.e. the packages listed
+            /// by `Pkg.status()`.
+            pub fn status<O>(&self, res_sender: O) -> Dispatch<'borrow, DispatchMain>
+            where
+                O: OneshotSender<JlrsResult<Vec<PackageInfo>>>,
+            {
+                self.julia.blocking_task_with_affinity(
+                    |mut frame| unsafe { dependencies(&mut frame, true) },
+                    res_sender,
+                )
+            }
+
+            /// Returns all packages in the active environment, including indirect dependencies.
+            pub fn dependencies<O>(&self, res_sender: O) -> Dispatch<'borrow, DispatchMain>
+            where
+                O: OneshotSender<JlrsResult<Vec<PackageInfo>>>,
+            {
+                self.julia.blocking_task_with_affinity(
+                    |mut frame| unsafe { dependencies(&mut frame, false) },
+                    res_sender,
+                )
+            }
+
+            /// Returns the path to the `Project.toml` file of the active project, if there is
+            /// one.
+            pub fn active_project<O>(&self, res_sender: O) -> Dispatch<'borrow, DispatchMain>
+            where
+                O: OneshotSender<JlrsResult<Option<PathBuf>>>,
+            {
+                self.julia.blocking_task_with_affinity(
+                    |mut frame| unsafe { active_project(&mut frame) },
+                    res_sender,
+                )
+            }
+
+            /// Returns the names of all packages that have been loaded.
+            pub fn loaded_packages<O>(&self, res_sender: O) -> Dispatch<'borrow, DispatchMain>
+            where
+                O: OneshotSender<JlrsResult<Vec<String>>>,
+            {
+                self.julia.blocking_task_with_affinity(
+                    |mut frame| unsafe { loaded_packages(&mut frame) },
+                    res_sender,
+                )
+            }
+        }
+    }
+}
+
+// Load Pkg if necessary and return the module.
+unsafe fn pkg_module<'target>(frame: &mut GcFrame<'target>) -> JlrsResult<Module<'target>> {
+    let main = Module::main(&*frame);
+    main.require(&mut *frame, "Pkg")
+        .into_jlrs_result()?
+        .cast::<Module>()
+}
+
+unsafe fn activate(frame: &mut GcFrame, path: &Path) -> JlrsResult<()> {
+    let pkg = pkg_module(frame)?;
+    let path = JuliaString::new(&mut *frame, path.to_string_lossy()).as_value();
+    pkg.function(&*frame, "activate")?
+        .as_managed()
+        .call1(&mut *frame, path)
+        .into_jlrs_result()?;
+
+    Ok(())
+}
+
+unsafe fn instantiate(frame: &mut GcFrame) -> JlrsResult<()> {
+    let pkg = pkg_module(frame)?;
+    pkg.function(&*frame, "instantiate")?
+        .as_managed()
+        .call0(&mut *frame)
+        .into_jlrs_result()?;
+
+    Ok(())
+}
+
+unsafe fn develop(frame: &mut GcFrame, path: &Path) -> JlrsResult<()> {
+    if !path.exists() {
+        Err(IOError::NotFound {
+            path: path.to_string_lossy().into(),
+        })?
+    }
+
+    let pkg = pkg_module(frame)?;
+    let path = JuliaString::new(&mut *frame, path.to_string_lossy()).as_value();
+    let kws = crate::named_tuple!(frame.as_extended_target(), "path" => path);
+    pkg.function(&*frame, "develop")?
+        .as_managed()
+        .provide_keywords(kws)?
+        .call0(&mut *frame)
+        .into_jlrs_result()?;
+
+    Ok(())
+}
+
+unsafe fn offline(frame: &mut GcFrame, enable: bool) -> JlrsResult<()> {
+    let pkg = pkg_module(frame)?;
+    let enable = Value::new(&mut *frame, enable);
+    pkg.function(&*frame, "offline")?
+        .as_managed()
+        .call1(&mut *frame, enable)
+        .into_jlrs_result()?;
+
+    Ok(())
+}
+
+unsafe fn dependencies(frame: &mut GcFrame, direct_only: bool) -> JlrsResult<Vec<PackageInfo>> {
+    let pkg = pkg_module(frame)?;
+    let base = Module::base(&*frame);
+    let string = base.function(&*frame, "string")?.as_managed();
+    let deps = pkg
+        .function(&*frame, "dependencies")?
+        .as_managed()
+        .call0(&mut *frame)
+        .into_jlrs_result()?;
+
+    // Every item is a Pair{UUID, PackageInfo}
+    let pairs = deps.iter(frame).collect::<JlrsResult<Vec<_>>>()?;
+    let mut packages = Vec::with_capacity(pairs.len());
+    for pair in pairs {
+        let info = pair.get_nth_field(&mut *frame, 1)?;
+
+        let is_direct_dep = info
+            .get_field(&mut *frame, "is_direct_dep")?
+            .unbox::<bool>()?
+            .as_bool();
+        if direct_only && !is_direct_dep {
+            continue;
+        }
+
+        let uuid = pair.get_nth_field(&mut *frame, 0)?;
+        let uuid = string
+            .call1(&mut *frame, uuid)
+            .into_jlrs_result()?
+            .cast::<JuliaString>()?
+            .as_str()?
+            .to_string();
+
+        let name = info
+            .get_field(&mut *frame, "name")?
+            .cast::<JuliaString>()?
+            .as_str()?
+            .to_string();
+
+        let version = info.get_field(&mut *frame, "version")?;
+        let version = if version.is::<Nothing>() {
+            None
+        } else {
+            Some(PackageVersion {
+                major: version.get_field(&mut *frame, "major")?.unbox::<u32>()?,
+                minor: version.get_field(&mut *frame, "minor")?.unbox::<u32>()?,
+                patch: version.get_field(&mut *frame, "patch")?.unbox::<u32>()?,
+            })
+        };
+
+        let is_tracking_path = info
+            .get_field(&mut *frame, "is_tracking_path")?
+            .unbox::<bool>()?
+            .as_bool();
+
+        let source = info
+            .get_field(&mut *frame, "source")?
+            .cast::<JuliaString>()?
+            .as_str()?
+            .into();
+
+        packages.push(PackageInfo {
+            name,
+            uuid,
+            version,
+            is_direct_dep,
+            is_tracking_path,
+            source,
+        });
+    }
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packages)
+}
+
+unsafe fn active_project(frame: &mut GcFrame) -> JlrsResult<Option<PathBuf>> {
+    let project = Module::base(&*frame)
+        .function(&*frame, "active_project")?
+        .as_managed()
+        .call0(&mut *frame)
+        .into_jlrs_result()?;
+
+    if project.is::<Nothing>() {
+        return Ok(None);
+    }
+
+    Ok(Some(project.cast::<JuliaString>()?.as_str()?.into()))
+}
+
+unsafe fn loaded_packages(frame: &mut GcFrame) -> JlrsResult<Vec<String>> {
+    let base = Module::base(&*frame);
+    let loaded_modules = base.global(&*frame, "loaded_modules")?.as_value();
+    let keys = base
+        .function(&*frame, "keys")?
+        .as_managed()
+        .call1(&mut *frame, loaded_modules)
+        .into_jlrs_result()?;
+
+    // Every key is a Base.PkgId
+    let pkg_ids = keys.iter(frame).collect::<JlrsResult<Vec<_>>>()?;
+    let mut names = Vec::with_capacity(pkg_ids.len());
+    for pkg_id in pkg_ids {
+        let name = pkg_id
+            .get_field(&mut *frame, "name")?
+            .cast::<JuliaString>()?
+            .as_str()?
+            .to_string();
+        names.push(name);
+    }
+
+    names.sort();
+    Ok(names)
+}
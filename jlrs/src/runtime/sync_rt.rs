@@ -18,7 +18,7 @@ use crate::{
         stack_frame::{PinnedFrame, StackFrame},
         target::frame::GcFrame,
     },
-    runtime::{builder::RuntimeBuilder, INIT},
+    runtime::{builder::RuntimeBuilder, package_manager::PackageManager, INIT},
     INSTALL_METHOD,
 };
 
@@ -31,10 +31,14 @@ pub struct PendingJulia {
 
 impl PendingJulia {
     pub(crate) unsafe fn init(builder: RuntimeBuilder) -> JlrsResult<Self> {
+        builder.options.validate()?;
+
         if jl_is_initialized() != 0 || INIT.swap(true, Ordering::Relaxed) {
             Err(RuntimeError::AlreadyInitialized)?;
         }
 
+        builder.options.apply();
+
         if let Some((julia_bindir, image_path)) = builder.image {
             let julia_bindir_str = julia_bindir.to_string_lossy().to_string();
             let image_path_str = image_path.to_string_lossy().to_string();
@@ -60,6 +64,7 @@ impl PendingJulia {
         }
 
         assert!(jl_is_initialized() != 0);
+        builder.options.apply_depot_path()?;
 
         let install_method = builder.install_jlrs_core.clone();
         INSTALL_METHOD.get_or_init(|| install_method);
@@ -106,7 +111,7 @@ pub struct Julia<'context> {
     stack: &'context Stack,
 }
 
-impl Julia<'_> {
+impl<'context> Julia<'context> {
     /// Enable or disable colored error messages originating from Julia. If this is enabled the
     /// error message in [`JlrsError::Exception`] can contain ANSI color codes. This feature is
     /// disabled by default.
@@ -170,6 +175,12 @@ impl Julia<'_> {
         })?
     }
 
+    /// Returns a [`PackageManager`] that can be used to manage the active project environment
+    /// and its packages.
+    pub fn package_manager(&mut self) -> PackageManager<'_, 'context> {
+        PackageManager::new(self)
+    }
+
     /// This method is a main entrypoint to interact with Julia. It takes a closure with one
     /// argument, a `GcFrame`, and can return arbitrary results.
     ///
@@ -252,6 +252,38 @@ mod tests {
         })
     }
 
+    fn access_array_element() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = vec![1.0f64, 2.0, 3.0, 4.0];
+                    let arr = Array::from_vec(frame.as_extended_target(), data, (2, 2))?
+                        .into_jlrs_result()?;
+
+                    let elem = arr
+                        .as_value()
+                        .field_accessor()
+                        .field((1, 0))?
+                        .access::<f64>()?;
+                    assert_eq!(elem, 2.0);
+
+                    let elem = arr
+                        .as_value()
+                        .field_accessor()
+                        .field((0, 1))?
+                        .access::<f64>()?;
+                    assert_eq!(elem, 3.0);
+
+                    assert!(arr.as_value().field_accessor().field((2, 0)).is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
     fn access_ua_array_field() {
         JULIA.with(|j| {
             let mut frame = StackFrame::new();
@@ -613,6 +645,7 @@ mod tests {
         access_raw_fields_ptr_and_non_bits_union();
         access_raw_fields_wrong_ty();
         access_array_field();
+        access_array_element();
         access_ua_array_field();
         access_raw_fields_nonexistent_name();
         access_nth_raw_fields_bits();
@@ -0,0 +1,197 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "jlrs-arrow"))]
+#[cfg(not(feature = "julia-1-6"))]
+mod tests {
+    use std::ffi::c_void;
+
+    use jlrs::{
+        convert::arrow::{
+            import_primitive, import_utf8, ArrowArray, ArrowExport, ArrowSchema, ToArrow,
+            ARROW_FLAG_NULLABLE,
+        },
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    struct Utf8Buffers {
+        _offsets: Vec<i32>,
+        _data: Vec<u8>,
+        _buffers: Box<[*const c_void; 3]>,
+    }
+
+    unsafe extern "C" fn release_utf8(array: *mut ArrowArray) {
+        std::mem::drop(Box::from_raw((*array).private_data.cast::<Utf8Buffers>()));
+        (*array).release = None;
+    }
+
+    unsafe extern "C" fn release_schema(schema: *mut ArrowSchema) {
+        (*schema).release = None;
+    }
+
+    fn utf8_array(strings: &[&str]) -> (ArrowArray, ArrowSchema) {
+        let mut offsets = vec![0i32];
+        let mut data = Vec::new();
+        for s in strings {
+            data.extend_from_slice(s.as_bytes());
+            offsets.push(data.len() as i32);
+        }
+
+        let mut buffers = Box::new([
+            std::ptr::null(),
+            offsets.as_ptr().cast(),
+            data.as_ptr().cast(),
+        ]);
+        let buffers_ptr = buffers.as_mut_ptr();
+        let private_data = Box::new(Utf8Buffers {
+            _offsets: offsets,
+            _data: data,
+            _buffers: buffers,
+        });
+
+        let mut array = ArrowArray::empty();
+        array.length = strings.len() as i64;
+        array.n_buffers = 3;
+        array.buffers = buffers_ptr;
+        array.release = Some(release_utf8);
+        array.private_data = Box::into_raw(private_data).cast();
+
+        let mut schema = ArrowSchema::empty();
+        schema.format = "u\0".as_ptr().cast();
+        schema.release = Some(release_schema);
+
+        (array, schema)
+    }
+
+    fn export_bits_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = TypedArray::<f64>::from_vec(
+                        frame.as_extended_target(),
+                        vec![1.0, 2.0, 3.0],
+                        3,
+                    )?
+                    .into_jlrs_result()?;
+
+                    let data = arr.bits_data()?;
+                    let export = data.to_arrow()?;
+
+                    assert_eq!(export.array().length, 3);
+                    assert_eq!(export.array().null_count, 0);
+                    assert_eq!(export.array().n_buffers, 2);
+                    assert!(!export.array().is_released());
+                    assert_eq!(export.schema().format(), Some("g"));
+                    assert_eq!(export.schema().flags, 0);
+
+                    let buffer = *export.array().buffers.add(1);
+                    assert_eq!(buffer, data.as_slice().as_ptr().cast());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn export_union_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(
+                        &mut frame,
+                        "Union{Missing, Float64}[1.0, missing, 3.0, missing]",
+                    )
+                    .into_jlrs_result()?
+                    .cast::<Array>()?;
+
+                    let data = arr.union_data()?;
+                    let export: ArrowExport<f64> = data.to_arrow()?;
+
+                    assert_eq!(export.array().length, 4);
+                    assert_eq!(export.array().null_count, 2);
+                    assert_eq!(export.schema().format(), Some("g"));
+                    assert_eq!(export.schema().flags, ARROW_FLAG_NULLABLE);
+
+                    let validity = *export.array().buffers.add(0);
+                    assert_eq!(*validity.cast::<u8>(), 0b0101);
+
+                    let values = *export.array().buffers.add(1);
+                    assert_eq!(*values.cast::<f64>(), 1.0);
+                    assert_eq!(*values.cast::<f64>().add(2), 3.0);
+
+                    let res: JlrsResult<ArrowExport<i32>> = data.to_arrow();
+                    assert!(res.is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn import_exported_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr =
+                        TypedArray::<i64>::from_vec(frame.as_extended_target(), vec![1, 2, 3], 3)?
+                            .into_jlrs_result()?;
+
+                    let data = arr.bits_data()?;
+                    let (array, schema) = data.to_arrow()?.into_raw();
+
+                    let imported =
+                        import_primitive::<i64, _>(frame.as_extended_target(), array, &schema)?;
+                    assert_eq!(imported.bits_data()?.as_slice(), &[1, 2, 3]);
+
+                    let (array, schema) = data.to_arrow()?.into_raw();
+                    let res =
+                        import_primitive::<f64, _>(frame.as_extended_target(), array, &schema);
+                    assert!(res.is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn import_string_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let (array, schema) = utf8_array(&["foo", "", "barbaz"]);
+                    let imported = import_utf8(frame.as_extended_target(), array, &schema)?;
+
+                    let offsets = imported.get_field(&mut frame, "offsets")?;
+                    let offsets = offsets.cast::<TypedArray<i32>>()?;
+                    assert_eq!(offsets.bits_data()?.as_slice(), &[0, 3, 3, 9]);
+
+                    let data = imported.get_field(&mut frame, "data")?;
+                    let data = data.cast::<TypedArray<u8>>()?;
+                    assert_eq!(data.bits_data()?.as_slice(), b"foobarbaz");
+
+                    let released = ArrowArray::empty();
+                    let res = import_utf8(frame.as_extended_target(), released, &schema);
+                    assert!(res.is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn arrow_tests() {
+        export_bits_array();
+        export_union_array();
+        import_exported_array();
+        import_string_array();
+    }
+}
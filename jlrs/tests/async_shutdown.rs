@@ -0,0 +1,98 @@
+#[cfg(feature = "async-std-rt")]
+#[cfg(test)]
+mod util;
+
+#[cfg(feature = "async-std-rt")]
+#[cfg(test)]
+mod tests {
+    use std::{
+        num::NonZeroUsize,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use jlrs::{
+        error::{Cancelled, JlrsError, RuntimeError},
+        prelude::*,
+        runtime::async_rt::shutdown::{ShutdownPolicy, ShutdownSummary},
+    };
+
+    use super::util::async_tasks::*;
+
+    #[test]
+    fn shutdown_cancels_tasks_and_calls_hooks() {
+        let (julia, handle) = unsafe {
+            RuntimeBuilder::new()
+                .async_runtime::<AsyncStd>()
+                .channel_capacity(NonZeroUsize::new_unchecked(32))
+                .start::<1>()
+                .expect("Could not init Julia")
+        };
+
+        let flushed = Arc::new(AtomicBool::new(false));
+        {
+            let flushed = flushed.clone();
+            julia.on_shutdown(move |mut frame| unsafe {
+                Value::eval_string(&mut frame, "flush(stdout)").into_jlrs_result()?;
+                flushed.store(true, Ordering::Relaxed);
+                Ok(())
+            });
+        }
+
+        julia.on_shutdown(|mut frame| unsafe {
+            Value::eval_string(&mut frame, "error(\"failing hook\")").into_jlrs_result()?;
+            Ok(())
+        });
+
+        // The first task occupies the only available stack, the second one stays queued.
+        let (running_sender, running_receiver) = crossbeam_channel::bounded(1);
+        julia
+            .task(SleepingTask { seconds: 30.0 }, running_sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        let (queued_sender, queued_receiver) = crossbeam_channel::bounded(1);
+        julia
+            .task(SleepingTask { seconds: 30.0 }, queued_sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        std::thread::sleep(Duration::from_millis(100));
+        let shutdown = julia.shutdown(ShutdownPolicy::Cancel);
+
+        let (late_sender, late_receiver) = crossbeam_channel::bounded(1);
+        julia
+            .task(SleepingTask { seconds: 0.0 }, late_sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        match *late_receiver.recv().unwrap().unwrap_err() {
+            JlrsError::RuntimeError(RuntimeError::ChannelClosed) => (),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let summary = async_std::task::block_on(shutdown).unwrap();
+        assert_eq!(
+            summary,
+            ShutdownSummary {
+                dropped: 1,
+                cancelled: 1,
+                failed_hooks: 1,
+            }
+        );
+        assert!(flushed.load(Ordering::Relaxed));
+
+        for receiver in [running_receiver, queued_receiver] {
+            match *receiver.recv().unwrap().unwrap_err() {
+                JlrsError::Cancelled(Cancelled::Shutdown) => (),
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+
+        std::mem::drop(julia);
+        handle.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(feature = "async-std-rt")]
+#[cfg(test)]
+mod util;
+
+#[cfg(feature = "async-std-rt")]
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroUsize, time::Duration};
+
+    use jlrs::{
+        prelude::*,
+        runtime::async_rt::shutdown::{ShutdownPolicy, ShutdownSummary},
+    };
+
+    use super::util::async_tasks::*;
+
+    #[test]
+    fn drain_completes_queued_tasks() {
+        let (julia, handle) = unsafe {
+            RuntimeBuilder::new()
+                .async_runtime::<AsyncStd>()
+                .channel_capacity(NonZeroUsize::new_unchecked(32))
+                .start::<1>()
+                .expect("Could not init Julia")
+        };
+
+        // The first task occupies the only available stack, the second one stays queued.
+        let (running_sender, running_receiver) = crossbeam_channel::bounded(1);
+        julia
+            .task(SleepingTask { seconds: 0.5 }, running_sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        let (queued_sender, queued_receiver) = crossbeam_channel::bounded(1);
+        julia
+            .task(SleepingTask { seconds: 0.5 }, queued_sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        std::thread::sleep(Duration::from_millis(100));
+        let shutdown = julia.shutdown(ShutdownPolicy::Drain);
+
+        let summary = async_std::task::block_on(shutdown).unwrap();
+        assert_eq!(summary, ShutdownSummary::default());
+
+        running_receiver.recv().unwrap().unwrap();
+        queued_receiver.recv().unwrap().unwrap();
+
+        std::mem::drop(julia);
+        handle.join().unwrap().unwrap();
+    }
+}
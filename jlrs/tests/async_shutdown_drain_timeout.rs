@@ -0,0 +1,70 @@
+#[cfg(feature = "async-std-rt")]
+#[cfg(test)]
+mod util;
+
+#[cfg(feature = "async-std-rt")]
+#[cfg(test)]
+mod tests {
+    use std::{
+        num::NonZeroUsize,
+        time::{Duration, Instant},
+    };
+
+    use jlrs::{
+        error::{Cancelled, JlrsError},
+        prelude::*,
+        runtime::async_rt::shutdown::{ShutdownPolicy, ShutdownSummary},
+    };
+
+    use super::util::async_tasks::*;
+
+    #[test]
+    fn drain_with_timeout_cancels_remaining_tasks() {
+        let (julia, handle) = unsafe {
+            RuntimeBuilder::new()
+                .async_runtime::<AsyncStd>()
+                .channel_capacity(NonZeroUsize::new_unchecked(32))
+                .start::<1>()
+                .expect("Could not init Julia")
+        };
+
+        // The first task occupies the only available stack, the second one stays queued.
+        let (running_sender, running_receiver) = crossbeam_channel::bounded(1);
+        julia
+            .task(SleepingTask { seconds: 30.0 }, running_sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        let (queued_sender, queued_receiver) = crossbeam_channel::bounded(1);
+        julia
+            .task(SleepingTask { seconds: 30.0 }, queued_sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        std::thread::sleep(Duration::from_millis(100));
+        let timeout = Duration::from_millis(500);
+        let start = Instant::now();
+        let shutdown = julia.shutdown(ShutdownPolicy::DrainWithTimeout(timeout));
+
+        let summary = async_std::task::block_on(shutdown).unwrap();
+        assert!(start.elapsed() >= timeout);
+        assert_eq!(
+            summary,
+            ShutdownSummary {
+                dropped: 1,
+                cancelled: 1,
+                failed_hooks: 0,
+            }
+        );
+
+        for receiver in [running_receiver, queued_receiver] {
+            match *receiver.recv().unwrap().unwrap_err() {
+                JlrsError::Cancelled(Cancelled::Shutdown) => (),
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+
+        std::mem::drop(julia);
+        handle.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+#[cfg(feature = "async-std-rt")]
+#[cfg(test)]
+mod tests {
+    use jlrs::{
+        error::{JlrsError, RuntimeError},
+        prelude::*,
+        runtime::async_rt::shutdown::ShutdownPolicy,
+    };
+
+    #[test]
+    fn shutdown_resolves_if_runtime_fails_to_start() {
+        let (julia, handle) = unsafe {
+            RuntimeBuilder::new()
+                .async_runtime::<AsyncStd>()
+                .image("/jlrs/does/not/exist", "/jlrs/does/not/exist/sys.so")
+                .start::<1>()
+                .expect("Could not start the runtime thread")
+        };
+
+        // The runtime thread exits with an error before Julia has been initialized.
+        let before_exit = julia.shutdown(ShutdownPolicy::Drain);
+        assert!(handle.join().unwrap().is_err());
+        let after_exit = julia.shutdown(ShutdownPolicy::Drain);
+
+        for shutdown in [before_exit, after_exit] {
+            match *async_std::task::block_on(shutdown).unwrap_err() {
+                JlrsError::RuntimeError(RuntimeError::ChannelClosed) => (),
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+    }
+}
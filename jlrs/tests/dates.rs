@@ -0,0 +1,148 @@
+mod util;
+
+#[cfg(test)]
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        data::layout::dates::{Date, DateTime, Millisecond, Nanosecond, Time},
+        memory::target::frame::GcFrame,
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    fn eval<'target>(
+        frame: &mut GcFrame<'target>,
+        cmd: &str,
+    ) -> JlrsResult<Value<'target, 'static>> {
+        unsafe { Value::eval_string(frame, cmd).into_jlrs_result() }
+    }
+
+    fn load_dates() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    eval(&mut frame, "using Dates")?;
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn unbox_dates() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let dt = eval(&mut frame, "DateTime(1970, 1, 1, 0, 0, 1)")?;
+                    assert!(dt.is::<DateTime>());
+                    assert!(!dt.is::<Date>());
+                    assert_eq!(dt.unbox::<DateTime>()?.unix_millis(), 1000);
+
+                    let d = eval(&mut frame, "Date(1, 1, 1)")?;
+                    assert_eq!(d.unbox::<Date>()?, Date::new(1));
+
+                    let t = eval(&mut frame, "Time(0, 0, 1)")?;
+                    assert_eq!(t.unbox::<Time>()?, Time::new(1_000_000_000));
+
+                    let p = eval(&mut frame, "Millisecond(5)")?;
+                    assert_eq!(p.unbox::<Millisecond>()?, Millisecond::new(5));
+                    assert!(p.unbox::<Nanosecond>().is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn create_dates() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let dt = Value::new(&mut frame, DateTime::from_unix_millis(0));
+                    let expected = eval(&mut frame, "Dates.UNIXEPOCH")?;
+                    let func = Module::base(&frame).function(&mut frame, "==")?;
+                    let res = func
+                        .call2(&mut frame, dt, expected)
+                        .into_jlrs_result()?
+                        .unbox::<bool>()?;
+                    assert!(res.as_bool());
+
+                    let data = vec![Nanosecond::new(1), Nanosecond::new(2)];
+                    let arr = TypedArray::from_vec(frame.as_extended_target(), data, 2)?
+                        .into_jlrs_result()?;
+                    let func = Module::base(&frame).function(&mut frame, "sum")?;
+                    let res = func
+                        .call1(&mut frame, arr.as_value())
+                        .into_jlrs_result()?
+                        .unbox::<Nanosecond>()?;
+                    assert_eq!(res, Nanosecond::new(3));
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[cfg(feature = "chrono")]
+    fn chrono_conversions() {
+        use std::convert::TryFrom;
+
+        use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+        let ndt = NaiveDate::from_ymd_opt(2023, 5, 17)
+            .unwrap()
+            .and_hms_milli_opt(12, 30, 15, 250)
+            .unwrap();
+        let dt = DateTime::from(ndt);
+        assert_eq!(NaiveDateTime::try_from(dt).unwrap(), ndt);
+        assert_eq!(dt.unix_millis(), ndt.and_utc().timestamp_millis());
+
+        let nd = NaiveDate::from_ymd_opt(1, 1, 1).unwrap();
+        assert_eq!(Date::from(nd), Date::new(1));
+        assert_eq!(NaiveDate::try_from(Date::new(1)).unwrap(), nd);
+        assert!(NaiveDate::try_from(Date::new(i64::MAX)).is_err());
+
+        let nt = NaiveTime::from_hms_nano_opt(1, 2, 3, 4).unwrap();
+        assert_eq!(NaiveTime::try_from(Time::from(nt)).unwrap(), nt);
+        assert!(NaiveTime::try_from(Time::new(-1)).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    fn time_conversions() {
+        use std::convert::TryFrom;
+
+        use time::{Date as TimeDate, Month, OffsetDateTime, Time as TimeTime};
+
+        let odt = OffsetDateTime::from_unix_timestamp(1_000_000).unwrap();
+        let dt = DateTime::from(odt);
+        assert_eq!(dt.unix_millis(), 1_000_000_000);
+        assert_eq!(OffsetDateTime::try_from(dt).unwrap(), odt);
+
+        let td = TimeDate::from_calendar_date(1, Month::January, 1).unwrap();
+        assert_eq!(Date::from(td), Date::new(1));
+        assert_eq!(TimeDate::try_from(Date::new(1)).unwrap(), td);
+        assert!(TimeDate::try_from(Date::new(i64::MAX)).is_err());
+
+        let tt = TimeTime::from_hms_nano(23, 59, 59, 999_999_999).unwrap();
+        assert_eq!(TimeTime::try_from(Time::from(tt)).unwrap(), tt);
+        assert!(TimeTime::try_from(Time::new(86_400_000_000_000)).is_err());
+    }
+
+    #[test]
+    fn dates_tests() {
+        load_dates();
+        unbox_dates();
+        create_dates();
+        #[cfg(feature = "chrono")]
+        chrono_conversions();
+        #[cfg(feature = "time")]
+        time_conversions();
+    }
+}
@@ -3,7 +3,10 @@ mod util;
 
 #[cfg(all(test, feature = "jlrs-derive", feature = "sync-rt"))]
 mod tests {
-    use jlrs::prelude::*;
+    use jlrs::{
+        data::layout::valid_layout::{ValidField, ValidLayout},
+        prelude::*,
+    };
 
     use super::util::{new_derive_impls::*, JULIA_DERIVE};
     fn derive_bits_type_bool() {
@@ -28,6 +31,58 @@ mod tests {
         })
     }
 
+    fn derive_enum() {
+        JULIA_DERIVE.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::new(&mut frame, DeriveFruit::Orange);
+                    assert!(v.is::<DeriveFruit>());
+                    assert!(v.is::<AlignedFruit>());
+                    assert!(!v.is::<WideFruit>());
+                    assert_eq!(v.unbox::<DeriveFruit>()?, Ok(DeriveFruit::Orange));
+
+                    // Only the layout is checked, the value is checked when it's unboxed.
+                    assert!(v.is::<MismatchedFruit>());
+                    assert_eq!(v.unbox::<MismatchedFruit>()?, Ok(MismatchedFruit::Orange));
+
+                    let kiwi = Value::eval_string(&mut frame, "derive_kiwi").into_jlrs_result()?;
+                    assert!(kiwi.is::<DeriveFruit>());
+                    assert_eq!(kiwi.unbox::<DeriveFruit>()?, Ok(DeriveFruit::Kiwi));
+                    assert_eq!(kiwi.unbox::<AlignedFruit>()?, Ok(AlignedFruit::Kiwi));
+                    assert_eq!(kiwi.unbox::<MismatchedFruit>()?, Err(4));
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn derive_int128_field() {
+        JULIA_DERIVE.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let int128 = Value::new(&mut frame, 1i128).datatype();
+                    let same_align = int128.align() == Some(std::mem::align_of::<i128>() as u16);
+
+                    let ty = Value::eval_string(&mut frame, "WithInt128").into_jlrs_result()?;
+                    assert_eq!(WithInt128::valid_layout(ty), same_align);
+                    assert_eq!(i128::valid_field(int128.as_value()), same_align);
+                    assert!(i128::valid_layout(int128.as_value()));
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
     /*
        fn derive_generic_tu() {
            JULIA_DERIVE.with(|j| {
@@ -1100,6 +1155,8 @@ mod tests {
     #[test]
     fn derive_tests() {
         derive_bits_type_bool();
+        derive_enum();
+        derive_int128_field();
         //derive_generic_tu();
         // derive_bits_type_char();
         // derive_bits_type_uint8();
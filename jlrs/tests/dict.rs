@@ -0,0 +1,215 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        data::managed::{
+            dict::{Dict, TypedDict},
+            value::typed::TypedValue,
+        },
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    fn create_dict() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let key_type = DataType::string_type(&frame).as_value();
+                    let value_type = DataType::float64_type(&frame).as_value();
+                    let dict = Dict::new(&mut frame, key_type, value_type).into_jlrs_result()?;
+
+                    assert!(dict.as_value().is::<Dict>());
+                    assert!(dict.as_value().is::<TypedDict<JuliaString, f64>>());
+                    assert!(!dict.as_value().is::<TypedDict<JuliaString, f32>>());
+                    unsafe {
+                        assert_eq!(dict.len()?, 0);
+                        assert!(dict.is_empty()?);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn create_invalid_dict() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let key_type = Value::new(&mut frame, 1usize);
+                    let value_type = DataType::float64_type(&frame).as_value();
+                    assert!(Dict::new(&mut frame, key_type, value_type).is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn insert_get_remove() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let dict = Value::eval_string(&mut frame, "Dict{String, Int}()")
+                        .into_jlrs_result()?
+                        .cast::<Dict>()?;
+
+                    let key = JuliaString::new(&mut frame, "foo").as_value();
+                    let value = Value::new(&mut frame, 3isize);
+                    dict.insert(&mut frame, key, value).into_jlrs_result()?;
+                    assert_eq!(dict.len()?, 1);
+                    assert!(dict.contains_key(key)?);
+
+                    let found = dict.get(&mut frame, key)?.unwrap();
+                    assert_eq!(found.unbox::<isize>()?, 3);
+
+                    let other = JuliaString::new(&mut frame, "bar").as_value();
+                    assert!(dict.get(&mut frame, other)?.is_none());
+
+                    let removed = dict.remove(&mut frame, key)?.unwrap();
+                    assert_eq!(removed.unbox::<isize>()?, 3);
+                    assert!(dict.remove(&mut frame, key)?.is_none());
+                    assert!(dict.is_empty()?);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn insert_wrong_type() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let dict = Value::eval_string(&mut frame, "Dict{String, Int}()")
+                        .into_jlrs_result()?
+                        .cast::<Dict>()?;
+
+                    let key = JuliaString::new(&mut frame, "foo").as_value();
+                    let value = Value::new(&mut frame, 1.5f64);
+                    assert!(dict.insert(&mut frame, key, value).is_err());
+                    assert!(dict.is_empty()?);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn iterate_dict() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let dict = Value::eval_string(&mut frame, "Dict(1 => 2.0, 3 => 4.0, 5 => 6.0)")
+                        .into_jlrs_result()?
+                        .cast::<Dict>()?;
+
+                    let mut pairs = dict
+                        .iter(&mut frame)
+                        .map(|pair| {
+                            let (key, value) = pair?;
+                            Ok((
+                                key.as_value().unbox::<i64>()?,
+                                value.as_value().unbox::<f64>()?,
+                            ))
+                        })
+                        .collect::<JlrsResult<Vec<_>>>()?;
+
+                    pairs.sort_by_key(|pair| pair.0);
+                    assert_eq!(pairs, vec![(1, 2.0), (3, 4.0), (5, 6.0)]);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn iterate_large_dict() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let dict = Value::eval_string(&mut frame, "Dict(i => i for i in 1:100_000)")
+                        .into_jlrs_result()?
+                        .cast::<Dict>()?;
+
+                    let sum = dict
+                        .iter(&frame)
+                        .map(|pair| {
+                            let (key, value) = pair?;
+                            Ok(key.as_value().unbox::<i64>()? + value.as_value().unbox::<i64>()?)
+                        })
+                        .sum::<JlrsResult<i64>>()?;
+
+                    assert_eq!(sum, 100_000 * 100_001);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn typed_dict() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let dict = TypedDict::<i32, f32>::new(frame.as_extended_target())?
+                        .into_jlrs_result()?;
+                    assert!(dict.as_value().is::<TypedDict<i32, f32>>());
+
+                    for i in 0..4 {
+                        let key = TypedValue::new(&mut frame, i);
+                        let value = TypedValue::new(&mut frame, i as f32 / 2.0);
+                        dict.insert(&mut frame, key, value).into_jlrs_result()?;
+                    }
+                    assert_eq!(dict.len()?, 4);
+
+                    let key = TypedValue::new(&mut frame, 3i32);
+                    let value = dict.get(&mut frame, key)?.unwrap();
+                    assert_eq!(value.unbox::<f32>()?, 1.5);
+
+                    let sum = dict
+                        .iter(&mut frame)
+                        .map(|pair| {
+                            let (key, value) = pair?;
+                            Ok(key.as_value().unbox::<i32>()? as f32
+                                + value.as_value().unbox::<f32>()?)
+                        })
+                        .sum::<JlrsResult<f32>>()?;
+                    assert_eq!(sum, 9.0);
+
+                    let untyped = dict.as_dict();
+                    assert!(untyped.try_as_typed::<i32, f32>().is_ok());
+                    assert!(untyped.try_as_typed::<i64, f32>().is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn dict_tests() {
+        create_dict();
+        create_invalid_dict();
+        insert_get_remove();
+        insert_wrong_type();
+        iterate_dict();
+        iterate_large_dict();
+        typed_dict();
+    }
+}
@@ -0,0 +1,313 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        error::{
+            exception::{BacktraceFrame, ExceptionKind},
+            Exception, JlrsError,
+        },
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    fn decode(cmd: &str) -> ExceptionKind {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            let kind = jlrs
+                .instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let err = Value::eval_string(&mut frame, cmd)
+                        .into_decoded_jlrs_result()
+                        .unwrap_err();
+
+                    match *err {
+                        JlrsError::Exception(e) => Ok(e.kind().unwrap().clone()),
+                        _ => panic!("expected an exception"),
+                    }
+                })
+                .unwrap();
+            kind
+        })
+    }
+
+    fn decode_bounds_error() {
+        match decode("[1, 2, 3][4]") {
+            ExceptionKind::BoundsError {
+                value_type,
+                indices,
+            } => {
+                assert_eq!(value_type.as_deref(), Some("Vector{Int64}"));
+                assert_eq!(indices, Some(vec![4]));
+            }
+            k => panic!("expected a BoundsError, got {:?}", k),
+        }
+
+        match decode("zeros(2, 2)[3, 1]") {
+            ExceptionKind::BoundsError { indices, .. } => assert_eq!(indices, Some(vec![3, 1])),
+            k => panic!("expected a BoundsError, got {:?}", k),
+        }
+    }
+
+    fn decode_method_error() {
+        match decode("sin(\"foo\", 1)") {
+            ExceptionKind::MethodError {
+                function,
+                arg_types,
+            } => {
+                assert_eq!(function, "sin");
+                assert_eq!(arg_types, vec!["String", "Int64"]);
+            }
+            k => panic!("expected a MethodError, got {:?}", k),
+        }
+    }
+
+    fn decode_other_kinds() {
+        assert_eq!(
+            decode("throw(ArgumentError(\"foo\"))"),
+            ExceptionKind::ArgumentError { msg: "foo".into() }
+        );
+        assert_eq!(
+            decode("convert(UInt8, 256)"),
+            ExceptionKind::InexactError {
+                function: "convert".into(),
+                target_type: "UInt8".into(),
+                value: "256".into(),
+            }
+        );
+        match decode("sqrt(-1.0)") {
+            ExceptionKind::DomainError { value, .. } => assert_eq!(value, "-1.0"),
+            k => panic!("expected a DomainError, got {:?}", k),
+        }
+        assert_eq!(
+            decode("Dict(1 => 2)[3]"),
+            ExceptionKind::KeyError { key: "3".into() }
+        );
+        assert_eq!(
+            decode("throw(InterruptException())"),
+            ExceptionKind::InterruptException
+        );
+        assert_eq!(
+            decode("error(\"bar\")"),
+            ExceptionKind::ErrorException { msg: "bar".into() }
+        );
+        assert_eq!(
+            decode("this_variable_does_not_exist"),
+            ExceptionKind::UndefVarError {
+                var: "this_variable_does_not_exist".into()
+            }
+        );
+        assert_eq!(
+            decode("throw(DivideError())"),
+            ExceptionKind::Other {
+                exception_type: "DivideError".into()
+            }
+        );
+    }
+
+    fn into_jlrs_result_is_not_decoded() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let err = Value::eval_string(&mut frame, "[1, 2, 3][4]")
+                        .into_jlrs_result()
+                        .unwrap_err();
+
+                    match *err {
+                        JlrsError::Exception(e) => {
+                            assert!(e.kind().is_none());
+                            assert!(e.get_message().contains("BoundsError"));
+                        }
+                        _ => panic!("expected an exception"),
+                    }
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn decode_backtrace() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let res = Value::eval_string(
+                        &mut frame,
+                        "try\n error(\"baz\")\n catch e\n (e, catch_backtrace())\n end",
+                    )
+                    .into_jlrs_result()?;
+
+                    let exception = res.get_nth_field(&mut frame, 0)?;
+                    let backtrace = res.get_nth_field(&mut frame, 1)?;
+                    let decoded =
+                        Exception::decode_with_backtrace(&mut frame, exception, backtrace)?;
+
+                    assert_eq!(
+                        decoded.kind(),
+                        Some(&ExceptionKind::ErrorException { msg: "baz".into() })
+                    );
+                    assert!(decoded
+                        .backtrace()
+                        .unwrap()
+                        .frames()
+                        .iter()
+                        .any(|f: &BacktraceFrame| f.function == "error"));
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn call_with_backtrace() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let func = Value::eval_string(
+                        &mut frame,
+                        "function throws_with_backtrace(x)\n x > 0 && throw(ArgumentError(\"positive\"))\n x\n end",
+                    )
+                    .into_jlrs_result()?;
+
+                    let arg = Value::new(&mut frame, -1isize);
+                    let res = func.call_with_backtrace(&mut frame, [arg])?;
+                    assert_eq!(res.unbox::<isize>()?, -1);
+
+                    let arg = Value::new(&mut frame, 1isize);
+                    let err = func.call_with_backtrace(&mut frame, [arg]).unwrap_err();
+                    match *err {
+                        JlrsError::Exception(e) => {
+                            assert_eq!(
+                                e.kind(),
+                                Some(&ExceptionKind::ArgumentError {
+                                    msg: "positive".into()
+                                })
+                            );
+
+                            let backtrace = e.backtrace().unwrap();
+                            assert!(backtrace
+                                .frames()
+                                .iter()
+                                .any(|f| f.function == "throws_with_backtrace"));
+                            assert!(backtrace.to_string().contains("Stacktrace:"));
+                        }
+                        _ => panic!("expected an exception"),
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn call_with_backtrace_keywords() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let func = Value::eval_string(
+                        &mut frame,
+                        "function throws_kw(x; limit=0)\n x > limit && throw(ArgumentError(\"too large\"))\n x\n end",
+                    )
+                    .into_jlrs_result()?;
+
+                    let arg = Value::new(&mut frame, 1isize);
+                    let err = func.call1(&mut frame, arg).into_jlrs_result().unwrap_err();
+                    match *err {
+                        JlrsError::Exception(e) => assert!(e.backtrace().is_none()),
+                        _ => panic!("expected an exception"),
+                    }
+
+                    let limit = Value::new(&mut frame, 2isize);
+                    let kws = named_tuple!(frame.as_extended_target(), "limit" => limit);
+                    let res = func
+                        .provide_keywords(kws)?
+                        .call_with_backtrace(&mut frame, [arg])?;
+                    assert_eq!(res.unbox::<isize>()?, 1);
+
+                    let limit = Value::new(&mut frame, 0isize);
+                    let kws = named_tuple!(frame.as_extended_target(), "limit" => limit);
+                    let err = func
+                        .provide_keywords(kws)?
+                        .call_with_backtrace(&mut frame, [arg])
+                        .unwrap_err();
+                    match *err {
+                        JlrsError::Exception(e) => assert!(e
+                            .backtrace()
+                            .unwrap()
+                            .frames()
+                            .iter()
+                            .any(|f| f.function == "throws_kw")),
+                        _ => panic!("expected an exception"),
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn reused_exception_backtraces() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    // Both functions throw the same exception object.
+                    let first = Value::eval_string(
+                        &mut frame,
+                        "struct ReusedError <: Exception end\n throws_reused_first() = throw(ReusedError())",
+                    )
+                    .into_jlrs_result()?;
+                    let second =
+                        Value::eval_string(&mut frame, "throws_reused_second() = throw(ReusedError())")
+                            .into_jlrs_result()?;
+
+                    let first_err = first.call_with_backtrace(&mut frame, []).unwrap_err();
+                    let second_err = second.call_with_backtrace(&mut frame, []).unwrap_err();
+
+                    let has_frame = |err: &JlrsError, name: &str| match err {
+                        JlrsError::Exception(e) => e
+                            .backtrace()
+                            .unwrap()
+                            .frames()
+                            .iter()
+                            .any(|f| f.function == name),
+                        _ => panic!("expected an exception"),
+                    };
+
+                    assert!(has_frame(&first_err, "throws_reused_first"));
+                    assert!(!has_frame(&first_err, "throws_reused_second"));
+                    assert!(has_frame(&second_err, "throws_reused_second"));
+                    assert!(!has_frame(&second_err, "throws_reused_first"));
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn exception_kind_tests() {
+        decode_bounds_error();
+        decode_method_error();
+        decode_other_kinds();
+        into_jlrs_result_is_not_decoded();
+        decode_backtrace();
+        call_with_backtrace();
+        call_with_backtrace_keywords();
+        reused_exception_backtraces();
+    }
+}
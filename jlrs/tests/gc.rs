@@ -1,6 +1,8 @@
 mod util;
 #[cfg(feature = "sync-rt")]
 mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
     use jlrs::{
         memory::gc::{Gc, GcCollection},
         prelude::*,
@@ -65,10 +67,78 @@ mod tests {
         })
     }
 
+    fn gc_statistics() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut jlrs = jlrs.instance(&mut frame);
+
+            jlrs.scope(|mut frame| {
+                let before = frame.gc_stats()?;
+
+                frame.scope(|mut frame| {
+                    for i in 0..1000 {
+                        Value::new(&mut frame, i as u64);
+                    }
+                    Ok(())
+                })?;
+
+                frame.gc_collect(GcCollection::Full);
+                let after = frame.gc_stats()?;
+
+                assert!(after.total_allocated > before.total_allocated);
+                assert!(after.full_collections > before.full_collections);
+                assert!(after.total_pause >= before.total_pause);
+                assert!(after.live_bytes > 0);
+
+                Ok(())
+            })
+            .unwrap();
+        })
+    }
+
+    fn gc_pressure_callback() {
+        static CALLED: AtomicBool = AtomicBool::new(false);
+
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let jlrs = jlrs.instance(&mut frame);
+
+            // Panics in the callback are caught, so only set the flag if the checks succeed.
+            jlrs.gc_set_pressure_callback(0, |pressure| {
+                if pressure.live_bytes >= pressure.threshold && pressure.full {
+                    CALLED.store(true, Ordering::Relaxed);
+                }
+            });
+            jlrs.gc_collect(GcCollection::Full);
+            assert!(CALLED.load(Ordering::Relaxed));
+
+            jlrs.gc_remove_pressure_callback();
+            CALLED.store(false, Ordering::Relaxed);
+            jlrs.gc_collect(GcCollection::Full);
+            assert!(!CALLED.load(Ordering::Relaxed));
+        })
+    }
+
+    #[cfg(feature = "julia-1-10")]
+    fn set_max_memory() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let jlrs = jlrs.instance(&mut frame);
+            jlrs.gc_set_max_memory(u64::MAX);
+        })
+    }
+
     #[test]
     fn gc_tests() {
         disable_enable_gc();
         collect_garbage();
         insert_safepoint();
+        gc_statistics();
+        gc_pressure_callback();
+        #[cfg(feature = "julia-1-10")]
+        set_max_memory();
     }
 }
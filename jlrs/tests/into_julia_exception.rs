@@ -0,0 +1,113 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        convert::into_julia_exception::{IntoJuliaException, JlrsException},
+        error::{exception::ExceptionKind, Exception, JlrsError},
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    fn convert<E: IntoJuliaException>(error: E) -> ExceptionKind {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            let kind = jlrs
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let exception = error.into_julia_exception(&mut frame);
+                    Ok(Exception::decode(exception).kind().unwrap().clone())
+                })
+                .unwrap();
+
+            kind
+        })
+    }
+
+    fn argument_error() {
+        assert_eq!(
+            convert(JlrsException::argument_error("bad argument")),
+            ExceptionKind::ArgumentError {
+                msg: "bad argument".into()
+            }
+        );
+    }
+
+    fn error_exception() {
+        assert_eq!(
+            convert(JlrsException::error("failed")),
+            ExceptionKind::ErrorException {
+                msg: "failed".into()
+            }
+        );
+    }
+
+    fn domain_error() {
+        assert_eq!(
+            convert(JlrsException::domain_error(-1.0f64, "negative")),
+            ExceptionKind::DomainError {
+                value: "-1.0".into(),
+                msg: "negative".into()
+            }
+        );
+    }
+
+    fn bounds_error() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let array = Array::new::<f64, _, _>(frame.as_extended_target(), (2, 2))
+                        .into_jlrs_result()?;
+                    let exception = JlrsException::bounds_error(array.as_value(), &[3, 1])
+                        .into_julia_exception(&mut frame);
+
+                    assert_eq!(
+                        Exception::decode(exception).kind(),
+                        Some(&ExceptionKind::BoundsError {
+                            value_type: Some("Matrix{Float64}".into()),
+                            indices: Some(vec![3, 1]),
+                        })
+                    );
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn custom_exception() {
+        let exception = JlrsException::new(|frame| unsafe {
+            Value::eval_string(frame, "KeyError(:missing)").into_jlrs_result()
+        });
+
+        assert_eq!(
+            convert(exception),
+            ExceptionKind::KeyError {
+                key: ":missing".into()
+            }
+        );
+    }
+
+    fn jlrs_error() {
+        match convert(JlrsError::exception("error")) {
+            ExceptionKind::Other { exception_type } => {
+                assert!(exception_type.ends_with("JlrsError"))
+            }
+            _ => panic!("expected a JlrsError"),
+        }
+    }
+
+    #[test]
+    fn into_julia_exception_tests() {
+        argument_error();
+        error_exception();
+        domain_error();
+        bounds_error();
+        custom_exception();
+        jlrs_error();
+    }
+}
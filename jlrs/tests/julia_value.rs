@@ -0,0 +1,220 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use std::collections::HashMap;
+
+    use jlrs::{
+        convert::{from_julia_value::FromJuliaValue, into_julia_value::IntoJuliaValue},
+        error::JlrsError,
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    fn convert_string() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let s = String::from("foo");
+                    let v = s.clone().into_julia_value(frame.as_extended_target())?;
+                    assert!(v.is::<JuliaString>());
+
+                    let t = String::from_julia_value(&mut frame, v)?;
+                    assert_eq!(s, t);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_option() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let v = Some(3u16).into_julia_value(frame.as_extended_target())?;
+                    assert!(v.is::<u16>());
+                    assert_eq!(Option::<u16>::from_julia_value(&mut frame, v)?, Some(3));
+
+                    let v = None::<u16>.into_julia_value(frame.as_extended_target())?;
+                    assert!(v.is::<Nothing>());
+                    assert_eq!(Option::<u16>::from_julia_value(&mut frame, v)?, None);
+
+                    let ty = Option::<u16>::julia_type(frame.as_extended_target())?;
+                    let expected =
+                        unsafe { Value::eval_string(&mut frame, "Union{Nothing, UInt16}") }
+                            .into_jlrs_result()?;
+                    assert!(ty.egal(expected));
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_vec() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = vec![Some(String::from("foo")), None, Some(String::from("bar"))];
+                    let v = data.clone().into_julia_value(frame.as_extended_target())?;
+
+                    let expected =
+                        unsafe { Value::eval_string(&mut frame, "Vector{Union{Nothing, String}}") }
+                            .into_jlrs_result()?;
+                    assert!(v.datatype().as_value().egal(expected));
+
+                    let converted = Vec::<Option<String>>::from_julia_value(&mut frame, v)?;
+                    assert_eq!(data, converted);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_bits_vec() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = vec![1.0f64, 2.0, 3.0];
+                    let v = data.clone().into_julia_value(frame.as_extended_target())?;
+
+                    let expected = unsafe { Value::eval_string(&mut frame, "Vector{Float64}") }
+                        .into_jlrs_result()?;
+                    assert!(v.datatype().as_value().egal(expected));
+
+                    let converted = Vec::<f64>::from_julia_value(&mut frame, v)?;
+                    assert_eq!(data, converted);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_invalid_char() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(&mut frame, "reinterpret(Char, 0xd800)")
+                        .into_jlrs_result()?;
+                    let err = char::from_julia_value(&mut frame, v).unwrap_err();
+                    assert!(matches!(*err, JlrsError::TypeError(_)));
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_matrix_to_vec() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(&mut frame, "[1 3; 2 4]").into_jlrs_result()?;
+                    let converted = Vec::<i64>::from_julia_value(&mut frame, v)?;
+                    assert_eq!(converted, vec![1, 2, 3, 4]);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_hash_map() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let mut map = HashMap::new();
+                    map.insert(String::from("a"), vec![1.0f64, 2.0]);
+                    map.insert(String::from("b"), vec![]);
+
+                    let v = map.clone().into_julia_value(frame.as_extended_target())?;
+                    let expected =
+                        unsafe { Value::eval_string(&mut frame, "Dict{String, Vector{Float64}}") }
+                            .into_jlrs_result()?;
+                    assert!(v.datatype().as_value().egal(expected));
+
+                    let converted = HashMap::<String, Vec<f64>>::from_julia_value(&mut frame, v)?;
+                    assert_eq!(map, converted);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_tuple() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = (String::from("foo"), 1i32, vec![true, false]);
+                    let v = data.clone().into_julia_value(frame.as_extended_target())?;
+                    assert!(v.is::<Tuple>());
+                    assert_eq!(v.n_fields(), 3);
+
+                    let converted = <(String, i32, Vec<bool>)>::from_julia_value(&mut frame, v)?;
+                    assert_eq!(data, converted);
+
+                    assert!(<(String, i32)>::from_julia_value(&mut frame, v).is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn call_with_converted_args() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arg = vec![String::from("foo"), String::from("bar")]
+                        .into_julia_value(frame.as_extended_target())?;
+                    let sep = ", ".into_julia_value(frame.as_extended_target())?;
+
+                    let res = Module::base(&frame)
+                        .function(&frame, "join")?
+                        .as_managed()
+                        .call2(&mut frame, arg, sep)
+                        .into_jlrs_result()?;
+
+                    assert_eq!(String::from_julia_value(&mut frame, res)?, "foo, bar");
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn julia_value_tests() {
+        convert_string();
+        convert_option();
+        convert_vec();
+        convert_bits_vec();
+        convert_invalid_char();
+        convert_matrix_to_vec();
+        convert_hash_map();
+        convert_tuple();
+        call_with_converted_args();
+    }
+}
@@ -4,10 +4,12 @@ mod util;
 #[cfg(all(feature = "sync-rt", feature = "jlrs-ndarray"))]
 mod tests {
     use jlrs::{
-        convert::ndarray::{NdArrayView, NdArrayViewMut},
+        convert::ndarray::{IntoJuliaArray, NdArrayView, NdArrayViewMut},
         data::managed::array::{Array, TypedArray},
-        memory::stack_frame::StackFrame,
+        memory::{stack_frame::StackFrame, target::frame::GcFrame},
+        prelude::*,
     };
+    use ndarray::{s, Array as NdArray, ShapeBuilder};
 
     use super::util::JULIA;
 
@@ -149,6 +151,177 @@ mod tests {
         });
     }
 
+    // Call `getindex` with one-based indices.
+    fn get_index(frame: &mut GcFrame, value: Value, i: isize, j: isize) -> JlrsResult<i64> {
+        frame.scope(|mut frame| unsafe {
+            let i = Value::new(&mut frame, i);
+            let j = Value::new(&mut frame, j);
+            Module::base(&frame)
+                .function(&frame, "getindex")?
+                .as_managed()
+                .call3(&mut frame, value, i, j)
+                .into_jlrs_result()?
+                .unbox::<i64>()
+        })
+    }
+
+    fn convert_column_major() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let array =
+                        NdArray::from_shape_vec((2, 3).f(), vec![1i64, 2, 3, 4, 5, 6]).unwrap();
+                    let ptr = array.as_ptr();
+
+                    let converted = array.into_typed_array(frame.as_extended_target())?;
+                    assert_eq!(converted.dimensions().as_slice(), &[2, 3]);
+
+                    let data = converted.bits_data()?;
+                    assert_eq!(data.as_slice(), &[1, 2, 3, 4, 5, 6]);
+                    assert_eq!(data.as_slice().as_ptr(), ptr);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_row_major() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let array = NdArray::from_shape_vec((2, 3), vec![1i64, 2, 3, 4, 5, 6]).unwrap();
+
+                    let wrapped = array.clone().into_julia_array(frame.as_extended_target())?;
+                    assert_eq!(wrapped.datatype().name(), "PermutedDimsArray");
+                    for i in 0..2 {
+                        for j in 0..3 {
+                            let v = get_index(&mut frame, wrapped, i + 1, j + 1)?;
+                            assert_eq!(v, array[[i as usize, j as usize]]);
+                        }
+                    }
+
+                    let copied = array.into_typed_array(frame.as_extended_target())?;
+                    assert_eq!(copied.dimensions().as_slice(), &[2, 3]);
+                    assert_eq!(copied.bits_data()?.as_slice(), &[1, 4, 2, 5, 3, 6]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_strided_view() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let mut array = NdArray::from_shape_fn((4, 6), |(i, j)| (10 * i + j) as i64);
+                    let expected = array.slice(s![..;2, 1..;2]).to_owned();
+
+                    let view = array.slice_mut(s![..;2, 1..;2]);
+                    let wrapped = view.into_julia_array(frame.as_extended_target())?;
+                    for i in 0..2 {
+                        for j in 0..3 {
+                            let v = get_index(&mut frame, wrapped, i + 1, j + 1)?;
+                            assert_eq!(v, expected[[i as usize, j as usize]]);
+                        }
+                    }
+
+                    let sliced = array.slice_move(s![..;2, 1..;2]);
+                    let wrapped = sliced.into_julia_array(frame.as_extended_target())?;
+                    assert_eq!(wrapped.datatype().name(), "PermutedDimsArray");
+                    for i in 0..2 {
+                        for j in 0..3 {
+                            let v = get_index(&mut frame, wrapped, i + 1, j + 1)?;
+                            assert_eq!(v, expected[[i as usize, j as usize]]);
+                        }
+                    }
+
+                    let mut reversed = expected.clone();
+                    reversed.invert_axis(ndarray::Axis(1));
+                    let wrapped = reversed
+                        .clone()
+                        .into_julia_array(frame.as_extended_target())?;
+                    for i in 0..2 {
+                        for j in 0..3 {
+                            let v = get_index(&mut frame, wrapped, i + 1, j + 1)?;
+                            assert_eq!(v, reversed[[i as usize, j as usize]]);
+                        }
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_disjoint_views() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let mut array = NdArray::from_shape_fn((4, 6), |(i, j)| (10 * i + j) as i64);
+                    let expected_even = array.slice(s![.., ..;2]).to_owned();
+                    let expected_odd = array.slice(s![.., 1..;2]).to_owned();
+
+                    // The views are interleaved, neither owns the elements between its own.
+                    let (even, odd) = array.multi_slice_mut((s![.., ..;2], s![.., 1..;2]));
+                    let even = even.into_julia_array(frame.as_extended_target())?;
+                    let odd = odd.into_julia_array(frame.as_extended_target())?;
+
+                    for (wrapped, expected) in [(even, &expected_even), (odd, &expected_odd)] {
+                        assert_eq!(wrapped.datatype().name(), "Array");
+                        for i in 0..4 {
+                            for j in 0..3 {
+                                let v = get_index(&mut frame, wrapped, i + 1, j + 1)?;
+                                assert_eq!(v, expected[[i as usize, j as usize]]);
+                            }
+                        }
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_ranked() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let array = NdArray::<f32, _>::zeros((2, 3).f());
+                    array
+                        .clone()
+                        .into_typed_ranked_array::<_, 2>(frame.as_extended_target())?;
+                    assert!(array
+                        .into_typed_ranked_array::<_, 3>(frame.as_extended_target())
+                        .is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
     #[test]
     fn ndarray_tests() {
         bits_array_view();
@@ -156,5 +329,10 @@ mod tests {
         inline_array_view();
         copied_array_view();
         copied_array_view_mut();
+        convert_column_major();
+        convert_row_major();
+        convert_strided_view();
+        convert_disjoint_views();
+        convert_ranked();
     }
 }
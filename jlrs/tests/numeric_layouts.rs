@@ -0,0 +1,163 @@
+mod util;
+
+#[cfg(test)]
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        data::layout::{complex::Complex, rational::Rational},
+        memory::target::frame::GcFrame,
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    fn eval<'target>(
+        frame: &mut GcFrame<'target>,
+        cmd: &str,
+    ) -> JlrsResult<Value<'target, 'static>> {
+        unsafe { Value::eval_string(frame, cmd).into_jlrs_result() }
+    }
+
+    fn complex_values() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let z = Value::new(&mut frame, Complex::new(1.0f64, -2.0));
+                    assert!(z.is::<Complex<f64>>());
+                    assert!(!z.is::<Complex<f32>>());
+                    assert_eq!(z.datatype().name(), "Complex");
+
+                    let func = Module::base(&frame).function(&mut frame, "conj")?;
+                    let res = func
+                        .call1(&mut frame, z)
+                        .into_jlrs_result()?
+                        .unbox::<Complex<f64>>()?;
+                    assert_eq!(res, Complex::new(1.0, 2.0));
+
+                    let z = eval(&mut frame, "ComplexF32(3, 4)")?;
+                    assert_eq!(z.unbox::<Complex<f32>>()?, Complex::new(3.0, 4.0));
+                    assert!(z.unbox::<Complex<f64>>().is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn complex_arrays() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let data = vec![Complex::new(1.0f64, 1.0), Complex::new(2.0, -3.0)];
+                    let arr = TypedArray::from_vec(frame.as_extended_target(), data, 2)?
+                        .into_jlrs_result()?;
+
+                    let func = Module::base(&frame).function(&mut frame, "sum")?;
+                    let res = func
+                        .call1(&mut frame, arr.as_value())
+                        .into_jlrs_result()?
+                        .unbox::<Complex<f64>>()?;
+                    assert_eq!(res, Complex::new(3.0, -2.0));
+
+                    let arr = eval(&mut frame, "ComplexF64[1im, 2, 3 + 3im]")?.cast::<Array>()?;
+                    let data = arr.bits_data::<Complex<f64>>()?;
+                    assert_eq!(data[2], Complex::new(3.0, 3.0));
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn int128_values() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let x = Value::new(&mut frame, i128::MAX);
+                    assert!(x.is::<i128>());
+                    assert!(!x.is::<u128>());
+                    assert_eq!(x.unbox::<i128>()?, i128::MAX);
+
+                    let x = eval(&mut frame, "typemax(UInt128) - UInt128(1)")?;
+                    assert_eq!(x.unbox::<u128>()?, u128::MAX - 1);
+
+                    let arr = eval(&mut frame, "Int128[-1, 1 << 100]")?.cast::<Array>()?;
+                    let data = arr.bits_data::<i128>()?;
+                    assert_eq!(data.as_slice(), &[-1, 1 << 100]);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn rational_values() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let x = Value::new(&mut frame, Rational::new_raw(1i64, 3));
+                    assert!(x.is::<Rational<i64>>());
+
+                    let func = Module::base(&frame).function(&mut frame, "+")?;
+                    let res = func
+                        .call2(&mut frame, x, x)
+                        .into_jlrs_result()?
+                        .unbox::<Rational<i64>>()?;
+                    assert_eq!(res, Rational::new_raw(2, 3));
+
+                    let x = eval(&mut frame, "Int32(3) // Int32(6)")?;
+                    assert_eq!(x.unbox::<Rational<i32>>()?, Rational::new_raw(1, 2));
+                    assert!(x.unbox::<Complex<i32>>().is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[cfg(all(feature = "num-complex", feature = "num-rational"))]
+    fn num_interop() {
+        use num_complex::Complex64;
+        use num_rational::Ratio;
+
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let z = Value::new(&mut frame, Complex64::new(0.5, 1.5));
+                    assert!(z.is::<Complex<f64>>());
+                    assert_eq!(z.unbox::<Complex64>()?, Complex64::new(0.5, 1.5));
+                    assert_eq!(
+                        Complex64::from(z.unbox::<Complex<f64>>()?),
+                        Complex64::new(0.5, 1.5)
+                    );
+
+                    let x = Value::new(&mut frame, Rational::from(Ratio::new(4i64, 6)));
+                    let x = x.unbox::<Rational<i64>>()?;
+                    assert_eq!(Ratio::from(x), Ratio::new(2, 3));
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn numeric_layout_tests() {
+        complex_values();
+        complex_arrays();
+        int128_values();
+        rational_values();
+        #[cfg(all(feature = "num-complex", feature = "num-rational"))]
+        num_interop();
+    }
+}
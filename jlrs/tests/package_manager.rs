@@ -0,0 +1,64 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::prelude::*;
+
+    use super::util::JULIA;
+
+    const PKG_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/util/JlrsPkgTest");
+
+    fn develop_local_package() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut julia = jlrs.instance(&mut frame);
+            let mut pkg = julia.package_manager();
+
+            let project_dir = std::env::temp_dir().join("jlrs_package_manager_test");
+            let _ = std::fs::remove_dir_all(&project_dir);
+            std::fs::create_dir_all(&project_dir).unwrap();
+
+            pkg.offline(true).unwrap();
+            pkg.activate(&project_dir).unwrap();
+            assert!(pkg
+                .active_project()
+                .unwrap()
+                .unwrap()
+                .starts_with(&project_dir));
+
+            unsafe {
+                pkg.develop(PKG_PATH).unwrap();
+                pkg.instantiate().unwrap();
+            }
+
+            let status = pkg.status().unwrap();
+            assert_eq!(status.len(), 1);
+            assert_eq!(status[0].name, "JlrsPkgTest");
+            assert_eq!(status[0].uuid, "5d4e3a4c-2b1f-4c8e-9a6d-7e0f1b2c3d4e");
+            assert!(status[0].is_direct_dep);
+            assert!(status[0].is_tracking_path);
+            assert_eq!(status[0].version.unwrap().to_string(), "0.1.0");
+
+            let loaded = pkg.loaded_packages().unwrap();
+            assert!(loaded.iter().any(|name| name == "Pkg"));
+            assert!(!loaded.iter().any(|name| name == "JlrsPkgTest"));
+        });
+    }
+
+    fn develop_nonexistent_package() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut julia = jlrs.instance(&mut frame);
+            let mut pkg = julia.package_manager();
+
+            unsafe { assert!(pkg.develop("nonexistent/package/").is_err()) }
+        });
+    }
+
+    #[test]
+    fn package_manager_tests() {
+        develop_local_package();
+        develop_nonexistent_package();
+    }
+}
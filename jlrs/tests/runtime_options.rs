@@ -0,0 +1,60 @@
+mod util;
+#[cfg(feature = "tokio-rt")]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use jlrs::prelude::*;
+
+    // Creates an empty project in a temporary directory.
+    fn project_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join("jlrs_runtime_options");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Project.toml"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn runtime_options() {
+        let project = project_dir();
+        let (julia, _handle) = unsafe {
+            RuntimeBuilder::new()
+                .async_runtime::<Tokio>()
+                .n_threads(2)
+                .project(&project)
+                .optimization_level(1)
+                .start::<1>()
+                .expect("Could not init Julia")
+        };
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        julia
+            .blocking_task(
+                |mut frame| unsafe {
+                    let active_project = Value::eval_string(&mut frame, "Base.active_project()")
+                        .into_jlrs_result()?
+                        .unbox::<String>()?
+                        .unwrap();
+                    let n_threads = Value::eval_string(&mut frame, "Threads.nthreads()")
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+                    let opt_level = Value::eval_string(&mut frame, "Base.JLOptions().opt_level")
+                        .into_jlrs_result()?
+                        .unbox::<i8>()?;
+
+                    Ok((active_project, n_threads, opt_level))
+                },
+                sender,
+            )
+            .try_dispatch_any()
+            .expect("Could not send blocking task");
+
+        let (active_project, n_threads, opt_level) = receiver
+            .blocking_recv()
+            .expect("Could not receive reply")
+            .expect("Blocking task failed");
+
+        assert_eq!(PathBuf::from(active_project), project.join("Project.toml"));
+        assert_eq!(n_threads, 2);
+        assert_eq!(opt_level, 1);
+    }
+}
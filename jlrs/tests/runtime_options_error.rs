@@ -0,0 +1,81 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        error::{IOError, JlrsError, RuntimeError},
+        runtime::builder::RuntimeBuilder,
+    };
+
+    fn invalid_optimization_level() {
+        let res = unsafe { RuntimeBuilder::new().optimization_level(4).start() };
+        match res {
+            Err(e) => match *e {
+                JlrsError::RuntimeError(RuntimeError::InvalidOption { option, .. }) => {
+                    assert_eq!(option, "optimization_level")
+                }
+                _ => panic!("unexpected error"),
+            },
+            Ok(_) => panic!("runtime was started with an invalid optimization level"),
+        }
+    }
+
+    fn empty_depot_path() {
+        let res = unsafe {
+            RuntimeBuilder::new()
+                .depot_path(Vec::<String>::new())
+                .start()
+        };
+        match res {
+            Err(e) => match *e {
+                JlrsError::RuntimeError(RuntimeError::InvalidOption { option, .. }) => {
+                    assert_eq!(option, "depot_path")
+                }
+                _ => panic!("unexpected error"),
+            },
+            Ok(_) => panic!("runtime was started without depots"),
+        }
+    }
+
+    fn nonexistent_project() {
+        let res = unsafe {
+            RuntimeBuilder::new()
+                .project("nonexistent/project/")
+                .start()
+        };
+        match res {
+            Err(e) => match *e {
+                JlrsError::IOError(IOError::NotFound { .. }) => (),
+                _ => panic!("unexpected error"),
+            },
+            Ok(_) => panic!("runtime was started with a nonexistent project"),
+        }
+    }
+
+    fn project_spec() {
+        // The project spec is resolved by Julia, so it's not rejected when the options are
+        // validated. The builder has an invalid optimization level so Julia isn't started.
+        let res = unsafe {
+            RuntimeBuilder::new()
+                .project("@.")
+                .optimization_level(4)
+                .start()
+        };
+        match res {
+            Err(e) => match *e {
+                JlrsError::RuntimeError(RuntimeError::InvalidOption { option, .. }) => {
+                    assert_eq!(option, "optimization_level")
+                }
+                _ => panic!("unexpected error"),
+            },
+            Ok(_) => panic!("runtime was started with an invalid optimization level"),
+        }
+    }
+
+    #[test]
+    fn runtime_options_error() {
+        invalid_optimization_level();
+        empty_depot_path();
+        nonexistent_project();
+        project_spec();
+    }
+}
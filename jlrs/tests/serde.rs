@@ -0,0 +1,261 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "jlrs-serde"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use jlrs::{
+        convert::serde::{from_value, to_value, to_value_as},
+        data::types::typecheck::NamedTuple,
+        prelude::*,
+    };
+    use serde::{Deserialize, Serialize};
+
+    use super::util::JULIA;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Shape<'a> {
+        name: &'a str,
+        points: Vec<Point>,
+        closed: bool,
+        tag: Option<u32>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Command {
+        Stop,
+        Move(i64),
+        Resize(u32, u32),
+        Rename { name: String },
+    }
+
+    fn serialize_primitives() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let v = to_value(frame.as_extended_target(), &3i16)?;
+                    assert_eq!(v.unbox::<i16>()?, 3);
+
+                    let v = to_value(frame.as_extended_target(), &true)?;
+                    assert!(v.unbox::<bool>()?.as_bool());
+
+                    let v = to_value(frame.as_extended_target(), "foo")?;
+                    assert_eq!(v.unbox::<String>()?.unwrap(), "foo");
+
+                    let v = to_value(frame.as_extended_target(), &None::<u8>)?;
+                    assert!(v.is::<Nothing>());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn serialize_struct() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let point = Point { x: 1.0, y: 2.0 };
+                    let v = to_value(frame.as_extended_target(), &point)?;
+                    assert!(v.is::<NamedTuple>());
+                    assert_eq!(v.get_field(&mut frame, "x")?.unbox::<f64>()?, 1.0);
+                    assert_eq!(v.get_field(&mut frame, "y")?.unbox::<f64>()?, 2.0);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn serialize_struct_as() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let ty = Value::eval_string(
+                        &mut frame,
+                        "struct SerdePoint; y::Float32; x::Float32; end; SerdePoint",
+                    )
+                    .into_jlrs_result()?
+                    .cast::<DataType>()?;
+
+                    let point = Point { x: 1.0, y: 2.0 };
+                    let v = to_value_as(frame.as_extended_target(), &point, ty)?;
+                    assert_eq!(v.datatype(), ty);
+                    assert_eq!(v.get_nth_field(&mut frame, 0)?.unbox::<f32>()?, 2.0);
+                    assert_eq!(v.get_nth_field(&mut frame, 1)?.unbox::<f32>()?, 1.0);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn serialize_vec() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let v = to_value(frame.as_extended_target(), &vec![1u32, 2, 3])?;
+                    let arr = v.cast::<Array>()?;
+                    assert!(arr.contains::<u32>());
+
+                    let data = unsafe { arr.bits_data::<u32>()? };
+                    assert_eq!(data.as_slice(), &[1, 2, 3]);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn serialize_map() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut map = HashMap::new();
+                    map.insert("a", 1i64);
+                    map.insert("b", 2i64);
+                    let v = to_value(frame.as_extended_target(), &map)?;
+
+                    let key = JuliaString::new(&mut frame, "b").as_value();
+                    let res = Module::base(&frame)
+                        .function(&frame, "getindex")?
+                        .as_managed()
+                        .call2(&mut frame, v, key)
+                        .into_jlrs_result()?
+                        .unbox::<i64>()?;
+
+                    assert_eq!(res, 2);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn roundtrip_struct() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let shape = Shape {
+                        name: "triangle",
+                        points: vec![
+                            Point { x: 0.0, y: 0.0 },
+                            Point { x: 1.0, y: 0.0 },
+                            Point { x: 0.0, y: 1.0 },
+                        ],
+                        closed: true,
+                        tag: Some(3),
+                    };
+
+                    let v = to_value(frame.as_extended_target(), &shape)?;
+                    let deserialized = from_value::<Shape>(v)?;
+                    assert_eq!(shape, deserialized);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn roundtrip_map() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let mut map = HashMap::new();
+                    map.insert(String::from("a"), vec![1u8, 2]);
+                    map.insert(String::from("b"), vec![]);
+                    map.insert(String::from("c"), vec![3]);
+
+                    let v = to_value(frame.as_extended_target(), &map)?;
+                    let deserialized = from_value::<HashMap<String, Vec<u8>>>(v)?;
+                    assert_eq!(map, deserialized);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn roundtrip_enum() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let commands = vec![
+                        Command::Stop,
+                        Command::Move(-2),
+                        Command::Resize(3, 4),
+                        Command::Rename {
+                            name: String::from("foo"),
+                        },
+                    ];
+
+                    let v = to_value(frame.as_extended_target(), &commands)?;
+                    let deserialized = from_value::<Vec<Command>>(v)?;
+                    assert_eq!(commands, deserialized);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn deserialize_julia_data() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v =
+                        Value::eval_string(&mut frame, "(x = 1.0, y = 2.0)").into_jlrs_result()?;
+                    assert_eq!(from_value::<Point>(v)?, Point { x: 1.0, y: 2.0 });
+
+                    let v = Value::eval_string(&mut frame, "Int32[1 3; 2 4]").into_jlrs_result()?;
+                    assert_eq!(from_value::<Vec<i32>>(v)?, vec![1, 2, 3, 4]);
+
+                    let v =
+                        Value::eval_string(&mut frame, "(1, :foo, \"bar\")").into_jlrs_result()?;
+                    assert_eq!(from_value::<(i64, &str, &str)>(v)?, (1, "foo", "bar"));
+
+                    let v = Value::eval_string(&mut frame, "1.0").into_jlrs_result()?;
+                    assert!(from_value::<String>(v).is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn serde_tests() {
+        serialize_primitives();
+        serialize_struct();
+        serialize_struct_as();
+        serialize_vec();
+        serialize_map();
+        roundtrip_struct();
+        roundtrip_map();
+        roundtrip_enum();
+        deserialize_julia_data();
+    }
+}
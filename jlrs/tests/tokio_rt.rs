@@ -5,9 +5,13 @@ mod util;
 #[cfg(all(feature = "tokio-rt",))]
 #[cfg(test)]
 mod tests {
-    use std::{num::NonZeroUsize, sync::Arc};
+    use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 
-    use jlrs::prelude::*;
+    use jlrs::{
+        error::{Cancelled, JlrsError},
+        prelude::*,
+        runtime::async_rt::stats::TaskKind,
+    };
     use once_cell::sync::OnceCell;
 
     use super::util::{async_tasks::*, ASYNC_TESTS_JL};
@@ -489,6 +493,62 @@ mod tests {
         assert_eq!(receiver.recv().unwrap().unwrap(), 2.0);
     }
 
+    #[test]
+    fn test_iterate_channel() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(IterateChannelTask, sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        assert_eq!(receiver.recv().unwrap().unwrap(), 55);
+    }
+
+    #[test]
+    fn test_channel_stream() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(ChannelStreamTask, sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        assert_eq!(receiver.recv().unwrap().unwrap(), 55);
+    }
+
+    #[test]
+    fn test_channel_stream_error() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(ChannelStreamErrorTask, sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        assert_eq!(receiver.recv().unwrap().unwrap(), (1, true));
+    }
+
+    #[test]
+    fn test_channel_sink() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(ChannelSinkTask, sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        assert_eq!(receiver.recv().unwrap().unwrap(), 3);
+    }
+
     #[test]
     fn test_post_task() {
         let julia = JULIA.get_or_init(init);
@@ -516,4 +576,124 @@ mod tests {
 
         assert_eq!(receiver.recv().unwrap().unwrap(), 2.0);
     }
+
+    #[test]
+    fn test_cancel_queued_task() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        let dispatch = julia.task(SleepingTask { seconds: 30.0 }, sender);
+        let handle = dispatch.cancel_handle();
+        handle.cancel();
+        assert!(handle.is_cancelled());
+
+        // The result sender receives the error before the task has been received.
+        match *receiver.try_recv().unwrap().unwrap_err() {
+            JlrsError::Cancelled(Cancelled::Requested) => (),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        dispatch.try_dispatch_any().unwrap();
+    }
+
+    #[test]
+    fn test_cancel_running_task() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        let dispatch = julia.task(SleepingTask { seconds: 30.0 }, sender);
+        let handle = dispatch.cancel_handle();
+        dispatch.try_dispatch_any().unwrap();
+
+        std::thread::sleep(Duration::from_millis(100));
+        handle.cancel();
+
+        match *receiver.recv().unwrap().unwrap_err() {
+            JlrsError::Cancelled(Cancelled::Requested) => (),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_cancel_persistent() {
+        let julia = JULIA.get_or_init(init);
+
+        let (is, ir) = crossbeam_channel::bounded(1);
+        julia
+            .register_persistent::<AccumulatorTask, _>(is)
+            .try_dispatch_any()
+            .unwrap();
+        ir.recv().unwrap().unwrap();
+
+        let (handle_sender, handle_receiver) = crossbeam_channel::bounded(1);
+        let dispatch = julia.persistent::<UnboundedChannel<_>, _, _>(
+            AccumulatorTask { init_value: 5.0 },
+            handle_sender,
+        );
+        let cancel_handle = dispatch.cancel_handle();
+        dispatch.try_dispatch_any().expect("Cannot send task");
+
+        let handle = handle_receiver
+            .recv()
+            .expect("Channel was closed")
+            .expect("Cannot init task");
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        handle.try_call(7.0, sender.clone()).unwrap();
+        assert_eq!(receiver.recv().unwrap().unwrap(), 12.0);
+
+        cancel_handle.cancel();
+        std::thread::sleep(Duration::from_millis(100));
+
+        // The task no longer receives calls, either the call can't be sent or it's dropped
+        // without being called.
+        if handle.try_call(12.0, sender).is_ok() {
+            assert!(receiver.recv().is_err());
+        }
+    }
+
+    #[test]
+    fn test_task_timeout() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(SleepingTask { seconds: 30.0 }, sender)
+            .timeout(Duration::from_millis(100))
+            .try_dispatch_any()
+            .unwrap();
+
+        match *receiver.recv().unwrap().unwrap_err() {
+            JlrsError::Cancelled(Cancelled::DeadlineExceeded) => (),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_runtime_stats() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(SleepingTask { seconds: 0.0 }, sender)
+            .try_dispatch_any()
+            .unwrap();
+
+        receiver.recv().unwrap().unwrap();
+
+        let stats = julia.stats();
+        assert!(stats.completed >= 1);
+        assert_eq!(stats.in_flight_per_thread.len(), 1);
+        assert!(stats.any_queue_len.is_none());
+        assert!(stats.worker_queue_len.is_none());
+        assert!(stats.wait_time.count >= 1);
+
+        let execution_time = stats.execution_time(TaskKind::Async);
+        assert!(execution_time.count >= 1);
+        assert!(execution_time.p50 <= execution_time.max);
+    }
 }
@@ -0,0 +1,94 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        data::managed::{array::TypedArray, tuple_value::TupleValue},
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    fn create_tuple_value() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let s = JuliaString::new(&mut frame, "foo");
+                    let tup = TupleValue::new(frame.as_extended_target(), (1u8, s, 2.0f32))
+                        .into_jlrs_result()?;
+
+                    assert!(tup.as_value().is::<Tuple>());
+                    assert!(tup.as_value().is::<TupleValue>());
+                    assert_eq!(tup.len(), 3);
+                    assert!(tup.element_type(0).unwrap().is::<u8>());
+                    assert!(tup.element_type(1).unwrap().is::<JuliaString>());
+                    assert!(tup.element_type(2).unwrap().is::<f32>());
+                    assert!(tup.element_type(3).is_none());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn destructure_tuple_value() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let tup = Value::eval_string(&mut frame, "(1, \"bar\", Float32[1, 2], :baz)")
+                        .into_jlrs_result()?
+                        .cast::<TupleValue>()?;
+
+                    let (a, b, c, d) =
+                        tup.destructure::<(i64, JuliaString, TypedArray<f32>, Symbol)>(&mut frame)?;
+                    assert_eq!(a, 1);
+                    assert_eq!(b.as_str()?, "bar");
+                    assert_eq!(c.bits_data()?.as_slice(), &[1.0, 2.0]);
+                    assert_eq!(d.as_str()?, "baz");
+
+                    let (a, b) = TupleValue::new(frame.as_extended_target(), (tup, 3i16))
+                        .into_jlrs_result()?
+                        .destructure::<(TupleValue, Value)>(&mut frame)?;
+                    assert_eq!(a.len(), 4);
+                    assert_eq!(b.unbox::<i16>()?, 3);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn destructure_wrong_types() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let tup = Value::eval_string(&mut frame, "(1, \"bar\")")
+                        .into_jlrs_result()?
+                        .cast::<TupleValue>()?;
+
+                    assert!(tup.destructure::<(i64, Symbol)>(&mut frame).is_err());
+                    assert!(tup.destructure::<(i32, JuliaString)>(&mut frame).is_err());
+                    assert!(tup.destructure::<(i64,)>(&mut frame).is_err());
+                    assert!(tup
+                        .destructure::<(i64, JuliaString, Value)>(&mut frame)
+                        .is_err());
+                    assert!(tup.destructure::<(Value, Value)>(&mut frame).is_ok());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn tuple_value_tests() {
+        create_tuple_value();
+        destructure_tuple_value();
+        destructure_wrong_types();
+    }
+}
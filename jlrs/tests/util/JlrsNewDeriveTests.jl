@@ -252,6 +252,13 @@ struct Empty end
 
 struct TypedEmpty{T} end
 
+@enum DeriveFruit::Int32 derive_apple=1 derive_orange=2 derive_kiwi=4
+
+struct WithInt128
+    a::Int8
+    b::Int128
+end
+
 #reflect([
 #    BitsCharBitsIntChar,
 #    BitsCharFloat32Float64,
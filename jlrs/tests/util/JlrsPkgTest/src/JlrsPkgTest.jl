@@ -0,0 +1,5 @@
+module JlrsPkgTest
+
+answer() = 42
+
+end
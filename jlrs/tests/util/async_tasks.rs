@@ -1,4 +1,8 @@
-use jlrs::{memory::gc::Gc, prelude::*};
+use futures::{SinkExt, StreamExt};
+use jlrs::{
+    async_util::julia_channel::JuliaChannel, data::managed::value::typed::TypedValue,
+    memory::gc::Gc, prelude::*,
+};
 
 pub struct MyTask {
     pub dims: isize,
@@ -798,3 +802,142 @@ impl AsyncTask for BorrowArrayData {
         Ok(v)
     }
 }
+
+pub struct IterateChannelTask;
+
+#[async_trait(?Send)]
+impl AsyncTask for IterateChannelTask {
+    type Output = i64;
+    type Affinity = DispatchAny;
+
+    async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<Self::Output> {
+        let mut sum = 0;
+
+        unsafe {
+            let channel = Value::eval_string(
+                &mut frame,
+                "Channel{Int}(ch -> foreach(i -> put!(ch, i), 1:10), 2)",
+            )
+            .into_jlrs_result()?;
+
+            let mut iter = channel.iter_async(&mut frame);
+            while let Some(item) = iter.next().await {
+                sum += item?.unbox::<i64>()?;
+            }
+        }
+
+        Ok(sum)
+    }
+}
+
+pub struct ChannelStreamTask;
+
+#[async_trait(?Send)]
+impl AsyncTask for ChannelStreamTask {
+    type Output = i64;
+    type Affinity = DispatchAny;
+
+    async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<Self::Output> {
+        let mut sum = 0;
+
+        unsafe {
+            let channel = Value::eval_string(
+                &mut frame,
+                "Channel{Int}(ch -> foreach(i -> put!(ch, i), 1:10), 2)",
+            )
+            .into_jlrs_result()?;
+
+            let mut channel = JuliaChannel::new(&mut frame, channel)?;
+            while let Some(item) = channel.next().await {
+                sum += item?.unbox::<i64>()?;
+            }
+        }
+
+        Ok(sum)
+    }
+}
+
+pub struct ChannelStreamErrorTask;
+
+#[async_trait(?Send)]
+impl AsyncTask for ChannelStreamErrorTask {
+    type Output = (i64, bool);
+    type Affinity = DispatchAny;
+
+    async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<Self::Output> {
+        unsafe {
+            let channel = Value::eval_string(
+                &mut frame,
+                "Channel{Int}(ch -> (put!(ch, 1); error(\"failed\")))",
+            )
+            .into_jlrs_result()?;
+
+            let mut channel = JuliaChannel::new(&mut frame, channel)?;
+            let first = channel.next().await.unwrap()?.unbox::<i64>()?;
+            let failed = channel.next().await.unwrap().is_err();
+            Ok((first, failed))
+        }
+    }
+}
+
+pub struct ChannelSinkTask;
+
+#[async_trait(?Send)]
+impl AsyncTask for ChannelSinkTask {
+    type Output = i64;
+    type Affinity = DispatchAny;
+
+    async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<Self::Output> {
+        let mut sum = 0;
+
+        unsafe {
+            let channel = Value::eval_string(&mut frame, "Channel{Int}(2)").into_jlrs_result()?;
+            assert!(JuliaChannel::new(&mut frame, channel)?
+                .try_as_typed::<f64>()
+                .await
+                .is_err());
+
+            let one = TypedValue::new(&mut frame, 1i64);
+            let two = TypedValue::new(&mut frame, 2i64);
+
+            let mut channel = JuliaChannel::new(&mut frame, channel)?
+                .try_as_typed::<i64>()
+                .await?;
+
+            channel.send(one).await?;
+            channel.send(two).await?;
+            channel.close().await?;
+
+            while let Some(item) = channel.next().await {
+                sum += item?.unbox::<i64>()?;
+            }
+        }
+
+        Ok(sum)
+    }
+}
+
+pub struct SleepingTask {
+    pub seconds: f64,
+}
+
+#[async_trait(?Send)]
+impl AsyncTask for SleepingTask {
+    type Output = ();
+    type Affinity = DispatchAny;
+
+    async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<Self::Output> {
+        let seconds = Value::new(&mut frame, self.seconds);
+
+        unsafe {
+            Module::base(&frame)
+                .function(&frame, "sleep")?
+                .as_managed()
+                .call_async(&mut frame, [seconds])
+                .await
+                .into_jlrs_result()?;
+        }
+
+        Ok(())
+    }
+}
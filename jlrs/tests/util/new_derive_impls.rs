@@ -858,3 +858,59 @@ pub struct WithValueType {
 pub struct WithValueTypeTypeConstructor<N> {
     _n: ::std::marker::PhantomData<N>,
 }
+
+#[repr(i32)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Unbox,
+    ValidLayout,
+    Typecheck,
+    IntoJulia,
+    ConstructType,
+    CCallReturn,
+)]
+#[jlrs(julia_type = "Main.DeriveFruit")]
+pub enum DeriveFruit {
+    Apple = 1,
+    Orange = 2,
+    Kiwi = 4,
+}
+
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Unbox, ValidLayout, Typecheck)]
+#[jlrs(julia_type = "Main.DeriveFruit")]
+pub enum MismatchedFruit {
+    Apple = 1,
+    Orange = 2,
+    Kiwi = 3,
+}
+
+#[repr(i64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Unbox, ValidLayout, Typecheck)]
+#[jlrs(julia_type = "Main.DeriveFruit")]
+pub enum WideFruit {
+    Apple = 1,
+    Orange = 2,
+    Kiwi = 4,
+}
+
+#[repr(i32, align(4))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Unbox, ValidLayout, Typecheck)]
+#[jlrs(julia_type = "Main.DeriveFruit")]
+pub enum AlignedFruit {
+    Apple = 1,
+    Orange = 2,
+    Kiwi = 4,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, Unbox, ValidLayout, Typecheck, ValidField)]
+#[jlrs(julia_type = "Main.WithInt128")]
+pub struct WithInt128 {
+    pub a: i8,
+    pub b: i128,
+}
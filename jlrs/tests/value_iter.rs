@@ -0,0 +1,120 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::prelude::*;
+
+    use super::util::JULIA;
+
+    fn iterate_vector() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(&mut frame, "[1.0, 2.0, 3.0]").into_jlrs_result()?;
+
+                    let items = v
+                        .iter(&frame)
+                        .map(|item| item?.unbox::<f64>())
+                        .collect::<JlrsResult<Vec<_>>>()?;
+                    assert_eq!(items, vec![1.0, 2.0, 3.0]);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn iterate_generator_with_reusable_slot() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let gen =
+                        Value::eval_string(&mut frame, "(x^2 for x in 1:4)").into_jlrs_result()?;
+
+                    let mut slot = frame.reusable_slot();
+                    let stack_size = frame.stack_size();
+                    let mut iter = gen.iter(&frame);
+                    let mut sum = 0;
+                    while let Some(item) = iter.next_with(&mut slot)? {
+                        sum += item.as_value().unbox::<i64>()?;
+                    }
+
+                    assert_eq!(sum, 30);
+                    assert!(iter.is_done());
+                    assert!(iter.next_with(&mut slot)?.is_none());
+                    assert_eq!(frame.stack_size(), stack_size + 2);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn iterate_empty() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(&mut frame, "()").into_jlrs_result()?;
+                    assert_eq!(v.iter(&frame).count(), 0);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn iterate_throws() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let gen = Value::eval_string(
+                        &mut frame,
+                        "(x < 3 ? x : throw(ArgumentError(\"x\")) for x in 1:5)",
+                    )
+                    .into_jlrs_result()?;
+
+                    let mut iter = gen.iter(&frame);
+                    assert_eq!(iter.next().unwrap()?.unbox::<i64>()?, 1);
+                    assert_eq!(iter.next().unwrap()?.unbox::<i64>()?, 2);
+                    assert!(iter.next().unwrap().is_err());
+                    assert!(iter.next().is_none());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn iterate_not_iterable() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|frame| unsafe {
+                    let v = Value::nothing(&frame);
+                    let mut iter = v.iter(&frame);
+                    assert!(iter.next().unwrap().is_err());
+                    assert!(iter.is_done());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn value_iter_tests() {
+        iterate_vector();
+        iterate_generator_with_reusable_slot();
+        iterate_empty();
+        iterate_throws();
+        iterate_not_iterable();
+    }
+}
@@ -132,8 +132,9 @@ impl JlrsFieldAttr {
 
 pub fn impl_into_julia(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
-    if !is_repr_c(ast) {
-        panic!("IntoJulia can only be derived for types with the attribute #[repr(C)].");
+    check_enum(ast, "IntoJulia");
+    if !is_repr_c(ast) && enum_repr(ast).is_none() {
+        panic!("IntoJulia can only be derived for types with the attribute #[repr(C)] and fieldless enums with an integer representation.");
     }
 
     let mut attrs = JlrsTypeAttrs::parse(ast);
@@ -231,8 +232,13 @@ pub fn impl_into_julia_fn(attrs: &JlrsTypeAttrs) -> Option<TS2> {
 
 pub fn impl_unbox(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    check_enum(ast, "Unbox");
+    if let Some(repr) = enum_repr(ast) {
+        return impl_enum_unbox(ast, &repr);
+    }
+
     if !is_repr_c(ast) {
-        panic!("Unbox can only be derived for types with the attribute #[repr(C)].");
+        panic!("Unbox can only be derived for types with the attribute #[repr(C)] and fieldless enums with an integer representation.");
     }
 
     let generics = &ast.generics;
@@ -273,8 +279,9 @@ pub fn impl_unbox(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_typecheck(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
-    if !is_repr_c(ast) {
-        panic!("Typecheck can only be derived for types with the attribute #[repr(C)].");
+    check_enum(ast, "Typecheck");
+    if !is_repr_c(ast) && enum_repr(ast).is_none() {
+        panic!("Typecheck can only be derived for types with the attribute #[repr(C)] and fieldless enums with an integer representation.");
     }
 
     let generics = &ast.generics;
@@ -326,6 +333,7 @@ pub fn impl_typecheck(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_construct_type(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    check_enum(ast, "ConstructType");
     let mut attrs = JlrsTypeAttrs::parse(ast);
     let jl_type = attrs.julia_type
         .take()
@@ -612,8 +620,13 @@ pub fn impl_construct_type(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_valid_layout(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    check_enum(ast, "ValidLayout");
+    if let Some(repr) = enum_repr(ast) {
+        return impl_enum_valid_layout(ast, &repr);
+    }
+
     if !is_repr_c(ast) {
-        panic!("ValidLayout can only be derived for types with the attribute #[repr(C)].");
+        panic!("ValidLayout can only be derived for types with the attribute #[repr(C)] and fieldless enums with an integer representation.");
     }
 
     let generics = &ast.generics;
@@ -712,8 +725,12 @@ pub fn impl_valid_layout(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_valid_field(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    if let syn::Data::Enum(_) = ast.data {
+        panic!("ValidField can't be derived for enums because the value isn't checked when it's read, a Julia enum can have instances that aren't variants of the Rust enum. Use the integer representation as the field type instead.");
+    }
+
     if !is_repr_c(ast) {
-        panic!("ValidLayout can only be derived for types with the attribute #[repr(C)].");
+        panic!("ValidField can only be derived for types with the attribute #[repr(C)].");
     }
 
     let generics = &ast.generics;
@@ -756,8 +773,12 @@ pub fn impl_valid_field(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_ccall_arg(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    if let syn::Data::Enum(_) = ast.data {
+        panic!("CCallArg can't be derived for enums because the value isn't checked when it's read, a Julia enum can have instances that aren't variants of the Rust enum. Use the integer representation as the argument type instead.");
+    }
+
     if !is_repr_c(ast) {
-        panic!("ValidLayout can only be derived for types with the attribute #[repr(C)].");
+        panic!("CCallArg can only be derived for types with the attribute #[repr(C)].");
     }
 
     let generics = &ast.generics;
@@ -799,8 +820,9 @@ pub fn impl_ccall_arg(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_ccall_return(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
-    if !is_repr_c(ast) {
-        panic!("ValidLayout can only be derived for types with the attribute #[repr(C)].");
+    check_enum(ast, "CCallReturn");
+    if !is_repr_c(ast) && enum_repr(ast).is_none() {
+        panic!("CCallReturn can only be derived for types with the attribute #[repr(C)] and fieldless enums with an integer representation.");
     }
 
     let generics = &ast.generics;
@@ -854,3 +876,109 @@ fn is_repr_c(ast: &syn::DeriveInput) -> bool {
 
     false
 }
+
+fn impl_enum_valid_layout(ast: &syn::DeriveInput, repr: &syn::Ident) -> TokenStream {
+    let name = &ast.ident;
+
+    let valid_layout_impl = quote! {
+        unsafe impl ::jlrs::data::layout::valid_layout::ValidLayout for #name {
+            fn valid_layout(v: ::jlrs::data::managed::value::Value) -> bool {
+                if let Ok(dt) = v.cast::<::jlrs::data::managed::datatype::DataType>() {
+                    return ::jlrs::data::layout::enums::is_enum_layout::<#repr>(dt);
+                }
+
+                false
+            }
+
+            const IS_REF: bool = false;
+        }
+    };
+
+    valid_layout_impl.into()
+}
+
+// The value is checked against the discriminants of the variants when it's unboxed, a Julia
+// enum can have instances that aren't variants of the Rust enum.
+fn impl_enum_unbox(ast: &syn::DeriveInput, repr: &syn::Ident) -> TokenStream {
+    let name = &ast.ident;
+    let variants = match &ast.data {
+        syn::Data::Enum(e) => e.variants.iter().map(|v| &v.ident),
+        _ => unreachable!(),
+    };
+    let variants_b = variants.clone();
+
+    let unbox_impl = quote! {
+        unsafe impl ::jlrs::convert::unbox::Unbox for #name {
+            type Output = ::std::result::Result<Self, #repr>;
+
+            unsafe fn unbox(value: ::jlrs::data::managed::value::Value) -> Self::Output {
+                let raw = value.data_ptr().cast::<#repr>().as_ptr().read();
+                #(
+                    if raw == #name::#variants as #repr {
+                        return Ok(#name::#variants_b);
+                    }
+                )*
+
+                Err(raw)
+            }
+        }
+    };
+
+    unbox_impl.into()
+}
+
+// Panics if `ast` is an enum that can't be mapped to a Julia enum type.
+fn check_enum(ast: &syn::DeriveInput, trait_name: &str) {
+    let syn::Data::Enum(e) = &ast.data else {
+        return;
+    };
+
+    if e.variants.iter().any(|v| !matches!(v.fields, syn::Fields::Unit)) {
+        panic!("{} can't be derived for enums with fields, only fieldless enums with an integer representation can be mapped to a Julia enum type.", trait_name);
+    }
+
+    if enum_repr(ast).is_none() {
+        panic!("{} can only be derived for fieldless enums with an integer representation, e.g. #[repr(i32)].", trait_name);
+    }
+}
+
+// Returns the representation of a fieldless enum with an integer representation, e.g. `i32` for
+// an enum with the attribute `#[repr(i32)]` or `#[repr(i32, align(4))]`.
+fn enum_repr(ast: &syn::DeriveInput) -> Option<syn::Ident> {
+    let syn::Data::Enum(e) = &ast.data else {
+        return None;
+    };
+
+    if !e
+        .variants
+        .iter()
+        .all(|v| matches!(v.fields, syn::Fields::Unit))
+    {
+        return None;
+    }
+
+    for attr in &ast.attrs {
+        if attr.path().is_ident("repr") {
+            let reprs = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated);
+            if let Ok(reprs) = reprs {
+                for repr in reprs {
+                    let Meta::Path(path) = repr else {
+                        continue
+                    };
+
+                    let Some(repr) = path.get_ident() else {
+                        continue
+                    };
+
+                    match repr.to_string().as_str() {
+                        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64"
+                        | "usize" => return Some(repr.clone()),
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
@@ -51,10 +51,86 @@ use self::version::emit_if_compatible;
 ///     // This syntax can be used to extend existing functions.
 ///     fn foo(arr: Array) -> usize as Base.bar!;
 ///
+///     // Exports the function `checked_sqrt`, which returns a `Result`.
+///     //
+///     // Exported functions and methods can return `Result<T, E>` if `T` implements `IntoJulia`
+///     // and `E` implements `IntoJuliaException`, e.g. `JlrsException` or `JlrsError`. A Rust
+///     // function is generated that converts the result to a `RustResult`, if an error is
+///     // returned it's thrown as an exception by the generated Julia function. Because a Rust
+///     // function is generated, `checked_sqrt` doesn't have to use the C ABI. The return type
+///     // must be written as `Result`, `JlrsResult`, or their full path in `std`, `core` or
+///     // `jlrs`, type aliases aren't recognized.
+///     fn checked_sqrt(x: f64) -> Result<f64, JlrsException>;
+///
+///     // Exports the function `apply_n`, which takes a Julia function as an argument.
+///     //
+///     // A `JuliaFn<Args, Ret>` argument accepts any Julia function, in Rust it can be resolved
+///     // to a method that is called with arguments of type `Args` and returns `Ret`. Resolving
+///     // it checks that such a method exists and creates a function pointer with `@cfunction`,
+///     // calling this pointer avoids dynamic dispatch.
+///     fn apply_n(f: JuliaFn<(f64,), f64>, x: f64, n: usize) -> JlrsResult<f64>;
+///
+///     // Exports the function `solve`, which takes the keyword arguments `tol` and `maxiter`.
+///     //
+///     // Arguments annotated with `#[kw]` become keyword arguments of the generated Julia
+///     // function, `#[default = "..."]` provides a default value for a positional or keyword
+///     // argument. The default value is a Julia expression that can refer to the preceding
+///     // arguments, positional arguments with a default value must come last. The Rust
+///     // function is exported with a hidden name and called with all arguments in order by a
+///     // Julia function that is defined when the module is initialized. The attributes are only
+///     // used by this macro, the arguments of the Rust function must not be annotated.
+///     fn solve(
+///         a: Array,
+///         b: Array,
+///         #[kw] #[default = "1e-8"] tol: f64,
+///         #[kw] #[default = "100"] maxiter: usize,
+///     ) -> JlrsResult<Array>;
+///
+///     // Exports the generic function `scale` for `f32`, `f64` and `i64`.
+///     //
+///     // Generic functions and methods can be exported by listing the types each type parameter
+///     // must be instantiated with. Each instantiation is exported as a separate method of the
+///     // same function, the argument types of each method are constructed with `ConstructType`
+///     // so Julia's dispatch selects the right one. Every instantiation must result in a
+///     // distinct Julia signature, instantiations with identical argument types are rejected.
+///     // A type parameter is only replaced where it's used as a path of a single segment, e.g.
+///     // `T` and `Vec<T>` but not `T::Output`. A Rust function is generated for every
+///     // instantiation, so `scale` doesn't have to use the C ABI. Multiple type parameters are
+///     // instantiated by nesting `for` items: `for T in [f32, f64] for U in [u8, u16] fn ...`.
+///     // A docstring is only added once, not once per instantiation.
+///     for T in [f32, f64, i64]
+///     fn scale<T>(arr: TypedArray<T>, k: T) -> JlrsResult<Nothing>;
+///
 ///     // Exports the struct `MyType` as `MyForeignType`. `MyType` must implement `OpaqueType`
 ///     // or `ForeignType`.
 ///     struct MyType as MyForeignType;
 ///
+///     // Exports the struct `MyNumber` as a subtype of `AbstractMyNumber`.
+///     //
+///     // The supertype is a Julia type that's evaluated in the module, it overrides the supertype
+///     // returned by `OpaqueType::super_type`.
+///     struct MyNumber <: AbstractMyNumber;
+///
+///     // Exports the generic struct `Grid<T>` as the parametric type `Grid{T}`, and its
+///     // instantiations `Grid<f32>` and `Grid<f64>` as `Grid{Float32}` and `Grid{Float64}`.
+///     //
+///     // `Grid<T>` must implement `ParametricBase` and every listed instantiation must implement
+///     // `ParametricVariant`. The parametric type is created for the first instantiation, the
+///     // names of the type parameters can be used in its supertype.
+///     for T in [f32, f64]
+///     struct Grid<T> as Grid <: AbstractMatrix{T};
+///
+///     // Exports the struct `MyIter` with methods for several protocols and properties.
+///     //
+///     // The traits listed after `impl` add methods to functions from `Base`: `Display` or
+///     // `Debug` add a method to `show`, `PartialEq` to `==`, `Hash` to `hash`, `Iterator` to
+///     // `iterate`, `ExactSizeIterator` to `length`, and `Index<I>` to `getindex`. `MyIter` must
+///     // implement these traits, see the `jlrs::ccall::protocols` module for the additional
+///     // requirements, e.g. `Index<I>` requires implementing `CheckedIndex<I>` too. The fields listed in braces are exposed as properties and must be
+///     // `Clone` and `IntoJulia`, fields marked with `mut` can be set if the new value can be
+///     // unboxed as the type of that field.
+///     struct MyIter impl Debug, Iterator, ExactSizeIterator { count: usize, mut step: usize };
+///
 ///     // Exports `MyType::new` as `MyForeignType`, turning it into a constructor for that type.
 ///     //
 ///     // A Rust function is generated to call this method, so unlike free-standing functions
@@ -68,6 +144,10 @@ use self::version::emit_if_compatible;
 ///     // `self` is taken by value, it's cloned after being tracked.
 ///     in MyType fn add(&mut self, incr: u32) -> RustResultRet<u32>  as increment!;
 ///
+///     // Exports `MyType::convert` for `u8` and `u16`.
+///     for T in [u8, u16]
+///     in MyType fn convert<T>(&self, value: T) -> RustResultRet<u32>;
+///
 ///     // Exports the function `long_running_func`, the returned closure is executed on another
 ///     // thread.
 ///     //
@@ -1,20 +1,23 @@
 use std::iter::FromIterator;
 
 use proc_macro::TokenStream;
-use proc_macro2::Span;
-use quote::{format_ident, ToTokens};
+use proc_macro2::{Delimiter, Group, Spacing, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
+    braced, bracketed,
     parse::{Parse, ParseStream},
     parse_quote, parse_quote_spanned,
     punctuated::Punctuated,
     spanned::Spanned,
-    token::Comma,
-    AttrStyle, Attribute, Error, Expr, ExprLit, FnArg, GenericArgument, Ident, ItemFn, Lit, Meta,
-    Path, PathArguments, Result, ReturnType, Signature, Token, Type, TypeImplTrait, TypeParamBound,
+    token::{Brace, Bracket, Comma},
+    AttrStyle, Attribute, Error, Expr, ExprLit, FnArg, GenericArgument, GenericParam, Ident,
+    ItemFn, Lit, Meta, Pat, Path, PathArguments, Result, ReturnType, Signature, Token, Type,
+    TypeImplTrait, TypeParamBound,
 };
 
 type RenameFragments = Punctuated<Ident, Token![.]>;
 
+#[derive(Clone)]
 struct InitFn {
     _become_token: Token![become],
     init_fn: Ident,
@@ -32,11 +35,23 @@ impl Parse for InitFn {
     }
 }
 
+#[derive(Clone)]
 struct ExportedType {
     _struct_token: Token![struct],
     name: Ident,
+    _lt_token: Option<Token![<]>,
+    type_params: Punctuated<Ident, Comma>,
+    _gt_token: Option<Token![>]>,
+    // The types the type parameters are instantiated with.
+    type_args: Vec<Type>,
     _as_token: Option<Token![as]>,
     name_override: Option<RenameFragments>,
+    // The Julia source of the declared supertype, e.g. `AbstractMatrix{T}`.
+    super_type: Option<String>,
+    _impl_token: Option<Token![impl]>,
+    protocols: Punctuated<Path, Comma>,
+    _brace_token: Option<Brace>,
+    properties: Punctuated<ExportedProperty, Comma>,
 }
 
 impl Parse for ExportedType {
@@ -44,38 +59,162 @@ impl Parse for ExportedType {
         let struct_token = input.parse()?;
         let name = input.parse()?;
 
-        let lookahead = input.lookahead1();
-        if lookahead.peek(Token![as]) {
+        let (lt_token, type_params, gt_token) = if input.peek(Token![<]) && !input.peek2(Token![:])
+        {
+            let lt_token = input.parse()?;
+            let type_params = Punctuated::parse_separated_nonempty(input)?;
+            let gt_token = input.parse()?;
+            (Some(lt_token), type_params, Some(gt_token))
+        } else {
+            (None, Punctuated::new(), None)
+        };
+
+        let (as_token, name_override) = if input.peek(Token![as]) {
             let as_token = input.parse()?;
             let name_override = RenameFragments::parse_separated_nonempty(input)?;
+            (Some(as_token), Some(name_override))
+        } else {
+            (None, None)
+        };
 
-            Ok(ExportedType {
-                _struct_token: struct_token,
-                name,
-                _as_token: Some(as_token),
-                name_override: Some(name_override),
-            })
+        let super_type = if input.peek(Token![<]) && input.peek2(Token![:]) {
+            Some(parse_super_type(input)?)
         } else {
-            Ok(ExportedType {
-                _struct_token: struct_token,
-                name,
-                _as_token: None,
-                name_override: None,
-            })
+            None
+        };
+
+        let (impl_token, protocols) = if input.peek(Token![impl]) {
+            let impl_token = input.parse()?;
+            let protocols = Punctuated::parse_separated_nonempty(input)?;
+            (Some(impl_token), protocols)
+        } else {
+            (None, Punctuated::new())
+        };
+
+        let (brace_token, properties) = if input.peek(Brace) {
+            let content;
+            let brace_token = braced!(content in input);
+            let properties = content.parse_terminated(ExportedProperty::parse, Token![,])?;
+            (Some(brace_token), properties)
+        } else {
+            (None, Punctuated::new())
+        };
+
+        Ok(ExportedType {
+            _struct_token: struct_token,
+            name,
+            _lt_token: lt_token,
+            type_params,
+            _gt_token: gt_token,
+            type_args: Vec::new(),
+            _as_token: as_token,
+            name_override,
+            super_type,
+            _impl_token: impl_token,
+            protocols,
+            _brace_token: brace_token,
+            properties,
+        })
+    }
+}
+
+impl ExportedType {
+    // The Rust type, including the types the type parameters are instantiated with.
+    fn rust_type(&self) -> Type {
+        let name = &self.name;
+        if self.type_args.is_empty() {
+            parse_quote! { #name }
+        } else {
+            let type_args = &self.type_args;
+            parse_quote! { #name<#(#type_args),*> }
         }
     }
 }
 
+// Parses `<: Path{Params}` and returns it as Julia source, the parameters are optional. A
+// brace-delimited group that contains properties is not part of the supertype.
+fn parse_super_type(input: ParseStream) -> Result<String> {
+    let _: Token![<] = input.parse()?;
+    let _: Token![:] = input.parse()?;
+    let path = RenameFragments::parse_separated_nonempty(input)?;
+    let mut source = path
+        .iter()
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>()
+        .join(".");
+
+    if input.peek(Brace) {
+        let fork = input.fork();
+        let content;
+        braced!(content in fork);
+        let is_properties = content
+            .parse_terminated(ExportedProperty::parse, Token![,])
+            .is_ok();
+
+        if !is_properties {
+            let content;
+            braced!(content in input);
+            let params: TokenStream2 = content.parse()?;
+            source.push('{');
+            source.push_str(&params.to_string());
+            source.push('}');
+        }
+    }
+
+    Ok(source)
+}
+
+// A field of an exported type that is exposed as a property, it can only be set if it's
+// preceded by `mut`.
+#[derive(Clone)]
+struct ExportedProperty {
+    mutability: Option<Token![mut]>,
+    name: Ident,
+    _colon_token: Token![:],
+    ty: Type,
+}
+
+impl Parse for ExportedProperty {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mutability = input.parse()?;
+        let name = input.parse()?;
+        let colon_token = input.parse()?;
+        let ty = input.parse()?;
+
+        Ok(ExportedProperty {
+            mutability,
+            name,
+            _colon_token: colon_token,
+            ty,
+        })
+    }
+}
+
+// An argument of the generated Julia function of an exported function or method that takes
+// keyword arguments or has default values.
+#[derive(Clone)]
+struct JuliaArg {
+    name: String,
+    keyword: bool,
+    default: Option<String>,
+}
+
+#[derive(Clone)]
 struct ExportedFunction {
     func: Signature,
     _as_token: Option<Token![as]>,
     name_override: Option<RenameFragments>,
     exclamation_mark_token: Option<Token![!]>,
+    type_args: Vec<Type>,
+    julia_args: Option<Vec<JuliaArg>>,
+    // The name of the Julia function if it's not a valid Rust identifier, e.g. `==`.
+    julia_name: Option<String>,
 }
 
 impl Parse for ExportedFunction {
     fn parse(input: ParseStream) -> Result<Self> {
-        let func = input.parse()?;
+        let mut func = input.parse()?;
+        let julia_args = extract_julia_args(&mut func)?;
 
         let lookahead = input.lookahead1();
         if lookahead.peek(Token![as]) {
@@ -88,6 +227,9 @@ impl Parse for ExportedFunction {
                 _as_token: Some(as_token),
                 name_override: Some(name_override),
                 exclamation_mark_token,
+                type_args: Vec::new(),
+                julia_args,
+                julia_name: None,
             })
         } else {
             Ok(ExportedFunction {
@@ -95,11 +237,15 @@ impl Parse for ExportedFunction {
                 _as_token: None,
                 name_override: None,
                 exclamation_mark_token: None,
+                type_args: Vec::new(),
+                julia_args,
+                julia_name: None,
             })
         }
     }
 }
 
+#[derive(Clone)]
 struct ExportedMethod {
     _in_token: Token![in],
     parent: Type,
@@ -107,13 +253,16 @@ struct ExportedMethod {
     _as_token: Option<Token![as]>,
     name_override: Option<RenameFragments>,
     exclamation_mark_token: Option<Token![!]>,
+    type_args: Vec<Type>,
+    julia_args: Option<Vec<JuliaArg>>,
 }
 
 impl Parse for ExportedMethod {
     fn parse(input: ParseStream) -> Result<Self> {
         let in_token = input.parse()?;
         let parent = input.parse()?;
-        let func = input.parse()?;
+        let mut func = input.parse()?;
+        let julia_args = extract_julia_args(&mut func)?;
 
         let lookahead = input.lookahead1();
         if lookahead.peek(Token![as]) {
@@ -128,6 +277,8 @@ impl Parse for ExportedMethod {
                 _as_token: Some(as_token),
                 name_override: Some(name_override),
                 exclamation_mark_token,
+                type_args: Vec::new(),
+                julia_args,
             })
         } else {
             Ok(ExportedMethod {
@@ -137,11 +288,14 @@ impl Parse for ExportedMethod {
                 _as_token: None,
                 name_override: None,
                 exclamation_mark_token: None,
+                type_args: Vec::new(),
+                julia_args,
             })
         }
     }
 }
 
+#[derive(Clone)]
 struct ExportedAsyncCallback {
     _async_token: Token![async],
     func: Signature,
@@ -180,6 +334,7 @@ impl Parse for ExportedAsyncCallback {
     }
 }
 
+#[derive(Clone)]
 struct ExportedConst {
     _const_token: Token![const],
     name: Ident,
@@ -222,6 +377,7 @@ impl Parse for ExportedConst {
     }
 }
 
+#[derive(Clone)]
 struct ExportedGlobal {
     _static_token: Token![static],
     name: Ident,
@@ -264,6 +420,7 @@ impl Parse for ExportedGlobal {
     }
 }
 
+#[derive(Clone)]
 struct ItemWithAttrs {
     attrs: Vec<Attribute>,
     item: Box<ModuleItem>,
@@ -330,6 +487,38 @@ impl Parse for ItemWithAttrs {
     }
 }
 
+#[derive(Clone)]
+struct GenericItem {
+    _for_token: Token![for],
+    param: Ident,
+    _in_token: Token![in],
+    _bracket_token: Bracket,
+    types: Punctuated<Type, Comma>,
+    item: Box<ModuleItem>,
+}
+
+impl Parse for GenericItem {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let for_token = input.parse()?;
+        let param = input.parse()?;
+        let in_token = input.parse()?;
+        let content;
+        let bracket_token = bracketed!(content in input);
+        let types = content.parse_terminated(Type::parse, Token![,])?;
+        let item: ModuleItem = input.parse()?;
+
+        Ok(GenericItem {
+            _for_token: for_token,
+            param,
+            _in_token: in_token,
+            _bracket_token: bracket_token,
+            types,
+            item: Box::new(item),
+        })
+    }
+}
+
+#[derive(Clone)]
 enum ModuleItem {
     InitFn(InitFn),
     ExportedType(ExportedType),
@@ -339,6 +528,7 @@ enum ModuleItem {
     ExportedConst(ExportedConst),
     ExportedGlobal(ExportedGlobal),
     ItemWithAttrs(ItemWithAttrs),
+    GenericItem(GenericItem),
 }
 
 impl ModuleItem {
@@ -495,6 +685,133 @@ impl ModuleItem {
     }
 }
 
+impl ModuleItem {
+    // Pushes the instantiations of this item to `items`, `env` maps the type parameters of the
+    // enclosing `for` items to the type they're instantiated with. Items that aren't generic are
+    // pushed unchanged.
+    fn instantiate(&self, env: &mut Vec<(Ident, Type)>, items: &mut Vec<ModuleItem>) -> Result<()> {
+        match self {
+            ModuleItem::GenericItem(generic) => {
+                let mut signatures = Vec::new();
+                for ty in generic.types.iter() {
+                    let start = items.len();
+                    env.push((generic.param.clone(), ty.clone()));
+                    let res = generic.item.instantiate(env, items);
+                    env.pop();
+                    res?;
+
+                    // Different instantiations must not add the same method to a function.
+                    for item in items[start..].iter() {
+                        let signature = match item.instantiated_signature() {
+                            Some(signature) => signature,
+                            None => continue,
+                        };
+
+                        if signatures.contains(&signature) {
+                            Err(syn::Error::new_spanned(
+                                ty.to_token_stream(),
+                                format!(
+                                    "instantiating `{}` with `{}` exports a method with the same signature as a previous instantiation",
+                                    generic.param,
+                                    ty.to_token_stream()
+                                ),
+                            ))?;
+                        }
+
+                        signatures.push(signature);
+                    }
+                }
+            }
+            ModuleItem::ItemWithAttrs(ItemWithAttrs { attrs, item }) => {
+                // The docstring is only added once, it applies to all instantiations.
+                let mut instances = Vec::new();
+                item.instantiate(env, &mut instances)?;
+                let mut instances = instances.into_iter();
+
+                match instances.next() {
+                    Some(ModuleItem::ItemWithAttrs(mut inner)) => {
+                        inner.attrs.splice(0..0, attrs.iter().cloned());
+                        items.push(ModuleItem::ItemWithAttrs(inner));
+                    }
+                    Some(first) => items.push(ModuleItem::ItemWithAttrs(ItemWithAttrs {
+                        attrs: attrs.clone(),
+                        item: Box::new(first),
+                    })),
+                    None => (),
+                }
+
+                items.extend(instances);
+            }
+            ModuleItem::ExportedType(ty) => {
+                let mut ty = ty.clone();
+                ty.type_args = ty
+                    .type_params
+                    .iter()
+                    .map(|param| match lookup_type_param(param, env) {
+                        Some(arg) => Ok(arg.clone()),
+                        None => Err(syn::Error::new_spanned(
+                            param.to_token_stream(),
+                            format!(
+                                "type parameter `{}` must be instantiated with `for {} in [...]`",
+                                param, param
+                            ),
+                        )),
+                    })
+                    .collect::<Result<_>>()?;
+                items.push(ModuleItem::ExportedType(ty));
+            }
+            _ if env.is_empty() => items.push(self.clone()),
+            ModuleItem::ExportedFunction(func) => {
+                let (sig, type_args) = instantiate_signature(&func.func, env)?;
+                let mut func = func.clone();
+                func.func = sig;
+                func.type_args = type_args;
+                items.push(ModuleItem::ExportedFunction(func));
+            }
+            ModuleItem::ExportedMethod(method) => {
+                let (sig, type_args) = instantiate_signature(&method.func, env)?;
+                let mut method = method.clone();
+                method.parent =
+                    syn::parse2(substitute_type_params(method.parent.to_token_stream(), env))?;
+                method.func = sig;
+                method.type_args = type_args;
+                items.push(ModuleItem::ExportedMethod(method));
+            }
+            _ => Err(Error::new(
+                Span::call_site(),
+                "only types, functions and methods can be instantiated with `for`",
+            ))?,
+        }
+
+        Ok(())
+    }
+
+    // Returns the parent and argument types of an instantiated function or method. Types are
+    // compared by name, different names that map to the same Julia type aren't detected.
+    fn instantiated_signature(&self) -> Option<String> {
+        let (parent, sig) = match self {
+            ModuleItem::ItemWithAttrs(ItemWithAttrs { item, .. }) => {
+                return item.instantiated_signature()
+            }
+            ModuleItem::ExportedFunction(func) => (None, &func.func),
+            ModuleItem::ExportedMethod(method) => (Some(&method.parent), &method.func),
+            _ => return None,
+        };
+
+        let inputs = sig.inputs.iter().map(|arg| match arg {
+            FnArg::Receiver(recv) => recv.ty.to_token_stream().to_string(),
+            FnArg::Typed(pat) => pat.ty.to_token_stream().to_string(),
+        });
+
+        let parent = parent.map(|p| p.to_token_stream().to_string());
+        Some(format!(
+            "{:?}({})",
+            parent,
+            Vec::from_iter(inputs).join(", ")
+        ))
+    }
+}
+
 impl Parse for ModuleItem {
     fn parse(input: ParseStream) -> Result<Self> {
         let lookahead = input.lookahead1();
@@ -514,25 +831,50 @@ impl Parse for ModuleItem {
             input.parse().map(ModuleItem::ExportedGlobal)
         } else if lookahead.peek(Token![#]) {
             input.parse().map(ModuleItem::ItemWithAttrs)
+        } else if lookahead.peek(Token![for]) {
+            input.parse().map(ModuleItem::GenericItem)
         } else {
             Err(Error::new(
                 input.span(),
-                "Expected `become`, `fn`, `in`, `struct`, `const`, or `static`.",
+                "Expected `become`, `fn`, `in`, `struct`, `const`, `static`, or `for`.",
             ))
         }
     }
 }
 
 pub(crate) struct JuliaModule {
-    items: Punctuated<ModuleItem, Token![;]>,
+    items: Vec<ModuleItem>,
+    protocol_fns: Vec<ItemFn>,
 }
 
 impl Parse for JuliaModule {
     fn parse(input: ParseStream) -> Result<Self> {
         let content = input;
-        let items = content.parse_terminated(ModuleItem::parse, Token![;])?;
+        let parsed = content.parse_terminated(ModuleItem::parse, Token![;])?;
+
+        // Generic functions and methods are expanded to one item per instantiation.
+        let mut items = Vec::with_capacity(parsed.len());
+        let mut env = Vec::new();
+        for item in parsed.iter() {
+            item.instantiate(&mut env, &mut items)?;
+        }
 
-        Ok(JuliaModule { items: items })
+        // The protocol methods of exported types are exported as functions that call a
+        // generated function.
+        let mut protocol_fns = Vec::new();
+        let mut protocol_items = Vec::new();
+        let types = items.iter().filter(|it| it.is_exported_type());
+        for (index, ty) in types.enumerate() {
+            let (funcs, fns) = protocol_fragments((index, ty.get_exported_type()))?;
+            protocol_items.extend(funcs.into_iter().map(ModuleItem::ExportedFunction));
+            protocol_fns.extend(fns);
+        }
+        items.extend(protocol_items);
+
+        Ok(JuliaModule {
+            items: items,
+            protocol_fns,
+        })
     }
 }
 
@@ -547,6 +889,7 @@ impl JuliaModule {
         let type_fragments = TypeFragments::generate(&self, init_fn);
         let const_fragments = ConstFragments::generate(&self, init_fn);
         let global_fragments = GlobalFragments::generate(&self, init_fn);
+        let julia_code_fragments = JuliaCodeFragments::generate(&self, init_fn);
         let doc_fragments = DocFragments::generate(&self, init_fn)?;
 
         let type_init_fn = type_fragments.type_init_fn;
@@ -563,6 +906,8 @@ impl JuliaModule {
         let const_init_fn_ident = const_fragments.const_init_ident;
         let global_init_fn = global_fragments.global_init_fn;
         let global_init_fn_ident = global_fragments.global_init_ident;
+        let julia_code_init_fn = julia_code_fragments.julia_code_init_fn;
+        let julia_code_init_fn_ident = julia_code_fragments.julia_code_init_ident;
         let doc_init_fn = doc_fragments.init_docs_fn;
         let doc_init_fn_ident = doc_fragments.init_docs_fn_ident;
 
@@ -594,6 +939,14 @@ impl JuliaModule {
             }
         };
 
+        let invoke_julia_code_init: Expr = parse_quote! {
+            if precompiling == 1 {
+                #julia_code_init_fn_ident(&mut frame, module);
+            }
+        };
+
+        let protocol_fns = &self.protocol_fns;
+
         let generated = quote::quote! {
 
             #[no_mangle]
@@ -601,6 +954,10 @@ impl JuliaModule {
                 module: ::jlrs::data::managed::module::Module,
                 precompiling: u8,
             ) -> ::jlrs::data::managed::value::ValueRet {
+                #(
+                    #protocol_fns
+                )*
+
                 #type_init_fn
 
                 #type_reinit_fn
@@ -615,6 +972,8 @@ impl JuliaModule {
 
                 #global_init_fn
 
+                #julia_code_init_fn
+
                 #doc_init_fn
 
                 static IS_INIT: ::std::sync::atomic::AtomicBool = ::std::sync::atomic::AtomicBool::new(false);
@@ -658,6 +1017,7 @@ impl JuliaModule {
                     #invoke_type_init;
                     #invoke_const_init;
                     #invoke_global_init;
+                    #invoke_julia_code_init;
 
                     let mut arr = ::jlrs::data::managed::array::Array::new_for_unchecked(frame.as_extended_target(), 0, function_info_ty.as_value());
                     #function_init_fn_ident(&mut frame, &mut arr, module, function_info_ty);
@@ -938,14 +1298,20 @@ struct TypeFragments {
 impl TypeFragments {
     fn generate(info: &JuliaModule, init_fn: &InitFn) -> Self {
         let init_types_fn_ident = format_ident!("{}_types", init_fn.init_fn);
-        let init_types_fragments = info.get_exported_types().map(init_type_fragment);
+
+        // The parametric type is only created for the first instantiation of a generic type.
+        let mut bases = Vec::new();
+        let init_types_fragments = info
+            .get_exported_types()
+            .map(|ty| init_type_fragment(ty, is_new_base(&mut bases, ty)))
+            .collect::<Vec<_>>();
 
         let type_init_fn = parse_quote! {
             unsafe fn #init_types_fn_ident(
                 frame: &mut ::jlrs::memory::target::frame::GcFrame,
                 module: ::jlrs::data::managed::module::Module,
             ) {
-                frame.scope(|frame| {
+                frame.scope(|mut frame| {
                     let mut output = frame.output();
 
                     #(
@@ -953,19 +1319,23 @@ impl TypeFragments {
                     )*
 
                     Ok(())
-                }).unwrap();
+                }).expect("Failed to create exported types");
             }
         };
 
         let reinit_types_fn_ident = format_ident!("{}_reinittypes", init_fn.init_fn);
-        let reinit_types_fragments = info.get_exported_types().map(reinit_type_fragment);
+        let mut bases = Vec::new();
+        let reinit_types_fragments = info
+            .get_exported_types()
+            .map(|ty| reinit_type_fragment(ty, is_new_base(&mut bases, ty)))
+            .collect::<Vec<_>>();
 
         let type_reinit_fn = parse_quote! {
             unsafe fn #reinit_types_fn_ident(
                 frame: &mut ::jlrs::memory::target::frame::GcFrame,
                 module: jlrs::data::managed::module::Module
             ) {
-                frame.scope(|frame| {
+                frame.scope(|mut frame| {
                     let mut output = frame.output();
 
                     #(
@@ -973,7 +1343,7 @@ impl TypeFragments {
                     )*
 
                     Ok(())
-                }).unwrap();
+                }).expect("Failed to reinitialize exported types");
             }
         };
 
@@ -1044,6 +1414,94 @@ impl GlobalFragments {
     }
 }
 
+struct JuliaCodeFragments {
+    julia_code_init_fn: ItemFn,
+    julia_code_init_ident: Ident,
+}
+
+impl JuliaCodeFragments {
+    fn generate(module: &JuliaModule, init_fn: &InitFn) -> Self {
+        let julia_code_init_ident = format_ident!("{}_julia_code", init_fn.init_fn);
+
+        let function_sources = module.get_exported_functions().filter_map(|info| {
+            let julia_args = info.julia_args.as_ref()?;
+            let name_ident = &info.func.ident;
+            let mut rename = info
+                .name_override
+                .as_ref()
+                .and_then(|parts| parts.last())
+                .unwrap_or(name_ident)
+                .to_string();
+
+            if info.exclamation_mark_token.is_some() {
+                rename.push('!')
+            }
+
+            Some(keyword_wrapper_source(
+                &rename,
+                &info.name_override,
+                julia_args,
+            ))
+        });
+
+        let method_sources = module.get_exported_methods().filter_map(|info| {
+            let julia_args = info.julia_args.as_ref()?;
+            let name_ident = &info.func.ident;
+            let mut rename = info
+                .name_override
+                .as_ref()
+                .and_then(|parts| parts.last())
+                .unwrap_or(name_ident)
+                .to_string();
+
+            if info.exclamation_mark_token.is_some() {
+                rename.push('!')
+            }
+
+            Some(keyword_wrapper_source(
+                &rename,
+                &info.name_override,
+                julia_args,
+            ))
+        });
+
+        let type_sources = module
+            .get_exported_types()
+            .flat_map(protocol_julia_code_sources);
+
+        // All instantiations of a generic function share the same wrapper, it must only be
+        // defined once.
+        let mut sources: Vec<String> = Vec::new();
+        for source in function_sources.chain(method_sources).chain(type_sources) {
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+        }
+
+        let eval_fragments = sources.iter().map(|source| eval_julia_fragment(source));
+
+        let julia_code_init_fn = parse_quote! {
+            unsafe fn #julia_code_init_ident(
+                frame: &mut ::jlrs::memory::target::frame::GcFrame,
+                module: ::jlrs::data::managed::module::Module,
+            ) {
+                frame.scope(move |mut frame| {
+                    #(
+                        #eval_fragments;
+                    )*
+
+                    Ok(())
+                }).unwrap();
+            }
+        };
+
+        JuliaCodeFragments {
+            julia_code_init_ident,
+            julia_code_init_fn,
+        }
+    }
+}
+
 fn doc_info_fragment((index, info): (usize, &ItemWithAttrs)) -> Result<Expr> {
     match info.item.as_ref() {
         ModuleItem::InitFn(i) => Err(syn::Error::new_spanned(
@@ -1252,6 +1710,7 @@ fn doc_info_fragment((index, info): (usize, &ItemWithAttrs)) -> Result<Expr> {
             Ok(q)
         }
         ModuleItem::ItemWithAttrs(_) => unreachable!(),
+        ModuleItem::GenericItem(_) => unreachable!(),
     }
 }
 
@@ -1272,6 +1731,18 @@ fn function_info_fragment((index, info): (usize, &ExportedFunction)) -> Result<E
         rename.push('!')
     }
 
+    if let Some(julia_name) = info.julia_name.as_ref() {
+        rename = julia_name.clone();
+    }
+
+    // If the function takes keyword arguments or has default values, it's exported with a hidden
+    // name and called by a generated Julia function.
+    let (rename, override_module_fragment) = if info.julia_args.is_some() {
+        (hidden_name(&rename), parse_quote! { { module } })
+    } else {
+        (rename, override_module_fragment)
+    };
+
     let tys = info.func.inputs.iter().map(|x| match x {
         FnArg::Typed(pat) => &pat.ty,
         _ => unreachable!(),
@@ -1280,6 +1751,22 @@ fn function_info_fragment((index, info): (usize, &ExportedFunction)) -> Result<E
     let punctuated_tys = Punctuated::<_, Comma>::from_iter(tys);
     let ret_ty = &info.func.output;
 
+    // Instantiated generic functions are called from a generated function too, so they don't
+    // have to use the C ABI.
+    let func_fragment: TokenStream2 = if returns_result(ret_ty) || !info.type_args.is_empty() {
+        let invoke_fn = invoke_fn_fragment(info);
+        quote! {
+            #invoke_fn
+            let func = Value::new(&mut frame, invoke as *mut ::std::ffi::c_void);
+        }
+    } else {
+        quote! {
+            // Ensure a compile error happens if the signatures of the function don't match.
+            let func: unsafe extern "C" fn(#punctuated_tys) #ret_ty = #name_ident;
+            let func = Value::new(&mut frame, func as *mut ::std::ffi::c_void);
+        }
+    };
+
     let (ccall_ret_type, julia_ret_type) = return_type_fragments(&info.func.output);
 
     let ccall_arg_idx = 0..n_args;
@@ -1292,9 +1779,7 @@ fn function_info_fragment((index, info): (usize, &ExportedFunction)) -> Result<E
             frame.scope(|mut frame| {
                 let name = Symbol::new(&frame, #rename);
                 let type_type = ::jlrs::data::managed::union_all::UnionAll::type_type(&frame).as_value();
-                // Ensure a compile error happens if the signatures of the function don't match.
-                let func: unsafe extern "C" fn(#punctuated_tys) #ret_ty = #name_ident;
-                let func = Value::new(&mut frame, func as *mut ::std::ffi::c_void);
+                #func_fragment
 
                 unsafe {
                     let mut ccall_arg_types = ::jlrs::data::managed::array::Array::new_for_unchecked(
@@ -1518,7 +2003,7 @@ fn arg_type_fragments<'a>(
     Ok((ccall_arg_types, julia_arg_types))
 }
 
-fn init_type_fragment(info: &ExportedType) -> Expr {
+fn init_type_fragment(info: &ExportedType, create_base: bool) -> Expr {
     let override_module_fragment = override_module_fragment(&info.name_override);
     let name_ident = &info.name;
 
@@ -1530,19 +2015,88 @@ fn init_type_fragment(info: &ExportedType) -> Expr {
         .unwrap_or(name_ident)
         .to_string();
 
-    let ty = format_ident!("{}", info.name);
+    let ty = info.rust_type();
+
+    if info.type_params.is_empty() {
+        return match info.super_type.as_ref() {
+            Some(super_type) => {
+                let eval_super_type = eval_julia_fragment(super_type);
+
+                parse_quote! {
+                    {
+                        let super_type = #eval_super_type.cast::<::jlrs::data::managed::datatype::DataType>()?;
+                        let sym = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
+                        let module = #override_module_fragment;
+                        let ty = <#ty as ::jlrs::data::types::foreign_type::OpaqueType>::create_type_with_super_type(&mut output, sym, module, super_type);
+                        module.set_const_unchecked(sym, <::jlrs::data::managed::datatype::DataType as ::jlrs::data::managed::Managed>::as_value(ty));
+                    }
+                }
+            }
+            None => parse_quote! {
+                {
+                    let sym = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
+                    let module = #override_module_fragment;
+                    let ty = <#ty as ::jlrs::data::types::foreign_type::OpaqueType>::create_type(&mut output, sym, module);
+                    module.set_const_unchecked(sym, <::jlrs::data::managed::datatype::DataType as ::jlrs::data::managed::Managed>::as_value(ty));
+                }
+            },
+        };
+    }
+
+    let create_variant: Expr = parse_quote! {
+        {
+            let target = ::jlrs::memory::target::Target::into_extended_target(&mut output, &mut frame);
+            <#ty as ::jlrs::data::types::foreign_type::ParametricVariant>::create_variant(target)?;
+        }
+    };
+
+    if !create_base {
+        return create_variant;
+    }
+
+    let create_base: Expr = match info.super_type.as_ref() {
+        Some(super_type) => {
+            // The supertype can depend on the type parameters, it's constructed by a function
+            // that takes them as arguments.
+            let params = info
+                .type_params
+                .iter()
+                .map(|param| param.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let eval_super_type = eval_julia_fragment(&format!("({}) -> {}", params, super_type));
+
+            parse_quote! {
+                {
+                    let super_type = #eval_super_type;
+                    let sym = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
+                    let module = #override_module_fragment;
+                    let target = ::jlrs::memory::target::Target::into_extended_target(&mut output, &mut frame);
+                    let ty = <#ty as ::jlrs::data::types::foreign_type::ParametricBase>::create_type_with_super_type(target, sym, module, super_type)?;
+                    module.set_const_unchecked(sym, ty);
+                }
+            }
+        }
+        None => parse_quote! {
+            {
+                let sym = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
+                let module = #override_module_fragment;
+                let target = ::jlrs::memory::target::Target::into_extended_target(&mut output, &mut frame);
+                let ty = <#ty as ::jlrs::data::types::foreign_type::ParametricBase>::create_type(target, sym, module)?;
+                module.set_const_unchecked(sym, ty);
+            }
+        },
+    };
 
     parse_quote! {
         {
-            let sym = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
-            let module = #override_module_fragment;
-            let ty = <#ty as ::jlrs::data::types::foreign_type::OpaqueType>::create_type(&mut output, sym, module);
-            module.set_const_unchecked(sym, <::jlrs::data::managed::datatype::DataType as ::jlrs::data::managed::Managed>::as_value(ty));
+            #create_base
+            #create_variant
         }
     }
 }
 
-fn reinit_type_fragment(info: &ExportedType) -> Expr {
+fn reinit_type_fragment(info: &ExportedType, reinit_base: bool) -> Expr {
     {
         let override_module_fragment = override_module_fragment(&info.name_override);
         let name_ident = &info.name;
@@ -1555,25 +2109,98 @@ fn reinit_type_fragment(info: &ExportedType) -> Expr {
             .unwrap_or(name_ident)
             .to_string();
 
-        let ty = format_ident!("{}", info.name);
+        let ty = info.rust_type();
+
+        if info.type_params.is_empty() {
+            return parse_quote! {
+                {
+                    let module = #override_module_fragment;
+
+                    let dt = module
+                        .global(&frame, #rename)
+                        .unwrap()
+                        .as_value()
+                        .cast::<::jlrs::data::managed::datatype::DataType>()
+                        .unwrap();
+
+                    <#ty as ::jlrs::data::types::foreign_type::OpaqueType>::reinit_type(dt);
+                }
+            };
+        }
+
+        // The instantiations are cached by the precompiled parametric type, creating them again
+        // returns the cached type.
+        let create_variant: Expr = parse_quote! {
+            {
+                let target = ::jlrs::memory::target::Target::into_extended_target(&mut output, &mut frame);
+                <#ty as ::jlrs::data::types::foreign_type::ParametricVariant>::create_variant(target)?;
+            }
+        };
+
+        if !reinit_base {
+            return create_variant;
+        }
 
         parse_quote! {
             {
-                let module = #override_module_fragment;
+                {
+                    let module = #override_module_fragment;
 
-                let dt = module
-                    .global(&frame, #rename)
-                    .unwrap()
-                    .as_value()
-                    .cast::<::jlrs::data::managed::datatype::DataType>()
-                    .unwrap();
+                    let ty = module
+                        .global(&frame, #rename)
+                        .unwrap()
+                        .as_value();
 
-                <#ty as ::jlrs::data::types::foreign_type::OpaqueType>::reinit_type(dt);
+                    <#ty as ::jlrs::data::types::foreign_type::ParametricBase>::reinit_type(ty);
+                }
+                #create_variant
             }
         }
     }
 }
 
+// Returns `true` if `ty` is the first instantiation of a generic type in `bases`, the
+// parametric type must be created or reinitialized for this instantiation.
+fn is_new_base(bases: &mut Vec<String>, ty: &ExportedType) -> bool {
+    if ty.type_params.is_empty() {
+        return false;
+    }
+
+    let name = ty
+        .name_override
+        .as_ref()
+        .and_then(|parts| parts.last())
+        .unwrap_or(&ty.name);
+    let base = format!("{}{}", julia_module_path(&ty.name_override), name);
+
+    if bases.contains(&base) {
+        false
+    } else {
+        bases.push(base);
+        true
+    }
+}
+
+// Evaluates `source` in the module and returns the result, this requires a mutable frame named
+// `frame` and the module `module`.
+fn eval_julia_fragment(source: &str) -> Expr {
+    parse_quote! {
+        {
+            let include_string = ::jlrs::data::managed::module::Module::base(&frame)
+                .function(&frame, "include_string")?
+                .as_managed();
+            let source = ::jlrs::data::managed::string::JuliaString::new(&mut frame, #source);
+            let res = ::jlrs::call::Call::call2(
+                include_string,
+                &mut frame,
+                module.as_value(),
+                source.as_value(),
+            );
+            ::jlrs::convert::into_jlrs_result::IntoJlrsResult::into_jlrs_result(res)?
+        }
+    }
+}
+
 fn method_info_fragment((index, info): (usize, &ExportedMethod)) -> Expr {
     let n_args = info.func.inputs.len();
     let name_ident = &info.func.ident;
@@ -1591,6 +2218,14 @@ fn method_info_fragment((index, info): (usize, &ExportedMethod)) -> Expr {
         rename.push('!')
     }
 
+    // If the function takes keyword arguments or has default values, it's exported with a hidden
+    // name and called by a generated Julia function.
+    let (rename, override_module_fragment) = if info.julia_args.is_some() {
+        (hidden_name(&rename), parse_quote! { { module } })
+    } else {
+        (rename, override_module_fragment)
+    };
+
     let ret_ty = &info.func.output;
     let (ccall_ret_type, julia_ret_type) = return_type_fragments(ret_ty);
 
@@ -1988,8 +2623,594 @@ fn invoke_async_callback(info: &ExportedAsyncCallback, ret_ty: &Type) -> ItemFn
     }
 }
 
+// Extracts the `#[kw]` and `#[default = "..."]` attributes from the arguments of an exported
+// function or method. These attributes are removed from the signature. If no argument has such
+// an attribute, `None` is returned and the function is exported as is.
+fn extract_julia_args(sig: &mut Signature) -> Result<Option<Vec<JuliaArg>>> {
+    let mut julia_args = Vec::with_capacity(sig.inputs.len());
+    let mut has_attrs = false;
+
+    for (idx, arg) in sig.inputs.iter_mut().enumerate() {
+        let (attrs, name) = match arg {
+            FnArg::Receiver(r) => (&mut r.attrs, String::from("this")),
+            FnArg::Typed(pat) => {
+                let name = match pat.pat.as_ref() {
+                    Pat::Ident(ident) => ident.ident.to_string(),
+                    _ => format!("arg{}", idx + 1),
+                };
+                (&mut pat.attrs, name)
+            }
+        };
+
+        let mut keyword = false;
+        let mut default = None;
+
+        for attr in attrs.drain(..) {
+            match &attr.meta {
+                Meta::Path(path) if path.is_ident("kw") => keyword = true,
+                Meta::NameValue(kv) if kv.path.is_ident("default") => match &kv.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(s), ..
+                    }) => default = Some(s.value()),
+                    _ => Err(syn::Error::new_spanned(
+                        attr.to_token_stream(),
+                        "expected `#[default = \"<Julia expression>\"]`",
+                    ))?,
+                },
+                _ => Err(syn::Error::new_spanned(
+                    attr.to_token_stream(),
+                    "expected `#[kw]` or `#[default = \"<Julia expression>\"]`",
+                ))?,
+            }
+
+            has_attrs = true;
+        }
+
+        if let (FnArg::Receiver(r), true) = (&*arg, keyword || default.is_some()) {
+            Err(syn::Error::new_spanned(
+                r.to_token_stream(),
+                "`self` can't be a keyword argument or have a default value",
+            ))?
+        }
+
+        julia_args.push(JuliaArg {
+            name,
+            keyword,
+            default,
+        });
+    }
+
+    if !has_attrs {
+        return Ok(None);
+    }
+
+    // Julia requires that positional arguments with a default value come last.
+    if let Some((_, arg)) = julia_args
+        .iter()
+        .zip(sig.inputs.iter())
+        .filter(|(a, _)| !a.keyword)
+        .skip_while(|(a, _)| a.default.is_none())
+        .find(|(a, _)| a.default.is_none())
+    {
+        Err(syn::Error::new_spanned(
+            arg.to_token_stream(),
+            "positional arguments without a default value must come before those with one",
+        ))?
+    }
+
+    Ok(Some(julia_args))
+}
+
+// The name of the function that the generated Julia function with keyword arguments and default
+// values calls.
+fn hidden_name(rename: &str) -> String {
+    format!("#{}#jlrs", rename)
+}
+
+// Returns the path of the module an item is exported to relative to `Main`, e.g. `Main.Base.` if
+// it's exported as `Base.foo`. The path is empty if the item is exported to the module itself.
+fn julia_module_path(name_override: &Option<RenameFragments>) -> String {
+    let mut path = String::new();
+    if let Some(name_override) = name_override.as_ref() {
+        let n_parts = name_override.len();
+        if n_parts > 1 {
+            path.push_str("Main.");
+            for part in name_override.iter().take(n_parts - 1) {
+                path.push_str(&part.to_string());
+                path.push('.');
+            }
+        }
+    }
+
+    path
+}
+
+// Returns the Julia code that defines the function that takes keyword arguments and provides the
+// default values of the exported function `rename`, which calls the hidden function.
+fn keyword_wrapper_source(
+    rename: &str,
+    name_override: &Option<RenameFragments>,
+    julia_args: &[JuliaArg],
+) -> String {
+    let path = julia_module_path(name_override);
+
+    let with_default = |arg: &JuliaArg| match arg.default.as_ref() {
+        Some(default) => format!("{} = {}", arg.name, default),
+        None => arg.name.clone(),
+    };
+
+    let positional = julia_args
+        .iter()
+        .filter(|arg| !arg.keyword)
+        .map(with_default)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let keywords = julia_args
+        .iter()
+        .filter(|arg| arg.keyword)
+        .map(with_default)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let args = julia_args
+        .iter()
+        .map(|arg| arg.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let hidden = hidden_name(rename);
+    if keywords.is_empty() {
+        format!(
+            "function {}{}({})\n    var\"{}\"({})\nend",
+            path, rename, positional, hidden, args
+        )
+    } else {
+        format!(
+            "function {}{}({}; {})\n    var\"{}\"({})\nend",
+            path, rename, positional, keywords, hidden, args
+        )
+    }
+}
+
+// Returns the functions that are exported for the protocols and properties of an exported type,
+// and the functions they call.
+fn protocol_fragments(
+    (index, ty): (usize, &ExportedType),
+) -> Result<(Vec<ExportedFunction>, Vec<ItemFn>)> {
+    let name = ty.rust_type();
+    let mut funcs = Vec::new();
+    let mut fns = Vec::new();
+
+    let mut push = |julia_name: &str, base_fn: &str, item: ItemFn| {
+        let base_fn = format_ident!("{}", base_fn);
+        funcs.push(ExportedFunction {
+            func: item.sig.clone(),
+            _as_token: None,
+            name_override: Some(parse_quote! { Base.#base_fn }),
+            exclamation_mark_token: None,
+            type_args: Vec::new(),
+            julia_args: None,
+            julia_name: Some(julia_name.to_string()),
+        });
+        fns.push(item);
+    };
+
+    // Every instantiation of a generic type needs its own functions.
+    let fn_ident = |suffix: &str| format_ident!("__jlrs_{}_{}_{}", ty.name, index, suffix);
+
+    let mut has_show = false;
+    for protocol in ty.protocols.iter() {
+        let segment = match protocol.segments.last() {
+            Some(segment) => segment,
+            None => Err(Error::new_spanned(protocol, "expected a trait"))?,
+        };
+
+        match segment.ident.to_string().as_str() {
+            "Display" | "Debug" => {
+                if has_show {
+                    Err(Error::new_spanned(
+                        protocol,
+                        "only one of Display and Debug can be implemented",
+                    ))?
+                }
+                has_show = true;
+
+                let ident = fn_ident("show");
+                let show_fn = if segment.ident == "Display" {
+                    quote! { show_display }
+                } else {
+                    quote! { show_debug }
+                };
+
+                let item = parse_quote! {
+                    #[allow(non_snake_case)]
+                    unsafe extern "C" fn #ident(
+                        io: ::jlrs::data::managed::value::typed::TypedValue<::jlrs::data::types::abstract_types::IO>,
+                        this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                    ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> {
+                        ::jlrs::ccall::protocols::#show_fn(io, this)
+                    }
+                };
+                push("show", "show", item);
+            }
+            "PartialEq" => {
+                let ident = fn_ident("eq");
+                let item = parse_quote! {
+                    #[allow(non_snake_case)]
+                    unsafe extern "C" fn #ident(
+                        this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                        other: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                    ) -> ::jlrs::data::managed::rust_result::RustResultRet<bool> {
+                        ::jlrs::ccall::protocols::eq(this, other)
+                    }
+                };
+                push("==", "eq", item);
+            }
+            "Hash" => {
+                let ident = fn_ident("hash");
+                let item = parse_quote! {
+                    #[allow(non_snake_case)]
+                    unsafe extern "C" fn #ident(
+                        this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                        h: usize,
+                    ) -> ::jlrs::data::managed::rust_result::RustResultRet<usize> {
+                        ::jlrs::ccall::protocols::hash(this, h)
+                    }
+                };
+                push("hash", "hash", item);
+            }
+            "Iterator" => {
+                let ident = fn_ident("iterate");
+                let item = parse_quote! {
+                    #[allow(non_snake_case)]
+                    unsafe extern "C" fn #ident(
+                        this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                    ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> {
+                        ::jlrs::ccall::protocols::iterate(this)
+                    }
+                };
+                push("iterate", "iterate", item);
+
+                // The state is ignored, the exported data is the iterator.
+                let ident = fn_ident("iterate_state");
+                let item = parse_quote! {
+                    #[allow(non_snake_case)]
+                    unsafe extern "C" fn #ident(
+                        this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                        _state: ::jlrs::data::managed::value::Value,
+                    ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> {
+                        ::jlrs::ccall::protocols::iterate(this)
+                    }
+                };
+                push("iterate", "iterate", item);
+            }
+            "ExactSizeIterator" => {
+                let ident = fn_ident("length");
+                let item = parse_quote! {
+                    #[allow(non_snake_case)]
+                    unsafe extern "C" fn #ident(
+                        this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                    ) -> ::jlrs::data::managed::rust_result::RustResultRet<isize> {
+                        ::jlrs::ccall::protocols::length(this)
+                    }
+                };
+                push("length", "length", item);
+            }
+            "Index" => {
+                let index_ty = match &segment.arguments {
+                    PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+                        match args.args.first() {
+                            Some(GenericArgument::Type(ty)) => ty,
+                            _ => Err(Error::new_spanned(protocol, "expected Index<I>"))?,
+                        }
+                    }
+                    _ => Err(Error::new_spanned(protocol, "expected Index<I>"))?,
+                };
+
+                let ident = fn_ident("getindex");
+                let item = parse_quote! {
+                    #[allow(non_snake_case)]
+                    unsafe extern "C" fn #ident(
+                        this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                        index: isize,
+                    ) -> ::jlrs::data::managed::rust_result::RustResultRet<<#name as ::std::ops::Index<#index_ty>>::Output> {
+                        ::jlrs::ccall::protocols::getindex::<#name, #index_ty>(this, index)
+                    }
+                };
+                push("getindex", "getindex", item);
+            }
+            _ => Err(Error::new_spanned(
+                protocol,
+                "expected Display, Debug, PartialEq, Hash, Iterator, ExactSizeIterator or Index<I>",
+            ))?,
+        }
+    }
+
+    if !ty.properties.is_empty() {
+        let names = ty.properties.iter().map(|prop| prop.name.to_string());
+        let fields = ty.properties.iter().map(|prop| &prop.name);
+
+        let ident = fn_ident("getproperty");
+        let item = parse_quote! {
+            #[allow(non_snake_case)]
+            unsafe extern "C" fn #ident(
+                this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                name: ::jlrs::data::managed::symbol::Symbol,
+            ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> {
+                ::jlrs::ccall::protocols::get_property(this, name, |this, name, frame| match name {
+                    #(
+                        #names => Some(::jlrs::data::managed::value::Value::new(
+                            frame,
+                            ::std::clone::Clone::clone(&this.#fields),
+                        )),
+                    )*
+                    _ => None,
+                })
+            }
+        };
+        push("getproperty", "getproperty", item);
+
+        let mutable = ty
+            .properties
+            .iter()
+            .filter(|prop| prop.mutability.is_some())
+            .collect::<Vec<_>>();
+
+        if !mutable.is_empty() {
+            let names = mutable.iter().map(|prop| prop.name.to_string());
+            let fields = mutable.iter().map(|prop| &prop.name);
+            let tys = mutable.iter().map(|prop| &prop.ty);
+
+            let ident = fn_ident("setproperty");
+            let item = parse_quote! {
+                #[allow(non_snake_case)]
+                unsafe extern "C" fn #ident(
+                    this: ::jlrs::data::managed::value::typed::TypedValue<#name>,
+                    name: ::jlrs::data::managed::symbol::Symbol,
+                    value: ::jlrs::data::managed::value::Value,
+                ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> {
+                    ::jlrs::ccall::protocols::set_property(this, name, value, |this, name, value| match name {
+                        #(
+                            #names => Some(value.unbox::<#tys>().map(|value| this.#fields = value)),
+                        )*
+                        _ => None,
+                    })
+                }
+            };
+            push("setproperty!", "setproperty", item);
+        }
+    }
+
+    Ok((funcs, fns))
+}
+
+// Returns the Julia code that must be evaluated for the protocols and properties of an exported
+// type.
+fn protocol_julia_code_sources(ty: &ExportedType) -> Vec<String> {
+    let mut sources = Vec::new();
+    let path = julia_module_path(&ty.name_override);
+    let name = ty
+        .name_override
+        .as_ref()
+        .and_then(|parts| parts.last())
+        .unwrap_or(&ty.name);
+
+    let implements = |trait_name: &str| {
+        ty.protocols.iter().any(|protocol| {
+            matches!(protocol.segments.last(), Some(segment) if segment.ident == trait_name)
+        })
+    };
+
+    // Base.length is only available if the iterator implements ExactSizeIterator.
+    if implements("Iterator") && !implements("ExactSizeIterator") {
+        sources.push(format!(
+            "Base.IteratorSize(::Type{{<:{}{}}}) = Base.SizeUnknown()",
+            path, name
+        ));
+    }
+
+    if !ty.properties.is_empty() {
+        let names = ty
+            .properties
+            .iter()
+            .map(|prop| format!(":{},", prop.name))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        sources.push(format!(
+            "Base.propertynames(::{}{}, private::Bool = false) = ({})",
+            path, name, names
+        ));
+    }
+
+    sources
+}
+
+// Replaces the type parameters of the signature of a generic function or method with the types
+// they're instantiated with in `env`. Returns the instantiated signature and the type arguments
+// that must be provided explicitly when the function is called.
+fn instantiate_signature(sig: &Signature, env: &[(Ident, Type)]) -> Result<(Signature, Vec<Type>)> {
+    let mut sig = sig.clone();
+    let mut type_args = Vec::new();
+    let mut params = Punctuated::<GenericParam, Comma>::new();
+
+    for param in sig.generics.params.iter() {
+        match param {
+            GenericParam::Type(param) => match lookup_type_param(&param.ident, env) {
+                Some(ty) => type_args.push(ty.clone()),
+                None => Err(syn::Error::new_spanned(
+                    param.ident.to_token_stream(),
+                    format!(
+                        "type parameter `{}` must be instantiated with `for {} in [...]`",
+                        param.ident, param.ident
+                    ),
+                ))?,
+            },
+            GenericParam::Lifetime(_) => params.push(param.clone()),
+            GenericParam::Const(param) => Err(syn::Error::new_spanned(
+                param.to_token_stream(),
+                "const generics are not supported",
+            ))?,
+        }
+    }
+
+    // The bounds have been checked by the compiler when the function is instantiated.
+    sig.generics.params = params;
+    sig.generics.where_clause = None;
+    if sig.generics.params.is_empty() {
+        sig.generics.lt_token = None;
+        sig.generics.gt_token = None;
+    }
+
+    for arg in sig.inputs.iter_mut() {
+        if let FnArg::Typed(pat) = arg {
+            let ty = substitute_type_params(pat.ty.to_token_stream(), env);
+            *pat.ty = syn::parse2(ty)?;
+        }
+    }
+
+    if let ReturnType::Type(_, ty) = &mut sig.output {
+        let substituted = substitute_type_params(ty.to_token_stream(), env);
+        **ty = syn::parse2(substituted)?;
+    }
+
+    Ok((sig, type_args))
+}
+
+// Returns the type the type parameter `ident` is instantiated with, inner `for` items shadow
+// outer ones.
+fn lookup_type_param<'a>(ident: &Ident, env: &'a [(Ident, Type)]) -> Option<&'a Type> {
+    env.iter()
+        .rev()
+        .find(|(param, _)| param == ident)
+        .map(|(_, ty)| ty)
+}
+
+// Replaces the type parameters in `tokens` with the types they're instantiated with in `env`.
+// Only paths that consist of a single segment without arguments are replaced, e.g. in
+// `Vec<T>` `T` is replaced but `T::Output` and `module::T` are left unchanged.
+fn substitute_type_params(tokens: TokenStream2, env: &[(Ident, Type)]) -> TokenStream2 {
+    let tokens = Vec::from_iter(tokens);
+    let is_path_sep = |idx: usize| match (tokens.get(idx), tokens.get(idx + 1)) {
+        (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b))) => {
+            a.as_char() == ':' && a.spacing() == Spacing::Joint && b.as_char() == ':'
+        }
+        _ => false,
+    };
+
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(idx, tt)| match tt {
+            TokenTree::Ident(ident) => {
+                let preceded_by_sep = idx >= 2 && is_path_sep(idx - 2);
+                let followed_by_sep = is_path_sep(idx + 1);
+                let has_args =
+                    matches!(tokens.get(idx + 1), Some(TokenTree::Punct(p)) if p.as_char() == '<');
+                if preceded_by_sep || followed_by_sep || has_args {
+                    return tt.clone();
+                }
+
+                match lookup_type_param(ident, env) {
+                    // The type is wrapped in an invisible group to preserve its precedence.
+                    Some(ty) => TokenTree::Group(Group::new(Delimiter::None, ty.to_token_stream())),
+                    None => tt.clone(),
+                }
+            }
+            TokenTree::Group(group) => {
+                let mut new_group = Group::new(
+                    group.delimiter(),
+                    substitute_type_params(group.stream(), env),
+                );
+                new_group.set_span(group.span());
+                TokenTree::Group(new_group)
+            }
+            tt => tt.clone(),
+        })
+        .collect()
+}
+
+// The explicit type arguments of an instantiated generic function or method, e.g. `::<f32>`.
+fn turbofish_fragment(type_args: &[Type]) -> Option<TokenStream2> {
+    if type_args.is_empty() {
+        None
+    } else {
+        Some(quote! { ::<#(#type_args),*> })
+    }
+}
+
+// Returns `true` if the exported function returns a `Result` or `JlrsResult`.
+// Returns `true` if `ret_ty` is `Result` or `JlrsResult`. Types can't be resolved by the macro,
+// so only these names and their full paths in std, core and jlrs are recognized.
+fn returns_result(ret_ty: &ReturnType) -> bool {
+    let path = match ret_ty {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(p) if p.qself.is_none() => &p.path,
+            _ => return false,
+        },
+        ReturnType::Default => return false,
+    };
+
+    let segments = path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>();
+    let segments = segments.iter().map(String::as_str).collect::<Vec<_>>();
+
+    matches!(
+        segments.as_slice(),
+        ["Result"]
+            | ["std" | "core", "result", "Result"]
+            | ["JlrsResult"]
+            | ["jlrs", "error" | "prelude", "JlrsResult"]
+    )
+}
+
+// If the exported function returns a `Result`, the generated function converts it to a
+// `RustResult` so the generated Julia function throws the error as an exception.
+fn result_wrapper_fragments(ret_ty: &ReturnType, call: Expr) -> (ReturnType, Expr) {
+    match ret_ty {
+        ReturnType::Type(_, ty) if returns_result(ret_ty) => {
+            let ret_ty = parse_quote! {
+                -> ::jlrs::data::managed::rust_result::RustResultRet<<#ty as ::jlrs::convert::ccall_types::CCallReturn>::FunctionReturnType>
+            };
+            let call = parse_quote! {
+                ::jlrs::data::managed::rust_result::RustResult::from_result_internal(#call)
+            };
+            (ret_ty, call)
+        }
+        _ => (ret_ty.clone(), call),
+    }
+}
+
+fn invoke_fn_fragment(info: &ExportedFunction) -> ItemFn {
+    let name = &info.func.ident;
+    let span = info.func.ident.span();
+    let args = &info.func.inputs;
+    let turbofish = turbofish_fragment(&info.type_args);
+    let names = args.iter().map(|arg| match arg {
+        FnArg::Typed(ty) => &ty.pat,
+        _ => unreachable!(),
+    });
+
+    let names = Punctuated::<_, Comma>::from_iter(names);
+    let (ret_ty, call) = result_wrapper_fragments(
+        &info.func.output,
+        parse_quote! { #name #turbofish (#names) },
+    );
+
+    parse_quote_spanned! {
+        span=> unsafe extern "C" fn invoke(#args) #ret_ty {
+            #call
+        }
+    }
+}
+
 fn invoke_fn_no_self_method_fragment(info: &ExportedMethod) -> ItemFn {
     let name = &info.func.ident;
+    let turbofish = turbofish_fragment(&info.type_args);
     let span = info.func.ident.span();
     let ty = &info.parent;
     let ret_ty = &info.func.output;
@@ -2001,15 +3222,19 @@ fn invoke_fn_no_self_method_fragment(info: &ExportedMethod) -> ItemFn {
 
     let names = Punctuated::<_, Comma>::from_iter(names);
 
+    let (ret_ty, call) =
+        result_wrapper_fragments(ret_ty, parse_quote! { <#ty>::#name #turbofish (#names) });
+
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args) #ret_ty {
-            <#ty>::#name(#names)
+            #call
         }
     }
 }
 
 fn invoke_fn_ref_self_method_fragment(info: &ExportedMethod) -> ItemFn {
     let name = &info.func.ident;
+    let turbofish = turbofish_fragment(&info.type_args);
     let span = info.func.ident.span();
     let ty = &info.parent;
     let ret_ty = &info.func.output;
@@ -2030,10 +3255,13 @@ fn invoke_fn_ref_self_method_fragment(info: &ExportedMethod) -> ItemFn {
 
     let names = Punctuated::<_, Comma>::from_iter(names);
 
+    let (ret_ty, call) =
+        result_wrapper_fragments(ret_ty, parse_quote! { this.#name #turbofish (#names) });
+
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #ret_ty {
             match (&this).track_shared() {
-                Ok(this) => this.#name(#names),
+                Ok(this) => #call,
                 Err(_) => ::jlrs::data::managed::rust_result::RustResult::borrow_error_internal()
             }
         }
@@ -2042,6 +3270,7 @@ fn invoke_fn_ref_self_method_fragment(info: &ExportedMethod) -> ItemFn {
 
 fn invoke_fn_move_self_method_fragment(info: &ExportedMethod) -> ItemFn {
     let name = &info.func.ident;
+    let turbofish = turbofish_fragment(&info.type_args);
     let span = info.func.ident.span();
     let ty = &info.parent;
     let ret_ty = &info.func.output;
@@ -2062,10 +3291,15 @@ fn invoke_fn_move_self_method_fragment(info: &ExportedMethod) -> ItemFn {
 
     let names = Punctuated::<_, Comma>::from_iter(names);
 
+    let (ret_ty, call) = result_wrapper_fragments(
+        ret_ty,
+        parse_quote! { this.clone().#name #turbofish (#names) },
+    );
+
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #ret_ty {
             match (&this).track_shared() {
-                Ok(this) => this.clone().#name(#names),
+                Ok(this) => #call,
                 Err(_) => ::jlrs::data::managed::rust_result::RustResult::borrow_error_internal()
             }
         }
@@ -2074,6 +3308,7 @@ fn invoke_fn_move_self_method_fragment(info: &ExportedMethod) -> ItemFn {
 
 fn invoke_fn_mut_self_method_fragment(info: &ExportedMethod) -> ItemFn {
     let name = &info.func.ident;
+    let turbofish = turbofish_fragment(&info.type_args);
     let span = info.func.ident.span();
     let ty = &info.parent;
     let ret_ty = &info.func.output;
@@ -2094,10 +3329,13 @@ fn invoke_fn_mut_self_method_fragment(info: &ExportedMethod) -> ItemFn {
 
     let names = Punctuated::<_, Comma>::from_iter(names);
 
+    let (ret_ty, call) =
+        result_wrapper_fragments(ret_ty, parse_quote! { this.#name #turbofish (#names) });
+
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #ret_ty {
             match (&mut this).track_exclusive() {
-                Ok(mut this) => this.#name(#names),
+                Ok(mut this) => #call,
                 Err(_) => ::jlrs::data::managed::rust_result::RustResult::borrow_error_internal()
             }
         }
@@ -50,6 +50,49 @@ end
     @test JuliaModuleTest.freestanding_func_ret_rust_result(false) == 3
     @inferred JuliaModuleTest.freestanding_func_ret_rust_result(false)
     @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_ret_rust_result(true)
+
+    @test JuliaModuleTest.freestanding_func_ret_result(Int32(3)) == 3
+    @inferred JuliaModuleTest.freestanding_func_ret_result(Int32(3))
+    @test_throws DomainError JuliaModuleTest.freestanding_func_ret_result(Int32(-1))
+    @test_throws ArgumentError JuliaModuleTest.freestanding_func_ret_argument_error()
+    @test JuliaModuleTest.freestanding_func_ret_bounds_error([1, 2], 2) == 2
+    @test_throws BoundsError JuliaModuleTest.freestanding_func_ret_bounds_error([1, 2], 3)
+    @test JuliaModuleTest.freestanding_func_ret_std_result(Int32(3)) == 3
+    @test_throws DomainError JuliaModuleTest.freestanding_func_ret_std_result(Int32(-1))
+    @test JuliaModuleTest.freestanding_func_ret_jlrs_result(false) == 3
+    @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_ret_jlrs_result(true)
+
+    @test JuliaModuleTest.freestanding_func_kwargs(1.0) == 2.0
+    @test JuliaModuleTest.freestanding_func_kwargs(1.0, 3.0) == 3.0
+    @test JuliaModuleTest.freestanding_func_kwargs(1.0; offset = 1.0) == 3.0
+    @test JuliaModuleTest.freestanding_func_kwargs(1.0, 3.0; offset = -1.0) == 2.0
+    @test_throws MethodError JuliaModuleTest.freestanding_func_kwargs(1.0; scale = 3.0)
+end
+
+@testset "Typed callbacks" begin
+    @test JuliaModuleTest.freestanding_func_apply_n(x -> 2x, 1.0, 3) == 8.0
+    c = 0.5
+    @test JuliaModuleTest.freestanding_func_apply_n(x -> x + c, 0.0, 4) == 2.0
+    @test JuliaModuleTest.freestanding_func_apply_n(sqrt, 16.0, 2) == 2.0
+    @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_apply_n((x::Int) -> x, 1.0, 1)
+    @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_apply_n(x -> round(Int, x), 1.0, 1)
+
+    @test JuliaModuleTest.freestanding_func_call_dynamic(+, 1, 2) == 3
+    @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_call_dynamic(/, 1, 2)
+    @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_call_dynamic(x -> x, 1, 2)
+end
+
+@testset "Generic functions" begin
+    @test JuliaModuleTest.generic_func_add(1.0f0, 2.0f0) === 3.0f0
+    @test JuliaModuleTest.generic_func_add(1.0, 2.0) === 3.0
+    @test JuliaModuleTest.generic_func_add(1, 2) === 3
+    @inferred JuliaModuleTest.generic_func_add(1, 2)
+    @test_throws MethodError JuliaModuleTest.generic_func_add(Int32(1), Int32(2))
+    @test length(methods(JuliaModuleTest.generic_func_add)) == 3
+
+    @test JuliaModuleTest.generic_sum(Float32[1, 2, 3]) === 6.0f0
+    @test JuliaModuleTest.generic_sum([1.0, 2.0]) === 3.0
+    @test_throws MethodError JuliaModuleTest.generic_sum([1, 2])
 end
 
 @testset "OpaqueInt" begin
@@ -71,6 +114,55 @@ end
 
     @test JuliaModuleTest.unbox_opaque(opaque_int) == Int32(1)
     @inferred JuliaModuleTest.unbox_opaque(opaque_int)
+
+    @test JuliaModuleTest.checked_get(opaque_int) == Int32(1)
+    @test_throws ErrorException JuliaModuleTest.checked_get(JuliaModuleTest.OpaqueInt(Int32(-1)))
+
+    @test JuliaModuleTest.get_plus(opaque_int) == Int32(2)
+    @test JuliaModuleTest.get_plus(opaque_int; incr = Int32(2)) == Int32(3)
+    @test_throws ErrorException JuliaModuleTest.get_plus(opaque_int; incr = typemax(Int32))
+end
+
+@testset "Protocols" begin
+    opaque_int = JuliaModuleTest.OpaqueInt(Int32(1))
+    @test repr(opaque_int) == "OpaqueInt { a: 1 }"
+    @test opaque_int == JuliaModuleTest.OpaqueInt(Int32(1))
+    @test opaque_int != JuliaModuleTest.OpaqueInt(Int32(2))
+    @test hash(opaque_int) == hash(JuliaModuleTest.OpaqueInt(Int32(1)))
+
+    @test propertynames(opaque_int) == (:a,)
+    @test opaque_int.a === Int32(1)
+    opaque_int.a = Int32(3)
+    @test opaque_int.a === Int32(3)
+    @test_throws ErrorException opaque_int.b
+    @test_throws JlrsCore.JlrsError opaque_int.a = 1.0
+
+    range = JuliaModuleTest.OpaqueRange(Int32(1), Int32(4))
+    @test length(range) == 3
+    @test collect(range) == Int32[1, 2, 3]
+    @test length(range) == 0
+
+    vec = JuliaModuleTest.OpaqueVec(UInt(3))
+    @test vec[1] === 0.0
+    @test vec[3] === 2.0
+    @test_throws BoundsError vec[0]
+    @test_throws BoundsError vec[4]
+    @test repr(vec) == "OpaqueVec of length 3"
+end
+
+@testset "Parametric opaque type" begin
+    grid = JuliaModuleTest.Grid(UInt(2), UInt(3), 1.0)
+    @test grid isa JuliaModuleTest.Grid{Float64}
+    @test grid isa AbstractMatrix{Float64}
+    @test size(grid) == (2, 3)
+    @test grid[2, 3] === 1.0
+    @test sum(grid) == 6.0
+    @test_throws ErrorException grid[3, 1]
+
+    grid32 = JuliaModuleTest.Grid(UInt(1), UInt(2), 1.0f0)
+    @test grid32 isa JuliaModuleTest.Grid{Float32}
+    @test grid32 isa AbstractMatrix{Float32}
+    @test collect(grid32) == Float32[1.0 1.0]
 end
 
 @testset "ForeignThing" begin
@@ -91,6 +183,10 @@ end
 @testset "Associated function" begin
     @test JuliaModuleTest.assoc_func() == 1
     @inferred JuliaModuleTest.assoc_func()
+
+    @test JuliaModuleTest.generic_assoc_func(0x01) === 1.0
+    @test JuliaModuleTest.generic_assoc_func(Int32(-1)) === -1.0
+    @test_throws MethodError JuliaModuleTest.generic_assoc_func(1)
 end
 
 @testset "Async callback" begin
@@ -1,15 +1,23 @@
+use std::{fmt, ops::Index};
+
 use jlrs::{
-    ccall::AsyncCallback,
+    ccall::{julia_fn::JuliaFn, protocols::CheckedIndex, AsyncCallback},
+    convert::{into_julia::IntoJulia, into_julia_exception::JlrsException},
     data::{
+        layout::{
+            tuple::{Tuple1, Tuple2},
+            valid_layout::ValidField,
+        },
         managed::{
-            array::{ArrayRet, TypedArrayUnbound},
+            array::{dimensions::Dims, ArrayRet, TypedArray, TypedArrayUnbound},
             ccall_ref::CCallRef,
             rust_result::{RustResult, RustResultRet},
             value::typed::{TypedValue, TypedValueRef, TypedValueRet},
         },
         types::{
             abstract_types::{AnyType, Number},
-            foreign_type::{ForeignType, OpaqueType},
+            construct_type::ConstructType,
+            foreign_type::{ForeignType, OpaqueType, ParametricBase, ParametricVariant},
         },
     },
     error::JlrsError,
@@ -87,7 +95,72 @@ unsafe extern "C" fn freestanding_func_ret_rust_result(throw_err: Bool) -> RustR
     })
 }
 
-#[derive(Clone, Debug)]
+fn freestanding_func_ret_result(x: i32) -> Result<i32, JlrsException> {
+    if x < 0 {
+        Err(JlrsException::domain_error(x, "x must be non-negative"))
+    } else {
+        Ok(x)
+    }
+}
+
+fn freestanding_func_ret_argument_error() -> Result<Nothing, JlrsException> {
+    Err(JlrsException::argument_error("invalid argument"))
+}
+
+fn freestanding_func_ret_bounds_error(a: Array, idx: isize) -> Result<isize, JlrsException> {
+    if idx < 1 || idx as usize > unsafe { a.dimensions().size() } {
+        Err(unsafe { JlrsException::bounds_error(a.as_value(), &[idx]) })
+    } else {
+        Ok(idx)
+    }
+}
+
+fn freestanding_func_ret_std_result(x: i32) -> std::result::Result<i32, JlrsException> {
+    freestanding_func_ret_result(x)
+}
+
+fn freestanding_func_ret_jlrs_result(throw_err: Bool) -> JlrsResult<i32> {
+    if throw_err.as_bool() {
+        Err(JlrsError::exception("Error"))?
+    } else {
+        Ok(3)
+    }
+}
+
+fn freestanding_func_apply_n(f: JuliaFn<(f64,), f64>, x: f64, n: usize) -> JlrsResult<f64> {
+    unsafe {
+        CCall::invoke(|mut frame| {
+            let f = f.resolve(&mut frame)?;
+            Ok((0..n).fold(x, |x, _| f.call((x,))))
+        })
+    }
+}
+
+fn freestanding_func_call_dynamic(f: JuliaFn<(i64, i64), i64>, a: i64, b: i64) -> JlrsResult<i64> {
+    unsafe { CCall::invoke(|mut frame| f.call(&mut frame, (a, b))) }
+}
+
+unsafe extern "C" fn freestanding_func_kwargs(x: f64, scale: f64, offset: f64) -> f64 {
+    x * scale + offset
+}
+
+fn generic_func_add<T: std::ops::Add<Output = T>>(a: T, b: T) -> T {
+    a + b
+}
+
+fn generic_func_sum<T>(arr: TypedArray<T>) -> Result<T, JlrsException>
+where
+    T: ValidField + Copy + std::iter::Sum,
+{
+    unsafe {
+        let data = arr
+            .bits_data()
+            .map_err(|_| JlrsException::argument_error("array is not a bits array"))?;
+        Ok(data.as_slice().iter().copied().sum())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
 struct OpaqueInt {
     a: i32,
 }
@@ -121,6 +194,20 @@ impl OpaqueInt {
         }
     }
 
+    fn checked_get(&self) -> Result<i32, JlrsException> {
+        if self.a < 0 {
+            Err(JlrsException::error("negative value"))
+        } else {
+            Ok(self.a)
+        }
+    }
+
+    fn get_plus(&self, incr: i32) -> Result<i32, JlrsException> {
+        self.a
+            .checked_add(incr)
+            .ok_or_else(|| JlrsException::error("overflow"))
+    }
+
     fn get_cloned(self) -> RustResultRet<i32> {
         unsafe {
             CCall::invoke(|mut frame| {
@@ -131,6 +218,122 @@ impl OpaqueInt {
     }
 }
 
+struct OpaqueRange {
+    next: i32,
+    end: i32,
+}
+
+unsafe impl OpaqueType for OpaqueRange {}
+
+impl OpaqueRange {
+    fn new(start: i32, end: i32) -> TypedValueRet<OpaqueRange> {
+        unsafe {
+            CCall::invoke(|mut frame| {
+                TypedValue::new(&mut frame, OpaqueRange { next: start, end }).leak()
+            })
+        }
+    }
+}
+
+impl Iterator for OpaqueRange {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+        if self.next < self.end {
+            self.next += 1;
+            Some(self.next - 1)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end - self.next).max(0) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for OpaqueRange {}
+
+struct OpaqueVec {
+    data: Vec<f64>,
+}
+
+unsafe impl OpaqueType for OpaqueVec {}
+
+impl OpaqueVec {
+    fn new(len: usize) -> TypedValueRet<OpaqueVec> {
+        let data = (0..len).map(|i| i as f64).collect();
+        unsafe { CCall::invoke(|mut frame| TypedValue::new(&mut frame, OpaqueVec { data }).leak()) }
+    }
+}
+
+impl Index<usize> for OpaqueVec {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &f64 {
+        &self.data[index]
+    }
+}
+
+impl CheckedIndex<usize> for OpaqueVec {
+    fn checked_index(&self, index: usize) -> Option<&f64> {
+        self.data.get(index)
+    }
+}
+
+impl fmt::Display for OpaqueVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OpaqueVec of length {}", self.data.len())
+    }
+}
+
+struct Grid<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+unsafe impl<T: Send + 'static> OpaqueType for Grid<T> {}
+
+unsafe impl<T: Send + 'static> ParametricBase for Grid<T> {
+    type Key = Grid<()>;
+
+    fn type_parameters() -> &'static [&'static str] {
+        &["T"]
+    }
+}
+
+unsafe impl<T: ConstructType + Send + 'static> ParametricVariant for Grid<T> {
+    type Parameters = Tuple1<T>;
+}
+
+impl<T> Grid<T>
+where
+    T: IntoJulia + ConstructType + Clone + Send + 'static,
+{
+    fn new(rows: usize, cols: usize, fill: T) -> TypedValueRet<Grid<T>> {
+        let data = vec![fill; rows * cols];
+        unsafe {
+            CCall::invoke(|mut frame| TypedValue::new(&mut frame, Grid { data, rows, cols }).leak())
+        }
+    }
+
+    fn size(&self) -> Result<Tuple2<isize, isize>, JlrsException> {
+        Ok(Tuple2(self.rows as isize, self.cols as isize))
+    }
+
+    fn get(&self, row: isize, col: isize) -> Result<T, JlrsException> {
+        let (rows, cols) = (self.rows as isize, self.cols as isize);
+        if row < 1 || row > rows || col < 1 || col > cols {
+            return Err(JlrsException::error("index out of bounds"));
+        }
+
+        let idx = (col - 1) * rows + row - 1;
+        Ok(self.data[idx as usize].clone())
+    }
+}
+
 pub struct ForeignThing {
     a: ValueRef<'static, 'static>,
 }
@@ -185,6 +388,10 @@ impl UnexportedType {
     fn assoc_func() -> isize {
         1
     }
+
+    fn generic_assoc_func<T: Into<f64>>(value: T) -> f64 {
+        value.into()
+    }
 }
 
 fn async_callback(arr: TypedArrayUnbound<isize>) -> JlrsResult<impl AsyncCallback<isize>> {
@@ -218,19 +425,55 @@ julia_module! {
     fn freestanding_func_typevaluearg(a: TypedValue<usize>) -> usize;
     fn freestanding_func_ret_array(dt: DataType) -> ArrayRet;
     fn freestanding_func_ret_rust_result(throw_err: Bool) -> RustResultRet<i32>;
-
-    struct OpaqueInt;
+    fn freestanding_func_ret_result(x: i32) -> Result<i32, JlrsException>;
+    fn freestanding_func_ret_argument_error() -> Result<Nothing, JlrsException>;
+    fn freestanding_func_ret_bounds_error(a: Array, idx: isize) -> Result<isize, JlrsException>;
+    fn freestanding_func_ret_std_result(x: i32) -> std::result::Result<i32, JlrsException>;
+    fn freestanding_func_ret_jlrs_result(throw_err: Bool) -> JlrsResult<i32>;
+    fn freestanding_func_apply_n(f: JuliaFn<(f64,), f64>, x: f64, n: usize) -> JlrsResult<f64>;
+    fn freestanding_func_call_dynamic(f: JuliaFn<(i64, i64), i64>, a: i64, b: i64) -> JlrsResult<i64>;
+    fn freestanding_func_kwargs(
+        x: f64,
+        #[default = "2.0"] scale: f64,
+        #[kw] #[default = "0.0"] offset: f64
+    ) -> f64;
+
+    for T in [f32, f64, i64]
+    fn generic_func_add<T>(a: T, b: T) -> T;
+    for T in [f32, f64]
+    fn generic_func_sum<T>(arr: TypedArray<T>) -> Result<T, JlrsException> as generic_sum;
+
+    struct OpaqueInt impl Debug, PartialEq, Hash { mut a: i32 };
     in OpaqueInt fn new(value: i32) -> TypedValueRet<OpaqueInt> as OpaqueInt;
     in OpaqueInt fn increment(&mut self) -> RustResultRet<Nothing> as increment!;
     in OpaqueInt fn get(&self) -> RustResultRet<i32> as unbox_opaque;
+    in OpaqueInt fn checked_get(&self) -> Result<i32, JlrsException>;
+    in OpaqueInt fn get_plus(&self, #[kw] #[default = "Int32(1)"] incr: i32) -> Result<i32, JlrsException>;
     in OpaqueInt fn get_cloned(self) -> RustResultRet<i32>;
 
+    struct OpaqueRange impl Iterator, ExactSizeIterator;
+    in OpaqueRange fn new(start: i32, end: i32) -> TypedValueRet<OpaqueRange> as OpaqueRange;
+
+    struct OpaqueVec impl Display, Index<usize>;
+    in OpaqueVec fn new(len: usize) -> TypedValueRet<OpaqueVec> as OpaqueVec;
+
+    for T in [f32, f64]
+    struct Grid<T> as Grid <: AbstractMatrix{T};
+    for T in [f32, f64]
+    in Grid<T> fn new(rows: usize, cols: usize, fill: T) -> TypedValueRet<Grid<T>> as Grid;
+    for T in [f32, f64]
+    in Grid<T> fn size(&self) -> Result<Tuple2<isize, isize>, JlrsException> as Base.size;
+    for T in [f32, f64]
+    in Grid<T> fn get(&self, row: isize, col: isize) -> Result<T, JlrsException> as Base.getindex;
+
     struct ForeignThing;
     in ForeignThing fn new(value: Value) -> TypedValueRet<ForeignThing> as ForeignThing;
     in ForeignThing fn get(&self) -> RustResultRet<AnyType> as extract_inner;
     in ForeignThing fn set(&mut self, value: Value) -> RustResultRet<Nothing> as set_inner!;
 
     in UnexportedType fn assoc_func() -> isize;
+    for T in [u8, i32]
+    in UnexportedType fn generic_assoc_func<T>(value: T) -> f64;
 
     #[doc = "    async_callback(array::Array{Int})::Int"]
     #[doc = ""]